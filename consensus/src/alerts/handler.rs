@@ -1,14 +1,68 @@
 use crate::{
-    alerts::{Alert, AlertConfig, AlertMessage, AlerterResponse, ForkProof, ForkingNotification},
-    Data, Hasher, Keychain, MultiKeychain, Multisigned, NodeIndex, Recipient, SessionId, Signed,
-    UncheckedSigned,
+    alerts::{
+        Alert, AlertConfig, AlertMessage, AlerterResponse, ForkEvidence, ForkProof,
+        ForkingNotification, RmcHash, RmcMessage,
+    },
+    Data, Hasher, Index, Keychain, Metrics, MultiKeychain, Multisigned, NodeCount, NodeIndex,
+    NoopMetrics, Recipient, SessionId, Signed, UncheckedSigned,
 };
 use aleph_bft_types::Round;
+use codec::{Decode, Encode};
 use std::{
     collections::{HashMap, HashSet},
     fmt::{Display, Formatter},
+    sync::Arc,
 };
 
+/// Stages in the life of an alert, as seen by the local alerter.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AlertStage {
+    /// We created the alert ourselves.
+    Created,
+    /// We received a valid alert from the network.
+    Received,
+    /// We registered the alert for reliable multicast.
+    RmcStarted,
+    /// The reliable multicast of the alert completed.
+    RmcCompleted,
+    /// The units committed to in the alert were released to consensus.
+    UnitsReleased,
+}
+
+/// An interface for observing the lifecycle of alerts, e.g. to build forensic timelines.
+/// An alert is identified by its sender and the forker it concerns.
+pub trait AlertObserver: Send + Sync + 'static {
+    /// Called whenever an alert reaches the given stage.
+    fn on_alert_stage(&self, sender: NodeIndex, forker: NodeIndex, stage: AlertStage);
+
+    /// Called whenever a fork proof from the network passes verification and reveals a forker
+    /// we did not know of before.
+    fn on_fork_evidence(&self, _evidence: ForkEvidence) {}
+}
+
+/// The state of a single alert, as summarized in an [`AlerterSnapshot`].
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq)]
+pub struct AlertSummary<H: Hasher> {
+    /// The node that sent the alert.
+    pub sender: NodeIndex,
+    /// The forker the alert concerns.
+    pub forker: NodeIndex,
+    /// The hash of the alert being multicast.
+    pub hash: H::Hash,
+    /// Whether the reliable multicast of the alert completed.
+    pub rmc_completed: bool,
+}
+
+/// A summary of the state of the alerter, meant for comparing the views of different nodes when
+/// debugging forking incidents. It contains no secret material.
+#[derive(Clone, Debug, Decode, Encode, Eq, PartialEq)]
+pub struct AlerterSnapshot<H: Hasher> {
+    /// All the forkers we know of, in ascending order.
+    pub forkers: Vec<NodeIndex>,
+    /// All the alerts registered for reliable multicast, ordered by sender and forker.
+    pub alerts: Vec<AlertSummary<H>>,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Error {
     // commitment validity errors
@@ -46,13 +100,18 @@ impl Display for Error {
 type KnownAlerts<H, D, MK> =
     HashMap<<H as Hasher>::Hash, Signed<Alert<H, D, <MK as Keychain>::Signature>, MK>>;
 
+pub(crate) type UncheckedAlert<H, D, MK> =
+    UncheckedSigned<Alert<H, D, <MK as Keychain>::Signature>, <MK as Keychain>::Signature>;
+
+pub(crate) type CheckedAlert<H, D, MK> = Signed<Alert<H, D, <MK as Keychain>::Signature>, MK>;
+
 type OnOwnAlertResult<H, D, MK> = (
     AlertMessage<H, D, <MK as Keychain>::Signature, <MK as MultiKeychain>::PartialMultisignature>,
     Recipient,
     <H as Hasher>::Hash,
 );
 
-type OnNetworkAlertResult<H, D, MK> = Result<
+pub(crate) type OnNetworkAlertResult<H, D, MK> = Result<
     (
         Option<ForkingNotification<H, D, <MK as Keychain>::Signature>>,
         <H as Hasher>::Hash,
@@ -60,7 +119,10 @@ type OnNetworkAlertResult<H, D, MK> = Result<
     Error,
 >;
 
-type OnMessageResult<H, D, MK> = Result<
+type OnAlertConfirmedResult<H, D, MK> =
+    Result<ForkingNotification<H, D, <MK as Keychain>::Signature>, Error>;
+
+pub(crate) type OnMessageResult<H, D, MK> = Result<
     Option<
         AlerterResponse<
             H,
@@ -82,6 +144,14 @@ pub struct Handler<H: Hasher, D: Data, MK: MultiKeychain> {
     known_forkers: HashMap<NodeIndex, ForkProof<H, D, MK::Signature>>,
     known_alerts: KnownAlerts<H, D, MK>,
     known_rmcs: HashMap<(NodeIndex, NodeIndex), H::Hash>,
+    // At most one per sender and forker, like `known_rmcs`.
+    completed_rmcs: HashSet<H::Hash>,
+    confirmed_forkers: HashSet<NodeIndex>,
+    confirmation_threshold: Option<NodeCount>,
+    // The members known to hold the body of an alert, because they signed its hash.
+    alert_signers: HashMap<H::Hash, HashSet<NodeIndex>>,
+    observer: Option<Arc<dyn AlertObserver>>,
+    metrics: Arc<dyn Metrics>,
 }
 
 impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
@@ -92,6 +162,28 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
             known_forkers: HashMap::new(),
             known_alerts: HashMap::new(),
             known_rmcs: HashMap::new(),
+            completed_rmcs: HashSet::new(),
+            confirmed_forkers: HashSet::new(),
+            confirmation_threshold: config.confirmation_threshold,
+            alert_signers: HashMap::new(),
+            observer: None,
+            metrics: Arc::new(NoopMetrics),
+        }
+    }
+
+    pub fn with_observer(mut self, observer: Option<Arc<dyn AlertObserver>>) -> Self {
+        self.observer = observer;
+        self
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    fn observe(&self, sender: NodeIndex, forker: NodeIndex, stage: AlertStage) {
+        if let Some(observer) = &self.observer {
+            observer.on_alert_stage(sender, forker, stage);
         }
     }
 
@@ -100,9 +192,82 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
     }
 
     fn on_new_forker_detected(&mut self, forker: NodeIndex, proof: ForkProof<H, D, MK::Signature>) {
+        if let Some(observer) = &self.observer {
+            observer.on_fork_evidence(ForkEvidence::new(&proof));
+        }
         self.known_forkers.insert(forker, proof);
     }
 
+    /// The number of distinct forkers for which an alert completed reliable multicast.
+    /// Forkers we only suspect, with their alerts still being multicast, are not counted.
+    pub fn confirmed_forker_count(&self) -> usize {
+        self.confirmed_forkers.len()
+    }
+
+    /// All the forkers we know of, whether their alerts completed reliable multicast or not,
+    /// in no particular order.
+    pub fn known_forkers(&self) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.known_forkers.keys().cloned()
+    }
+
+    /// The proof we hold for the given forker, if we know of them.
+    pub fn fork_proof(&self, forker: NodeIndex) -> Option<&ForkProof<H, D, MK::Signature>> {
+        self.known_forkers.get(&forker)
+    }
+
+    /// Proofs for all the forkers we know of, used for reconciling our view with consensus.
+    pub fn known_forker_proofs(&self) -> Vec<ForkProof<H, D, MK::Signature>> {
+        self.known_forkers()
+            .filter_map(|forker| self.fork_proof(forker))
+            .cloned()
+            .collect()
+    }
+
+    /// A summary of the current state, for comparing with the states of other nodes.
+    pub fn debug_snapshot(&self) -> AlerterSnapshot<H> {
+        let mut forkers: Vec<_> = self.known_forkers().collect();
+        forkers.sort();
+        let mut alerts: Vec<_> = self
+            .known_rmcs
+            .iter()
+            .map(|(&(sender, forker), hash)| AlertSummary {
+                sender,
+                forker,
+                hash: *hash,
+                rmc_completed: self.completed_rmcs.contains(hash),
+            })
+            .collect();
+        alerts.sort_by_key(|alert| (alert.sender, alert.forker));
+        AlerterSnapshot { forkers, alerts }
+    }
+
+    /// The hashes of all the alerts whose bodies we hold and can send to anyone asking, sorted,
+    /// so that the sets of different nodes can be compared directly.
+    pub fn known_alert_hashes(&self) -> Vec<H::Hash> {
+        let mut hashes: Vec<_> = self.known_alerts.keys().cloned().collect();
+        hashes.sort();
+        hashes
+    }
+
+    /// Drops the body of the alert with the given hash, after which we can no longer send it to
+    /// anyone asking. The proof of the forker and the record of the alert being multicast are
+    /// kept, so the forker stays known and repeated copies of the alert are still recognized.
+    pub fn forget_alert(&mut self, hash: H::Hash) {
+        self.known_alerts.remove(&hash);
+    }
+
+    /// Forgets the body of a completed alert once every member signed its hash. Members only sign
+    /// alerts they hold, so nobody can still ask us for it.
+    fn forget_alert_held_by_everyone(&mut self, hash: &H::Hash) {
+        let held_by_everyone = self.alert_signers.get(hash).map_or(false, |signers| {
+            signers.len() >= self.keychain.node_count().0
+        });
+        if held_by_everyone && self.completed_rmcs.contains(hash) {
+            self.alert_signers.remove(hash);
+            self.forget_alert(*hash);
+        }
+    }
+
     // Correctness rules:
     // 1) All units must be created by forker
     // 2) All units must come from different rounds
@@ -129,34 +294,6 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
         Ok(())
     }
 
-    fn verify_fork(&self, alert: &Alert<H, D, MK::Signature>) -> Result<(), Error> {
-        let (u1, u2) = &alert.proof;
-        let (u1, u2) = {
-            let u1 = u1.clone().check(&self.keychain);
-            let u2 = u2.clone().check(&self.keychain);
-            match (u1, u2) {
-                (Ok(u1), Ok(u2)) => (u1, u2),
-                _ => return Err(Error::IncorrectlySignedUnit(alert.sender)),
-            }
-        };
-        let full_unit1 = u1.as_signable();
-        let full_unit2 = u2.as_signable();
-        if full_unit1.session_id() != self.session_id || full_unit2.session_id() != self.session_id
-        {
-            return Err(Error::WrongSession(alert.sender));
-        }
-        if full_unit1 == full_unit2 {
-            return Err(Error::SingleUnit(alert.sender));
-        }
-        if full_unit1.creator() != full_unit2.creator() {
-            return Err(Error::WrongCreator(alert.sender));
-        }
-        if full_unit1.round() != full_unit2.round() {
-            return Err(Error::DifferentRounds(alert.sender));
-        }
-        Ok(())
-    }
-
     /// Registers the RMC but does not actually send it; the returned hash must be passed to `start_rmc()` separately
     fn rmc_alert(
         &mut self,
@@ -164,9 +301,10 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
         alert: Signed<Alert<H, D, MK::Signature>, MK>,
     ) -> H::Hash {
         let hash = alert.as_signable().hash();
-        self.known_rmcs
-            .insert((alert.as_signable().sender, forker), hash);
+        let sender = alert.as_signable().sender;
+        self.known_rmcs.insert((sender, forker), hash);
         self.known_alerts.insert(hash, alert);
+        self.observe(sender, forker, AlertStage::RmcStarted);
         hash
     }
 
@@ -177,6 +315,7 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
     ) -> OnOwnAlertResult<H, D, MK> {
         let forker = alert.forker();
         self.known_forkers.insert(forker, alert.proof.clone());
+        self.observe(alert.sender, forker, AlertStage::Created);
         let alert = Signed::sign(alert, &self.keychain);
         let hash = self.rmc_alert(forker, alert.clone());
         (
@@ -189,22 +328,49 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
     /// May return a `ForkingNotification`, which should be propagated
     fn on_network_alert(
         &mut self,
-        alert: UncheckedSigned<Alert<H, D, MK::Signature>, MK::Signature>,
+        alert: UncheckedAlert<H, D, MK>,
+    ) -> OnNetworkAlertResult<H, D, MK> {
+        let alert = check_network_alert(&self.keychain, self.session_id, alert)?;
+        self.on_checked_network_alert(alert)
+    }
+
+    /// A verifier of alerts from the network, independent of the state of the handler, so that
+    /// alerts can be verified concurrently.
+    pub fn verifier(&self) -> AlertVerifier<MK> {
+        AlertVerifier {
+            keychain: self.keychain.clone(),
+            session_id: self.session_id,
+        }
+    }
+
+    /// Processes a batch of alerts from the network verified with [`Handler::verifier`], with the
+    /// same results as processing them one by one in order.
+    pub fn on_verified_network_alerts(
+        &mut self,
+        alerts: Vec<Result<CheckedAlert<H, D, MK>, Error>>,
+    ) -> Vec<OnNetworkAlertResult<H, D, MK>> {
+        alerts
+            .into_iter()
+            .map(|alert| self.on_checked_network_alert(alert?))
+            .collect()
+    }
+
+    fn on_checked_network_alert(
+        &mut self,
+        alert: CheckedAlert<H, D, MK>,
     ) -> OnNetworkAlertResult<H, D, MK> {
-        let alert = match alert.check(&self.keychain) {
-            Ok(alert) => alert,
-            Err(_) => {
-                return Err(Error::IncorrectlySignedAlert);
-            }
-        };
         let contents = alert.as_signable();
-        self.verify_fork(contents)?;
         let forker = contents.forker();
         let sender = alert.as_signable().sender;
         if self.known_rmcs.contains_key(&(contents.sender, forker)) {
-            self.known_alerts.insert(contents.hash(), alert);
+            // We might have asked for the alert after learning its hash, unless it is completed,
+            // in which case we either still have it or chose to forget it.
+            if !self.completed_rmcs.contains(&contents.hash()) {
+                self.known_alerts.insert(contents.hash(), alert);
+            }
             return Err(Error::RepeatedAlert(sender, forker));
         }
+        self.observe(sender, forker, AlertStage::Received);
         let propagate_alert = if self.is_forker(forker) {
             None
         } else {
@@ -227,7 +393,7 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
                 .on_network_alert(alert)
                 .map(|(n, h)| Some(AlerterResponse::ForkResponse(n, h))),
             RmcMessage(sender, message) => {
-                let hash = message.hash();
+                let hash = &message.hash().0;
                 if let Some(alert) = self.known_alerts.get(hash) {
                     let alert_id = (alert.as_signable().sender, alert.as_signable().forker());
                     if self.known_rmcs.get(&alert_id) == Some(hash) || message.is_complete() {
@@ -235,6 +401,9 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
                     } else {
                         Ok(None)
                     }
+                } else if self.completed_rmcs.contains(hash) {
+                    // Forgotten once everyone held it, so there is nothing left to ask for.
+                    Ok(None)
                 } else {
                     Ok(Some(AlerterResponse::AlertRequest(
                         *hash,
@@ -255,32 +424,162 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Handler<H, D, MK> {
     /// May return a `ForkingNotification`, which should be propagated
     pub fn alert_confirmed(
         &mut self,
-        multisigned: Multisigned<H::Hash, MK>,
-    ) -> Result<ForkingNotification<H, D, MK::Signature>, Error> {
-        let alert = match self.known_alerts.get(multisigned.as_signable()) {
+        multisigned: Multisigned<RmcHash<H::Hash>, MK>,
+    ) -> OnAlertConfirmedResult<H, D, MK> {
+        self.confirm_alert(&multisigned.as_signable().0)
+    }
+
+    /// Whether alerts are confirmed by counting the signatures of individual members rather than
+    /// by multisignatures, see [`AlertConfig::confirmation_threshold`].
+    pub fn counts_signatures(&self) -> bool {
+        self.confirmation_threshold.is_some()
+    }
+
+    /// Records the signature of an alert carried by the RMC message, to forget the alert once
+    /// everyone holds it. If alerts are confirmed by counting signatures, may return a
+    /// `ForkingNotification`, which should be propagated, once the alert is signed by as many
+    /// members as the threshold says.
+    pub fn on_rmc_message(
+        &mut self,
+        message: &RmcMessage<RmcHash<H::Hash>, MK::Signature, MK::PartialMultisignature>,
+    ) -> Option<OnAlertConfirmedResult<H, D, MK>> {
+        let unchecked = match message {
+            RmcMessage::SignedHash(unchecked) => unchecked,
+            RmcMessage::MultisignedHash(_) => return None,
+        };
+        let hash = unchecked.as_signable_strip_index().0;
+        let signer = unchecked.index();
+        if !self.known_alerts.contains_key(&hash)
+            || self
+                .alert_signers
+                .get(&hash)
+                .map_or(false, |signers| signers.contains(&signer))
+            || unchecked.clone().check(&self.keychain).is_err()
+        {
+            return None;
+        }
+        let signers = self.alert_signers.entry(hash).or_default();
+        signers.insert(signer);
+        let confirmation = match self.confirmation_threshold {
+            Some(threshold)
+                if signers.len() >= threshold.0 && !self.completed_rmcs.contains(&hash) =>
+            {
+                Some(self.confirm_alert(&hash))
+            }
+            _ => None,
+        };
+        self.forget_alert_held_by_everyone(&hash);
+        confirmation
+    }
+
+    fn confirm_alert(&mut self, hash: &H::Hash) -> OnAlertConfirmedResult<H, D, MK> {
+        let alert = match self.known_alerts.get(hash) {
             Some(alert) => alert.as_signable(),
             None => return Err(Error::UnknownAlertRMC),
         };
         let forker = alert.proof.0.as_signable().creator();
-        self.known_rmcs.insert((alert.sender, forker), alert.hash());
-        self.verify_commitment(alert)?;
-        Ok(ForkingNotification::Units(alert.legit_units.clone()))
+        let hash = alert.hash();
+        if let Some(replaced) = self.known_rmcs.insert((alert.sender, forker), hash) {
+            if replaced != hash {
+                self.completed_rmcs.remove(&replaced);
+            }
+        }
+        self.completed_rmcs.insert(hash);
+        self.observe(alert.sender, forker, AlertStage::RmcCompleted);
+        if self.confirmed_forkers.insert(forker) {
+            self.metrics
+                .report_confirmed_forkers(self.confirmed_forker_count());
+        }
+        let notification = self.verify_commitment(alert).map(|()| {
+            self.observe(alert.sender, forker, AlertStage::UnitsReleased);
+            ForkingNotification::Units(alert.legit_units.clone())
+        });
+        self.forget_alert_held_by_everyone(&hash);
+        notification
+    }
+}
+
+fn verify_fork<H: Hasher, D: Data, MK: MultiKeychain>(
+    keychain: &MK,
+    session_id: SessionId,
+    alert: &Alert<H, D, MK::Signature>,
+) -> Result<(), Error> {
+    let (u1, u2) = &alert.proof;
+    let (u1, u2) = {
+        let u1 = u1.clone().check(keychain);
+        let u2 = u2.clone().check(keychain);
+        match (u1, u2) {
+            (Ok(u1), Ok(u2)) => (u1, u2),
+            _ => return Err(Error::IncorrectlySignedUnit(alert.sender)),
+        }
+    };
+    let full_unit1 = u1.as_signable();
+    let full_unit2 = u2.as_signable();
+    if full_unit1.session_id() != session_id || full_unit2.session_id() != session_id {
+        return Err(Error::WrongSession(alert.sender));
+    }
+    if full_unit1 == full_unit2 {
+        return Err(Error::SingleUnit(alert.sender));
     }
+    if full_unit1.creator() != full_unit2.creator() {
+        return Err(Error::WrongCreator(alert.sender));
+    }
+    if full_unit1.round() != full_unit2.round() {
+        return Err(Error::DifferentRounds(alert.sender));
+    }
+    Ok(())
+}
+
+/// Verifies the signatures and fork proofs of alerts from the network, see
+/// [`Handler::verifier`].
+#[derive(Clone)]
+pub struct AlertVerifier<MK: MultiKeychain> {
+    keychain: MK,
+    session_id: SessionId,
+}
+
+impl<MK: MultiKeychain> AlertVerifier<MK> {
+    pub fn verify<H: Hasher, D: Data>(
+        &self,
+        alert: UncheckedAlert<H, D, MK>,
+    ) -> Result<CheckedAlert<H, D, MK>, Error> {
+        check_network_alert(&self.keychain, self.session_id, alert)
+    }
+}
+
+/// The part of processing an alert from the network that depends on nothing but the keychain and
+/// the session, so it can be done for many alerts at once.
+fn check_network_alert<H: Hasher, D: Data, MK: MultiKeychain>(
+    keychain: &MK,
+    session_id: SessionId,
+    alert: UncheckedAlert<H, D, MK>,
+) -> Result<CheckedAlert<H, D, MK>, Error> {
+    let alert = alert
+        .check(keychain)
+        .map_err(|_| Error::IncorrectlySignedAlert)?;
+    verify_fork(keychain, session_id, alert.as_signable())?;
+    Ok(alert)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         alerts::{
-            handler::{Error, Handler},
-            Alert, AlertConfig, AlertMessage, AlerterResponse, ForkProof, ForkingNotification,
-            RmcMessage,
+            handler::{
+                verify_fork, AlertObserver, AlertStage, AlertSummary, AlerterSnapshot, Error,
+                Handler,
+            },
+            Alert, AlertConfig, AlertMessage, AlerterResponse, ForkEvidence, ForkProof,
+            ForkingNotification, RmcHash, RmcMessage,
         },
         units::{ControlHash, FullUnit, PreUnit},
-        PartiallyMultisigned, Recipient, Round,
+        Metrics, Multisigned, PartiallyMultisigned, Recipient, Round,
     };
-    use aleph_bft_mock::{Data, Hasher64, Keychain, Signature};
-    use aleph_bft_types::{NodeCount, NodeIndex, NodeMap, Signable, Signed};
+    use aleph_bft_mock::{Data, Hash64, Hasher64, Keychain, Signature};
+    use aleph_bft_types::{NodeCount, NodeIndex, NodeMap, Signed};
+    use codec::Encode;
+    use parking_lot::Mutex;
+    use std::{sync::Arc, thread};
 
     type TestForkProof = ForkProof<Hasher64, Data, Signature>;
 
@@ -327,12 +626,14 @@ mod tests {
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = make_fork_proof(forker_index, &forker_keychain, 0, n_members);
         let alert = Alert::new(own_index, fork_proof, vec![]);
         let signed_alert = Signed::sign(alert.clone(), &this.keychain).into_unchecked();
-        let alert_hash = Signable::hash(&alert);
+        let alert_hash = alert.hash();
         assert_eq!(
             this.on_own_alert(alert),
             (
@@ -355,11 +656,13 @@ mod tests {
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = make_fork_proof(forker_index, &forker_keychain, 0, n_members);
         let alert = Alert::new(own_index, fork_proof.clone(), vec![]);
-        let alert_hash = Signable::hash(&alert);
+        let alert_hash = alert.hash();
         let signed_alert = Signed::sign(alert, &this.keychain).into_unchecked();
         assert_eq!(
             this.on_network_alert(signed_alert),
@@ -367,6 +670,80 @@ mod tests {
         );
     }
 
+    #[test]
+    fn batch_of_network_alerts_matches_serial_processing() {
+        let n_members = NodeCount(7);
+        let config = AlertConfig {
+            n_members,
+            session_id: 0,
+            max_units_per_forker_round: n_members.0,
+            confirmation_threshold: None,
+        };
+        let keychains: Vec<_> = n_members
+            .into_iterator()
+            .map(|index| Keychain::new(n_members, index))
+            .collect();
+        let signed_alert = |sender: usize, proof: TestForkProof| {
+            Signed::sign(
+                Alert::new(NodeIndex(sender), proof, vec![]),
+                &keychains[sender],
+            )
+            .into_unchecked()
+        };
+        let forker_proof = make_fork_proof(NodeIndex(6), &keychains[6], 0, n_members);
+        let other_forker_proof = make_fork_proof(NodeIndex(5), &keychains[5], 0, n_members);
+        let single_unit = Signed::sign(
+            full_unit(n_members, NodeIndex(5), 0, Some(0)),
+            &keychains[5],
+        )
+        .into_unchecked();
+        let different_rounds = Signed::sign(
+            full_unit(n_members, NodeIndex(5), 1, Some(1)),
+            &keychains[5],
+        )
+        .into_unchecked();
+        let alerts = vec![
+            signed_alert(1, forker_proof.clone()),
+            signed_alert(1, forker_proof.clone()),
+            signed_alert(2, forker_proof),
+            signed_alert(3, (single_unit.clone(), single_unit.clone())),
+            signed_alert(3, (single_unit, different_rounds)),
+            signed_alert(3, other_forker_proof),
+        ];
+
+        let mut serial = Handler::new(keychains[0], config.clone());
+        let serial_results: Vec<_> = alerts
+            .iter()
+            .cloned()
+            .map(|alert| serial.on_network_alert(alert))
+            .collect();
+        let mut batched = Handler::new(keychains[0], config);
+        let verifier = batched.verifier();
+        let verified = alerts
+            .into_iter()
+            .map(|alert| {
+                let verifier = verifier.clone();
+                thread::spawn(move || verifier.verify(alert))
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|verification| verification.join().expect("verification should not panic"))
+            .collect();
+        let batched_results = batched.on_verified_network_alerts(verified);
+
+        assert_eq!(batched_results, serial_results);
+        assert!(matches!(serial_results[0], Ok((Some(_), _))));
+        assert_eq!(
+            serial_results[1],
+            Err(Error::RepeatedAlert(NodeIndex(1), NodeIndex(6)))
+        );
+        assert!(matches!(serial_results[2], Ok((None, _))));
+        assert_eq!(serial_results[3], Err(Error::SingleUnit(NodeIndex(3))));
+        assert_eq!(serial_results[4], Err(Error::DifferentRounds(NodeIndex(3))));
+        assert!(matches!(serial_results[5], Ok((Some(_), _))));
+        assert_eq!(batched.debug_snapshot(), serial.debug_snapshot());
+    }
+
     #[test]
     fn asks_about_unknown_alert() {
         let n_members = NodeCount(7);
@@ -381,13 +758,15 @@ mod tests {
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = make_fork_proof(forker_index, &forker_keychain, 0, n_members);
         let alert = Alert::new(alerter_index, fork_proof, vec![]);
-        let alert_hash = Signable::hash(&alert);
+        let alert_hash = alert.hash();
         let signed_alert_hash =
-            Signed::sign_with_index(alert_hash, &alerter_keychain).into_unchecked();
+            Signed::sign_with_index(RmcHash(alert_hash), &alerter_keychain).into_unchecked();
         let message =
             AlertMessage::RmcMessage(alerter_index, RmcMessage::SignedHash(signed_alert_hash));
         let response = this.on_message(message);
@@ -412,6 +791,8 @@ mod tests {
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let valid_unit = Signed::sign(
@@ -440,6 +821,8 @@ mod tests {
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let alert = Alert::new(
@@ -447,7 +830,7 @@ mod tests {
             make_fork_proof(forker_index, &forker_keychain, 0, n_members),
             vec![],
         );
-        let alert_hash = Signable::hash(&alert);
+        let alert_hash = alert.hash();
         let signed_alert = Signed::sign(alert, &own_keychain).into_unchecked();
         this.on_message(AlertMessage::ForkAlert(signed_alert.clone()))
             .unwrap();
@@ -463,6 +846,38 @@ mod tests {
         }
     }
 
+    #[test]
+    fn enumerates_known_alert_hashes() {
+        let n_members = NodeCount(7);
+        let own_keychain = Keychain::new(n_members, NodeIndex(0));
+        let mut this = Handler::new(
+            own_keychain,
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        );
+        assert!(this.known_alert_hashes().is_empty());
+        let mut expected = Vec::new();
+        for (sender, forker) in [(1, 6), (2, 6), (3, 5)] {
+            let sender_keychain = Keychain::new(n_members, NodeIndex(sender));
+            let forker_keychain = Keychain::new(n_members, NodeIndex(forker));
+            let alert = Alert::new(
+                NodeIndex(sender),
+                make_fork_proof(NodeIndex(forker), &forker_keychain, 0, n_members),
+                vec![],
+            );
+            expected.push(alert.hash());
+            let signed_alert = Signed::sign(alert, &sender_keychain).into_unchecked();
+            this.on_message(AlertMessage::ForkAlert(signed_alert))
+                .unwrap();
+        }
+        expected.sort();
+        assert_eq!(this.known_alert_hashes(), expected);
+    }
+
     #[test]
     fn notifies_only_about_multisigned_alert() {
         let n_members = NodeCount(7);
@@ -478,15 +893,17 @@ mod tests {
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
         let empty_alert = Alert::new(double_committer, fork_proof.clone(), vec![]);
-        let empty_alert_hash = Signable::hash(&empty_alert);
+        let empty_alert_hash = empty_alert.hash();
         let signed_empty_alert =
             Signed::sign(empty_alert, &keychains[double_committer.0]).into_unchecked();
         let signed_empty_alert_hash =
-            Signed::sign_with_index(empty_alert_hash, &keychains[double_committer.0])
+            Signed::sign_with_index(RmcHash(empty_alert_hash), &keychains[double_committer.0])
                 .into_unchecked();
         let multisigned_empty_alert_hash = signed_empty_alert_hash
             .check(&keychains[double_committer.0])
@@ -506,11 +923,11 @@ mod tests {
         );
         let forker_unit = fork_proof.0.clone();
         let nonempty_alert = Alert::new(double_committer, fork_proof, vec![forker_unit]);
-        let nonempty_alert_hash = Signable::hash(&nonempty_alert);
+        let nonempty_alert_hash = nonempty_alert.hash();
         let signed_nonempty_alert =
             Signed::sign(nonempty_alert, &keychains[double_committer.0]).into_unchecked();
         let signed_nonempty_alert_hash =
-            Signed::sign_with_index(nonempty_alert_hash, &keychains[double_committer.0])
+            Signed::sign_with_index(RmcHash(nonempty_alert_hash), &keychains[double_committer.0])
                 .into_unchecked();
         let mut multisigned_nonempty_alert_hash = signed_nonempty_alert_hash
             .check(&keychains[double_committer.0])
@@ -519,7 +936,7 @@ mod tests {
         for i in 1..n_members.0 - 2 {
             let node_id = NodeIndex(i);
             let signed_nonempty_alert_hash =
-                Signed::sign_with_index(nonempty_alert_hash, &keychains[node_id.0])
+                Signed::sign_with_index(RmcHash(nonempty_alert_hash), &keychains[node_id.0])
                     .into_unchecked();
             multisigned_nonempty_alert_hash = multisigned_nonempty_alert_hash.add_signature(
                 signed_nonempty_alert_hash
@@ -554,11 +971,13 @@ mod tests {
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
         let empty_alert = Alert::new(double_committer, fork_proof.clone(), vec![]);
-        let empty_alert_hash = Signable::hash(&empty_alert);
+        let empty_alert_hash = empty_alert.hash();
         let signed_empty_alert =
             Signed::sign(empty_alert, &keychains[double_committer.0]).into_unchecked();
         assert_eq!(
@@ -570,11 +989,11 @@ mod tests {
         );
         let forker_unit = fork_proof.0.clone();
         let nonempty_alert = Alert::new(double_committer, fork_proof, vec![forker_unit]);
-        let nonempty_alert_hash = Signable::hash(&nonempty_alert);
+        let nonempty_alert_hash = nonempty_alert.hash();
         let signed_nonempty_alert =
             Signed::sign(nonempty_alert, &keychains[double_committer.0]).into_unchecked();
         let signed_nonempty_alert_hash =
-            Signed::sign_with_index(nonempty_alert_hash, &keychains[double_committer.0])
+            Signed::sign_with_index(RmcHash(nonempty_alert_hash), &keychains[double_committer.0])
                 .into_unchecked();
         let mut multisigned_nonempty_alert_hash = signed_nonempty_alert_hash
             .check(&keychains[double_committer.0])
@@ -583,7 +1002,7 @@ mod tests {
         for i in 1..3 {
             let node_id = NodeIndex(i);
             let signed_nonempty_alert_hash =
-                Signed::sign_with_index(nonempty_alert_hash, &keychains[node_id.0])
+                Signed::sign_with_index(RmcHash(nonempty_alert_hash), &keychains[node_id.0])
                     .into_unchecked();
             multisigned_nonempty_alert_hash = multisigned_nonempty_alert_hash.add_signature(
                 signed_nonempty_alert_hash
@@ -610,16 +1029,18 @@ mod tests {
         let forker_index = NodeIndex(6);
         let own_keychain = Keychain::new(n_members, own_index);
         let forker_keychain = Keychain::new(n_members, forker_index);
-        let this = Handler::new(
+        let this: Handler<Hasher64, Data, _> = Handler::new(
             own_keychain,
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = make_fork_proof(forker_index, &forker_keychain, 0, n_members);
         let alert = Alert::new(own_index, fork_proof, vec![]);
-        assert_eq!(this.verify_fork(&alert), Ok(()));
+        assert_eq!(verify_fork(&this.keychain, this.session_id, &alert), Ok(()));
     }
 
     #[test]
@@ -629,17 +1050,19 @@ mod tests {
         let forker_index = NodeIndex(6);
         let own_keychain = Keychain::new(n_members, own_index);
         let forker_keychain = Keychain::new(n_members, forker_index);
-        let this = Handler::new(
+        let this: Handler<Hasher64, Data, _> = Handler::new(
             own_keychain,
             AlertConfig {
                 n_members,
                 session_id: 1,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = make_fork_proof(forker_index, &forker_keychain, 0, n_members);
         let alert = Alert::new(own_index, fork_proof, vec![]);
         assert_eq!(
-            this.verify_fork(&alert),
+            verify_fork(&this.keychain, this.session_id, &alert),
             Err(Error::WrongSession(own_index))
         );
     }
@@ -650,11 +1073,13 @@ mod tests {
         let keychains: Vec<_> = (0..n_members.0)
             .map(|i| Keychain::new(n_members, NodeIndex(i)))
             .collect();
-        let this = Handler::new(
+        let this: Handler<Hasher64, Data, _> = Handler::new(
             keychains[0],
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = {
@@ -666,7 +1091,10 @@ mod tests {
         };
         let sender = NodeIndex(0);
         let alert = Alert::new(sender, fork_proof, vec![]);
-        assert_eq!(this.verify_fork(&alert), Err(Error::WrongCreator(sender)));
+        assert_eq!(
+            verify_fork(&this.keychain, this.session_id, &alert),
+            Err(Error::WrongCreator(sender))
+        );
     }
 
     #[test]
@@ -676,11 +1104,13 @@ mod tests {
         let forker_index = NodeIndex(6);
         let own_keychain = Keychain::new(n_members, own_index);
         let forker_keychain = Keychain::new(n_members, forker_index);
-        let this = Handler::new(
+        let this: Handler<Hasher64, Data, _> = Handler::new(
             own_keychain,
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = {
@@ -692,7 +1122,7 @@ mod tests {
         };
         let alert = Alert::new(own_index, fork_proof, vec![]);
         assert_eq!(
-            this.verify_fork(&alert),
+            verify_fork(&this.keychain, this.session_id, &alert),
             Err(Error::DifferentRounds(own_index))
         );
     }
@@ -712,6 +1142,38 @@ mod tests {
         alert_confirmed(true, true);
     }
 
+    fn multisign_alert_hash(
+        alert_hash: Hash64,
+        keychains: &[Keychain],
+        own_index: NodeIndex,
+        forker_index: NodeIndex,
+    ) -> Multisigned<RmcHash<Hash64>, Keychain> {
+        let n_members = keychains.len();
+        let signed_alert_hash =
+            Signed::sign_with_index(RmcHash(alert_hash), &keychains[own_index.0]).into_unchecked();
+        let mut multisigned_alert_hash = signed_alert_hash
+            .check(&keychains[forker_index.0])
+            .expect("the signature is correct")
+            .into_partially_multisigned(&keychains[own_index.0]);
+        for i in 1..n_members - 1 {
+            let node_id = NodeIndex(i);
+            let signed_alert_hash =
+                Signed::sign_with_index(RmcHash(alert_hash), &keychains[node_id.0])
+                    .into_unchecked();
+            multisigned_alert_hash = multisigned_alert_hash.add_signature(
+                signed_alert_hash
+                    .check(&keychains[forker_index.0])
+                    .expect("the signature is correct"),
+                &keychains[forker_index.0],
+            );
+        }
+        assert!(multisigned_alert_hash.is_complete());
+        match multisigned_alert_hash {
+            PartiallyMultisigned::Complete { multisigned } => multisigned,
+            PartiallyMultisigned::Incomplete { .. } => unreachable!(),
+        }
+    }
+
     fn alert_confirmed(make_known: bool, good_commitment: bool) {
         let n_members = NodeCount(7);
         let own_index = NodeIndex(1);
@@ -724,6 +1186,8 @@ mod tests {
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
         let fork_proof = if good_commitment {
@@ -736,33 +1200,13 @@ mod tests {
             (signed_unit_0, signed_unit_1)
         };
         let alert = Alert::new(own_index, fork_proof, vec![]);
-        let alert_hash = Signable::hash(&alert);
+        let alert_hash = alert.hash();
         let signed_alert = Signed::sign(alert, &keychains[own_index.0]).into_unchecked();
         if make_known {
             let _ = this.on_network_alert(signed_alert);
         }
-        let signed_alert_hash =
-            Signed::sign_with_index(alert_hash, &keychains[own_index.0]).into_unchecked();
-        let mut multisigned_alert_hash = signed_alert_hash
-            .check(&keychains[forker_index.0])
-            .expect("the signature is correct")
-            .into_partially_multisigned(&keychains[own_index.0]);
-        for i in 1..n_members.0 - 1 {
-            let node_id = NodeIndex(i);
-            let signed_alert_hash =
-                Signed::sign_with_index(alert_hash, &keychains[node_id.0]).into_unchecked();
-            multisigned_alert_hash = multisigned_alert_hash.add_signature(
-                signed_alert_hash
-                    .check(&keychains[forker_index.0])
-                    .expect("the signature is correct"),
-                &keychains[forker_index.0],
-            );
-        }
-        assert!(multisigned_alert_hash.is_complete());
-        let multisigned_alert_hash = match multisigned_alert_hash {
-            PartiallyMultisigned::Complete { multisigned } => multisigned,
-            PartiallyMultisigned::Incomplete { .. } => unreachable!(),
-        };
+        let multisigned_alert_hash =
+            multisign_alert_hash(alert_hash, &keychains, own_index, forker_index);
         let expected = match (make_known, good_commitment) {
             (true, true) => Ok(ForkingNotification::Units(vec![])),
             (true, false) => Err(Error::UnknownAlertRMC),
@@ -771,4 +1215,431 @@ mod tests {
         };
         assert_eq!(this.alert_confirmed(multisigned_alert_hash), expected);
     }
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        stages: Mutex<Vec<(NodeIndex, NodeIndex, AlertStage)>>,
+        evidence: Mutex<Vec<ForkEvidence>>,
+    }
+
+    impl AlertObserver for RecordingObserver {
+        fn on_alert_stage(&self, sender: NodeIndex, forker: NodeIndex, stage: AlertStage) {
+            self.stages.lock().push((sender, forker, stage));
+        }
+
+        fn on_fork_evidence(&self, evidence: ForkEvidence) {
+            self.evidence.lock().push(evidence);
+        }
+    }
+
+    #[test]
+    fn observer_gets_evidence_of_verified_fork() {
+        let n_members = NodeCount(7);
+        let own_index = NodeIndex(1);
+        let alerter_index = NodeIndex(2);
+        let forker_index = NodeIndex(6);
+        let keychains: Vec<_> = (0..n_members.0)
+            .map(|i| Keychain::new(n_members, NodeIndex(i)))
+            .collect();
+        let observer = Arc::new(RecordingObserver::default());
+        let mut this = Handler::new(
+            keychains[own_index.0],
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        )
+        .with_observer(Some(observer.clone()));
+        let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 3, n_members);
+        let alert = Alert::new(alerter_index, fork_proof.clone(), vec![]);
+        let signed_alert = Signed::sign(alert, &keychains[alerter_index.0]).into_unchecked();
+
+        assert!(this.on_network_alert(signed_alert).is_ok());
+
+        assert_eq!(
+            *observer.evidence.lock(),
+            vec![ForkEvidence {
+                forker: forker_index,
+                round: 3,
+                units: (fork_proof.0.encode(), fork_proof.1.encode()),
+            }]
+        );
+    }
+
+    #[test]
+    fn observer_sees_alert_lifecycle() {
+        let n_members = NodeCount(7);
+        let own_index = NodeIndex(1);
+        let forker_index = NodeIndex(6);
+        let keychains: Vec<_> = (0..n_members.0)
+            .map(|i| Keychain::new(n_members, NodeIndex(i)))
+            .collect();
+        let observer = Arc::new(RecordingObserver::default());
+        let mut this = Handler::new(
+            keychains[own_index.0],
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        )
+        .with_observer(Some(observer.clone()));
+        let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
+        let alert = Alert::new(own_index, fork_proof, vec![]);
+
+        let (_, _, alert_hash) = this.on_own_alert(alert);
+        let multisigned_alert_hash =
+            multisign_alert_hash(alert_hash, &keychains, own_index, forker_index);
+        assert_eq!(
+            this.alert_confirmed(multisigned_alert_hash),
+            Ok(ForkingNotification::Units(vec![]))
+        );
+
+        assert_eq!(
+            *observer.stages.lock(),
+            vec![
+                (own_index, forker_index, AlertStage::Created),
+                (own_index, forker_index, AlertStage::RmcStarted),
+                (own_index, forker_index, AlertStage::RmcCompleted),
+                (own_index, forker_index, AlertStage::UnitsReleased),
+            ]
+        );
+    }
+
+    #[test]
+    fn snapshot_reflects_forkers_and_rmcs() {
+        let n_members = NodeCount(7);
+        let own_index = NodeIndex(1);
+        let alerter_index = NodeIndex(2);
+        let forker_index = NodeIndex(6);
+        let keychains: Vec<_> = (0..n_members.0)
+            .map(|i| Keychain::new(n_members, NodeIndex(i)))
+            .collect();
+        let mut this = Handler::new(
+            keychains[own_index.0],
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        );
+        assert_eq!(
+            this.debug_snapshot(),
+            AlerterSnapshot {
+                forkers: vec![],
+                alerts: vec![],
+            }
+        );
+
+        let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
+        let network_alert = Alert::new(alerter_index, fork_proof.clone(), vec![]);
+        let network_alert_hash = network_alert.hash();
+        let signed_alert =
+            Signed::sign(network_alert, &keychains[alerter_index.0]).into_unchecked();
+        assert!(this.on_network_alert(signed_alert).is_ok());
+        let own_alert = Alert::new(own_index, fork_proof, vec![]);
+        let (_, _, own_alert_hash) = this.on_own_alert(own_alert);
+        let multisigned_alert_hash =
+            multisign_alert_hash(own_alert_hash, &keychains, own_index, forker_index);
+        assert!(this.alert_confirmed(multisigned_alert_hash).is_ok());
+
+        assert_eq!(
+            this.debug_snapshot(),
+            AlerterSnapshot {
+                forkers: vec![forker_index],
+                alerts: vec![
+                    AlertSummary {
+                        sender: own_index,
+                        forker: forker_index,
+                        hash: own_alert_hash,
+                        rmc_completed: true,
+                    },
+                    AlertSummary {
+                        sender: alerter_index,
+                        forker: forker_index,
+                        hash: network_alert_hash,
+                        rmc_completed: false,
+                    },
+                ],
+            }
+        );
+    }
+
+    #[derive(Default)]
+    struct ConfirmedForkersMetrics {
+        reported: Mutex<Vec<usize>>,
+    }
+
+    impl Metrics for ConfirmedForkersMetrics {
+        fn report_confirmed_forkers(&self, count: usize) {
+            self.reported.lock().push(count);
+        }
+    }
+
+    #[test]
+    fn counts_forker_as_confirmed_only_once_rmc_completes() {
+        let n_members = NodeCount(7);
+        let own_index = NodeIndex(1);
+        let alerter_index = NodeIndex(2);
+        let forker_index = NodeIndex(6);
+        let keychains: Vec<_> = (0..n_members.0)
+            .map(|i| Keychain::new(n_members, NodeIndex(i)))
+            .collect();
+        let metrics = Arc::new(ConfirmedForkersMetrics::default());
+        let mut this = Handler::new(
+            keychains[own_index.0],
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        )
+        .with_metrics(metrics.clone());
+        let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
+
+        let network_alert = Alert::new(alerter_index, fork_proof.clone(), vec![]);
+        let network_alert_hash = network_alert.hash();
+        let signed_alert =
+            Signed::sign(network_alert, &keychains[alerter_index.0]).into_unchecked();
+        assert!(this.on_network_alert(signed_alert).is_ok());
+        let (_, _, own_alert_hash) = this.on_own_alert(Alert::new(own_index, fork_proof, vec![]));
+        assert_eq!(this.debug_snapshot().forkers, vec![forker_index]);
+        assert_eq!(this.confirmed_forker_count(), 0);
+        assert!(metrics.reported.lock().is_empty());
+
+        let multisigned_alert_hash =
+            multisign_alert_hash(own_alert_hash, &keychains, own_index, forker_index);
+        assert!(this.alert_confirmed(multisigned_alert_hash).is_ok());
+        assert_eq!(this.confirmed_forker_count(), 1);
+        assert_eq!(*metrics.reported.lock(), vec![1]);
+
+        // Another alert about the same forker completing does not make it any more confirmed.
+        let multisigned_alert_hash =
+            multisign_alert_hash(network_alert_hash, &keychains, own_index, forker_index);
+        assert!(this.alert_confirmed(multisigned_alert_hash).is_ok());
+        assert_eq!(this.confirmed_forker_count(), 1);
+        assert_eq!(*metrics.reported.lock(), vec![1]);
+    }
+
+    #[test]
+    fn exposes_forkers_detected_from_network_alerts() {
+        let n_members = NodeCount(7);
+        let own_index = NodeIndex(0);
+        let alerter_index = NodeIndex(1);
+        let forker_index = NodeIndex(6);
+        let alerter_keychain = Keychain::new(n_members, alerter_index);
+        let forker_keychain = Keychain::new(n_members, forker_index);
+        let mut this = Handler::new(
+            Keychain::new(n_members, own_index),
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        );
+        assert_eq!(this.known_forkers().count(), 0);
+        assert!(this.fork_proof(forker_index).is_none());
+
+        let fork_proof = make_fork_proof(forker_index, &forker_keychain, 0, n_members);
+        let alert = Alert::new(alerter_index, fork_proof.clone(), vec![]);
+        let signed_alert = Signed::sign(alert, &alerter_keychain).into_unchecked();
+        assert!(this.on_network_alert(signed_alert).is_ok());
+
+        assert_eq!(this.known_forkers().collect::<Vec<_>>(), vec![forker_index]);
+        assert_eq!(this.fork_proof(forker_index), Some(&fork_proof));
+        assert!(this.fork_proof(alerter_index).is_none());
+    }
+
+    #[test]
+    fn forgets_completed_alerts_held_by_everyone() {
+        let n_members = NodeCount(7);
+        let own_index = NodeIndex(1);
+        let alerter_index = NodeIndex(2);
+        let forker_index = NodeIndex(6);
+        let keychains: Vec<_> = (0..n_members.0)
+            .map(|i| Keychain::new(n_members, NodeIndex(i)))
+            .collect();
+        let mut this = Handler::new(
+            keychains[own_index.0],
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        );
+        let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
+        let alert = Alert::new(alerter_index, fork_proof, vec![]);
+        let alert_hash = alert.hash();
+        let signed_alert = Signed::sign(alert, &keychains[alerter_index.0]).into_unchecked();
+        assert!(this.on_network_alert(signed_alert.clone()).is_ok());
+        let requester = NodeIndex(3);
+        let signed_hash = |signer: usize| {
+            RmcMessage::SignedHash(
+                Signed::sign_with_index(RmcHash(alert_hash), &keychains[signer]).into_unchecked(),
+            )
+        };
+
+        // Everyone holds the alert, but it is not completed yet.
+        for signer in 0..n_members.0 {
+            assert!(this.on_rmc_message(&signed_hash(signer)).is_none());
+        }
+        assert!(this
+            .on_message(AlertMessage::AlertRequest(requester, alert_hash))
+            .is_ok());
+
+        let multisigned_alert_hash =
+            multisign_alert_hash(alert_hash, &keychains, own_index, forker_index);
+        assert!(this.alert_confirmed(multisigned_alert_hash).is_ok());
+        assert_eq!(
+            this.on_message(AlertMessage::AlertRequest(requester, alert_hash)),
+            Err(Error::UnknownAlertRequest)
+        );
+        assert!(this.is_forker(forker_index));
+        assert_eq!(this.known_forker_proofs().len(), 1);
+        // Nobody gets asked for it again when it keeps being multicast.
+        assert_eq!(
+            this.on_message(AlertMessage::RmcMessage(requester, signed_hash(3))),
+            Ok(None)
+        );
+        // A repeated copy is still recognized, without bringing the body back.
+        assert_eq!(
+            this.on_network_alert(signed_alert),
+            Err(Error::RepeatedAlert(alerter_index, forker_index))
+        );
+        assert_eq!(
+            this.on_message(AlertMessage::AlertRequest(requester, alert_hash)),
+            Err(Error::UnknownAlertRequest)
+        );
+    }
+
+    #[test]
+    fn keeps_completed_alerts_someone_might_ask_for() {
+        let n_members = NodeCount(7);
+        let own_index = NodeIndex(1);
+        let alerter_index = NodeIndex(2);
+        let forker_index = NodeIndex(6);
+        let keychains: Vec<_> = (0..n_members.0)
+            .map(|i| Keychain::new(n_members, NodeIndex(i)))
+            .collect();
+        let mut this = Handler::new(
+            keychains[own_index.0],
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        );
+        let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
+        let alert = Alert::new(alerter_index, fork_proof, vec![]);
+        let alert_hash = alert.hash();
+        let signed_alert = Signed::sign(alert, &keychains[alerter_index.0]).into_unchecked();
+        assert!(this.on_network_alert(signed_alert).is_ok());
+        let multisigned_alert_hash =
+            multisign_alert_hash(alert_hash, &keychains, own_index, forker_index);
+        assert!(this.alert_confirmed(multisigned_alert_hash).is_ok());
+
+        // The forker never signs, so it might still ask.
+        for keychain in &keychains[..forker_index.0] {
+            let signed_hash =
+                Signed::sign_with_index(RmcHash(alert_hash), keychain).into_unchecked();
+            assert!(this
+                .on_rmc_message(&RmcMessage::SignedHash(signed_hash))
+                .is_none());
+        }
+        assert!(this
+            .on_message(AlertMessage::AlertRequest(forker_index, alert_hash))
+            .is_ok());
+
+        this.forget_alert(alert_hash);
+        assert_eq!(
+            this.on_message(AlertMessage::AlertRequest(forker_index, alert_hash)),
+            Err(Error::UnknownAlertRequest)
+        );
+        assert!(this.is_forker(forker_index));
+    }
+
+    #[test]
+    fn confirms_alert_at_the_quorum_of_the_keychain() {
+        let n_members = NodeCount(7);
+        let quorum = 6;
+        let own_index = NodeIndex(0);
+        let forker_index = NodeIndex(6);
+        let keychains: Vec<_> = (0..n_members.0)
+            .map(|i| Keychain::new(n_members, NodeIndex(i)).with_quorum(quorum))
+            .collect();
+        let mut this = Handler::new(
+            keychains[own_index.0],
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
+            },
+        );
+        let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
+        let (_, _, alert_hash) = this.on_own_alert(Alert::new(own_index, fork_proof, vec![]));
+
+        let mut multisigned_alert_hash =
+            Signed::sign_with_index(RmcHash(alert_hash), &keychains[0])
+                .into_partially_multisigned(&keychains[own_index.0]);
+        // The default quorum of 5 out of 7 does not suffice.
+        for keychain in &keychains[1..quorum] {
+            assert!(!multisigned_alert_hash.is_complete());
+            multisigned_alert_hash = multisigned_alert_hash.add_signature(
+                Signed::sign_with_index(RmcHash(alert_hash), keychain),
+                &keychains[own_index.0],
+            );
+        }
+        let multisigned_alert_hash = match multisigned_alert_hash {
+            PartiallyMultisigned::Complete { multisigned } => multisigned,
+            PartiallyMultisigned::Incomplete { .. } => panic!("the quorum should be reached"),
+        };
+        assert!(this.alert_confirmed(multisigned_alert_hash).is_ok());
+        assert_eq!(this.confirmed_forker_count(), 1);
+    }
+
+    #[test]
+    fn confirms_alert_at_the_configured_threshold() {
+        let n_members = NodeCount(7);
+        let own_index = NodeIndex(0);
+        let forker_index = NodeIndex(6);
+        let keychains = Keychain::new_vec(n_members);
+        let mut this = Handler::new(
+            keychains[own_index.0],
+            AlertConfig {
+                n_members,
+                session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: Some(NodeCount(3)),
+            },
+        );
+        assert!(this.counts_signatures());
+        let fork_proof = make_fork_proof(forker_index, &keychains[forker_index.0], 0, n_members);
+        let (_, _, alert_hash) = this.on_own_alert(Alert::new(own_index, fork_proof, vec![]));
+        let signature_of = |keychain: &Keychain| {
+            RmcMessage::SignedHash(
+                Signed::sign_with_index(RmcHash(alert_hash), keychain).into_unchecked(),
+            )
+        };
+
+        assert!(this.on_rmc_message(&signature_of(&keychains[0])).is_none());
+        assert!(this.on_rmc_message(&signature_of(&keychains[1])).is_none());
+        // Repeated signatures do not count.
+        assert!(this.on_rmc_message(&signature_of(&keychains[1])).is_none());
+        assert_eq!(this.confirmed_forker_count(), 0);
+        assert_eq!(
+            this.on_rmc_message(&signature_of(&keychains[2])),
+            Some(Ok(ForkingNotification::Units(Vec::new())))
+        );
+        assert_eq!(this.confirmed_forker_count(), 1);
+        assert!(this.on_rmc_message(&signature_of(&keychains[3])).is_none());
+    }
 }
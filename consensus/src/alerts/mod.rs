@@ -1,6 +1,7 @@
 use crate::{
-    units::UncheckedSignedUnit, Data, Hasher, Index, Keychain, MultiKeychain, NodeCount, NodeIndex,
-    PartialMultisignature, Recipient, SessionId, Signable, Signature, UncheckedSigned,
+    signing::SigningDomain, units::UncheckedSignedUnit, Data, Hasher, Index, Keychain,
+    MultiKeychain, NodeCount, NodeIndex, PartialMultisignature, Recipient, Round, SessionId,
+    Signable, Signature, UncheckedSigned,
 };
 use aleph_bft_rmc::Message as RmcMessage;
 use codec::{Decode, Encode};
@@ -11,11 +12,59 @@ use std::ops::Deref;
 mod handler;
 mod service;
 
-pub use handler::Handler;
+pub use handler::{AlertObserver, AlertStage, AlertSummary, AlerterSnapshot, Handler};
 pub use service::Service;
 
 pub type ForkProof<H, D, S> = (UncheckedSignedUnit<H, D, S>, UncheckedSignedUnit<H, D, S>);
 
+/// A verified fork proof, meant to be archived as evidence of misbehavior. The two conflicting
+/// units signed by the forker are kept SCALE encoded, exactly as they were received.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForkEvidence {
+    /// The node that created both units.
+    pub forker: NodeIndex,
+    /// The round of both units.
+    pub round: Round,
+    /// The encoded conflicting units.
+    pub units: (Vec<u8>, Vec<u8>),
+}
+
+impl ForkEvidence {
+    /// The evidence contained in the proof, which should already be verified.
+    pub(crate) fn new<H: Hasher, D: Data, S: Signature>(proof: &ForkProof<H, D, S>) -> Self {
+        let full_unit = proof.0.as_signable();
+        ForkEvidence {
+            forker: full_unit.creator(),
+            round: full_unit.round(),
+            units: (proof.0.encode(), proof.1.encode()),
+        }
+    }
+}
+
+/// Notified about every newly detected forker, e.g. to report it to a slashing mechanism.
+/// Each forker is reported once, no matter how many more units it produces afterwards.
+pub trait ForkDetectionHandler<D: Data>: Send + 'static {
+    /// A fork of `forker` has been detected, with the given proof already verified.
+    fn fork_detected<H: Hasher, S: Signature>(
+        &mut self,
+        forker: NodeIndex,
+        proof: ForkProof<H, D, S>,
+    );
+}
+
+/// A [`ForkDetectionHandler`] ignoring all the detected forks.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopForkDetectionHandler;
+
+impl<D: Data> ForkDetectionHandler<D> for NoopForkDetectionHandler {
+    fn fork_detected<H: Hasher, S: Signature>(
+        &mut self,
+        _forker: NodeIndex,
+        _proof: ForkProof<H, D, S>,
+    ) {
+    }
+}
+
 pub type NetworkMessage<H, D, MK> =
     AlertMessage<H, D, <MK as Keychain>::Signature, <MK as MultiKeychain>::PartialMultisignature>;
 
@@ -60,7 +109,7 @@ impl<H: Hasher, D: Data, S: Signature> Alert<H, D, S> {
         }
     }
 
-    fn hash(&self) -> H::Hash {
+    pub(crate) fn hash(&self) -> H::Hash {
         let hash = *self.hash.read();
         match hash {
             Some(hash) => hash,
@@ -94,9 +143,21 @@ impl<H: Hasher, D: Data, S: Signature> Index for Alert<H, D, S> {
 }
 
 impl<H: Hasher, D: Data, S: Signature> Signable for Alert<H, D, S> {
-    type Hash = H::Hash;
+    type Hash = Vec<u8>;
+    fn hash(&self) -> Self::Hash {
+        SigningDomain::Alert.message(self.hash().as_ref())
+    }
+}
+
+/// The hash of an alert as multicast by the RMC. Signatures over it are tagged with their own
+/// domain, so that they cannot be passed off as signatures over any other payload.
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Debug, Decode, Encode)]
+pub struct RmcHash<T>(pub T);
+
+impl<T: AsRef<[u8]>> Signable for RmcHash<T> {
+    type Hash = Vec<u8>;
     fn hash(&self) -> Self::Hash {
-        self.hash()
+        SigningDomain::Rmc.message(self.0.as_ref())
     }
 }
 
@@ -106,7 +167,7 @@ pub enum AlertMessage<H: Hasher, D: Data, S: Signature, MS: PartialMultisignatur
     /// Alert regarding forks, signed by the person claiming misconduct.
     ForkAlert(UncheckedSigned<Alert<H, D, S>, S>),
     /// An internal RMC message, together with the id of the sender.
-    RmcMessage(NodeIndex, RmcMessage<H::Hash, S, MS>),
+    RmcMessage(NodeIndex, RmcMessage<RmcHash<H::Hash>, S, MS>),
     /// A request by a node for a fork alert identified by the given hash.
     AlertRequest(NodeIndex, H::Hash),
 }
@@ -145,7 +206,7 @@ pub enum AlerterResponse<H: Hasher, D: Data, S: Signature, MS: PartialMultisigna
     /// An internal RMC message, with the sender being the local node.
     ///
     /// This variant should be handled by sending the message.
-    RmcMessage(RmcMessage<H::Hash, S, MS>),
+    RmcMessage(RmcMessage<RmcHash<H::Hash>, S, MS>),
 }
 
 // Notifications being sent to consensus, so that it can learn about proven forkers and receive
@@ -154,10 +215,25 @@ pub enum AlerterResponse<H: Hasher, D: Data, S: Signature, MS: PartialMultisigna
 pub enum ForkingNotification<H: Hasher, D: Data, S: Signature> {
     Forker(ForkProof<H, D, S>),
     Units(Vec<UncheckedSignedUnit<H, D, S>>),
+    // All the forkers known to the alerter, sent periodically so that consensus can detect and
+    // make up for any notifications it missed.
+    KnownForkers(Vec<ForkProof<H, D, S>>),
 }
 
+/// Parameters of the alerter. By default an alert is confirmed once
+/// [`MultiKeychain::is_complete`](crate::MultiKeychain::is_complete) accepts its multisignature.
+/// Multisignatures are used only for alerts, so the quorum can differ from the threshold of parents
+/// of units.
 #[derive(Clone, Eq, PartialEq, Hash, Debug, Default)]
 pub struct AlertConfig {
     pub n_members: NodeCount,
     pub session_id: SessionId,
+    /// How many units of a single forker in a single round consensus accepts from alerts, anything
+    /// beyond is dropped. Honest nodes alert about a forker at most once each, committing to at most
+    /// one unit per round, so `n_members` is never exceeded by legit alerts.
+    pub max_units_per_forker_round: usize,
+    /// How many members have to sign an alert for it to be confirmed, if not the quorum of the
+    /// keychain. The signatures of individual members are counted then, and the multisignatures
+    /// are ignored. Meant for experiments with different fault assumptions.
+    pub confirmation_threshold: Option<NodeCount>,
 }
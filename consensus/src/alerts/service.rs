@@ -1,26 +1,48 @@
 use crate::{
     alerts::{
-        handler::Handler, Alert, AlertMessage, AlerterResponse, ForkingNotification, NetworkMessage,
+        handler::{AlertVerifier, CheckedAlert, Error, Handler, OnMessageResult, UncheckedAlert},
+        Alert, AlertMessage, AlerterResponse, ForkingNotification, NetworkMessage, RmcHash,
     },
-    Data, Hasher, MultiKeychain, Multisigned, NodeCount, NodeIndex, Receiver, Recipient, Sender,
-    Terminator,
+    runway::interval_due,
+    Clock, Data, Hasher, Keychain, MultiKeychain, Multisigned, NodeCount, NodeIndex, Receiver,
+    Recipient, Sender, SpawnHandle, Terminator,
 };
 use aleph_bft_rmc::{DoublingDelayScheduler, Message as RmcMessage, ReliableMulticast};
-use futures::{channel::mpsc, FutureExt, StreamExt};
-use log::{debug, error, warn};
-use std::time;
+use futures::{
+    channel::{mpsc, oneshot},
+    future::{self, BoxFuture},
+    FutureExt, StreamExt,
+};
+use log::{debug, error, trace, warn};
+use std::{sync::Arc, time};
 
 const LOG_TARGET: &str = "AlephBFT-alerter";
+// Messages from the network already waiting are handled together, up to this many at a time, so
+// that the alerts among them can be verified as a batch.
+const MAX_MESSAGE_BATCH: usize = 64;
+// A batch of alerts is verified on at most this many spawned tasks.
+const MAX_VERIFICATION_TASKS: usize = 8;
+
+type AlertRmcMessage<H, MK> = RmcMessage<
+    RmcHash<<H as Hasher>::Hash>,
+    <MK as Keychain>::Signature,
+    <MK as MultiKeychain>::PartialMultisignature,
+>;
+type VerificationSpawner = Box<dyn Fn(BoxFuture<'static, ()>) + Send>;
+type VerifiedChunk<H, D, MK> = oneshot::Receiver<Vec<Result<CheckedAlert<H, D, MK>, Error>>>;
 
 pub struct Service<H: Hasher, D: Data, MK: MultiKeychain> {
     messages_for_network: Sender<(NetworkMessage<H, D, MK>, Recipient)>,
     messages_from_network: Receiver<NetworkMessage<H, D, MK>>,
     notifications_for_units: Sender<ForkingNotification<H, D, MK::Signature>>,
     alerts_from_units: Receiver<Alert<H, D, MK::Signature>>,
-    rmc: ReliableMulticast<H::Hash, MK>,
-    messages_for_rmc: Sender<RmcMessage<H::Hash, MK::Signature, MK::PartialMultisignature>>,
-    messages_from_rmc: Receiver<RmcMessage<H::Hash, MK::Signature, MK::PartialMultisignature>>,
+    rmc: ReliableMulticast<RmcHash<H::Hash>, MK>,
+    messages_for_rmc: Sender<AlertRmcMessage<H, MK>>,
+    messages_from_rmc: Receiver<AlertRmcMessage<H, MK>>,
     node_index: NodeIndex,
+    clock: Arc<dyn Clock>,
+    reconciliation_interval: Option<time::Duration>,
+    verification_spawner: Option<VerificationSpawner>,
     exiting: bool,
 }
 
@@ -32,6 +54,7 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
         notifications_for_units: Sender<ForkingNotification<H, D, MK::Signature>>,
         alerts_from_units: Receiver<Alert<H, D, MK::Signature>>,
         n_members: NodeCount,
+        clock: Arc<dyn Clock>,
     ) -> Service<H, D, MK> {
         let (messages_for_rmc, messages_from_us) = mpsc::unbounded();
         let (messages_for_us, messages_from_rmc) = mpsc::unbounded();
@@ -41,7 +64,7 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
             messages_for_us,
             keychain.clone(),
             n_members,
-            DoublingDelayScheduler::new(time::Duration::from_millis(500)),
+            DoublingDelayScheduler::new(time::Duration::from_millis(500)).with_clock(clock.clone()),
         );
 
         Service {
@@ -53,14 +76,33 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
             messages_for_rmc,
             messages_from_rmc,
             node_index: keychain.index(),
+            clock,
+            reconciliation_interval: None,
+            verification_spawner: None,
             exiting: false,
         }
     }
 
-    fn rmc_message_to_network(
-        &mut self,
-        message: RmcMessage<H::Hash, MK::Signature, MK::PartialMultisignature>,
-    ) {
+    /// Sets how often the forkers known to the alerter are sent to the runway for reconciliation,
+    /// never by default.
+    pub fn with_reconciliation_interval(
+        mut self,
+        reconciliation_interval: Option<time::Duration>,
+    ) -> Self {
+        self.reconciliation_interval = reconciliation_interval;
+        self
+    }
+
+    /// Sets the spawn handle on which batches of alerts from the network are verified
+    /// concurrently, by default they are verified on the alerter task.
+    pub fn with_verification_spawner<SH: SpawnHandle>(mut self, spawn_handle: SH) -> Self {
+        self.verification_spawner = Some(Box::new(move |task| {
+            spawn_handle.spawn("AlephBFT-alerter/verification", task)
+        }));
+        self
+    }
+
+    fn rmc_message_to_network(&mut self, message: AlertRmcMessage<H, MK>) {
         self.send_message_for_network(
             AlertMessage::RmcMessage(self.node_index, message),
             Recipient::Everyone,
@@ -102,12 +144,101 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
         }
     }
 
-    fn handle_message_from_network(
+    /// Verifies the alerts on up to `MAX_VERIFICATION_TASKS` spawned tasks, or right away if there
+    /// is no spawner. Returns the chunks of verified alerts in order.
+    fn verify_alerts(
+        &self,
+        verifier: AlertVerifier<MK>,
+        alerts: Vec<UncheckedAlert<H, D, MK>>,
+    ) -> Vec<VerifiedChunk<H, D, MK>> {
+        let spawner = match &self.verification_spawner {
+            Some(spawner) if alerts.len() > 1 => spawner,
+            _ => {
+                let (verified_tx, verified_rx) = oneshot::channel();
+                let verified = alerts
+                    .into_iter()
+                    .map(|alert| verifier.verify(alert))
+                    .collect();
+                let _ = verified_tx.send(verified);
+                return vec![verified_rx];
+            }
+        };
+        let chunk_size = alerts.len() / MAX_VERIFICATION_TASKS + 1;
+        let mut alerts = alerts.into_iter();
+        let mut chunks = Vec::new();
+        loop {
+            let chunk: Vec<_> = alerts.by_ref().take(chunk_size).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            let (verified_tx, verified_rx) = oneshot::channel();
+            let verifier = verifier.clone();
+            spawner(Box::pin(async move {
+                let verified = chunk
+                    .into_iter()
+                    .map(|alert| verifier.verify(alert))
+                    .collect();
+                // The alerter might have exited in the meantime.
+                let _ = verified_tx.send(verified);
+            }));
+            chunks.push(verified_rx);
+        }
+        chunks
+    }
+
+    /// Handles the given message together with the ones already waiting behind it, up to
+    /// `MAX_MESSAGE_BATCH` in total, so that runs of alerts can be verified as a batch.
+    async fn handle_messages_from_network(
         &mut self,
         handler: &mut Handler<H, D, MK>,
         message: AlertMessage<H, D, MK::Signature, MK::PartialMultisignature>,
     ) {
-        match handler.on_message(message) {
+        let mut alerts = Vec::new();
+        let mut next = Some(message);
+        let mut batched = 1;
+        while let Some(message) = next.take() {
+            match message {
+                AlertMessage::ForkAlert(alert) => alerts.push(alert),
+                message => {
+                    self.handle_alerts_from_network(handler, std::mem::take(&mut alerts))
+                        .await;
+                    let response = handler.on_message(message);
+                    self.handle_response(handler, response);
+                }
+            }
+            if batched < MAX_MESSAGE_BATCH {
+                next = self.messages_from_network.next().now_or_never().flatten();
+                batched += 1;
+            }
+        }
+        self.handle_alerts_from_network(handler, alerts).await;
+    }
+
+    /// Verifies the alerts concurrently, then applies them to the handler state in order.
+    async fn handle_alerts_from_network(
+        &mut self,
+        handler: &mut Handler<H, D, MK>,
+        alerts: Vec<UncheckedAlert<H, D, MK>>,
+    ) {
+        if alerts.is_empty() {
+            return;
+        }
+        trace!(target: LOG_TARGET, "{:?} Handling a batch of {} alerts.", self.node_index, alerts.len());
+        let verified = collect_verified(self.verify_alerts(handler.verifier(), alerts)).await;
+        for result in handler.on_verified_network_alerts(verified) {
+            self.handle_response(
+                handler,
+                result.map(|(n, h)| Some(AlerterResponse::ForkResponse(n, h))),
+            );
+        }
+    }
+
+    fn handle_response(
+        &mut self,
+        handler: &mut Handler<H, D, MK>,
+        response: OnMessageResult<H, D, MK>,
+    ) {
+        match response {
             Ok(Some(AlerterResponse::ForkAlert(alert, recipient))) => {
                 self.send_message_for_network(AlertMessage::ForkAlert(alert), recipient);
             }
@@ -116,6 +247,7 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
                 self.send_message_for_network(message, peer);
             }
             Ok(Some(AlerterResponse::RmcMessage(message))) => {
+                self.handle_signatures(handler, &message);
                 if self.messages_for_rmc.unbounded_send(message).is_err() {
                     warn!(
                         target: LOG_TARGET,
@@ -125,7 +257,7 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
                 }
             }
             Ok(Some(AlerterResponse::ForkResponse(maybe_notification, hash))) => {
-                self.rmc.start_rmc(hash);
+                self.rmc.start_rmc(RmcHash(hash));
                 if let Some(notification) = maybe_notification {
                     self.send_notification_for_units(notification);
                 }
@@ -142,32 +274,63 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
     ) {
         let (message, recipient, hash) = handler.on_own_alert(alert);
         self.send_message_for_network(message, recipient);
-        self.rmc.start_rmc(hash);
+        self.rmc.start_rmc(RmcHash(hash));
     }
 
     fn handle_message_from_rmc(
         &mut self,
-        message: RmcMessage<H::Hash, MK::Signature, MK::PartialMultisignature>,
+        handler: &mut Handler<H, D, MK>,
+        message: AlertRmcMessage<H, MK>,
     ) {
+        self.handle_signatures(handler, &message);
         self.rmc_message_to_network(message)
     }
 
+    fn handle_signatures(
+        &mut self,
+        handler: &mut Handler<H, D, MK>,
+        message: &AlertRmcMessage<H, MK>,
+    ) {
+        match handler.on_rmc_message(message) {
+            Some(Ok(notification)) => self.send_notification_for_units(notification),
+            Some(Err(error)) => warn!(target: LOG_TARGET, "{}", error),
+            None => {}
+        }
+    }
+
     fn handle_multisigned(
         &mut self,
         handler: &mut Handler<H, D, MK>,
-        multisigned: Multisigned<H::Hash, MK>,
+        multisigned: Multisigned<RmcHash<H::Hash>, MK>,
     ) {
+        if handler.counts_signatures() {
+            trace!(
+                target: LOG_TARGET,
+                "{:?} Ignoring a multisigned alert, alerts are confirmed by counting signatures.",
+                self.node_index
+            );
+            return;
+        }
         match handler.alert_confirmed(multisigned) {
             Ok(notification) => self.send_notification_for_units(notification),
             Err(error) => warn!(target: LOG_TARGET, "{}", error),
         }
     }
 
+    fn reconcile_forkers(&mut self, handler: &Handler<H, D, MK>) {
+        debug!(target: LOG_TARGET, "{:?} Sending known forkers for reconciliation.", self.node_index);
+        trace!(target: LOG_TARGET, "{:?} Known alerts: {:?}.", self.node_index, handler.known_alert_hashes());
+        self.send_notification_for_units(ForkingNotification::KnownForkers(
+            handler.known_forker_proofs(),
+        ));
+    }
+
     pub async fn run(&mut self, mut handler: Handler<H, D, MK>, mut terminator: Terminator) {
+        let mut reconciliation = interval_due(self.clock.as_ref(), self.reconciliation_interval);
         loop {
             futures::select! {
                 message = self.messages_from_network.next() => match message {
-                    Some(message) => self.handle_message_from_network(&mut handler, message),
+                    Some(message) => self.handle_messages_from_network(&mut handler, message).await,
                     None => {
                         error!(target: LOG_TARGET, "{:?} Message stream closed.", self.node_index);
                         break;
@@ -181,13 +344,17 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
                     }
                 },
                 message = self.messages_from_rmc.next() => match message {
-                    Some(message) => self.handle_message_from_rmc(message),
+                    Some(message) => self.handle_message_from_rmc(&mut handler, message),
                     None => {
                         error!(target: LOG_TARGET, "{:?} RMC message stream closed.", self.node_index);
                         break;
                     }
                 },
                 multisigned = self.rmc.next_multisigned_hash().fuse() => self.handle_multisigned(&mut handler, multisigned),
+                _ = reconciliation => {
+                    self.reconcile_forkers(&handler);
+                    reconciliation = interval_due(self.clock.as_ref(), self.reconciliation_interval);
+                },
                 _ = terminator.get_exit().fuse() => {
                     debug!(target: LOG_TARGET, "{:?} received exit signal", self.node_index);
                     self.exiting = true;
@@ -198,9 +365,26 @@ impl<H: Hasher, D: Data, MK: MultiKeychain> Service<H, D, MK> {
                     target: LOG_TARGET,
                     "{:?} Alerter decided to exit.", self.node_index
                 );
+                trace!(target: LOG_TARGET, "{:?} Alerter state on exit: {:?}", self.node_index, handler.debug_snapshot());
                 terminator.terminate_sync().await;
                 break;
             }
         }
     }
 }
+
+/// Gathers the alerts verified with [`Service::verify_alerts`], in order.
+async fn collect_verified<H: Hasher, D: Data, MK: MultiKeychain>(
+    chunks: Vec<VerifiedChunk<H, D, MK>>,
+) -> Vec<Result<CheckedAlert<H, D, MK>, Error>> {
+    let mut verified = Vec::new();
+    for chunk in future::join_all(chunks).await {
+        match chunk {
+            Ok(chunk) => verified.extend(chunk),
+            Err(_) => {
+                debug!(target: LOG_TARGET, "Alert verification task dropped, skipping its alerts.")
+            }
+        }
+    }
+    verified
+}
@@ -1,7 +1,14 @@
-use crate::{NodeCount, NodeIndex, Round, SessionId};
+use crate::{
+    AlertObserver, AllParents, Clock, CreationGate, DefaultRequestStrategy, Metrics, NodeCount,
+    NodeIndex, NoopMetrics, ParentSelector, RequestStrategy, Round, RunwayCounters, SchedulingHint,
+    SessionId, SystemClock,
+};
+use derivative::Derivative;
 use log::error;
 use std::{
+    collections::hash_map::RandomState,
     fmt::{Debug, Formatter},
+    hash::{BuildHasher, Hasher as StdHasher},
     sync::Arc,
     time::Duration,
 };
@@ -15,6 +22,38 @@ pub type DelaySchedule = Arc<dyn Fn(usize) -> Duration + Sync + Send + 'static>;
 /// A function answering the question of how many nodes to query on the n-th (0-based) try.
 pub type RecipientCountSchedule = Arc<dyn Fn(usize) -> usize + Sync + Send + 'static>;
 
+/// Builds the hashers of the sets deduplicating requests for missing units.
+///
+/// Defaults to the randomly seeded std hasher. A deployment with huge missing sets might prefer
+/// a faster one, and a simulation a deterministic one, which makes iteration order reproducible.
+#[derive(Clone)]
+pub struct RequestHashing {
+    build: Arc<dyn Fn() -> Box<dyn StdHasher> + Sync + Send + 'static>,
+}
+
+impl RequestHashing {
+    pub fn new(build: impl Fn() -> Box<dyn StdHasher> + Sync + Send + 'static) -> Self {
+        RequestHashing {
+            build: Arc::new(build),
+        }
+    }
+}
+
+impl Default for RequestHashing {
+    fn default() -> Self {
+        let state = RandomState::new();
+        RequestHashing::new(move || Box::new(state.build_hasher()))
+    }
+}
+
+impl BuildHasher for RequestHashing {
+    type Hasher = Box<dyn StdHasher>;
+
+    fn build_hasher(&self) -> Self::Hasher {
+        (self.build)()
+    }
+}
+
 /// Configuration of several parameters related to delaying various tasks.
 #[derive(Clone)]
 pub struct DelayConfig {
@@ -37,11 +76,17 @@ pub struct DelayConfig {
     /// unknown parents of a unit.
     pub parent_request_delay: DelaySchedule,
     /// parent_request_recipients(k) represents the number of nodes to ask at the kth try when
-    /// requesting unknown parents of a unit.
+    /// requesting unknown parents of a unit. A count covering all the other nodes means asking
+    /// everyone, e.g. after the parents failed to arrive from a few single nodes.
     pub parent_request_recipients: RecipientCountSchedule,
     /// newest_request_delay(k) represents the delay between the kth and (k+1)st try when sending
     /// a broadcast request for newest units
     pub newest_request_delay: DelaySchedule,
+    /// newest_request_recipients(k) represents the number of nodes to ask at the kth try when
+    /// requesting newest units. A count covering all the other nodes means asking everyone, so
+    /// large committees might start with a subset comfortably exceeding the collection threshold
+    /// and escalate to everyone only if not enough responses arrive.
+    pub newest_request_recipients: RecipientCountSchedule,
 }
 
 impl Debug for DelayConfig {
@@ -60,9 +105,87 @@ impl Debug for DelayConfig {
     }
 }
 
+/// What to do when own units loaded from backup do not form a contiguous chain starting at round 0,
+/// each built on the previous one.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BackupGapPolicy {
+    /// Log a warning and continue with the loaded units.
+    #[default]
+    Warn,
+    /// Treat the backup as corrupted and refuse to continue.
+    Refuse,
+}
+
+/// What to do when a unit loaded from backup fails validation, e.g. it is from a different session.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum BackupLoadPolicy {
+    /// Treat the whole backup as corrupted and refuse to continue.
+    #[default]
+    FailFast,
+    /// Skip the invalid unit with a warning, and load the remaining valid ones.
+    BestEffort,
+}
+
+/// Which units of a forker to commit to in our own alert about them.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AlertCommitment {
+    /// All the units of the forker we know of.
+    #[default]
+    AllKnown,
+    /// Only the units not already committed to by alerts that were confirmed before ours was
+    /// formed, as those are legit for everyone anyway. Only the most recently confirmed units are
+    /// remembered, as many as [`Config::confirmed_hashes_retention`] says, so older ones may be
+    /// committed to again.
+    SkipConfirmed,
+}
+
+/// What to do when the data provider returns the same data as for our previous unit.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum RepeatedDataPolicy {
+    /// Include the data in the unit anyway.
+    #[default]
+    Keep,
+    /// Create the unit without data instead of repeating it.
+    Omit,
+}
+
+/// When to verify the signatures of units received as parents in a response to a parents request.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ParentVerification {
+    /// Verify every parent as soon as the response arrives.
+    #[default]
+    Eager,
+    /// Check the response against the control hash of the child first, and verify only the
+    /// parents we do not already hold, once they are about to be added to the store. This saves
+    /// verifying parents of responses that turn out to be wrong, at the cost of some latency.
+    Deferred,
+}
+
+/// What to do when the alerter stops working before the session ends.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum AlerterFailurePolicy {
+    /// Shut the whole session down.
+    #[default]
+    Halt,
+    /// Keep producing and finalizing units without any protection against forks, reporting the
+    /// condition through logs and metrics.
+    Degrade,
+}
+
+/// A limit on how many responses to requests for units and parents a node sends out in a given
+/// interval. Requests beyond the limit are dropped, the requesters retry and may ask other nodes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ResponseRateLimit {
+    /// The number of responses allowed in a single interval.
+    pub max_responses: usize,
+    /// The length of the interval.
+    pub interval: Duration,
+}
+
 /// Main configuration of the consensus. We refer to [the documentation](https://cardinal-cryptography.github.io/AlephBFT/aleph_bft_api.html#34-alephbft-sessions)
 /// Section 3.4 for a discussion of some of these parameters and their significance.
-#[derive(Clone, Debug)]
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
 pub struct Config {
     /// Identification number of the Member=0,..,(n_members-1).
     node_ix: NodeIndex,
@@ -74,6 +197,94 @@ pub struct Config {
     delay_config: DelayConfig,
     /// Maximum allowable round of a unit.
     max_round: Round,
+    /// How many rounds ahead of the creator's current round a unit may be, `max_round` if `None`.
+    max_round_advance: Option<Round>,
+    /// How to react to a gap or a break in the chain of own units loaded from backup.
+    backup_gap_policy: BackupGapPolicy,
+    /// How to react to units failing validation when loading the backup.
+    backup_load_policy: BackupLoadPolicy,
+    /// Receiver of measurements of the consensus internals.
+    #[derivative(Debug = "ignore")]
+    metrics: Arc<dyn Metrics>,
+    /// Counters of the runway activity, pulled by the user.
+    runway_counters: RunwayCounters,
+    /// Strategy for choosing parents of own units when more than required are available.
+    #[derivative(Debug = "ignore")]
+    parent_selector: Arc<dyn ParentSelector>,
+    /// Strategy for choosing the recipients of requests for units.
+    #[derivative(Debug = "ignore")]
+    request_strategy: Arc<dyn RequestStrategy>,
+    /// How to treat data repeated in consecutive own units.
+    repeated_data_policy: RepeatedDataPolicy,
+    /// Which units of a forker to commit to in our alerts.
+    alert_commitment: AlertCommitment,
+    /// When to verify signatures of units received as parents.
+    parent_verification: ParentVerification,
+    /// How to react to the alerter stopping early.
+    alerter_failure_policy: AlerterFailurePolicy,
+    /// How many hashes of recently finalized units to remember for dropping late duplicates.
+    finalized_hashes_retention: usize,
+    /// How many hashes of units from confirmed alerts to remember for skipping them in our alerts.
+    confirmed_hashes_retention: usize,
+    /// After how many failures to reconstruct the parents of a unit to give up on it.
+    wrong_control_hash_limit: usize,
+    /// After how many parents responses bringing nothing new to flag the responder.
+    duplicate_parents_response_limit: usize,
+    /// How many units of a single forker in a single round to accept from alerts.
+    max_units_per_forker_round: usize,
+    /// How many members have to sign an alert to confirm it, if not the quorum of the keychain.
+    alert_confirmation_threshold: Option<NodeCount>,
+    /// How long creation may be blocked only by our own missing unit before restoring it from backup.
+    own_parent_recovery_delay: Duration,
+    /// How many rounds unit creation may get ahead of the finalized round.
+    max_finalization_lag: Round,
+    /// How many times a missing unit is requested only from its creator before asking everyone.
+    coord_request_creator_attempts: usize,
+    /// How long to keep requesting a missing unit before giving up on it.
+    missing_coord_lifetime: Duration,
+    /// Optional limit on the rate of responses to requests from other nodes.
+    response_rate_limit: Option<ResponseRateLimit>,
+    /// How often to report the status of the session, if at all.
+    status_report_interval: Option<Duration>,
+    /// How often to check that the runway and the alerter agree on the forkers, if at all.
+    forker_reconciliation_interval: Option<Duration>,
+    /// How many times to restart consensus after it stops unexpectedly.
+    consensus_restart_attempts: usize,
+    /// How often to validate again units which failed validation only transiently, if at all.
+    validation_retry_interval: Option<Duration>,
+    /// How long to wait for data for a new unit before creating it without any, if at all.
+    data_provider_timeout: Option<Duration>,
+    /// How many rounds of missing units to request at once when a newest unit response shows we
+    /// are behind, if at all.
+    catch_up_burst: Option<Round>,
+    /// Whether to start the session by collecting our newest unit from other members.
+    initial_unit_collection: bool,
+    /// How many responses to the newest unit request to collect before deciding the starting round,
+    /// if not the default.
+    collection_threshold: Option<NodeCount>,
+    /// How long to wait for responses to the newest unit request, if not indefinitely.
+    collection_timeout: Option<Duration>,
+    /// How often to broadcast the newest unit request again while waiting for responses, if at all.
+    collection_resend_interval: Option<Duration>,
+    /// How far ahead of the backup the collection may place our newest unit before its result is
+    /// ignored, if there is such a limit.
+    collection_round_leeway: Option<Round>,
+    /// How many of our newest units to track the propagation of, if at all.
+    unit_propagation_tracking: Option<usize>,
+    /// How to schedule the tasks the progress of consensus depends on.
+    consensus_scheduling_hint: SchedulingHint,
+    /// Hashing used by the sets deduplicating requests for missing units.
+    #[derivative(Debug = "ignore")]
+    request_hashing: RequestHashing,
+    /// Optional veto on creating our units, consulted right before signing them.
+    #[derivative(Debug = "ignore")]
+    creation_gate: Option<Arc<dyn CreationGate>>,
+    /// Optional observer of the lifecycle of alerts.
+    #[derivative(Debug = "ignore")]
+    alert_observer: Option<Arc<dyn AlertObserver>>,
+    /// Source of time for all the delays and timeouts.
+    #[derivative(Debug = "ignore")]
+    clock: Arc<dyn Clock>,
 }
 
 impl Config {
@@ -92,6 +303,472 @@ impl Config {
     pub fn max_round(&self) -> Round {
         self.max_round
     }
+    pub fn max_round_advance(&self) -> Option<Round> {
+        self.max_round_advance
+    }
+
+    /// Sets how many rounds ahead of the creator's current round a unit may be before it is
+    /// ignored by the creator. By default it is [`Config::max_round`], so the creator accepts all
+    /// the units that pass validation and only ever keeps candidates for rounds up to that.
+    pub fn with_max_round_advance(mut self, max_round_advance: Option<Round>) -> Self {
+        self.max_round_advance = max_round_advance;
+        self
+    }
+    pub fn backup_gap_policy(&self) -> BackupGapPolicy {
+        self.backup_gap_policy
+    }
+
+    /// Sets the reaction to a gap or a break in the chain of own units loaded from backup,
+    /// [`BackupGapPolicy::Warn`] by default.
+    pub fn with_backup_gap_policy(mut self, backup_gap_policy: BackupGapPolicy) -> Self {
+        self.backup_gap_policy = backup_gap_policy;
+        self
+    }
+    pub fn backup_load_policy(&self) -> BackupLoadPolicy {
+        self.backup_load_policy
+    }
+
+    /// Sets the reaction to invalid units in backup, [`BackupLoadPolicy::FailFast`] by default.
+    pub fn with_backup_load_policy(mut self, backup_load_policy: BackupLoadPolicy) -> Self {
+        self.backup_load_policy = backup_load_policy;
+        self
+    }
+    pub fn metrics(&self) -> Arc<dyn Metrics> {
+        self.metrics.clone()
+    }
+
+    /// Sets the receiver of measurements of the consensus internals, [`NoopMetrics`] by default.
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+    pub fn runway_counters(&self) -> RunwayCounters {
+        self.runway_counters.clone()
+    }
+
+    /// Sets the counters of the runway activity, fresh ones by default. Either way they can be
+    /// scraped with [`RunwayCounters::snapshot_and_reset`] through the handle returned by
+    /// [`Config::runway_counters`].
+    pub fn with_runway_counters(mut self, runway_counters: RunwayCounters) -> Self {
+        self.runway_counters = runway_counters;
+        self
+    }
+    pub fn parent_selector(&self) -> Arc<dyn ParentSelector> {
+        self.parent_selector.clone()
+    }
+
+    /// Sets the strategy for choosing parents of own units, [`AllParents`] by default.
+    pub fn with_parent_selector(mut self, parent_selector: Arc<dyn ParentSelector>) -> Self {
+        self.parent_selector = parent_selector;
+        self
+    }
+    pub fn request_strategy(&self) -> Arc<dyn RequestStrategy> {
+        self.request_strategy.clone()
+    }
+
+    /// Sets the strategy for choosing the recipients of requests for units,
+    /// [`DefaultRequestStrategy`] by default.
+    pub fn with_request_strategy(mut self, request_strategy: Arc<dyn RequestStrategy>) -> Self {
+        self.request_strategy = request_strategy;
+        self
+    }
+    pub fn repeated_data_policy(&self) -> RepeatedDataPolicy {
+        self.repeated_data_policy
+    }
+
+    /// Sets how to treat data repeated in consecutive own units, [`RepeatedDataPolicy::Keep`] by default.
+    pub fn with_repeated_data_policy(mut self, repeated_data_policy: RepeatedDataPolicy) -> Self {
+        self.repeated_data_policy = repeated_data_policy;
+        self
+    }
+    pub fn alert_commitment(&self) -> AlertCommitment {
+        self.alert_commitment
+    }
+
+    /// Sets which units of a forker to commit to in our alerts, [`AlertCommitment::AllKnown`] by default.
+    pub fn with_alert_commitment(mut self, alert_commitment: AlertCommitment) -> Self {
+        self.alert_commitment = alert_commitment;
+        self
+    }
+    pub fn parent_verification(&self) -> ParentVerification {
+        self.parent_verification
+    }
+
+    /// Sets when to verify signatures of units received as parents, [`ParentVerification::Eager`] by default.
+    pub fn with_parent_verification(mut self, parent_verification: ParentVerification) -> Self {
+        self.parent_verification = parent_verification;
+        self
+    }
+    pub fn alerter_failure_policy(&self) -> AlerterFailurePolicy {
+        self.alerter_failure_policy
+    }
+
+    /// Sets how to react to the alerter stopping early, [`AlerterFailurePolicy::Halt`] by default.
+    pub fn with_alerter_failure_policy(
+        mut self,
+        alerter_failure_policy: AlerterFailurePolicy,
+    ) -> Self {
+        self.alerter_failure_policy = alerter_failure_policy;
+        self
+    }
+    pub fn finalized_hashes_retention(&self) -> usize {
+        self.finalized_hashes_retention
+    }
+
+    /// Sets how many hashes of recently finalized units to remember for dropping late duplicates
+    /// and telling whether a unit was finalized, 1000 by default. `usize::MAX` remembers the hashes
+    /// of all the units finalized in the session. Regardless of this, the coords and hashes of all
+    /// the ordered units are kept until the session ends, for restarts of the consensus and for
+    /// [`RunwayControl::DumpOrderedUnits`](crate::RunwayControl::DumpOrderedUnits), so they grow
+    /// up to `n_members * (max_round + 1)` entries.
+    pub fn with_finalized_hashes_retention(mut self, finalized_hashes_retention: usize) -> Self {
+        self.finalized_hashes_retention = finalized_hashes_retention;
+        self
+    }
+    pub fn confirmed_hashes_retention(&self) -> usize {
+        self.confirmed_hashes_retention
+    }
+
+    /// Sets how many hashes of units from confirmed alerts to remember for
+    /// [`AlertCommitment::SkipConfirmed`], 1000 by default.
+    pub fn with_confirmed_hashes_retention(mut self, confirmed_hashes_retention: usize) -> Self {
+        self.confirmed_hashes_retention = confirmed_hashes_retention;
+        self
+    }
+    pub fn wrong_control_hash_limit(&self) -> usize {
+        self.wrong_control_hash_limit
+    }
+
+    /// Sets after how many failures to reconstruct the parents of a unit with a wrong control hash
+    /// to give up on that unit, 10 by default.
+    pub fn with_wrong_control_hash_limit(mut self, wrong_control_hash_limit: usize) -> Self {
+        self.wrong_control_hash_limit = wrong_control_hash_limit;
+        self
+    }
+    pub fn duplicate_parents_response_limit(&self) -> usize {
+        self.duplicate_parents_response_limit
+    }
+
+    /// Sets after how many responses to parents requests for units whose parents we already know
+    /// a peer gets flagged with [`RunwayEvent::ExcessiveDuplicateResponses`](crate::RunwayEvent),
+    /// 10 by default. Every such response is reported through
+    /// [`Metrics::report_duplicate_parents_response`](crate::Metrics::report_duplicate_parents_response).
+    pub fn with_duplicate_parents_response_limit(
+        mut self,
+        duplicate_parents_response_limit: usize,
+    ) -> Self {
+        self.duplicate_parents_response_limit = duplicate_parents_response_limit;
+        self
+    }
+    pub fn max_units_per_forker_round(&self) -> usize {
+        self.max_units_per_forker_round
+    }
+
+    /// Sets how many units of a single forker in a single round to accept from alerts, dropping
+    /// the excess, `n_members` by default.
+    pub fn with_max_units_per_forker_round(mut self, max_units_per_forker_round: usize) -> Self {
+        self.max_units_per_forker_round = max_units_per_forker_round;
+        self
+    }
+    pub fn alert_confirmation_threshold(&self) -> Option<NodeCount> {
+        self.alert_confirmation_threshold
+    }
+
+    /// Sets how many members have to sign an alert for it to be confirmed, by default whenever
+    /// the keychain accepts its multisignature. With a threshold, the signatures of individual
+    /// members are counted instead, e.g. to model a different fault assumption for alerts than
+    /// for creating units. Thresholds of zero or above `N` are rejected.
+    pub fn with_alert_confirmation_threshold(
+        mut self,
+        alert_confirmation_threshold: Option<NodeCount>,
+    ) -> Result<Self, InvalidConfigError> {
+        if let Some(threshold) = alert_confirmation_threshold {
+            if threshold == NodeCount(0) || threshold > self.n_members {
+                error!(
+                    target: "AlephBFT-config",
+                    "Alert confirmation threshold {:?} is outside of the allowed range from 1 to {:?}.", threshold, self.n_members
+                );
+                return Err(InvalidConfigError);
+            }
+        }
+        self.alert_confirmation_threshold = alert_confirmation_threshold;
+        Ok(self)
+    }
+    pub fn own_parent_recovery_delay(&self) -> Duration {
+        self.own_parent_recovery_delay
+    }
+
+    /// Sets how long unit creation may be blocked only by our own unit from the previous round
+    /// missing, before that unit is restored from backup, 30 seconds by default. The unit is only
+    /// restored if it is the newest one we backed up, so no round ever gets a second unit.
+    pub fn with_own_parent_recovery_delay(mut self, own_parent_recovery_delay: Duration) -> Self {
+        self.own_parent_recovery_delay = own_parent_recovery_delay;
+        self
+    }
+    pub fn max_finalization_lag(&self) -> Round {
+        self.max_finalization_lag
+    }
+
+    /// Sets how many rounds ahead of the finalized round a unit may be created before unit creation
+    /// pauses until finalization catches up, [`Config::max_round`] by default.
+    pub fn with_max_finalization_lag(mut self, max_finalization_lag: Round) -> Self {
+        self.max_finalization_lag = max_finalization_lag;
+        self
+    }
+    pub fn coord_request_creator_attempts(&self) -> usize {
+        self.coord_request_creator_attempts
+    }
+
+    /// Sets how many times a missing unit is requested only from its creator before the request
+    /// is sent to everyone, 0 by default. With 0 the requests go to random peers according to
+    /// [`DelayConfig::coord_request_recipients`] instead. The requests are repeated according to
+    /// [`DelayConfig::coord_request_delay`] either way.
+    pub fn with_coord_request_creator_attempts(
+        mut self,
+        coord_request_creator_attempts: usize,
+    ) -> Self {
+        self.coord_request_creator_attempts = coord_request_creator_attempts;
+        self
+    }
+    pub fn missing_coord_lifetime(&self) -> Duration {
+        self.missing_coord_lifetime
+    }
+
+    /// Sets how long a missing unit is requested before giving up on it, 10 minutes by default.
+    /// A unit might never arrive, e.g. when a forker referred to a unit it never sent anyone.
+    pub fn with_missing_coord_lifetime(mut self, missing_coord_lifetime: Duration) -> Self {
+        self.missing_coord_lifetime = missing_coord_lifetime;
+        self
+    }
+    pub fn response_rate_limit(&self) -> Option<ResponseRateLimit> {
+        self.response_rate_limit
+    }
+
+    /// Sets the limit on the rate of responses to requests for units and parents, no limit by
+    /// default. Our own units and requests are not affected.
+    pub fn with_response_rate_limit(
+        mut self,
+        response_rate_limit: Option<ResponseRateLimit>,
+    ) -> Self {
+        self.response_rate_limit = response_rate_limit;
+        self
+    }
+    pub fn consensus_restart_attempts(&self) -> usize {
+        self.consensus_restart_attempts
+    }
+
+    /// Sets how many times to restart consensus after it stops unexpectedly before shutting the
+    /// session down, 0 by default. A restarted consensus is fed all the units we hold and
+    /// continues creating units after our newest one, finalized data is not delivered again.
+    pub fn with_consensus_restart_attempts(mut self, consensus_restart_attempts: usize) -> Self {
+        self.consensus_restart_attempts = consensus_restart_attempts;
+        self
+    }
+    pub fn validation_retry_interval(&self) -> Option<Duration> {
+        self.validation_retry_interval
+    }
+
+    /// Sets how often units which failed validation only transiently, i.e. ones deferred by the
+    /// [`UnitInspector`](crate::UnitInspector), are validated again, never by default. Without
+    /// retries such units are dropped like all the other invalid ones.
+    pub fn with_validation_retry_interval(
+        mut self,
+        validation_retry_interval: Option<Duration>,
+    ) -> Self {
+        self.validation_retry_interval = validation_retry_interval;
+        self
+    }
+    pub fn data_provider_timeout(&self) -> Option<Duration> {
+        self.data_provider_timeout
+    }
+
+    /// Sets how long to wait for the data provider when creating a unit, no limit by default.
+    /// Once it passes, the call to the provider is dropped and the unit is created without data,
+    /// so that a provider with nothing to offer does not hold up the round.
+    pub fn with_data_provider_timeout(mut self, data_provider_timeout: Option<Duration>) -> Self {
+        self.data_provider_timeout = data_provider_timeout;
+        self
+    }
+    pub fn catch_up_burst(&self) -> Option<Round> {
+        self.catch_up_burst
+    }
+
+    /// Sets how many rounds of units, starting right above our newest one, are requested at once
+    /// when a response to the newest unit request at startup carries a unit of a higher round,
+    /// none by default. Otherwise units are only requested once consensus finds them missing.
+    pub fn with_catch_up_burst(mut self, catch_up_burst: Option<Round>) -> Self {
+        self.catch_up_burst = catch_up_burst;
+        self
+    }
+    pub fn initial_unit_collection(&self) -> bool {
+        self.initial_unit_collection
+    }
+
+    /// Sets whether the session starts by collecting our newest unit from other members, which
+    /// waits for responses from a threshold of them before creating any units, or right away from
+    /// the round following the units in the backup. Defaults to whether the
+    /// `initial_unit_collection` feature is enabled. Skipping the collection is only safe
+    /// when the backup is known to be complete, e.g. for a brand-new session.
+    pub fn with_initial_unit_collection(mut self, initial_unit_collection: bool) -> Self {
+        self.initial_unit_collection = initial_unit_collection;
+        self
+    }
+    pub fn collection_threshold(&self) -> Option<NodeCount> {
+        self.collection_threshold
+    }
+
+    /// Sets how many responses to the newest unit request at startup, counting our own, are
+    /// enough to decide the starting round once the catch up delay passes, `2N/3 + 1` by default.
+    /// A lower threshold starts the session faster after a crash, at a higher risk of starting
+    /// below our newest unit. Thresholds below `N/3 + 1` or above `N` are rejected.
+    pub fn with_collection_threshold(
+        mut self,
+        collection_threshold: Option<NodeCount>,
+    ) -> Result<Self, InvalidConfigError> {
+        if let Some(threshold) = collection_threshold {
+            let minimal_threshold = self.n_members / 3 + NodeCount(1);
+            if threshold < minimal_threshold || threshold > self.n_members {
+                error!(
+                    target: "AlephBFT-config",
+                    "Collection threshold {:?} is outside of the allowed range from {:?} to {:?}.", threshold, minimal_threshold, self.n_members
+                );
+                return Err(InvalidConfigError);
+            }
+        }
+        self.collection_threshold = collection_threshold;
+        Ok(self)
+    }
+    pub fn collection_timeout(&self) -> Option<Duration> {
+        self.collection_timeout
+    }
+
+    /// Sets how long to wait for responses to the newest unit request at startup, indefinitely
+    /// by default. Once it passes, the starting round is decided from the responses that arrived
+    /// if they come from at least `N/3 + 1` nodes, counting ourselves, and otherwise the session
+    /// gives up without creating any units.
+    pub fn with_collection_timeout(mut self, collection_timeout: Option<Duration>) -> Self {
+        self.collection_timeout = collection_timeout;
+        self
+    }
+    pub fn collection_resend_interval(&self) -> Option<Duration> {
+        self.collection_resend_interval
+    }
+
+    /// Sets how often the newest unit request at startup is broadcast to everyone again while
+    /// responses from too few nodes arrived, never by default. This helps when the first broadcast
+    /// got lost for some peers, on top of the repetitions scheduled according to
+    /// [`DelayConfig::newest_request_delay`].
+    pub fn with_collection_resend_interval(
+        mut self,
+        collection_resend_interval: Option<Duration>,
+    ) -> Self {
+        self.collection_resend_interval = collection_resend_interval;
+        self
+    }
+    pub fn collection_round_leeway(&self) -> Option<Round> {
+        self.collection_round_leeway
+    }
+
+    /// Sets how many rounds ahead of the backup the newest unit request at startup may place our
+    /// newest unit, unlimited by default. Beyond that the collection result is treated as forged
+    /// and ignored, so the session starts right after the units in the backup. Within it a
+    /// collection result ahead of the backup still stops the session, as usual.
+    pub fn with_collection_round_leeway(mut self, collection_round_leeway: Option<Round>) -> Self {
+        self.collection_round_leeway = collection_round_leeway;
+        self
+    }
+    pub fn unit_propagation_tracking(&self) -> Option<usize> {
+        self.unit_propagation_tracking
+    }
+
+    /// Sets how many of our newest broadcast units have their propagation tracked, none by
+    /// default. A peer counts as having received our unit once it requests it or creates a unit
+    /// on top of it, and the number of such peers for our newest unit is reported in the
+    /// [`StatusSnapshot`](crate::StatusSnapshot).
+    pub fn with_unit_propagation_tracking(
+        mut self,
+        unit_propagation_tracking: Option<usize>,
+    ) -> Self {
+        self.unit_propagation_tracking = unit_propagation_tracking;
+        self
+    }
+    pub fn consensus_scheduling_hint(&self) -> SchedulingHint {
+        self.consensus_scheduling_hint
+    }
+
+    /// Sets the hint passed to the [`SpawnHandle`](crate::SpawnHandle) when spawning consensus
+    /// and the alerter, e.g. to run them on a dedicated executor when many sessions share a node,
+    /// [`SchedulingHint::Normal`] by default.
+    pub fn with_consensus_scheduling_hint(
+        mut self,
+        consensus_scheduling_hint: SchedulingHint,
+    ) -> Self {
+        self.consensus_scheduling_hint = consensus_scheduling_hint;
+        self
+    }
+    pub fn status_report_interval(&self) -> Option<Duration> {
+        self.status_report_interval
+    }
+
+    /// Sets how often the status of the session is reported in logs and metrics, every 10 seconds
+    /// by default. `None` disables the reports entirely.
+    pub fn with_status_report_interval(mut self, status_report_interval: Option<Duration>) -> Self {
+        self.status_report_interval = status_report_interval;
+        self
+    }
+    pub fn forker_reconciliation_interval(&self) -> Option<Duration> {
+        self.forker_reconciliation_interval
+    }
+
+    /// Sets how often the alerter sends the forkers it knows to the runway, which marks the ones
+    /// it missed, e.g. because of a dropped notification, every 30 seconds by default. `None`
+    /// disables the reconciliation.
+    pub fn with_forker_reconciliation_interval(
+        mut self,
+        forker_reconciliation_interval: Option<Duration>,
+    ) -> Self {
+        self.forker_reconciliation_interval = forker_reconciliation_interval;
+        self
+    }
+    pub fn request_hashing(&self) -> RequestHashing {
+        self.request_hashing.clone()
+    }
+
+    /// Sets the hashing of the sets deduplicating requests for missing units, the randomly seeded
+    /// std hasher by default.
+    pub fn with_request_hashing(mut self, request_hashing: RequestHashing) -> Self {
+        self.request_hashing = request_hashing;
+        self
+    }
+    pub fn creation_gate(&self) -> Option<Arc<dyn CreationGate>> {
+        self.creation_gate.clone()
+    }
+
+    /// Sets the gate consulted before every unit of ours is signed, which can defer its creation,
+    /// none by default.
+    pub fn with_creation_gate(mut self, creation_gate: Arc<dyn CreationGate>) -> Self {
+        self.creation_gate = Some(creation_gate);
+        self
+    }
+    pub fn alert_observer(&self) -> Option<Arc<dyn AlertObserver>> {
+        self.alert_observer.clone()
+    }
+
+    /// Sets an observer notified about every stage in the lifecycle of alerts, none by default.
+    pub fn with_alert_observer(mut self, alert_observer: Arc<dyn AlertObserver>) -> Self {
+        self.alert_observer = Some(alert_observer);
+        self
+    }
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Sets the source of time for all the delays and timeouts of the session, e.g. a virtual
+    /// clock driven by a simulation, [`SystemClock`] by default.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
 }
 
 pub fn exponential_slowdown(
@@ -138,6 +815,45 @@ pub fn create_config(
         n_members,
         delay_config,
         max_round,
+        max_round_advance: None,
+        backup_gap_policy: BackupGapPolicy::default(),
+        backup_load_policy: BackupLoadPolicy::default(),
+        metrics: Arc::new(NoopMetrics),
+        runway_counters: RunwayCounters::default(),
+        parent_selector: Arc::new(AllParents),
+        request_strategy: Arc::new(DefaultRequestStrategy),
+        repeated_data_policy: RepeatedDataPolicy::default(),
+        alert_commitment: AlertCommitment::default(),
+        parent_verification: ParentVerification::default(),
+        alerter_failure_policy: AlerterFailurePolicy::default(),
+        finalized_hashes_retention: 1000,
+        confirmed_hashes_retention: 1000,
+        wrong_control_hash_limit: 10,
+        duplicate_parents_response_limit: 10,
+        max_units_per_forker_round: n_members.0,
+        alert_confirmation_threshold: None,
+        own_parent_recovery_delay: Duration::from_secs(30),
+        max_finalization_lag: max_round,
+        coord_request_creator_attempts: 0,
+        missing_coord_lifetime: Duration::from_secs(600),
+        response_rate_limit: None,
+        status_report_interval: Some(Duration::from_secs(10)),
+        forker_reconciliation_interval: Some(Duration::from_secs(30)),
+        consensus_restart_attempts: 0,
+        validation_retry_interval: None,
+        data_provider_timeout: None,
+        catch_up_burst: None,
+        initial_unit_collection: cfg!(feature = "initial_unit_collection"),
+        collection_threshold: None,
+        collection_timeout: None,
+        collection_resend_interval: None,
+        collection_round_leeway: None,
+        unit_propagation_tracking: None,
+        consensus_scheduling_hint: SchedulingHint::default(),
+        request_hashing: RequestHashing::default(),
+        creation_gate: None,
+        alert_observer: None,
+        clock: Arc::new(SystemClock),
     })
 }
 
@@ -174,6 +890,7 @@ pub fn default_delay_config() -> DelayConfig {
         parent_request_delay: Arc::new(|_| Duration::from_millis(3000)),
         parent_request_recipients: Arc::new(|_| 1),
         newest_request_delay: Arc::new(|_| Duration::from_millis(3000)),
+        newest_request_recipients: Arc::new(|_| usize::MAX),
     }
 }
 
@@ -235,6 +952,7 @@ mod tests {
             parent_request_delay: Arc::new(|_| Duration::from_millis(3000)),
             parent_request_recipients: Arc::new(|_| 1),
             newest_request_delay: Arc::new(|_| Duration::from_millis(3000)),
+            newest_request_recipients: Arc::new(|_| usize::MAX),
         }
     }
 
@@ -296,4 +1014,56 @@ mod tests {
 
         assert!(config.is_ok());
     }
+
+    #[test]
+    fn collection_threshold_outside_allowed_range_is_rejected() {
+        let config = create_config(
+            NodeCount(7),
+            NodeIndex(1),
+            3,
+            7000,
+            delay_config_for_tests(),
+            Duration::from_millis(MILLIS_IN_WEEK),
+        )
+        .expect("config should be valid");
+
+        assert!(config
+            .clone()
+            .with_collection_threshold(Some(NodeCount(2)))
+            .is_err());
+        assert!(config
+            .clone()
+            .with_collection_threshold(Some(NodeCount(8)))
+            .is_err());
+        let config = config
+            .with_collection_threshold(Some(NodeCount(3)))
+            .expect("threshold of N/3 + 1 should be accepted");
+        assert_eq!(config.collection_threshold(), Some(NodeCount(3)));
+    }
+
+    #[test]
+    fn alert_confirmation_threshold_outside_allowed_range_is_rejected() {
+        let config = create_config(
+            NodeCount(7),
+            NodeIndex(1),
+            3,
+            7000,
+            delay_config_for_tests(),
+            Duration::from_millis(MILLIS_IN_WEEK),
+        )
+        .expect("config should be valid");
+
+        assert!(config
+            .clone()
+            .with_alert_confirmation_threshold(Some(NodeCount(0)))
+            .is_err());
+        assert!(config
+            .clone()
+            .with_alert_confirmation_threshold(Some(NodeCount(8)))
+            .is_err());
+        let config = config
+            .with_alert_confirmation_threshold(Some(NodeCount(7)))
+            .expect("threshold of N should be accepted");
+        assert_eq!(config.alert_confirmation_threshold(), Some(NodeCount(7)));
+    }
 }
@@ -8,34 +8,47 @@ use log::{debug, error, warn};
 use crate::{
     config::Config,
     creation,
-    extender::Extender,
+    extender::{Extender, OrderedBatch},
     handle_task_termination,
+    metrics::TrackedReceiver,
     runway::{NotificationIn, NotificationOut},
     terminal::Terminal,
-    Hasher, Receiver, Round, Sender, SpawnHandle, Terminator,
+    Hasher, Round, Sender, SpawnHandle, Terminator,
 };
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn run<H: Hasher + 'static>(
     conf: Config,
-    incoming_notifications: Receiver<NotificationIn<H>>,
+    incoming_notifications: TrackedReceiver<NotificationIn<H>>,
     outgoing_notifications: Sender<NotificationOut<H>>,
-    ordered_batch_tx: Sender<Vec<H::Hash>>,
+    ordered_batch_tx: Sender<OrderedBatch<H>>,
     spawn_handle: impl SpawnHandle,
     starting_round: oneshot::Receiver<Option<Round>>,
+    snapshot_io: creation::SnapshotIO,
     mut terminator: Terminator,
 ) {
     debug!(target: "AlephBFT", "{:?} Starting all services...", conf.node_ix());
 
     let n_members = conf.n_members();
     let index = conf.node_ix();
+    let scheduling_hint = conf.consensus_scheduling_hint();
 
     let (electors_tx, electors_rx) = mpsc::unbounded();
-    let mut extender = Extender::<H>::new(index, n_members, electors_rx, ordered_batch_tx);
+    let (finalized_rounds_for_creator, finalized_rounds_from_extender) = mpsc::unbounded();
+    let mut extender = Extender::<H>::new(
+        index,
+        n_members,
+        electors_rx,
+        ordered_batch_tx,
+        finalized_rounds_for_creator,
+    );
     let extender_terminator = terminator.add_offspring_connection("AlephBFT-extender");
     let mut extender_handle = spawn_handle
-        .spawn_essential("consensus/extender", async move {
-            extender.extend(extender_terminator).await
-        })
+        .spawn_essential_with_hint(
+            "consensus/extender",
+            async move { extender.extend(extender_terminator).await },
+            scheduling_hint,
+        )
         .fuse();
 
     let (parents_for_creator, parents_from_terminal) = mpsc::unbounded();
@@ -44,11 +57,15 @@ pub(crate) async fn run<H: Hasher + 'static>(
     let io = creation::IO {
         outgoing_units: outgoing_notifications.clone(),
         incoming_parents: parents_from_terminal,
+        finalized_rounds: finalized_rounds_from_extender,
+        initial_snapshot: snapshot_io.initial,
+        snapshots: snapshot_io.snapshots,
     };
     let creator_handle = spawn_handle
-        .spawn_essential(
+        .spawn_essential_with_hint(
             "consensus/creation",
             creation::run(conf.into(), io, starting_round, creator_terminator),
+            scheduling_hint,
         )
         .shared();
     let creator_handle_for_panic = creator_handle.clone();
@@ -61,6 +78,13 @@ pub(crate) async fn run<H: Hasher + 'static>(
 
     let mut terminal = Terminal::new(index, incoming_notifications, outgoing_notifications);
 
+    // hand our own units restored from backup directly to the creator, which lost them
+    let restored_for_creator = parents_for_creator.clone();
+    terminal.register_restore_hook(Box::new(move |u| {
+        if restored_for_creator.unbounded_send(u.into()).is_err() {
+            warn!(target: "AlephBFT", "Channel to creator was closed.");
+        }
+    }));
     // send a new parent candidate to the creator
     let mut parents_for_creator = Some(parents_for_creator);
     terminal.register_post_insert_hook(Box::new(move |u| {
@@ -80,9 +104,11 @@ pub(crate) async fn run<H: Hasher + 'static>(
 
     let terminal_terminator = terminator.add_offspring_connection("terminal");
     let mut terminal_handle = spawn_handle
-        .spawn_essential("consensus/terminal", async move {
-            terminal.run(terminal_terminator).await
-        })
+        .spawn_essential_with_hint(
+            "consensus/terminal",
+            async move { terminal.run(terminal_terminator).await },
+            scheduling_hint,
+        )
         .fuse();
     debug!(target: "AlephBFT", "{:?} All services started.", index);
 
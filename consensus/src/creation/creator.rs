@@ -1,10 +1,60 @@
 use crate::{
     units::{ControlHash, PreUnit, Unit},
-    Hasher, NodeCount, NodeIndex, NodeMap, Round,
+    Hasher, NodeCount, NodeIndex, NodeMap, NodeSubset, Round, SessionId,
 };
 use anyhow::Result;
+use codec::{Decode, Encode};
+use log::{trace, warn};
+use std::{sync::Arc, time::Duration};
 use thiserror::Error;
 
+/// Chooses the parents of a newly created unit, whenever more than the required threshold of
+/// candidates is available.
+pub trait ParentSelector: Send + Sync + 'static {
+    /// Returns the creators whose units from the previous round should become parents of a unit
+    /// created by `node_id`. The result should be a subset of `candidates` of size at least
+    /// `threshold`. The unit of `node_id` is always used as a parent, regardless of the selection.
+    fn select(
+        &self,
+        node_id: NodeIndex,
+        candidates: &NodeSubset,
+        threshold: NodeCount,
+    ) -> NodeSubset;
+}
+
+/// A [`ParentSelector`] choosing all the available candidates.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AllParents;
+
+impl ParentSelector for AllParents {
+    fn select(&self, _: NodeIndex, candidates: &NodeSubset, _: NodeCount) -> NodeSubset {
+        candidates.clone()
+    }
+}
+
+/// The verdict of a [`CreationGate`] on creating our unit of some round.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CreationDecision {
+    /// Go ahead, the unit gets signed and sent out.
+    Create,
+    /// Hold the unit back and ask again after the given delay, possibly with more parents.
+    Defer(Duration),
+}
+
+/// A last chance for the application to veto creating our unit of a round, e.g. to respect rate
+/// limits, consulted right before the unit is signed. Vetoed creation can only be deferred, never
+/// skipped, since our unit of every round has to be a parent of our unit of the next one.
+pub trait CreationGate: Send + Sync + 'static {
+    /// Decides whether our unit of the given round can be created now.
+    fn decide(&self, round: Round) -> CreationDecision;
+}
+
+/// The number of parents a unit needs by default, floor(2N/3) + 1, which guarantees that any two
+/// sets of parents of that size share an honest node when at most floor((N-1)/3) nodes are faulty.
+pub fn default_threshold(n_members: NodeCount) -> NodeCount {
+    (n_members * 2) / 3 + NodeCount(1)
+}
+
 #[derive(Eq, Error, Debug, PartialEq)]
 enum ConstraintError {
     #[error("Not enough parents.")]
@@ -13,17 +63,48 @@ enum ConstraintError {
     MissingOwnParent,
 }
 
+/// The candidates for parents collected by a [`Creator`] in the rounds it has not pruned yet,
+/// enough to resume creation without seeing all the units again.
+#[derive(Clone, Debug, Eq, PartialEq, Decode, Encode)]
+pub struct CreatorSnapshot<H: Hasher> {
+    session_id: SessionId,
+    base_round: Round,
+    candidates: Vec<NodeMap<H::Hash>>,
+}
+
+#[derive(Eq, Error, Debug, PartialEq)]
+pub enum SnapshotError {
+    #[error("Snapshot contains no rounds.")]
+    NoRounds,
+    #[error("Snapshot taken for {0:?} members.")]
+    WrongMemberCount(NodeCount),
+    #[error("Snapshot taken in session {0}.")]
+    WrongSession(SessionId),
+    #[error("Snapshot taken at round {0}, after the newest backed up unit.")]
+    AheadOfBackup(Round),
+}
+
 #[derive(Clone)]
 struct UnitsCollector<H: Hasher> {
     candidates: NodeMap<H::Hash>,
     n_candidates: NodeCount,
+    threshold: NodeCount,
 }
 
 impl<H: Hasher> UnitsCollector<H> {
-    pub fn new(n_members: NodeCount) -> Self {
+    pub fn new(n_members: NodeCount, threshold: NodeCount) -> Self {
         Self {
             candidates: NodeMap::with_size(n_members),
             n_candidates: NodeCount(0),
+            threshold,
+        }
+    }
+
+    fn from_candidates(candidates: NodeMap<H::Hash>, threshold: NodeCount) -> Self {
+        Self {
+            n_candidates: NodeCount(candidates.item_count()),
+            candidates,
+            threshold,
         }
     }
 
@@ -41,9 +122,7 @@ impl<H: Hasher> UnitsCollector<H> {
         &self,
         node_id: NodeIndex,
     ) -> Result<&NodeMap<H::Hash>, ConstraintError> {
-        let threshold = (self.candidates.size() * 2) / 3 + NodeCount(1);
-
-        if self.n_candidates < threshold {
+        if self.n_candidates < self.threshold {
             return Err(ConstraintError::NotEnoughParents);
         }
         if self.candidates.get(node_id).is_none() {
@@ -66,69 +145,250 @@ fn create_unit<H: Hasher>(
 }
 
 pub struct Creator<H: Hasher> {
+    // The collector of round `base_round + i` is at index `i`, older ones are pruned.
     round_collectors: Vec<UnitsCollector<H>>,
+    base_round: Round,
     node_id: NodeIndex,
     n_members: NodeCount,
+    threshold: NodeCount,
+    parent_selector: Arc<dyn ParentSelector>,
+    max_round_advance: Round,
+    session_id: SessionId,
 }
 
 impl<H: Hasher> Creator<H> {
-    pub fn new(node_id: NodeIndex, n_members: NodeCount) -> Self {
+    /// A creator requiring `threshold` parents for every unit above round 0, usually
+    /// [`default_threshold`] of `n_members`.
+    pub fn new(node_id: NodeIndex, n_members: NodeCount, threshold: NodeCount) -> Self {
         Creator {
             node_id,
             n_members,
-            round_collectors: vec![UnitsCollector::new(n_members)],
+            threshold,
+            round_collectors: vec![UnitsCollector::new(n_members, threshold)],
+            base_round: 0,
+            parent_selector: Arc::new(AllParents),
+            max_round_advance: Round::MAX,
+            session_id: 0,
         }
     }
 
+    pub fn with_parent_selector(mut self, parent_selector: Arc<dyn ParentSelector>) -> Self {
+        self.parent_selector = parent_selector;
+        self
+    }
+
+    /// Units more than `max_round_advance` rounds ahead of the current round are ignored,
+    /// so that a single unit cannot force allocating collectors for a huge number of rounds.
+    pub fn with_max_round_advance(mut self, max_round_advance: Round) -> Self {
+        self.max_round_advance = max_round_advance;
+        self
+    }
+
+    /// The session recorded in snapshots and required from the ones restored, 0 by default.
+    pub fn with_session_id(mut self, session_id: SessionId) -> Self {
+        self.session_id = session_id;
+        self
+    }
+
     pub fn current_round(&self) -> Round {
-        (self.round_collectors.len() - 1) as Round
+        self.base_round + (self.round_collectors.len() - 1) as Round
+    }
+
+    fn collector(&self, round: Round) -> Option<&UnitsCollector<H>> {
+        let round_ix = usize::from(round.checked_sub(self.base_round)?);
+        self.round_collectors.get(round_ix)
+    }
+
+    /// Drops the collectors of all the rounds strictly below `round`, they are only needed for
+    /// creating units of at most one round later. The collector of the current round is always
+    /// kept. Units of the dropped rounds are ignored from now on.
+    pub fn prune_below(&mut self, round: Round) {
+        let round = round.min(self.current_round());
+        if round <= self.base_round {
+            return;
+        }
+        self.round_collectors
+            .drain(..usize::from(round - self.base_round));
+        self.base_round = round;
+    }
+
+    /// Captures the candidates for parents of all the rounds not pruned so far.
+    pub fn snapshot(&self) -> CreatorSnapshot<H> {
+        CreatorSnapshot {
+            session_id: self.session_id,
+            base_round: self.base_round,
+            candidates: self
+                .round_collectors
+                .iter()
+                .map(|collector| collector.candidates.clone())
+                .collect(),
+        }
+    }
+
+    /// Replaces all the collected candidates with the ones from the snapshot, so that creation can
+    /// continue from `starting_round`. The snapshot has to be taken in our session, no later than
+    /// after creating our unit of the round before `starting_round`.
+    pub fn restore(
+        &mut self,
+        snapshot: CreatorSnapshot<H>,
+        starting_round: Round,
+    ) -> Result<(), SnapshotError> {
+        let CreatorSnapshot {
+            session_id,
+            base_round,
+            candidates,
+        } = snapshot;
+        if session_id != self.session_id {
+            return Err(SnapshotError::WrongSession(session_id));
+        }
+        if base_round >= starting_round {
+            return Err(SnapshotError::AheadOfBackup(base_round));
+        }
+        if candidates.is_empty() {
+            return Err(SnapshotError::NoRounds);
+        }
+        if let Some(wrong) = candidates
+            .iter()
+            .find(|candidates| candidates.size() != self.n_members)
+        {
+            return Err(SnapshotError::WrongMemberCount(wrong.size()));
+        }
+        self.round_collectors = candidates
+            .into_iter()
+            .map(|candidates| UnitsCollector::from_candidates(candidates, self.threshold))
+            .collect();
+        self.base_round = base_round;
+        Ok(())
     }
 
-    // gets or initializes a unit collector for a given round (and all between if not there)
+    /// The round the creator is heading for, as a target for external timing logic: the one after
+    /// the current round if the units collected so far already allow creating a unit there,
+    /// otherwise the current round.
+    pub fn next_creation_round(&self) -> Round {
+        let current_round = self.current_round();
+        let can_advance = self.collector(current_round).map_or(false, |collector| {
+            collector.prospective_parents(self.node_id).is_ok()
+        });
+        if can_advance {
+            current_round + 1
+        } else {
+            current_round
+        }
+    }
+
+    /// The highest round above 0 for which [`Creator::create_unit`] would currently succeed, i.e.
+    /// the one after the newest round with enough candidates for parents including our own unit.
+    /// Round 0 needs no parents, so `None` means that only round 0 is creatable.
+    pub fn max_creatable_round(&self) -> Option<Round> {
+        self.round_collectors
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, collector)| collector.prospective_parents(self.node_id).is_ok())
+            .map(|(round_ix, _)| self.base_round + round_ix as Round + 1)
+    }
+
+    // gets or initializes a unit collector for a given round (and all between if not there),
+    // the round must not be pruned
     fn get_or_initialize_collector_for_round(&mut self, round: Round) -> &mut UnitsCollector<H> {
-        let round_ix = usize::from(round);
+        let round_ix = usize::from(round - self.base_round);
         if round > self.current_round() {
             let new_size = round_ix + 1;
-            self.round_collectors
-                .resize(new_size, UnitsCollector::new(self.n_members));
+            self.round_collectors.resize(
+                new_size,
+                UnitsCollector::new(self.n_members, self.threshold),
+            );
         };
         &mut self.round_collectors[round_ix]
     }
 
-    /// To create a new unit, we need to have at least floor(2*N/3) + 1 parents available in previous round.
+    /// Whether creating a unit at the given round is blocked only by our own unit from the
+    /// previous round missing.
+    pub fn missing_own_parent(&self, round: Round) -> bool {
+        let prev_round = match round.checked_sub(1) {
+            Some(prev_round) => prev_round,
+            None => return false,
+        };
+        matches!(
+            self.collector(prev_round)
+                .map(|collector| collector.prospective_parents(self.node_id)),
+            Some(Err(ConstraintError::MissingOwnParent))
+        )
+    }
+
+    /// To create a new unit, we need to have at least the threshold of parents available in previous round.
     /// Additionally, our unit from previous round must be available.
     pub fn create_unit(&self, round: Round) -> Result<(PreUnit<H>, Vec<H::Hash>)> {
         if round == 0 {
             let parents = NodeMap::with_size(self.n_members);
             return Ok(create_unit(self.node_id, parents, round));
         }
-        let prev_round = usize::from(round - 1);
-
         let parents = self
-            .round_collectors
-            .get(prev_round)
+            .collector(round - 1)
             .ok_or(ConstraintError::NotEnoughParents)?
             .prospective_parents(self.node_id)?;
 
-        Ok(create_unit(self.node_id, parents.clone(), round))
+        Ok(create_unit(
+            self.node_id,
+            self.select_parents(parents),
+            round,
+        ))
+    }
+
+    fn select_parents(&self, candidates: &NodeMap<H::Hash>) -> NodeMap<H::Hash> {
+        let threshold = self.threshold;
+        if candidates.item_count() <= threshold.0 {
+            return candidates.clone();
+        }
+        let candidate_ids = candidates.to_subset();
+        let mut selected_ids = self
+            .parent_selector
+            .select(self.node_id, &candidate_ids, threshold);
+        selected_ids.insert(self.node_id);
+        let parents = NodeMap::from_hashmap(
+            self.n_members,
+            candidates
+                .iter()
+                .filter(|(id, _)| selected_ids.size() > id.0 && selected_ids[*id])
+                .map(|(id, hash)| (id, *hash))
+                .collect(),
+        );
+        if parents.item_count() < threshold.0 {
+            warn!(target: "AlephBFT-creator", "Parent selector chose too few of the available parents, using all of them instead.");
+            return candidates.clone();
+        }
+        parents
     }
 
     pub fn add_unit(&mut self, unit: &Unit<H>) {
-        self.get_or_initialize_collector_for_round(unit.round())
+        let round = unit.round();
+        if round > self.current_round().saturating_add(self.max_round_advance) {
+            warn!(target: "AlephBFT-creator", "Ignoring unit of round {} too far ahead of the current round {}.", round, self.current_round());
+            return;
+        }
+        if round < self.base_round {
+            trace!(target: "AlephBFT-creator", "Ignoring unit of round {} below the pruned round {}.", round, self.base_round);
+            return;
+        }
+        self.get_or_initialize_collector_for_round(round)
             .add_unit(unit);
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{Creator as GenericCreator, UnitsCollector};
+    use super::{
+        default_threshold, Creator as GenericCreator, CreatorSnapshot, ParentSelector,
+        SnapshotError, UnitsCollector,
+    };
     use crate::{
         creation::creator::ConstraintError,
-        units::{create_units, creator_set, preunit_to_unit},
-        NodeCount, NodeIndex,
+        units::{create_units, creator_set, preunit_to_unit, ControlHash, PreUnit},
+        NodeCount, NodeIndex, NodeMap, NodeSubset, Round,
     };
     use aleph_bft_mock::Hasher64;
-    use std::collections::HashSet;
+    use codec::{Decode, Encode};
+    use std::{collections::HashSet, sync::Arc};
 
     type Creator = GenericCreator<Hasher64>;
 
@@ -136,7 +396,7 @@ mod tests {
     fn creates_initial_unit() {
         let n_members = NodeCount(7);
         let round = 0;
-        let creator = Creator::new(NodeIndex(0), n_members);
+        let creator = Creator::new(NodeIndex(0), n_members, default_threshold(n_members));
         assert_eq!(creator.current_round(), round);
         let (preunit, parent_hashes) = creator
             .create_unit(round)
@@ -241,6 +501,34 @@ mod tests {
         dont_create_unit_below_parents_threshold(NodeCount(7));
     }
 
+    fn creates_unit_with_parents(n_parents: usize, threshold: NodeCount) -> bool {
+        let n_members = NodeCount(7);
+        let creators = creator_set(n_members);
+        let new_units: Vec<_> = create_units(creators.iter().take(n_parents), 0)
+            .into_iter()
+            .map(|(pu, _)| preunit_to_unit(pu, 0))
+            .collect();
+        let mut creator = Creator::new(NodeIndex(0), n_members, threshold);
+        creator.add_units(&new_units);
+        creator.create_unit(1).is_ok()
+    }
+
+    #[test]
+    fn respects_stricter_custom_threshold() {
+        let threshold = NodeCount(6);
+        assert!(threshold > default_threshold(NodeCount(7)));
+        assert!(creates_unit_with_parents(6, threshold));
+        assert!(!creates_unit_with_parents(5, threshold));
+    }
+
+    #[test]
+    fn respects_looser_custom_threshold() {
+        let threshold = NodeCount(3);
+        assert!(threshold < default_threshold(NodeCount(7)));
+        assert!(creates_unit_with_parents(3, threshold));
+        assert!(!creates_unit_with_parents(2, threshold));
+    }
+
     #[test]
     fn creates_two_units_when_possible() {
         let n_members = NodeCount(7);
@@ -305,7 +593,7 @@ mod tests {
             .map(|(pu, _)| preunit_to_unit(pu, 0))
             .collect();
 
-        let mut units_collector = UnitsCollector::new(n_members);
+        let mut units_collector = UnitsCollector::new(n_members, default_threshold(n_members));
         new_units
             .iter()
             .for_each(|unit| units_collector.add_unit(unit));
@@ -330,7 +618,7 @@ mod tests {
             .map(|(pu, _)| preunit_to_unit(pu, 0))
             .collect();
 
-        let mut units_collector = UnitsCollector::new(n_members);
+        let mut units_collector = UnitsCollector::new(n_members, default_threshold(n_members));
         new_units
             .iter()
             .for_each(|unit| units_collector.add_unit(unit));
@@ -352,7 +640,7 @@ mod tests {
             .map(|(pu, _)| preunit_to_unit(pu, 0))
             .collect();
 
-        let mut units_collector = UnitsCollector::new(n_members);
+        let mut units_collector = UnitsCollector::new(n_members, default_threshold(n_members));
         new_units
             .iter()
             .for_each(|unit| units_collector.add_unit(unit));
@@ -363,4 +651,251 @@ mod tests {
             ConstraintError::MissingOwnParent
         );
     }
+
+    /// Picks the candidates with the highest indices, just enough of them to reach the threshold.
+    struct HighestIndicesSelector;
+
+    impl ParentSelector for HighestIndicesSelector {
+        fn select(
+            &self,
+            _: NodeIndex,
+            candidates: &NodeSubset,
+            threshold: NodeCount,
+        ) -> NodeSubset {
+            let mut selected = NodeSubset::with_size(candidates.size().into());
+            let candidates: Vec<_> = candidates.elements().collect();
+            for id in candidates.into_iter().rev().take(threshold.0) {
+                selected.insert(id);
+            }
+            selected
+        }
+    }
+
+    #[test]
+    fn creates_unit_with_parents_chosen_by_selector() {
+        let n_members = NodeCount(7);
+        let creators = creator_set(n_members);
+        let new_units = create_units(creators.iter(), 0);
+        let new_units: Vec<_> = new_units
+            .into_iter()
+            .map(|(pu, _)| preunit_to_unit(pu, 0))
+            .collect();
+        let mut creator = Creator::new(NodeIndex(0), n_members, default_threshold(n_members))
+            .with_parent_selector(Arc::new(HighestIndicesSelector));
+        creator.add_units(&new_units);
+
+        let (preunit, parent_hashes) = creator.create_unit(1).expect("Creation should succeed.");
+
+        // The five highest indices, and our own unit on top of that.
+        let expected_parents = [0, 2, 3, 4, 5, 6];
+        let expected_hashes: Vec<_> = expected_parents
+            .iter()
+            .map(|id| new_units[*id].hash())
+            .collect();
+        let parents: Vec<_> = preunit.control_hash().parents().collect();
+        assert_eq!(
+            parents,
+            expected_parents
+                .iter()
+                .map(|id| NodeIndex(*id))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(parent_hashes, expected_hashes);
+    }
+
+    #[test]
+    fn ignores_unit_too_far_ahead() {
+        let n_members = NodeCount(4);
+        let mut creator = Creator::new(NodeIndex(0), n_members, default_threshold(n_members))
+            .with_max_round_advance(10);
+        let control_hash = ControlHash::new(&NodeMap::with_size(n_members));
+        let unit_of_round =
+            |round| preunit_to_unit(PreUnit::new(NodeIndex(1), round, control_hash.clone()), 0);
+
+        creator.add_unit(&unit_of_round(Round::MAX));
+        assert_eq!(creator.current_round(), 0);
+        assert_eq!(creator.round_collectors.len(), 1);
+
+        creator.add_unit(&unit_of_round(10));
+        assert_eq!(creator.current_round(), 10);
+    }
+
+    #[test]
+    fn creates_units_after_pruning() {
+        let n_members = NodeCount(4);
+        let mut creators = creator_set(n_members);
+        let mut units_per_round = Vec::new();
+        for round in 0..4 {
+            let units: Vec<_> = create_units(creators.iter(), round)
+                .into_iter()
+                .map(|(pu, _)| preunit_to_unit(pu, 0))
+                .collect();
+            for creator in creators.iter_mut() {
+                creator.add_units(&units);
+            }
+            units_per_round.push(units);
+        }
+        let creator = &mut creators[0];
+        assert_eq!(creator.current_round(), 3);
+
+        creator.prune_below(2);
+        assert_eq!(creator.round_collectors.len(), 2);
+        assert_eq!(creator.current_round(), 3);
+        let expected_hashes: Vec<_> = units_per_round[2].iter().map(|u| u.hash()).collect();
+        let (preunit, parent_hashes) = creator.create_unit(3).expect("Creation should succeed.");
+        assert_eq!(preunit.round(), 3);
+        assert_eq!(parent_hashes, expected_hashes);
+        assert!(creator.create_unit(2).is_err());
+        assert!(!creator.missing_own_parent(2));
+
+        // Units of pruned rounds are ignored, newer ones are still collected.
+        creator.add_units(&units_per_round[1]);
+        assert_eq!(creator.round_collectors.len(), 2);
+        creator.prune_below(Round::MAX);
+        assert_eq!(creator.round_collectors.len(), 1);
+        assert_eq!(creator.current_round(), 3);
+        assert!(creator.create_unit(4).is_ok());
+        assert!(creator.create_unit(3).is_err());
+    }
+
+    #[test]
+    fn restores_from_snapshot() {
+        let n_members = NodeCount(7);
+        let mut creators = creator_set(n_members);
+        for round in 0..4 {
+            // Only some of the units of the last round arrive, so creators differ in what they saw.
+            let n_units = if round == 3 { 5 } else { n_members.0 };
+            let units: Vec<_> = create_units(creators.iter().take(n_units), round)
+                .into_iter()
+                .map(|(pu, _)| preunit_to_unit(pu, 0))
+                .collect();
+            for creator in creators.iter_mut() {
+                creator.add_units(&units);
+            }
+        }
+        let creator = &mut creators[0];
+        creator.prune_below(2);
+
+        let snapshot = CreatorSnapshot::decode(&mut &creator.snapshot().encode()[..])
+            .expect("snapshot should decode");
+        let mut restored = Creator::new(NodeIndex(0), n_members, default_threshold(n_members));
+        restored
+            .restore(snapshot, 3)
+            .expect("snapshot should be valid");
+
+        assert_eq!(restored.current_round(), creator.current_round());
+        assert!(restored.create_unit(2).is_err());
+        for round in 3..5 {
+            let (preunit, parent_hashes) = creator
+                .create_unit(round)
+                .expect("Creation should succeed.");
+            let (restored_preunit, restored_parent_hashes) = restored
+                .create_unit(round)
+                .expect("Creation should succeed.");
+            assert_eq!(restored_preunit, preunit);
+            assert_eq!(restored_parent_hashes, parent_hashes);
+        }
+
+        let mut too_small =
+            Creator::new(NodeIndex(0), NodeCount(4), default_threshold(NodeCount(4)));
+        assert_eq!(
+            too_small.restore(creator.snapshot(), 3),
+            Err(SnapshotError::WrongMemberCount(n_members))
+        );
+        let mut other_session =
+            Creator::new(NodeIndex(0), n_members, default_threshold(n_members)).with_session_id(1);
+        assert_eq!(
+            other_session.restore(creator.snapshot(), 3),
+            Err(SnapshotError::WrongSession(0))
+        );
+    }
+
+    #[test]
+    fn rejects_snapshot_ahead_of_backup() {
+        let n_members = NodeCount(7);
+        let mut creators = creator_set(n_members);
+        for round in 0..3 {
+            let units: Vec<_> = create_units(creators.iter(), round)
+                .into_iter()
+                .map(|(pu, _)| preunit_to_unit(pu, 0))
+                .collect();
+            for creator in creators.iter_mut() {
+                creator.add_units(&units);
+            }
+        }
+        let creator = &mut creators[0];
+        // The snapshot taken after creating our unit of round 2, but we crashed before the unit
+        // was backed up, so we start from round 2 again.
+        creator.prune_below(2);
+        let snapshot = creator.snapshot();
+
+        let mut restored = Creator::new(NodeIndex(0), n_members, default_threshold(n_members));
+        assert_eq!(
+            restored.restore(snapshot.clone(), 2),
+            Err(SnapshotError::AheadOfBackup(2))
+        );
+        assert_eq!(restored.current_round(), 0);
+        assert!(restored.restore(snapshot, 3).is_ok());
+    }
+
+    #[test]
+    fn reports_next_creation_round() {
+        let n_members = NodeCount(7);
+        let mut creators = creator_set(n_members);
+        assert_eq!(creators[0].next_creation_round(), 0);
+
+        let new_units: Vec<_> = create_units(creators.iter(), 0)
+            .into_iter()
+            .map(|(pu, _)| preunit_to_unit(pu, 0))
+            .collect();
+        let creator = &mut creators[0];
+        creator.add_units(&new_units[1..]);
+        assert_eq!(creator.current_round(), 0);
+        assert_eq!(creator.next_creation_round(), 0);
+
+        creator.add_unit(&new_units[0]);
+        assert_eq!(creator.next_creation_round(), 1);
+
+        let (preunit, _) = creator.create_unit(1).expect("Creation should succeed.");
+        creator.add_unit(&preunit_to_unit(preunit, 0));
+        assert_eq!(creator.current_round(), 1);
+        assert_eq!(creator.next_creation_round(), 1);
+    }
+
+    #[test]
+    fn reports_max_creatable_round() {
+        let n_members = NodeCount(7);
+        let threshold = default_threshold(n_members).0;
+        let mut creators = creator_set(n_members);
+        let mut units_per_round = Vec::new();
+        for round in 0..3 {
+            let units: Vec<_> = create_units(creators.iter(), round)
+                .into_iter()
+                .map(|(pu, _)| preunit_to_unit(pu, 0))
+                .collect();
+            for creator in creators.iter_mut() {
+                creator.add_units(&units);
+            }
+            units_per_round.push(units);
+        }
+        let mut creator = Creator::new(NodeIndex(0), n_members, default_threshold(n_members));
+        assert_eq!(creator.max_creatable_round(), None);
+
+        // Round 0 complete, round 1 short of the threshold, round 2 without our own unit.
+        creator.add_units(&units_per_round[0]);
+        creator.add_units(&units_per_round[1][..threshold - 1]);
+        creator.add_units(&units_per_round[2][1..]);
+        assert_eq!(creator.current_round(), 2);
+        assert_eq!(creator.max_creatable_round(), Some(1));
+        assert!(creator.create_unit(1).is_ok());
+        assert!(creator.create_unit(2).is_err());
+
+        creator.add_unit(&units_per_round[1][threshold - 1]);
+        assert_eq!(creator.max_creatable_round(), Some(2));
+        assert!(creator.create_unit(2).is_ok());
+
+        creator.add_unit(&units_per_round[2][0]);
+        assert_eq!(creator.max_creatable_round(), Some(3));
+        assert!(creator.create_unit(3).is_ok());
+    }
 }
@@ -2,38 +2,58 @@ use crate::{
     config::{Config as GeneralConfig, DelaySchedule},
     runway::NotificationOut,
     units::{PreUnit, Unit},
-    Hasher, NodeCount, NodeIndex, Receiver, Round, Sender, Terminator,
+    Clock, Hasher, Metrics, NodeCount, NodeIndex, Receiver, Round, Sender, SessionId, Terminator,
 };
+use codec::{Decode, Encode};
 use futures::{
     channel::{
         mpsc::{SendError, TrySendError},
         oneshot,
     },
+    future::{BoxFuture, Fuse, FusedFuture},
     FutureExt, StreamExt,
 };
-use futures_timer::Delay;
 use log::{debug, error, trace, warn};
-use std::fmt::{Debug, Formatter};
+use std::{
+    fmt::{Debug, Formatter},
+    sync::Arc,
+    time::Duration,
+};
 
 mod creator;
 
-pub use creator::Creator;
+pub use creator::{
+    default_threshold, AllParents, CreationDecision, CreationGate, Creator, CreatorSnapshot,
+    ParentSelector,
+};
 
 /// The configuration needed for the process creating new units.
 #[derive(Clone)]
 pub struct Config {
     node_id: NodeIndex,
+    session_id: SessionId,
     n_members: NodeCount,
     create_lag: DelaySchedule,
     max_round: Round,
+    max_round_advance: Round,
+    parent_selector: Arc<dyn ParentSelector>,
+    own_parent_recovery_delay: Duration,
+    max_finalization_lag: Round,
+    creation_gate: Option<Arc<dyn CreationGate>>,
+    metrics: Arc<dyn Metrics>,
+    clock: Arc<dyn Clock>,
 }
 
 impl Debug for Config {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Config")
             .field("node id", &self.node_id)
+            .field("session id", &self.session_id)
             .field("member count", &self.n_members)
             .field("max round", &self.max_round)
+            .field("max round advance", &self.max_round_advance)
+            .field("own parent recovery delay", &self.own_parent_recovery_delay)
+            .field("max finalization lag", &self.max_finalization_lag)
             .finish()
     }
 }
@@ -42,9 +62,17 @@ impl From<GeneralConfig> for Config {
     fn from(conf: GeneralConfig) -> Self {
         Config {
             node_id: conf.node_ix(),
+            session_id: conf.session_id(),
             n_members: conf.n_members(),
             create_lag: conf.delay_config().unit_creation_delay.clone(),
             max_round: conf.max_round(),
+            max_round_advance: conf.max_round_advance().unwrap_or(conf.max_round()),
+            parent_selector: conf.parent_selector(),
+            own_parent_recovery_delay: conf.own_parent_recovery_delay(),
+            max_finalization_lag: conf.max_finalization_lag(),
+            creation_gate: conf.creation_gate(),
+            metrics: conf.metrics(),
+            clock: conf.clock(),
         }
     }
 }
@@ -52,6 +80,7 @@ impl From<GeneralConfig> for Config {
 enum CreatorError {
     OutChannelClosed(SendError),
     ParentsChannelClosed,
+    FinalizationStopped,
 }
 
 impl<T> From<TrySendError<T>> for CreatorError {
@@ -60,16 +89,39 @@ impl<T> From<TrySendError<T>> for CreatorError {
     }
 }
 
+/// An encoded [`CreatorSnapshot`] taken right after creating our unit of the given round.
+pub(crate) type RoundSnapshot = (Round, Vec<u8>);
+
+/// Where the creator takes the snapshot it starts from and reports the following ones to.
+#[derive(Clone, Default)]
+pub(crate) struct SnapshotIO {
+    pub(crate) initial: Option<Vec<u8>>,
+    pub(crate) snapshots: Option<Sender<RoundSnapshot>>,
+}
+
 pub struct IO<H: Hasher> {
     pub(crate) incoming_parents: Receiver<Unit<H>>,
     pub(crate) outgoing_units: Sender<NotificationOut<H>>,
+    pub(crate) finalized_rounds: Receiver<Round>,
+    /// An encoded [`CreatorSnapshot`] to start from instead of an empty creator.
+    pub(crate) initial_snapshot: Option<Vec<u8>>,
+    /// Receives an encoded snapshot of the creator after every unit it creates.
+    pub(crate) snapshots: Option<Sender<RoundSnapshot>>,
+}
+
+/// How to recover from our own unit from the previous round never reaching the creator.
+struct OwnParentRecovery {
+    delay: Duration,
+    clock: Arc<dyn Clock>,
 }
 
 async fn create_unit<H: Hasher>(
     round: Round,
     creator: &mut Creator<H>,
-    incoming_parents: &mut Receiver<Unit<H>>,
+    io: &mut IO<H>,
+    recovery: &OwnParentRecovery,
 ) -> Result<(PreUnit<H>, Vec<H::Hash>), CreatorError> {
+    let mut recovery_timeout = Fuse::terminated();
     loop {
         match creator.create_unit(round) {
             Ok(unit) => return Ok(unit),
@@ -77,8 +129,73 @@ async fn create_unit<H: Hasher>(
                 trace!(target: "AlephBFT-creator", "Creator unable to create a new unit at round {}: {}.", round, err)
             }
         }
-        process_unit(creator, incoming_parents).await?;
+        if !creator.missing_own_parent(round) {
+            recovery_timeout = Fuse::terminated();
+        } else if recovery_timeout.is_terminated() {
+            recovery_timeout = recovery.clock.sleep(recovery.delay).fuse();
+        }
+        futures::select! {
+            result = process_unit(creator, &mut io.incoming_parents).fuse() => result?,
+            _ = recovery_timeout => {
+                warn!(target: "AlephBFT-creator", "Own unit of round {} still missing, asking to restore it from backup.", round - 1);
+                io.outgoing_units
+                    .unbounded_send(NotificationOut::MissingOwnParent(round - 1))?;
+            },
+        }
+    }
+}
+
+/// Creates a unit of the given round once `gate` allows it, recreating it after every deferral
+/// so that it can pick up parents which arrived in the meantime.
+async fn create_gated_unit<H: Hasher>(
+    round: Round,
+    creator: &mut Creator<H>,
+    io: &mut IO<H>,
+    recovery: &OwnParentRecovery,
+    gate: &dyn CreationGate,
+    metrics: &dyn Metrics,
+) -> Result<(PreUnit<H>, Vec<H::Hash>), CreatorError> {
+    loop {
+        let unit = create_unit(round, creator, io, recovery).await?;
+        match gate.decide(round) {
+            CreationDecision::Create => return Ok(unit),
+            CreationDecision::Defer(delay) => {
+                debug!(target: "AlephBFT-creator", "Creation of unit at round {} deferred for {:?}.", round, delay);
+                metrics.report_deferred_creation(round);
+                let retry = recovery.clock.sleep(delay);
+                keep_processing_units_until(creator, &mut io.incoming_parents, retry).await?;
+            }
+        }
+    }
+}
+
+/// Keeps processing units until the finalized round, tracked in `finalized_round`, gets close enough
+/// to `round` for a unit of that round to be created.
+async fn wait_for_finalization<H: Hasher>(
+    round: Round,
+    creator: &mut Creator<H>,
+    io: &mut IO<H>,
+    max_finalization_lag: Round,
+    finalized_round: &mut Round,
+) -> Result<(), CreatorError> {
+    while let Some(Some(finalized)) = io.finalized_rounds.next().now_or_never() {
+        *finalized_round = (*finalized_round).max(finalized);
+    }
+    if round <= finalized_round.saturating_add(max_finalization_lag) {
+        return Ok(());
+    }
+    debug!(target: "AlephBFT-creator", "Pausing creation at round {}, finalized round is {}.", round, finalized_round);
+    while round > finalized_round.saturating_add(max_finalization_lag) {
+        futures::select! {
+            result = process_unit(creator, &mut io.incoming_parents).fuse() => result?,
+            finalized = io.finalized_rounds.next() => match finalized {
+                Some(finalized) => *finalized_round = (*finalized_round).max(finalized),
+                None => return Err(CreatorError::FinalizationStopped),
+            },
+        }
     }
+    debug!(target: "AlephBFT-creator", "Resuming creation at round {}, finalized round is {}.", round, finalized_round);
+    Ok(())
 }
 
 /// Tries to process a single parent from given `incoming_parents` receiver.
@@ -107,7 +224,7 @@ async fn keep_processing_units<H: Hasher>(
 async fn keep_processing_units_until<H: Hasher>(
     creator: &mut Creator<H>,
     incoming_parents: &mut Receiver<Unit<H>>,
-    until: Delay,
+    until: BoxFuture<'static, ()>,
 ) -> anyhow::Result<(), CreatorError> {
     futures::select! {
         result = keep_processing_units(creator, incoming_parents).fuse() => {
@@ -128,6 +245,7 @@ async fn keep_processing_units_until<H: Hasher>(
 /// - one of U's parents is the (r-1)-round unit by U's creator,
 /// - U has > floor(2*N/3) parents.
 /// - U will appear in the channel only if all U's parents appeared there before
+/// - U's round is at most `max_finalization_lag` rounds ahead of the finalized round
 /// The currently implemented strategy creates the unit U according to a delay schedule and when enough
 /// candidates for parents are available for all the above constraints to be satisfied.
 ///
@@ -175,6 +293,9 @@ async fn read_starting_round_and_run_creator<H: Hasher>(
             CreatorError::ParentsChannelClosed => {
                 debug!(target: "AlephBFT-creator", "Incoming parent channel closed, exiting.")
             }
+            CreatorError::FinalizationStopped => {
+                debug!(target: "AlephBFT-creator", "Finalized round channel closed, exiting.")
+            }
         }
     }
 }
@@ -186,31 +307,87 @@ async fn run_creator<H: Hasher>(
 ) -> anyhow::Result<(), CreatorError> {
     let Config {
         node_id,
+        session_id,
         n_members,
         create_lag,
         max_round,
+        max_round_advance,
+        parent_selector,
+        own_parent_recovery_delay,
+        max_finalization_lag,
+        creation_gate,
+        metrics,
+        clock,
     } = conf;
-    let mut creator = Creator::new(node_id, n_members);
-    let incoming_parents = &mut io.incoming_parents;
-    let outgoing_units = &io.outgoing_units;
+    let mut creator = Creator::new(node_id, n_members, default_threshold(n_members))
+        .with_parent_selector(parent_selector)
+        .with_max_round_advance(max_round_advance)
+        .with_session_id(session_id);
+    if let Some(snapshot) = io.initial_snapshot.take() {
+        match CreatorSnapshot::decode(&mut &snapshot[..]) {
+            Ok(snapshot) => match creator.restore(snapshot, starting_round) {
+                Ok(()) => {
+                    debug!(target: "AlephBFT-creator", "Creator restored from a snapshot at round {}.", creator.current_round())
+                }
+                Err(e) => {
+                    warn!(target: "AlephBFT-creator", "Unable to restore creator from a snapshot: {}", e)
+                }
+            },
+            Err(e) => {
+                warn!(target: "AlephBFT-creator", "Unable to decode the creator snapshot: {}", e)
+            }
+        }
+    }
+    let recovery = OwnParentRecovery {
+        delay: own_parent_recovery_delay,
+        clock: clock.clone(),
+    };
+    let mut finalized_round = 0;
 
     debug!(target: "AlephBFT-creator", "Creator starting from round {}", starting_round);
     for round in starting_round..max_round {
+        wait_for_finalization(
+            round,
+            &mut creator,
+            io,
+            max_finalization_lag,
+            &mut finalized_round,
+        )
+        .await?;
         // Skip waiting if someone created a unit of a higher round.
         // In such a case at least 2/3 nodes created units from this round so we aren't skipping a
         // delay we should observe.
         let skip_delay = creator.current_round() > round;
         if !skip_delay {
-            let lag = Delay::new(create_lag(round.into()));
+            let lag = clock.sleep(create_lag(round.into()));
 
-            keep_processing_units_until(&mut creator, incoming_parents, lag).await?;
+            keep_processing_units_until(&mut creator, &mut io.incoming_parents, lag).await?;
         }
 
-        let (unit, parent_hashes) = create_unit(round, &mut creator, incoming_parents).await?;
+        let (unit, parent_hashes) = match &creation_gate {
+            Some(gate) => {
+                create_gated_unit(round, &mut creator, io, &recovery, &**gate, &*metrics).await?
+            }
+            None => create_unit(round, &mut creator, io, &recovery).await?,
+        };
 
-        trace!(target: "AlephBFT-creator", "Created a new unit {:?} at round {:?}.", unit, round);
+        trace!(target: "AlephBFT-creator", "Created a new unit {:?} at round {:?}, next creation round {:?}.", unit, round, creator.next_creation_round());
+        metrics.report_max_creatable_round(creator.max_creatable_round().unwrap_or(0));
 
-        outgoing_units.unbounded_send(NotificationOut::CreatedPreUnit(unit, parent_hashes))?;
+        io.outgoing_units
+            .unbounded_send(NotificationOut::CreatedPreUnit(unit, parent_hashes))?;
+        // Only the collector of this round is needed for creating the next unit.
+        creator.prune_below(round);
+        // The snapshot is only passed on once the unit is backed up.
+        if let Some(snapshots) = &io.snapshots {
+            if snapshots
+                .unbounded_send((round, creator.snapshot().encode()))
+                .is_err()
+            {
+                debug!(target: "AlephBFT-creator", "Creator snapshot receiver gone.");
+                io.snapshots = None;
+            }
+        }
     }
 
     warn!(target: "AlephBFT-creator", "Maximum round reached. Not creating another unit.");
@@ -5,6 +5,13 @@ use log::{debug, warn};
 
 use crate::{Hasher, NodeCount, NodeIndex, NodeMap, Receiver, Round, Sender, Terminator};
 
+/// Hashes of finalized units, each together with the round of the unit.
+pub(crate) type OrderedBatch<H> = Vec<(<H as Hasher>::Hash, Round)>;
+
+/// How many rounds above a round are needed before its head can be accepted, as the first common
+/// vote in favour of a candidate is cast by units this far above it.
+pub(crate) const HEAD_ACCEPTANCE_DISTANCE: Round = 4;
+
 pub(crate) struct ExtenderUnit<H: Hasher> {
     creator: NodeIndex,
     round: Round,
@@ -55,7 +62,7 @@ impl CacheState {
 /// of all honest nodes. The static Aleph Consensus algorithm is then run on this Dag in order
 /// to finalize subsequent rounds of the Dag. More specifically whenever a new unit is received
 /// this process checks whether a new round can be finalized and if so, it computes the batch of
-/// units that should be finalized, unwraps them (leaving only a block hash per unit) and pushes
+/// units that should be finalized, unwraps them (leaving only a hash and round per unit) and pushes
 /// such a batch to a channel via the finalizer_tx endpoint.
 ///
 /// We refer to the documentation https://cardinal-cryptography.github.io/AlephBFT/internals.html
@@ -69,7 +76,8 @@ pub(crate) struct Extender<H: Hasher> {
     units_by_round: Vec<Vec<H::Hash>>,
     n_members: NodeCount,
     candidates: Vec<H::Hash>,
-    finalizer_tx: Sender<Vec<H::Hash>>,
+    finalizer_tx: Sender<OrderedBatch<H>>,
+    finalized_rounds_for_creator: Option<Sender<Round>>,
     exiting: bool,
 }
 
@@ -78,12 +86,14 @@ impl<H: Hasher> Extender<H> {
         node_id: NodeIndex,
         n_members: NodeCount,
         electors: Receiver<ExtenderUnit<H>>,
-        finalizer_tx: Sender<Vec<H::Hash>>,
+        finalizer_tx: Sender<OrderedBatch<H>>,
+        finalized_rounds_for_creator: Sender<Round>,
     ) -> Self {
         Extender {
             node_id,
             electors,
             finalizer_tx,
+            finalized_rounds_for_creator: Some(finalized_rounds_for_creator),
             state: CacheState::empty_dag_cache(),
             units: HashMap::new(),
             units_by_round: vec![vec![]],
@@ -138,7 +148,7 @@ impl<H: Hasher> Extender<H> {
         let mut queue = VecDeque::new();
         queue.push_back(self.units.remove(head).unwrap());
         while let Some(u) = queue.pop_front() {
-            batch.push(u.hash);
+            batch.push((u.hash, u.round));
             for u_hash in u.parents.into_values() {
                 if let Some(v) = self.units.remove(&u_hash) {
                     queue.push_back(v);
@@ -153,6 +163,13 @@ impl<H: Hasher> Extender<H> {
             warn!(target: "AlephBFT-extender", "{:?} Channel for batches should be open", self.node_id);
             self.exiting = true;
         }
+        if let Some(finalized_rounds_for_creator) = &self.finalized_rounds_for_creator {
+            // The creator stops listening once it reaches the maximum round.
+            if finalized_rounds_for_creator.unbounded_send(round).is_err() {
+                debug!(target: "AlephBFT-extender", "{:?} Channel with finalized rounds for creator closed.", self.node_id);
+                self.finalized_rounds_for_creator = None;
+            }
+        }
 
         debug!(target: "AlephBFT-extender", "{:?} Finalized round {:?} with head {:?}.", self.node_id, round, head);
         self.units_by_round[round as usize].clear();
@@ -355,7 +372,14 @@ mod tests {
         let rounds = 6;
         let (batch_tx, mut batch_rx) = mpsc::unbounded();
         let (electors_tx, electors_rx) = mpsc::unbounded();
-        let mut extender = Extender::<Hasher64>::new(0.into(), n_members, electors_rx, batch_tx);
+        let (finalized_rounds_tx, _finalized_rounds_rx) = mpsc::unbounded();
+        let mut extender = Extender::<Hasher64>::new(
+            0.into(),
+            n_members,
+            electors_rx,
+            batch_tx,
+            finalized_rounds_tx,
+        );
         let (exit_tx, exit_rx) = oneshot::channel();
         let extender_handle = tokio::spawn(async move {
             extender
@@ -379,4 +403,49 @@ mod tests {
         let _ = exit_tx.send(());
         let _ = extender_handle.await;
     }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+    async fn batches_carry_rounds_of_units() {
+        let n_members = NodeCount(4);
+        let rounds = 6;
+        let (batch_tx, mut batch_rx) = mpsc::unbounded();
+        let (electors_tx, electors_rx) = mpsc::unbounded();
+        let (finalized_rounds_tx, _finalized_rounds_rx) = mpsc::unbounded();
+        let mut extender = Extender::<Hasher64>::new(
+            0.into(),
+            n_members,
+            electors_rx,
+            batch_tx,
+            finalized_rounds_tx,
+        );
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let extender_handle = tokio::spawn(async move {
+            extender
+                .extend(Terminator::create_root(exit_rx, "AlephBFT-extender"))
+                .await
+        });
+
+        for round in 0..rounds {
+            for creator in n_members.into_iterator() {
+                let unit = construct_unit(creator, round, n_members);
+                electors_tx
+                    .unbounded_send(unit)
+                    .expect("Channel should be open");
+            }
+        }
+        // The head of round 0 has no parents, so the remaining units of round 0 are only
+        // finalized together with the head of round 1.
+        let batch_round_0 = batch_rx.next().await.unwrap();
+        assert_eq!(batch_round_0.len(), 1);
+        let batch_round_1 = batch_rx.next().await.unwrap();
+        let mut rounds: Vec<_> = batch_round_1.iter().map(|(_, round)| *round).collect();
+        rounds.dedup();
+        assert_eq!(rounds, vec![0, 1]);
+        for (hash, round) in batch_round_0.into_iter().chain(batch_round_1) {
+            let number = u64::from_ne_bytes(hash) as usize;
+            assert_eq!(number / n_members.0, round as usize);
+        }
+        let _ = exit_tx.send(());
+        let _ = extender_handle.await;
+    }
 }
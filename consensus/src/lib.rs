@@ -8,8 +8,12 @@ mod consensus;
 mod creation;
 mod extender;
 mod member;
+mod metrics;
 mod network;
+#[cfg(feature = "metrics")]
+mod prometheus;
 mod runway;
+mod signing;
 mod terminal;
 mod terminator;
 mod units;
@@ -19,17 +23,35 @@ mod task_queue;
 mod testing;
 
 pub use aleph_bft_types::{
-    Data, DataProvider, FinalizationHandler, Hasher, IncompleteMultisignatureError, Index, Indexed,
-    Keychain, MultiKeychain, Multisigned, Network, NodeCount, NodeIndex, NodeMap, NodeSubset,
-    PartialMultisignature, PartiallyMultisigned, Recipient, Round, SessionId, Signable, Signature,
-    SignatureError, SignatureSet, Signed, SpawnHandle, TaskHandle, UncheckedSigned,
+    Clock, Data, DataProvider, FinalizationHandler, FinalizedItem, Hasher,
+    IncompleteMultisignatureError, Index, Indexed, Keychain, MultiKeychain, Multisigned, Network,
+    NodeCount, NodeIndex, NodeMap, NodeSubset, PartialMultisignature, PartiallyMultisigned,
+    Recipient, Round, SchedulingHint, SessionId, Signable, Signature, SignatureError, SignatureSet,
+    Signed, SpawnHandle, SystemClock, TaskHandle, UncheckedSigned,
+};
+pub use alerts::{
+    AlertObserver, AlertStage, AlertSummary, AlerterSnapshot, ForkDetectionHandler, ForkEvidence,
+    ForkProof, NoopForkDetectionHandler,
 };
 pub use config::{
-    create_config, default_config, default_delay_config, exponential_slowdown, Config, DelayConfig,
+    create_config, default_config, default_delay_config, exponential_slowdown, AlertCommitment,
+    AlerterFailurePolicy, BackupGapPolicy, BackupLoadPolicy, Config, DelayConfig,
+    ParentVerification, RepeatedDataPolicy, RequestHashing, ResponseRateLimit,
 };
-pub use member::{run_session, LocalIO};
+pub use creation::{AllParents, CreationDecision, CreationGate, ParentSelector};
+pub use member::{run_session, DefaultRequestStrategy, LocalIO, RequestStrategy, UnitRequest};
+pub use metrics::{InternalChannel, Metrics, NoopMetrics, RunwayCounters, RunwayCountersSnapshot};
 pub use network::NetworkData;
+#[cfg(feature = "metrics")]
+pub use prometheus::PrometheusMetrics;
+pub use runway::{
+    collects_initial_units, BackupDirectory, BackupStats, BackupSync, CatchUpEstimate,
+    NetworkIOBuilder, RunwayControl, RunwayEvent, StatusSnapshot, ValidationFailure,
+};
 pub use terminator::{handle_task_termination, Terminator};
+pub use units::{
+    Inspection, UnitCoord, UnitInspector, UnitStoreStatus, ValidationErrorKind, Validator,
+};
 
 type Receiver<T> = futures::channel::mpsc::UnboundedReceiver<T>;
 type Sender<T> = futures::channel::mpsc::UnboundedSender<T>;
@@ -1,20 +1,23 @@
 use crate::{
+    alerts::{ForkDetectionHandler, NoopForkDetectionHandler},
     handle_task_termination,
     member::Task::{CoordRequest, ParentsRequest, RequestNewest, UnitBroadcast},
+    metrics::{TrackedReceiver, TrackedSendError, TrackedSender},
     network,
     runway::{
-        self, NetworkIO, NewestUnitResponse, Request, Response, RunwayIO, RunwayNotificationIn,
-        RunwayNotificationOut,
+        self, BackupSync, NetworkIOBuilder, NewestUnitResponse, Request, RequestResolved, Response,
+        RunwayControl, RunwayEvent, RunwayIO, RunwayNotificationIn, RunwayNotificationOut,
+        StatusSnapshot, ValidationFailure,
     },
     task_queue::TaskQueue,
-    units::{UncheckedSignedUnit, UnitCoord},
+    units::{UncheckedSignedUnit, UnitCoord, UnitInspector},
     Config, Data, DataProvider, FinalizationHandler, Hasher, MultiKeychain, Network, NodeIndex,
-    Receiver, Recipient, Round, Sender, Signature, SpawnHandle, Terminator, UncheckedSigned,
+    Receiver, Recipient, RequestHashing, Round, Sender, Signature, SpawnHandle, Terminator,
+    UncheckedSigned,
 };
 use aleph_bft_types::NodeMap;
 use codec::{Decode, Encode};
 use futures::{channel::mpsc, pin_mut, FutureExt, StreamExt};
-use futures_timer::Delay;
 use itertools::Itertools;
 use log::{debug, error, info, trace, warn};
 use network::NetworkData;
@@ -25,9 +28,13 @@ use std::{
     fmt::{self, Debug},
     io::{Read, Write},
     marker::PhantomData,
+    sync::Arc,
     time::Duration,
 };
 
+type NotResolvedParents<H> = HashSet<<H as Hasher>::Hash, RequestHashing>;
+type NotResolvedCoords = HashSet<UnitCoord, RequestHashing>;
+
 /// A message concerning units, either about new units or some requests for them.
 #[derive(Clone, Eq, PartialEq, Debug, Decode, Encode)]
 pub(crate) enum UnitMessage<H: Hasher, D: Data, S: Signature> {
@@ -39,8 +46,8 @@ pub(crate) enum UnitMessage<H: Hasher, D: Data, S: Signature> {
     ResponseCoord(UncheckedSignedUnit<H, D, S>),
     /// Request for the full list of parents of a unit.
     RequestParents(NodeIndex, H::Hash),
-    /// Response to a request for a full list of parents.
-    ResponseParents(H::Hash, Vec<UncheckedSignedUnit<H, D, S>>),
+    /// Response to a request for a full list of parents, together with the index of the responder.
+    ResponseParents(NodeIndex, H::Hash, Vec<UncheckedSignedUnit<H, D, S>>),
     /// Request by a node for the newest unit created by them, together with a u64 salt
     RequestNewest(NodeIndex, u64),
     /// Response to RequestNewest: (our index, maybe unit, salt) signed by us
@@ -54,7 +61,7 @@ impl<H: Hasher, D: Data, S: Signature> UnitMessage<H, D, S> {
             Self::RequestCoord(_, _) => Vec::new(),
             Self::ResponseCoord(uu) => uu.as_signable().included_data(),
             Self::RequestParents(_, _) => Vec::new(),
-            Self::ResponseParents(_, units) => units
+            Self::ResponseParents(_, _, units) => units
                 .iter()
                 .flat_map(|uu| uu.as_signable().included_data())
                 .collect(),
@@ -62,6 +69,53 @@ impl<H: Hasher, D: Data, S: Signature> UnitMessage<H, D, S> {
             UnitMessage::ResponseNewest(response) => response.as_signable().included_data(),
         }
     }
+
+    /// The units carried by this message.
+    pub(crate) fn units(&self) -> Vec<&UncheckedSignedUnit<H, D, S>> {
+        match self {
+            Self::NewUnit(uu) | Self::ResponseCoord(uu) => vec![uu],
+            Self::ResponseParents(_, _, units) => units.iter().collect(),
+            Self::ResponseNewest(response) => response.as_signable().unit().into_iter().collect(),
+            Self::RequestCoord(_, _) | Self::RequestParents(_, _) | Self::RequestNewest(_, _) => {
+                Vec::new()
+            }
+        }
+    }
+}
+
+/// A request for units sent out to other nodes, as seen by a [`RequestStrategy`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UnitRequest {
+    /// A request for the unit with the given coordinates.
+    Coord(UnitCoord),
+    /// A request for the parents of a unit.
+    Parents,
+    /// A request for our own newest unit, sent at startup.
+    NewestUnit,
+}
+
+/// Chooses the recipients of requests for units, e.g. to avoid peers known to be overloaded or
+/// to prefer the nearest ones.
+pub trait RequestStrategy: Send + Sync + 'static {
+    /// Returns the recipients of the given attempt (counting from 0) at sending the request.
+    /// `default` are the recipients chosen according to the [`Config`], which is what
+    /// [`DefaultRequestStrategy`] returns.
+    fn recipients_for(
+        &self,
+        request: UnitRequest,
+        attempt: usize,
+        default: Vec<Recipient>,
+    ) -> Vec<Recipient>;
+}
+
+/// A [`RequestStrategy`] keeping the recipients chosen according to the [`Config`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DefaultRequestStrategy;
+
+impl RequestStrategy for DefaultRequestStrategy {
+    fn recipients_for(&self, _: UnitRequest, _: usize, default: Vec<Recipient>) -> Vec<Recipient> {
+        default
+    }
 }
 
 #[derive(Eq, PartialEq, Debug)]
@@ -107,12 +161,29 @@ enum TaskDetails<H: Hasher, D: Data, S: Signature> {
     },
 }
 
-#[derive(Clone)]
-pub struct LocalIO<D: Data, DP: DataProvider<D>, FH: FinalizationHandler<D>, US: Write, UL: Read> {
+pub struct LocalIO<
+    D: Data,
+    DP: DataProvider<D>,
+    FH: FinalizationHandler<D>,
+    US: Write,
+    UL: Read,
+    FDH: ForkDetectionHandler<D> = NoopForkDetectionHandler,
+> {
     data_provider: DP,
     finalization_handler: FH,
+    fork_detection_handler: FDH,
     unit_saver: US,
     unit_loader: UL,
+    event_sender: Option<Sender<RunwayEvent>>,
+    status_sender: Option<Sender<StatusSnapshot>>,
+    validation_failure_sender: Option<Sender<ValidationFailure>>,
+    network_io_builder: NetworkIOBuilder,
+    control_receiver: Option<Receiver<RunwayControl>>,
+    unit_inspector: Option<Arc<dyn UnitInspector<D>>>,
+    genesis_data: Option<D>,
+    backup_sync: Option<BackupSync<US>>,
+    initial_creator_snapshot: Option<Vec<u8>>,
+    creator_snapshot_sender: Option<Sender<Vec<u8>>>,
     _phantom: PhantomData<D>,
 }
 
@@ -128,8 +199,161 @@ impl<D: Data, DP: DataProvider<D>, FH: FinalizationHandler<D>, US: Write, UL: Re
         LocalIO {
             data_provider,
             finalization_handler,
+            fork_detection_handler: NoopForkDetectionHandler,
             unit_saver,
             unit_loader,
+            event_sender: None,
+            status_sender: None,
+            validation_failure_sender: None,
+            network_io_builder: NetworkIOBuilder::new(),
+            control_receiver: None,
+            unit_inspector: None,
+            genesis_data: None,
+            backup_sync: None,
+            initial_creator_snapshot: None,
+            creator_snapshot_sender: None,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<
+        D: Data,
+        DP: DataProvider<D>,
+        FH: FinalizationHandler<D>,
+        US: Write,
+        UL: Read,
+        FDH: ForkDetectionHandler<D>,
+    > LocalIO<D, DP, FH, US, UL, FDH>
+{
+    /// Sets a [`ForkDetectionHandler`] notified once about every newly detected forker.
+    pub fn with_fork_detection_handler<F: ForkDetectionHandler<D>>(
+        self,
+        fork_detection_handler: F,
+    ) -> LocalIO<D, DP, FH, US, UL, F> {
+        LocalIO {
+            data_provider: self.data_provider,
+            finalization_handler: self.finalization_handler,
+            fork_detection_handler,
+            unit_saver: self.unit_saver,
+            unit_loader: self.unit_loader,
+            event_sender: self.event_sender,
+            status_sender: self.status_sender,
+            validation_failure_sender: self.validation_failure_sender,
+            network_io_builder: self.network_io_builder,
+            control_receiver: self.control_receiver,
+            unit_inspector: self.unit_inspector,
+            genesis_data: self.genesis_data,
+            backup_sync: self.backup_sync,
+            initial_creator_snapshot: self.initial_creator_snapshot,
+            creator_snapshot_sender: self.creator_snapshot_sender,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Sets a channel on which notable [`RunwayEvent`]s of the session will be reported.
+    pub fn with_event_sender(mut self, event_sender: Sender<RunwayEvent>) -> Self {
+        self.event_sender = Some(event_sender);
+        self
+    }
+
+    /// Sets a channel on which a [`StatusSnapshot`] of the session will be reported every time it
+    /// logs its status.
+    pub fn with_status_sender(mut self, status_sender: Sender<StatusSnapshot>) -> Self {
+        self.status_sender = Some(status_sender);
+        self
+    }
+
+    /// Sets a channel on which every unit received from the network and failing validation for
+    /// good is reported as a [`ValidationFailure`], e.g. to detect abuse based on their rate.
+    pub fn with_validation_failure_sender(
+        mut self,
+        validation_failure_sender: Sender<ValidationFailure>,
+    ) -> Self {
+        self.validation_failure_sender = Some(validation_failure_sender);
+        self
+    }
+
+    /// Sets how the unit channels between the session and the network are created, e.g. to bound
+    /// them with a [`NetworkIOBuilder`]. They are unbounded by default.
+    pub fn with_network_io_builder(mut self, network_io_builder: NetworkIOBuilder) -> Self {
+        self.network_io_builder = network_io_builder;
+        self
+    }
+
+    /// Sets a channel through which the session can be steered with [`RunwayControl`] commands.
+    /// The receiver is not carried over to clones of this [`LocalIO`].
+    pub fn with_control_receiver(mut self, control_receiver: Receiver<RunwayControl>) -> Self {
+        self.control_receiver = Some(control_receiver);
+        self
+    }
+
+    /// Sets a [`UnitInspector`] applying application-specific checks to all the incoming units.
+    pub fn with_unit_inspector(mut self, unit_inspector: Arc<dyn UnitInspector<D>>) -> Self {
+        self.unit_inspector = Some(unit_inspector);
+        self
+    }
+
+    /// Sets fixed data carried by the round-zero unit instead of data from the provider.
+    pub fn with_genesis_data(mut self, genesis_data: D) -> Self {
+        self.genesis_data = Some(genesis_data);
+        self
+    }
+
+    /// Sets a hook making the units saved to the backup durable, called after every flush of
+    /// the unit saver, e.g. [`File::sync_data`](std::fs::File::sync_data) for a file backup.
+    /// Our own units are only used once it returns successfully.
+    pub fn with_backup_sync(mut self, backup_sync: BackupSync<US>) -> Self {
+        self.backup_sync = Some(backup_sync);
+        self
+    }
+
+    /// Sets a channel on which an encoded snapshot of the candidate parents collected by the
+    /// creator is reported after every unit it creates, e.g. to persist the latest one. A
+    /// snapshot is only reported once the unit it was taken after is backed up.
+    pub fn with_creator_snapshot_sender(
+        mut self,
+        creator_snapshot_sender: Sender<Vec<u8>>,
+    ) -> Self {
+        self.creator_snapshot_sender = Some(creator_snapshot_sender);
+        self
+    }
+
+    /// Sets a snapshot previously reported through
+    /// [`with_creator_snapshot_sender`](LocalIO::with_creator_snapshot_sender), letting unit
+    /// creation resume at its round without waiting for all the units again.
+    pub fn with_initial_creator_snapshot(mut self, initial_creator_snapshot: Vec<u8>) -> Self {
+        self.initial_creator_snapshot = Some(initial_creator_snapshot);
+        self
+    }
+}
+
+impl<
+        D: Data,
+        DP: DataProvider<D> + Clone,
+        FH: FinalizationHandler<D> + Clone,
+        US: Write + Clone,
+        UL: Read + Clone,
+        FDH: ForkDetectionHandler<D> + Clone,
+    > Clone for LocalIO<D, DP, FH, US, UL, FDH>
+{
+    fn clone(&self) -> Self {
+        LocalIO {
+            data_provider: self.data_provider.clone(),
+            finalization_handler: self.finalization_handler.clone(),
+            fork_detection_handler: self.fork_detection_handler.clone(),
+            unit_saver: self.unit_saver.clone(),
+            unit_loader: self.unit_loader.clone(),
+            event_sender: self.event_sender.clone(),
+            status_sender: self.status_sender.clone(),
+            validation_failure_sender: self.validation_failure_sender.clone(),
+            network_io_builder: self.network_io_builder,
+            control_receiver: None,
+            unit_inspector: self.unit_inspector.clone(),
+            genesis_data: self.genesis_data.clone(),
+            backup_sync: self.backup_sync.clone(),
+            initial_creator_snapshot: self.initial_creator_snapshot.clone(),
+            creator_snapshot_sender: self.creator_snapshot_sender.clone(),
             _phantom: PhantomData,
         }
     }
@@ -137,15 +361,15 @@ impl<D: Data, DP: DataProvider<D>, FH: FinalizationHandler<D>, US: Write, UL: Re
 
 struct MemberStatus<'a, H: Hasher, D: Data, S: Signature> {
     task_queue: &'a TaskQueue<RepeatableTask<H, D, S>>,
-    not_resolved_parents: &'a HashSet<H::Hash>,
-    not_resolved_coords: &'a HashSet<UnitCoord>,
+    not_resolved_parents: &'a NotResolvedParents<H>,
+    not_resolved_coords: &'a NotResolvedCoords,
 }
 
 impl<'a, H: Hasher, D: Data, S: Signature> MemberStatus<'a, H, D, S> {
     fn new(
         task_queue: &'a TaskQueue<RepeatableTask<H, D, S>>,
-        not_resolved_parents: &'a HashSet<H::Hash>,
-        not_resolved_coords: &'a HashSet<UnitCoord>,
+        not_resolved_parents: &'a NotResolvedParents<H>,
+        not_resolved_coords: &'a NotResolvedCoords,
     ) -> Self {
         Self {
             task_queue,
@@ -227,15 +451,16 @@ where
 {
     config: Config,
     task_queue: TaskQueue<RepeatableTask<H, D, S>>,
-    not_resolved_parents: HashSet<H::Hash>,
-    not_resolved_coords: HashSet<UnitCoord>,
+    not_resolved_parents: NotResolvedParents<H>,
+    not_resolved_coords: NotResolvedCoords,
     newest_unit_resolved: bool,
+    newest_request_salt: Option<u64>,
     peers: Vec<Recipient>,
     unit_messages_for_network: Sender<(UnitMessage<H, D, S>, Recipient)>,
     unit_messages_from_network: Receiver<UnitMessage<H, D, S>>,
-    notifications_for_runway: Sender<RunwayNotificationIn<H, D, S>>,
-    notifications_from_runway: Receiver<RunwayNotificationOut<H, D, S>>,
-    resolved_requests: Receiver<Request<H>>,
+    notifications_for_runway: TrackedSender<RunwayNotificationIn<H, D, S>>,
+    notifications_from_runway: TrackedReceiver<RunwayNotificationOut<H, D, S>>,
+    resolved_requests: Receiver<RequestResolved<H>>,
     exiting: bool,
     top_units: NodeMap<Round>,
 }
@@ -250,9 +475,9 @@ where
         config: Config,
         unit_messages_for_network: Sender<(UnitMessage<H, D, S>, Recipient)>,
         unit_messages_from_network: Receiver<UnitMessage<H, D, S>>,
-        notifications_for_runway: Sender<RunwayNotificationIn<H, D, S>>,
-        notifications_from_runway: Receiver<RunwayNotificationOut<H, D, S>>,
-        resolved_requests: Receiver<Request<H>>,
+        notifications_for_runway: TrackedSender<RunwayNotificationIn<H, D, S>>,
+        notifications_from_runway: TrackedReceiver<RunwayNotificationOut<H, D, S>>,
+        resolved_requests: Receiver<RequestResolved<H>>,
     ) -> Self {
         let n_members = config.n_members();
         let peers = (0..n_members.0)
@@ -261,12 +486,15 @@ where
             .map(Recipient::Node)
             .collect();
 
+        let task_queue = TaskQueue::new().with_clock(config.clock());
+        let request_hashing = config.request_hashing();
         Self {
             config,
-            task_queue: TaskQueue::new(),
-            not_resolved_parents: HashSet::new(),
-            not_resolved_coords: HashSet::new(),
+            task_queue,
+            not_resolved_parents: HashSet::with_hasher(request_hashing.clone()),
+            not_resolved_coords: HashSet::with_hasher(request_hashing),
             newest_unit_resolved: false,
+            newest_request_salt: None,
             peers,
             unit_messages_for_network,
             unit_messages_from_network,
@@ -320,6 +548,15 @@ where
     }
 
     fn on_request_newest(&mut self, salt: u64) {
+        if self.newest_request_salt == Some(salt) {
+            // Broadcast again by the collection, the repetitions are already scheduled.
+            if !self.newest_unit_resolved {
+                let message = UnitMessage::RequestNewest(self.index(), salt);
+                self.send_unit_message(message, Recipient::Everyone);
+            }
+            return;
+        }
+        self.newest_request_salt = Some(salt);
         self.task_queue
             .schedule_now(RepeatableTask::new(RequestNewest(salt)));
         self.trigger_tasks();
@@ -392,19 +629,47 @@ where
     }
 
     fn recipients(&self, task: &Task<H, D, S>, counter: usize) -> Vec<Recipient> {
+        let default = self.default_recipients(task, counter);
+        let request = match task {
+            CoordRequest(coord) => UnitRequest::Coord(*coord),
+            ParentsRequest(_) => UnitRequest::Parents,
+            RequestNewest(_) => UnitRequest::NewestUnit,
+            UnitBroadcast(_) => return default,
+        };
+        self.config
+            .request_strategy()
+            .recipients_for(request, counter, default)
+    }
+
+    fn default_recipients(&self, task: &Task<H, D, S>, counter: usize) -> Vec<Recipient> {
         match task {
-            CoordRequest(_) => {
-                self.random_peers((self.config.delay_config().coord_request_recipients)(
-                    counter,
-                ))
+            CoordRequest(coord) => {
+                let creator_attempts = self.config.coord_request_creator_attempts();
+                if creator_attempts == 0 || coord.creator() == self.index() {
+                    self.random_peers((self.config.delay_config().coord_request_recipients)(
+                        counter,
+                    ))
+                } else if counter < creator_attempts {
+                    vec![Recipient::Node(coord.creator())]
+                } else {
+                    vec![Recipient::Everyone]
+                }
             }
             ParentsRequest(_) => {
-                self.random_peers((self.config.delay_config().parent_request_recipients)(
-                    counter,
-                ))
+                let count = (self.config.delay_config().parent_request_recipients)(counter);
+                match count >= self.peers.len() {
+                    true => vec![Recipient::Everyone],
+                    false => self.random_peers(count),
+                }
             }
             UnitBroadcast(_) => vec![Recipient::Everyone],
-            RequestNewest(_) => vec![Recipient::Everyone],
+            RequestNewest(_) => {
+                let count = (self.config.delay_config().newest_request_recipients)(counter);
+                match count >= self.peers.len() {
+                    true => vec![Recipient::Everyone],
+                    false => self.random_peers(count),
+                }
+            }
         }
     }
 
@@ -455,8 +720,8 @@ where
                     let message = UnitMessage::ResponseCoord(u);
                     self.send_unit_message(message, Recipient::Node(recipient))
                 }
-                Response::Parents(u_hash, parents) => {
-                    let message = UnitMessage::ResponseParents(u_hash, parents);
+                Response::Parents(responder, u_hash, parents) => {
+                    let message = UnitMessage::ResponseParents(responder, u_hash, parents);
                     self.send_unit_message(message, Recipient::Node(recipient))
                 }
                 Response::NewestUnit(response) => {
@@ -478,10 +743,11 @@ where
     }
 
     async fn run(mut self, mut terminator: Terminator) {
+        let clock = self.config.clock();
         let ticker_delay = self.config.delay_config().tick_interval;
-        let mut ticker = Delay::new(ticker_delay).fuse();
-        let status_ticker_delay = Duration::from_secs(10);
-        let mut status_ticker = Delay::new(status_ticker_delay).fuse();
+        let mut ticker = clock.sleep(ticker_delay).fuse();
+        let status_report_interval = self.config.status_report_interval();
+        let mut status_ticker = runway::interval_due(clock.as_ref(), status_report_interval);
 
         loop {
             futures::select! {
@@ -496,14 +762,18 @@ where
                 },
 
                 event = self.resolved_requests.next() => match event {
-                    Some(request) => match request {
+                    Some(RequestResolved { request, satisfied_by, abandoned }) => match request {
                         Request::Coord(coord) => {
+                            if abandoned {
+                                debug!(target: "AlephBFT-member", "{:?} No longer requesting abandoned coord {:?}.", self.index(), coord);
+                            }
                             self.not_resolved_coords.remove(&coord);
                         },
                         Request::Parents(u_hash) => {
                             self.not_resolved_parents.remove(&u_hash);
                         },
                         Request::NewestUnit(_) => {
+                            trace!(target: "AlephBFT-member", "{:?} Newest unit request resolved, satisfied by {:?}.", self.index(), satisfied_by);
                             self.newest_unit_resolved = true;
                         }
                     },
@@ -528,12 +798,12 @@ where
 
                 _ = &mut ticker => {
                     self.trigger_tasks();
-                    ticker = Delay::new(ticker_delay).fuse();
+                    ticker = clock.sleep(ticker_delay).fuse();
                 },
 
                 _ = &mut status_ticker => {
                     self.status_report();
-                    status_ticker = Delay::new(status_ticker_delay).fuse();
+                    status_ticker = runway::interval_due(clock.as_ref(), status_report_interval);
                 },
 
                 _ = terminator.get_exit().fuse() => {
@@ -552,13 +822,15 @@ where
     }
 
     fn send_notification_to_runway(&mut self, notification: RunwayNotificationIn<H, D, S>) {
-        if self
-            .notifications_for_runway
-            .unbounded_send(notification)
-            .is_err()
-        {
-            warn!(target: "AlephBFT-member", "{:?} Sender to runway with RunwayNotificationIn messages should be open", self.index());
-            self.exiting = true;
+        match self.notifications_for_runway.unbounded_send(notification) {
+            Ok(()) => {}
+            Err(TrackedSendError::Full(_)) => {
+                debug!(target: "AlephBFT-member", "{:?} Too many messages waiting for the runway, dropping one.", self.index());
+            }
+            Err(TrackedSendError::Disconnected(_)) => {
+                warn!(target: "AlephBFT-member", "{:?} Sender to runway with RunwayNotificationIn messages should be open", self.index());
+                self.exiting = true;
+            }
         }
     }
 }
@@ -575,12 +847,13 @@ pub async fn run_session<
     FH: FinalizationHandler<D>,
     US: Write + Send + Sync + 'static,
     UL: Read + Send + Sync + 'static,
+    FDH: ForkDetectionHandler<D>,
     N: Network<NetworkData<H, D, MK::Signature, MK::PartialMultisignature>> + 'static,
     SH: SpawnHandle,
     MK: MultiKeychain,
 >(
     config: Config,
-    local_io: LocalIO<D, DP, FH, US, UL>,
+    local_io: LocalIO<D, DP, FH, US, UL, FDH>,
     network: N,
     keychain: MK,
     spawn_handle: SH,
@@ -590,13 +863,10 @@ pub async fn run_session<
     info!(target: "AlephBFT-member", "{:?} Starting a new session.", index);
     debug!(target: "AlephBFT-member", "{:?} Spawning party for a session.", index);
 
-    let (alert_messages_for_alerter, alert_messages_from_network) = mpsc::unbounded();
-    let (alert_messages_for_network, alert_messages_from_alerter) = mpsc::unbounded();
     let (unit_messages_for_units, unit_messages_from_network) = mpsc::unbounded();
     let (unit_messages_for_network, unit_messages_from_units) = mpsc::unbounded();
-    let (runway_messages_for_runway, runway_messages_from_network) = mpsc::unbounded();
-    let (runway_messages_for_network, runway_messages_from_runway) = mpsc::unbounded();
-    let (resolved_requests_tx, resolved_requests_rx) = mpsc::unbounded();
+    let (network_io, network_ends) = local_io.network_io_builder.build();
+    let network_io = network_io.with_validation_failures(local_io.validation_failure_sender);
 
     debug!(target: "AlephBFT-member", "{:?} Spawning network.", index);
     let network_terminator = terminator.add_offspring_connection("AlephBFT-network");
@@ -607,8 +877,8 @@ pub async fn run_session<
                 network,
                 unit_messages_from_units,
                 unit_messages_for_units,
-                alert_messages_from_alerter,
-                alert_messages_for_alerter,
+                network_ends.alert_messages_from_alerter,
+                network_ends.alert_messages_for_alerter,
                 network_terminator,
             )
             .await
@@ -619,19 +889,23 @@ pub async fn run_session<
 
     debug!(target: "AlephBFT-member", "{:?} Initializing Runway.", index);
     let runway_terminator = terminator.add_offspring_connection("AlephBFT-runway");
-    let network_io = NetworkIO {
-        alert_messages_for_network,
-        alert_messages_from_network,
-        unit_messages_from_network: runway_messages_from_network,
-        unit_messages_for_network: runway_messages_for_network,
-        resolved_requests: resolved_requests_tx,
-    };
     let runway_io = RunwayIO::new(
         local_io.data_provider,
         local_io.finalization_handler,
         local_io.unit_saver,
         local_io.unit_loader,
-    );
+    )
+    .with_event_sender(local_io.event_sender)
+    .with_status_sender(local_io.status_sender)
+    .with_control_receiver(local_io.control_receiver)
+    .with_unit_inspector(local_io.unit_inspector)
+    .with_genesis_data(local_io.genesis_data)
+    .with_backup_sync(local_io.backup_sync)
+    .with_creator_snapshots(
+        local_io.initial_creator_snapshot,
+        local_io.creator_snapshot_sender,
+    )
+    .with_fork_detection_handler(local_io.fork_detection_handler);
     let spawn_copy = spawn_handle.clone();
     let config_copy = config.clone();
     let runway_handle = spawn_handle
@@ -655,9 +929,9 @@ pub async fn run_session<
         config,
         unit_messages_for_network,
         unit_messages_from_network,
-        runway_messages_for_runway,
-        runway_messages_from_runway,
-        resolved_requests_rx,
+        network_ends.unit_messages_for_runway,
+        network_ends.unit_messages_from_runway,
+        network_ends.resolved_requests,
     );
     let member_terminator = terminator.add_offspring_connection("AlephBFT-member");
     let member_handle = spawn_handle
@@ -710,11 +984,14 @@ mod tests {
     use itertools::Itertools;
     use std::sync::Arc;
 
+    type TestMember = Member<Hasher64, u32, Signature>;
+    type TestUnitMessage = UnitMessage<Hasher64, u32, Signature>;
+
     fn mock_member(
         node_ix: NodeIndex,
         node_count: NodeCount,
         delay_config: DelayConfig,
-    ) -> Member<Hasher64, u32, Signature> {
+    ) -> TestMember {
         let config = gen_config(node_ix, node_count, delay_config);
         let (unit_messages_for_network_sx, _) = unbounded();
         let (_, unit_messages_from_network_rx) = unbounded();
@@ -726,8 +1003,8 @@ mod tests {
             config,
             unit_messages_for_network_sx,
             unit_messages_from_network_rx,
-            notifications_for_runway_sx,
-            notifications_from_runway_rx,
+            notifications_for_runway_sx.into(),
+            notifications_from_runway_rx.into(),
             resolved_requests_rx,
         )
     }
@@ -806,6 +1083,30 @@ mod tests {
         assert!(!recipients.contains(&Recipient::Node(node_ix)));
     }
 
+    #[test]
+    fn newest_request_escalates_from_subset_to_everyone() {
+        let node_ix = NodeIndex(7);
+        let mut delay_config = gen_delay_config();
+        delay_config.newest_request_recipients = Arc::new(|t| if t == 0 { 15 } else { usize::MAX });
+
+        let member = mock_member(node_ix, NodeCount(20), delay_config);
+
+        let request = RequestNewest(12345);
+        let recipients = member.recipients(&request, 0);
+
+        assert_eq!(recipients.len(), 15);
+        assert_eq!(
+            recipients.iter().cloned().unique().collect::<Vec<_>>(),
+            recipients
+        );
+        assert!(!recipients.contains(&Recipient::Node(node_ix)));
+        assert!(!recipients.contains(&Recipient::Everyone));
+
+        let recipients = member.recipients(&request, 1);
+
+        assert_eq!(recipients, vec![Recipient::Everyone]);
+    }
+
     #[test]
     fn at_most_n_members_recipients_for_coord_request() {
         let mut delay_config = gen_delay_config();
@@ -819,6 +1120,168 @@ mod tests {
         assert_eq!(recipients.len(), member.config.n_members().0 - 1);
     }
 
+    /// A member together with the receiving end of the messages it sends to the network.
+    fn member_with_network(config: Config) -> (TestMember, Receiver<(TestUnitMessage, Recipient)>) {
+        let (unit_messages_for_network, unit_messages) = unbounded();
+        let (_, unit_messages_from_network) = unbounded();
+        let (notifications_for_runway, _) = unbounded();
+        let (_, notifications_from_runway) = unbounded();
+        let (_, resolved_requests) = unbounded();
+        let member = Member::new(
+            config,
+            unit_messages_for_network,
+            unit_messages_from_network,
+            notifications_for_runway.into(),
+            notifications_from_runway.into(),
+            resolved_requests,
+        );
+        (member, unit_messages)
+    }
+
+    #[test]
+    fn newest_request_broadcast_again_only_once() {
+        let node_ix = NodeIndex(7);
+        let mut delay_config = gen_delay_config();
+        delay_config.newest_request_delay = Arc::new(|_| Duration::from_secs(600));
+        let config = gen_config(node_ix, NodeCount(20), delay_config);
+        let (mut member, mut unit_messages) = member_with_network(config);
+        let salt = 12345;
+
+        member.on_request_newest(salt);
+        while let Some(Some(_)) = unit_messages.next().now_or_never() {}
+
+        // The repeated request is sent to everyone, without scheduling further repetitions.
+        member.on_request_newest(salt);
+        assert_eq!(
+            unit_messages.next().now_or_never(),
+            Some(Some((
+                UnitMessage::RequestNewest(node_ix, salt),
+                Recipient::Everyone
+            )))
+        );
+        member.trigger_tasks();
+        assert!(unit_messages.next().now_or_never().is_none());
+    }
+
+    #[test]
+    fn coord_request_sent_where_the_strategy_says() {
+        struct FixedRecipient(NodeIndex);
+
+        impl RequestStrategy for FixedRecipient {
+            fn recipients_for(
+                &self,
+                request: UnitRequest,
+                _: usize,
+                default: Vec<Recipient>,
+            ) -> Vec<Recipient> {
+                match request {
+                    UnitRequest::Coord(_) => vec![Recipient::Node(self.0)],
+                    _ => default,
+                }
+            }
+        }
+
+        let node_ix = NodeIndex(7);
+        let fixed = NodeIndex(11);
+        let config = gen_config(node_ix, NodeCount(20), gen_delay_config())
+            .with_coord_request_creator_attempts(2)
+            .with_request_strategy(Arc::new(FixedRecipient(fixed)));
+        let (mut member, mut unit_messages) = member_with_network(config);
+        let coord = UnitCoord::new(1, NodeIndex(3));
+
+        member.on_request_coord(coord);
+        assert_eq!(
+            unit_messages.next().now_or_never(),
+            Some(Some((
+                UnitMessage::RequestCoord(node_ix, coord),
+                Recipient::Node(fixed)
+            )))
+        );
+        assert!(unit_messages.next().now_or_never().is_none());
+    }
+
+    #[test]
+    fn coord_request_repeated_to_creator_then_everyone() {
+        let node_ix = NodeIndex(7);
+        let creator = NodeIndex(3);
+        let mut delay_config = gen_delay_config();
+        delay_config.coord_request_delay = Arc::new(|_| Duration::from_millis(50));
+        let config =
+            gen_config(node_ix, NodeCount(20), delay_config).with_coord_request_creator_attempts(2);
+        let (mut member, mut unit_messages) = member_with_network(config);
+        let coord = UnitCoord::new(1, creator);
+        let request = UnitMessage::RequestCoord(node_ix, coord);
+
+        member.on_request_coord(coord);
+        assert_eq!(
+            unit_messages.next().now_or_never(),
+            Some(Some((request.clone(), Recipient::Node(creator))))
+        );
+
+        // The creator dropped the request, which is repeated only once the interval passes.
+        member.trigger_tasks();
+        assert!(unit_messages.next().now_or_never().is_none());
+        std::thread::sleep(Duration::from_millis(60));
+        member.trigger_tasks();
+        assert_eq!(
+            unit_messages.next().now_or_never(),
+            Some(Some((request.clone(), Recipient::Node(creator))))
+        );
+
+        std::thread::sleep(Duration::from_millis(60));
+        member.trigger_tasks();
+        assert_eq!(
+            unit_messages.next().now_or_never(),
+            Some(Some((request, Recipient::Everyone)))
+        );
+
+        // Once the unit arrives, the request is not repeated anymore.
+        member.not_resolved_coords.remove(&coord);
+        std::thread::sleep(Duration::from_millis(60));
+        member.trigger_tasks();
+        assert!(unit_messages.next().now_or_never().is_none());
+    }
+
+    #[test]
+    fn parents_request_repeated_then_sent_to_everyone() {
+        let node_ix = NodeIndex(7);
+        let mut delay_config = gen_delay_config();
+        delay_config.parent_request_delay = Arc::new(|_| Duration::from_millis(50));
+        delay_config.parent_request_recipients = Arc::new(|t| if t < 2 { 1 } else { usize::MAX });
+        let (mut member, mut unit_messages) =
+            member_with_network(gen_config(node_ix, NodeCount(20), delay_config));
+        let u_hash = Hasher64::hash(&[0x0]);
+        let request = UnitMessage::RequestParents(node_ix, u_hash);
+        let mut next_recipient = || match unit_messages.next().now_or_never() {
+            Some(Some((message, recipient))) => {
+                assert_eq!(message, request);
+                Some(recipient)
+            }
+            _ => None,
+        };
+
+        member.on_request_parents(u_hash);
+        assert!(matches!(next_recipient(), Some(Recipient::Node(_))));
+
+        // The response got dropped, so the request is repeated once the interval passes.
+        member.trigger_tasks();
+        assert_eq!(next_recipient(), None);
+        std::thread::sleep(Duration::from_millis(60));
+        member.trigger_tasks();
+        assert!(matches!(next_recipient(), Some(Recipient::Node(_))));
+
+        std::thread::sleep(Duration::from_millis(60));
+        member.trigger_tasks();
+        assert_eq!(next_recipient(), Some(Recipient::Everyone));
+        assert_eq!(next_recipient(), None);
+
+        // Once the parents arrive, the request is not repeated anymore.
+        member.not_resolved_parents.remove(&u_hash);
+        std::thread::sleep(Duration::from_millis(60));
+        member.trigger_tasks();
+        assert_eq!(next_recipient(), None);
+    }
+
     #[test]
     fn no_recipients_for_coord_request_in_one_node_setup() {
         let mut delay_config = gen_delay_config();
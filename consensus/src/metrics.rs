@@ -0,0 +1,277 @@
+use crate::{NodeIndex, Receiver, Round, Sender};
+use futures::{
+    channel::mpsc::{self, TrySendError},
+    stream::FusedStream,
+    Stream, StreamExt,
+};
+use parking_lot::Mutex;
+use std::{
+    fmt, mem,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+/// Internal channels whose backlog is reported with [`Metrics::report_channel_depth`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum InternalChannel {
+    /// Notifications, mostly units, sent by the runway to consensus.
+    NotificationsForConsensus,
+    /// Unit messages and requests sent by the runway to the network layer.
+    UnitMessagesForNetwork,
+    /// Batches of units ordered by consensus, waiting to be finalized by the runway.
+    OrderedBatches,
+}
+
+/// An interface for reporting measurements of the consensus internals, e.g. to export them as
+/// gauges in a monitoring system. All the methods default to doing nothing, so an implementation
+/// only needs to override the ones it is interested in.
+pub trait Metrics: Send + Sync + 'static {
+    /// The highest round of a unit held in the unit store.
+    fn report_dag_height(&self, _height: Round) {}
+    /// The number of units held in the unit store for the given round.
+    fn report_dag_width(&self, _round: Round, _width: usize) {}
+    /// Called whenever a unit received from another node is about to be validated.
+    fn report_unit_received(&self) {}
+    /// The number of units finalized in a single batch ordered by consensus.
+    fn report_finalized_units(&self, _count: usize) {}
+    /// Called whenever a new forker is detected, so an alert about it gets raised.
+    fn report_forker_detected(&self) {}
+    /// The number of missing units we still wait for, sampled with every status report.
+    fn report_missing_coords(&self, _count: usize) {}
+    /// The number of forkers known to the alerter that consensus missed, reported whenever
+    /// a periodic reconciliation finds any.
+    fn report_forker_discrepancy(&self, _missed: usize) {}
+    /// The number of distinct forkers confirmed by the alerter, i.e. ones for which an alert
+    /// completed reliable multicast, reported whenever a new one gets confirmed.
+    fn report_confirmed_forkers(&self, _count: usize) {}
+    /// Called once the alerter stopped and the session continues without fork protection, see
+    /// [`AlerterFailurePolicy::Degrade`](crate::AlerterFailurePolicy::Degrade).
+    fn report_alerter_down(&self) {}
+    /// Called whenever a unit is given up on after repeatedly failing to reconstruct its parents,
+    /// see [`Config::with_wrong_control_hash_limit`](crate::Config::with_wrong_control_hash_limit).
+    fn report_abandoned_unit(&self) {}
+    /// Called whenever a unit arrives whose creator is outside of the committee, which points to
+    /// a misconfigured peer or an attack.
+    fn report_unknown_creator(&self) {}
+    /// Called whenever a peer answers a request for a unit with a unit at a coord we never asked
+    /// for, which points to a faulty or malicious peer.
+    fn report_unrequested_unit(&self) {}
+    /// Called whenever the given peer answers a request for parents we already know, see
+    /// [`Config::with_duplicate_parents_response_limit`](crate::Config::with_duplicate_parents_response_limit).
+    fn report_duplicate_parents_response(&self, _responder: NodeIndex) {}
+    /// The round from which unit creation starts in this session, reported once it is decided
+    /// based on the backup and the initial unit collection. It is non-zero when resuming.
+    fn report_starting_round(&self, _round: Round) {}
+    /// Called whenever a [`CreationGate`](crate::CreationGate) defers creating our unit of the
+    /// given round.
+    fn report_deferred_creation(&self, _round: Round) {}
+    /// The highest round for which we could create a unit with the parents known at the time,
+    /// sampled whenever we create one. It lagging behind the DAG height points to our own units
+    /// not reaching others.
+    fn report_max_creatable_round(&self, _round: Round) {}
+    /// The number of messages waiting in the given internal channel, sampled periodically.
+    fn report_channel_depth(&self, _channel: InternalChannel, _depth: usize) {}
+}
+
+/// An implementation of [`Metrics`] ignoring all the measurements.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {}
+
+/// The activity of the runway counted since the previous [`RunwayCounters::snapshot_and_reset`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RunwayCountersSnapshot {
+    /// Units received from other nodes.
+    pub units_received: u64,
+    /// Our own units sent out to other nodes.
+    pub units_broadcast: u64,
+    /// Requests for missing units sent out to other nodes.
+    pub requests_sent: u64,
+    /// Responses to requests of other nodes sent out.
+    pub responses_sent: u64,
+    /// Newly detected forkers.
+    pub forkers_detected: u64,
+}
+
+/// Counters of the runway activity, meant to be pulled periodically by a scraper computing rates,
+/// as opposed to the measurements pushed to [`Metrics`]. Clones share the counters, so one can be
+/// passed to [`Config::with_runway_counters`](crate::Config::with_runway_counters) while another
+/// is kept for scraping.
+#[derive(Clone, Debug, Default)]
+pub struct RunwayCounters {
+    values: Arc<Mutex<RunwayCountersSnapshot>>,
+}
+
+impl RunwayCounters {
+    /// The values of all the counters, which are reset to zero at the same time, so no increments
+    /// get lost between consecutive snapshots.
+    pub fn snapshot_and_reset(&self) -> RunwayCountersSnapshot {
+        mem::take(&mut *self.values.lock())
+    }
+
+    pub(crate) fn count(&self, update: impl FnOnce(&mut RunwayCountersSnapshot)) {
+        update(&mut self.values.lock());
+    }
+}
+
+/// The backlog of an unbounded channel, tracked by counting the messages at both of its ends.
+#[derive(Clone, Debug, Default)]
+struct ChannelDepth {
+    sent: Arc<AtomicUsize>,
+    received: Arc<AtomicUsize>,
+}
+
+impl ChannelDepth {
+    fn depth(&self) -> usize {
+        // Reading the received count first, so that it cannot overtake the sent count.
+        let received = self.received.load(Ordering::Relaxed);
+        self.sent.load(Ordering::Relaxed).saturating_sub(received)
+    }
+}
+
+/// Why a message could not be sent with a [`TrackedSender`].
+pub(crate) enum TrackedSendError<T> {
+    /// As many messages as the capacity allows are already waiting.
+    Full(T),
+    /// The receiving end is gone.
+    Disconnected(TrySendError<T>),
+}
+
+impl<T> fmt::Debug for TrackedSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackedSendError::Full(_) => write!(f, "Full(..)"),
+            TrackedSendError::Disconnected(e) => write!(f, "Disconnected({:?})", e),
+        }
+    }
+}
+
+impl<T> fmt::Display for TrackedSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackedSendError::Full(_) => write!(f, "channel is at capacity"),
+            TrackedSendError::Disconnected(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+/// The sending end of a channel that knows how many messages are waiting in it, and refuses
+/// messages once that reaches the capacity of the channel, if it has one.
+pub(crate) struct TrackedSender<T> {
+    sender: Sender<T>,
+    depth: ChannelDepth,
+    capacity: Option<usize>,
+}
+
+impl<T> Clone for TrackedSender<T> {
+    fn clone(&self) -> Self {
+        TrackedSender {
+            sender: self.sender.clone(),
+            depth: self.depth.clone(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+impl<T> From<Sender<T>> for TrackedSender<T> {
+    fn from(sender: Sender<T>) -> Self {
+        TrackedSender {
+            sender,
+            depth: ChannelDepth::default(),
+            capacity: None,
+        }
+    }
+}
+
+impl<T> TrackedSender<T> {
+    /// Sends the message, unless as many messages as the capacity allows are already waiting.
+    pub(crate) fn unbounded_send(&self, message: T) -> Result<(), TrackedSendError<T>> {
+        if self.is_full() {
+            return Err(TrackedSendError::Full(message));
+        }
+        self.sender
+            .unbounded_send(message)
+            .map_err(TrackedSendError::Disconnected)?;
+        self.depth.sent.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Sends the message even if as many messages as the capacity allows are already waiting,
+    /// for messages that must never be dropped.
+    pub(crate) fn send_past_capacity(&self, message: T) -> Result<(), TrySendError<T>> {
+        self.sender.unbounded_send(message)?;
+        self.depth.sent.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// The number of messages sent, but not yet received.
+    pub(crate) fn depth(&self) -> usize {
+        self.depth.depth()
+    }
+
+    /// Whether as many messages as the capacity allows are waiting, so no more should be sent.
+    pub(crate) fn is_full(&self) -> bool {
+        self.capacity
+            .map_or(false, |capacity| self.depth() >= capacity)
+    }
+}
+
+/// The receiving end of an unbounded channel, counting the messages it yields.
+pub(crate) struct TrackedReceiver<T> {
+    receiver: Receiver<T>,
+    depth: ChannelDepth,
+}
+
+impl<T> From<Receiver<T>> for TrackedReceiver<T> {
+    fn from(receiver: Receiver<T>) -> Self {
+        TrackedReceiver {
+            receiver,
+            depth: ChannelDepth::default(),
+        }
+    }
+}
+
+impl<T> Stream for TrackedReceiver<T> {
+    type Item = T;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let polled = self.receiver.poll_next_unpin(cx);
+        if let Poll::Ready(Some(_)) = polled {
+            self.depth.received.fetch_add(1, Ordering::Relaxed);
+        }
+        polled
+    }
+}
+
+impl<T> FusedStream for TrackedReceiver<T> {
+    fn is_terminated(&self) -> bool {
+        self.receiver.is_terminated()
+    }
+}
+
+/// Creates an unbounded channel whose sender can tell how many messages are waiting in it.
+pub(crate) fn tracked_unbounded<T>() -> (TrackedSender<T>, TrackedReceiver<T>) {
+    tracked_channel(None)
+}
+
+/// Creates a channel whose sender can tell how many messages are waiting in it, and refuses
+/// messages once that reaches the given capacity.
+pub(crate) fn tracked_channel<T>(
+    capacity: Option<usize>,
+) -> (TrackedSender<T>, TrackedReceiver<T>) {
+    let (sender, receiver) = mpsc::unbounded();
+    let depth = ChannelDepth::default();
+    (
+        TrackedSender {
+            sender,
+            depth: depth.clone(),
+            capacity,
+        },
+        TrackedReceiver { receiver, depth },
+    )
+}
@@ -1,6 +1,9 @@
 use crate::{
-    alerts::AlertMessage, member::UnitMessage, Data, Hasher, Network, PartialMultisignature,
-    Receiver, Recipient, Sender, Signature, Terminator,
+    alerts::AlertMessage,
+    member::UnitMessage,
+    units::{ValidationErrorKind, Validator},
+    Data, Hasher, Keychain, Network, PartialMultisignature, Receiver, Recipient, Sender, Signature,
+    Terminator,
 };
 use codec::{Decode, Encode};
 use futures::{FutureExt, StreamExt};
@@ -36,6 +39,24 @@ impl<H: Hasher, D: Data, S: Signature, MS: PartialMultisignature> NetworkData<H,
     pub fn included_data(&self) -> Vec<D> {
         self.0.included_data()
     }
+
+    /// Checks the units in the message the way a session using the same validator would, but
+    /// without any of the side effects of receiving them, e.g. to filter messages before relaying
+    /// them. Alerts are not checked, they are verified by the alerter.
+    pub fn validate_units<K: Keychain<Signature = S>>(
+        &self,
+        validator: &Validator<K, D>,
+    ) -> Result<(), ValidationErrorKind> {
+        match &self.0 {
+            NetworkDataInner::Units(message) => message.units().into_iter().try_for_each(|uu| {
+                validator
+                    .validate_unit(uu.clone())
+                    .map(|_| ())
+                    .map_err(|e| e.kind())
+            }),
+            NetworkDataInner::Alert(_) => Ok(()),
+        }
+    }
 }
 
 struct NetworkHub<
@@ -165,8 +186,11 @@ mod tests {
         alerts::AlertMessage,
         member::UnitMessage,
         network::NetworkDataInner::{Alert, Units},
-        units::{ControlHash, FullUnit, PreUnit, UncheckedSignedUnit, UnitCoord},
-        Hasher, NodeIndex, NodeSubset, Round, Signed,
+        units::{
+            ControlHash, FullUnit, PreUnit, UncheckedSignedUnit, UnitCoord, ValidationErrorKind,
+            Validator,
+        },
+        Hasher, NodeCount, NodeIndex, NodeSubset, Round, Signed,
     };
     use aleph_bft_mock::{Data, Hasher64, Keychain, PartialMultisignature, Signature};
     use codec::{Decode, Encode};
@@ -194,6 +218,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn validates_units_in_network_data() {
+        use UnitMessage::{NewUnit, RequestCoord, ResponseParents};
+
+        let validator = Validator::new(0, Keychain::new(7.into(), 0.into()), 100, NodeCount(5));
+        let valid = test_unchecked_unit(5.into(), 0, 1729);
+        let too_high = test_unchecked_unit(5.into(), 101, 1729);
+        let hash = valid.as_signable().hash();
+
+        let nd = TestNetworkData::new(Units(NewUnit(valid.clone())));
+        assert_eq!(nd.validate_units(&validator), Ok(()));
+        let nd = TestNetworkData::new(Units(RequestCoord(7.into(), UnitCoord::new(3, 13.into()))));
+        assert_eq!(nd.validate_units(&validator), Ok(()));
+        let nd = TestNetworkData::new(Units(ResponseParents(
+            1.into(),
+            hash,
+            vec![valid, too_high],
+        )));
+        assert_eq!(
+            nd.validate_units(&validator),
+            Err(ValidationErrorKind::RoundTooHigh)
+        );
+    }
+
     #[test]
     fn decoding_network_data_units_new_unit() {
         use UnitMessage::NewUnit;
@@ -305,7 +353,7 @@ mod tests {
             .collect();
         let parents = vec![p1, p2, p3];
 
-        let nd = TestNetworkData::new(Units(ResponseParents(h, parents.clone())));
+        let nd = TestNetworkData::new(Units(ResponseParents(NodeIndex(7), h, parents.clone())));
         let decoded = TestNetworkData::decode(&mut &nd.encode()[..]);
         assert!(decoded.is_ok(), "Bug in encode/decode for ResponseParents");
         let decoded = decoded.unwrap();
@@ -314,7 +362,8 @@ mod tests {
             included_data,
             "data decoded incorrectly"
         );
-        if let Units(ResponseParents(dh, dparents)) = decoded.0 {
+        if let Units(ResponseParents(dni, dh, dparents)) = decoded.0 {
+            assert_eq!(NodeIndex(7), dni, "decoded should equal encoded");
             assert_eq!(h, dh, "decoded should equal encoded");
             assert_eq!(
                 parents.len(),
@@ -0,0 +1,98 @@
+use crate::{Metrics, Round};
+use prometheus_client::{
+    metrics::{counter::Counter, gauge::Gauge},
+    registry::Registry,
+};
+
+/// An implementation of [`Metrics`] exporting the measurements most useful for operators as
+/// Prometheus counters and gauges. Clones share the underlying metrics, so one can be passed to
+/// [`Config::with_metrics`](crate::Config::with_metrics) while another is kept for inspection.
+#[derive(Clone, Debug, Default)]
+pub struct PrometheusMetrics {
+    units_received: Counter,
+    units_finalized: Counter,
+    alerts_raised: Counter,
+    missing_coords: Gauge,
+    current_round: Gauge,
+    max_creatable_round: Gauge,
+}
+
+impl PrometheusMetrics {
+    /// Creates the metrics and registers them in the given registry, prefixed with `aleph_bft`.
+    pub fn register(registry: &mut Registry) -> Self {
+        let metrics = Self::default();
+        let registry = registry.sub_registry_with_prefix("aleph_bft");
+        registry.register(
+            "units_received",
+            "Units received from other nodes",
+            metrics.units_received.clone(),
+        );
+        registry.register(
+            "units_finalized",
+            "Units finalized in batches ordered by consensus",
+            metrics.units_finalized.clone(),
+        );
+        registry.register(
+            "alerts_raised",
+            "Alerts raised about newly detected forkers",
+            metrics.alerts_raised.clone(),
+        );
+        registry.register(
+            "missing_coords",
+            "Missing units requested and still waited for",
+            metrics.missing_coords.clone(),
+        );
+        registry.register(
+            "current_round",
+            "The highest round of a unit held in the unit store",
+            metrics.current_round.clone(),
+        );
+        registry.register(
+            "max_creatable_round",
+            "The highest round for which we could create a unit when we last created one",
+            metrics.max_creatable_round.clone(),
+        );
+        metrics
+    }
+
+    /// The number of units received from other nodes so far.
+    pub fn units_received(&self) -> u64 {
+        self.units_received.get()
+    }
+
+    /// The number of units finalized so far.
+    pub fn units_finalized(&self) -> u64 {
+        self.units_finalized.get()
+    }
+
+    /// The number of alerts raised so far.
+    pub fn alerts_raised(&self) -> u64 {
+        self.alerts_raised.get()
+    }
+}
+
+impl Metrics for PrometheusMetrics {
+    fn report_dag_height(&self, height: Round) {
+        self.current_round.set(height.into());
+    }
+
+    fn report_unit_received(&self) {
+        self.units_received.inc();
+    }
+
+    fn report_finalized_units(&self, count: usize) {
+        self.units_finalized.inc_by(count as u64);
+    }
+
+    fn report_forker_detected(&self) {
+        self.alerts_raised.inc();
+    }
+
+    fn report_missing_coords(&self, count: usize) {
+        self.missing_coords.set(count as i64);
+    }
+
+    fn report_max_creatable_round(&self, round: Round) {
+        self.max_creatable_round.set(round.into());
+    }
+}
@@ -1,17 +1,29 @@
 use crate::{
-    units::{UncheckedSignedUnit, UnitCoord},
-    Data, Hasher, NodeIndex, Receiver, Round, Sender, SessionId, Signature, Terminator,
+    units::{ControlHash, UncheckedSignedUnit, UnitCoord},
+    BackupGapPolicy, BackupLoadPolicy, Data, Hasher, Metrics, NodeIndex, NodeMap, Receiver, Round,
+    Sender, SessionId, Signature, Terminator,
 };
 use codec::{Decode, Encode, Error as CodecError};
 use futures::{channel::oneshot, FutureExt, StreamExt};
 use log::{debug, error, info, warn};
 use std::{
-    collections::HashSet,
+    collections::{BTreeSet, HashMap},
     fmt,
     io::{Read, Write},
     marker::PhantomData,
+    sync::Arc,
 };
 
+/// Marks a backup written in the framed format. Read as the start of a unit in the legacy format
+/// it would imply a creator index far beyond any committee size, so the formats never get confused.
+const BACKUP_MAGIC: [u8; 8] = *b"ALEPHBFT";
+/// The version of the framed format, written right after [`BACKUP_MAGIC`] as a little endian `u16`.
+/// Bump it whenever the layout of frames changes, so that older releases refuse such backups.
+const BACKUP_VERSION: u16 = 1;
+const BACKUP_HEADER_LEN: usize = BACKUP_MAGIC.len() + 2;
+/// Every saved unit is preceded by its length and CRC32 checksum, both as little endian `u32`.
+const FRAME_PREFIX_LEN: usize = 8;
+
 /// Backup load error. Could be either caused by io error from Reader, or by decoding.
 #[derive(Debug)]
 pub enum LoaderError {
@@ -19,6 +31,11 @@ pub enum LoaderError {
     Codec(CodecError),
     InconsistentData(UnitCoord),
     WrongSession(UnitCoord, SessionId, SessionId),
+    MissingOwnUnit(UnitCoord),
+    BrokenOwnChain(UnitCoord),
+    UnsupportedVersion(u16),
+    CorruptRecord(usize),
+    TruncatedRecord(usize),
 }
 
 impl fmt::Display for LoaderError {
@@ -51,6 +68,46 @@ impl fmt::Display for LoaderError {
                     coord.round(), coord.creator(), expected_session, actual_session
                 )
             }
+
+            LoaderError::MissingOwnUnit(coord) => {
+                write!(
+                    f,
+                    "Own units in backup do not form a chain. Unit from round {:?} of creator {:?} is missing.",
+                    coord.round(), coord.creator()
+                )
+            }
+
+            LoaderError::BrokenOwnChain(coord) => {
+                write!(
+                    f,
+                    "Own units in backup do not form a chain. Unit from round {:?} of creator {:?} is not built on our previous unit.",
+                    coord.round(), coord.creator()
+                )
+            }
+
+            LoaderError::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "Backup written in format version {}, but only version {} is supported. Was it written by a newer release?",
+                    version, BACKUP_VERSION
+                )
+            }
+
+            LoaderError::CorruptRecord(offset) => {
+                write!(
+                    f,
+                    "Corrupt backup record at byte offset {}, its contents do not match the checksum.",
+                    offset
+                )
+            }
+
+            LoaderError::TruncatedRecord(offset) => {
+                write!(
+                    f,
+                    "Backup ends with a partial record at byte offset {}, probably an interrupted save.",
+                    offset
+                )
+            }
         }
     }
 }
@@ -67,9 +124,45 @@ impl From<CodecError> for LoaderError {
     }
 }
 
+/// The header starting every backup written by a [`UnitSaver`].
+fn backup_header() -> [u8; BACKUP_HEADER_LEN] {
+    let mut header = [0; BACKUP_HEADER_LEN];
+    let (magic, version) = header.split_at_mut(BACKUP_MAGIC.len());
+    magic.copy_from_slice(&BACKUP_MAGIC);
+    version.copy_from_slice(&BACKUP_VERSION.to_le_bytes());
+    header
+}
+
+/// The format version in the header the bytes start with, `None` if the header is incomplete.
+fn header_version(bytes: &[u8]) -> Option<u16> {
+    match bytes.get(BACKUP_MAGIC.len()..BACKUP_HEADER_LEN) {
+        Some(&[low, high]) => Some(u16::from_le_bytes([low, high])),
+        _ => None,
+    }
+}
+
+/// The CRC32 (IEEE) checksum of the given bytes.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Makes everything written to a backup durable, e.g. by calling
+/// [`File::sync_data`](std::fs::File::sync_data) when the backup is a file.
+pub type BackupSync<W> = Arc<dyn Fn(&mut W) -> std::io::Result<()> + Send + Sync>;
+
 /// Abstraction over Unit backup saving mechanism
 pub struct UnitSaver<W: Write, H: Hasher, D: Data, S: Signature> {
     inner: W,
+    header_written: bool,
+    sync: Option<BackupSync<W>>,
     _phantom: PhantomData<(H, D, S)>,
 }
 
@@ -83,14 +176,55 @@ impl<W: Write, H: Hasher, D: Data, S: Signature> UnitSaver<W, H, D, S> {
     pub fn new(write: W) -> Self {
         Self {
             inner: write,
+            header_written: false,
+            sync: None,
             _phantom: PhantomData,
         }
     }
 
+    /// Sets a hook making the saved units durable, called before our own units are handed back
+    /// and on every [`flush`](UnitSaver::flush).
+    pub fn with_sync(mut self, sync: Option<BackupSync<W>>) -> Self {
+        self.sync = sync;
+        self
+    }
+
+    /// Saves the unit as a single frame, preceded by the format header if it is the first one.
+    /// When appending to an existing backup after a restart the header gets repeated, which the
+    /// loader accepts between frames. The writer is flushed, but not synced.
     pub fn save(&mut self, unit: UncheckedSignedUnit<H, D, S>) -> Result<(), std::io::Error> {
-        self.inner.write_all(&unit.encode())?;
+        let payload = unit.encode();
+        let mut frame = Vec::with_capacity(BACKUP_HEADER_LEN + FRAME_PREFIX_LEN + payload.len());
+        if !self.header_written {
+            frame.extend_from_slice(&backup_header());
+        }
+        let len = u32::try_from(payload.len()).map_err(|_| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "unit too large to back up",
+            )
+        })?;
+        frame.extend_from_slice(&len.to_le_bytes());
+        frame.extend_from_slice(&crc32(&payload).to_le_bytes());
+        frame.extend_from_slice(&payload);
+        self.inner.write_all(&frame)?;
+        self.header_written = true;
+        self.inner.flush()
+    }
+
+    /// Calls the sync hook, if any, making everything saved so far durable.
+    pub fn sync(&mut self) -> Result<(), std::io::Error> {
+        match &self.sync {
+            Some(sync) => sync(&mut self.inner),
+            None => Ok(()),
+        }
+    }
+
+    /// Flushes the underlying writer and calls the sync hook, if any, making sure everything
+    /// saved so far reached the backup.
+    pub fn flush(&mut self) -> Result<(), std::io::Error> {
         self.inner.flush()?;
-        Ok(())
+        self.sync()
     }
 }
 
@@ -102,51 +236,244 @@ impl<R: Read, H: Hasher, D: Data, S: Signature> UnitLoader<R, H, D, S> {
         }
     }
 
-    fn load(mut self) -> Result<Vec<UncheckedSignedUnit<H, D, S>>, LoaderError> {
+    fn load(mut self) -> Result<DecodedBackup<H, D, S>, LoaderError> {
         let mut buf = Vec::new();
         self.inner.read_to_end(&mut buf)?;
-        let input = &mut &buf[..];
-        let mut result = Vec::new();
-        while !input.is_empty() {
-            result.push(<UncheckedSignedUnit<H, D, S>>::decode(input)?);
+        decode_backup(&buf)
+    }
+}
+
+/// The units decoded from a backup, up to the first damaged record, if any. The error describes
+/// that record.
+pub(crate) type DecodedBackup<H, D, S> = (Vec<UncheckedSignedUnit<H, D, S>>, Option<LoaderError>);
+
+/// Decodes the units in a backup, in the framed format if it starts with the format header and as
+/// plain concatenated units otherwise, as written by older versions. A partial or corrupt record
+/// of the framed format only ends decoding, the units before it are still returned.
+pub(crate) fn decode_backup<H: Hasher, D: Data, S: Signature>(
+    bytes: &[u8],
+) -> Result<DecodedBackup<H, D, S>, LoaderError> {
+    if !bytes.starts_with(&BACKUP_MAGIC) {
+        if !bytes.is_empty() && BACKUP_MAGIC.starts_with(bytes) {
+            return Ok((Vec::new(), Some(LoaderError::TruncatedRecord(0))));
         }
-        Ok(result)
+        return Ok((decode_legacy_backup(bytes)?, None));
     }
+    decode_framed_backup(bytes)
 }
 
-fn load_backup<H: Hasher, D: Data, S: Signature, R: Read>(
-    unit_loader: UnitLoader<R, H, D, S>,
-    session_id: SessionId,
+fn decode_legacy_backup<H: Hasher, D: Data, S: Signature>(
+    bytes: &[u8],
 ) -> Result<Vec<UncheckedSignedUnit<H, D, S>>, LoaderError> {
-    let loaded_units = unit_loader.load()?;
-    let mut already_loaded_coords = HashSet::new();
+    let input = &mut &bytes[..];
+    let mut result = Vec::new();
+    while !input.is_empty() {
+        result.push(<UncheckedSignedUnit<H, D, S>>::decode(input)?);
+    }
+    Ok(result)
+}
+
+fn decode_framed_backup<H: Hasher, D: Data, S: Signature>(
+    bytes: &[u8],
+) -> Result<DecodedBackup<H, D, S>, LoaderError> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let rest = &bytes[offset..];
+        // Appending after a restart repeats the header, possibly written by a different release.
+        if rest.starts_with(&BACKUP_MAGIC) {
+            match header_version(rest) {
+                Some(BACKUP_VERSION) => {
+                    offset += BACKUP_HEADER_LEN;
+                    continue;
+                }
+                Some(version) => return Err(LoaderError::UnsupportedVersion(version)),
+                None => return Ok((result, Some(LoaderError::TruncatedRecord(offset)))),
+            }
+        }
+        if rest.len() < FRAME_PREFIX_LEN {
+            return Ok((result, Some(LoaderError::TruncatedRecord(offset))));
+        }
+        let (prefix, rest) = rest.split_at(FRAME_PREFIX_LEN);
+        let len = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize;
+        let checksum = u32::from_le_bytes([prefix[4], prefix[5], prefix[6], prefix[7]]);
+        if rest.len() < len {
+            return Ok((result, Some(LoaderError::TruncatedRecord(offset))));
+        }
+        let payload = &rest[..len];
+        let input = &mut &payload[..];
+        match <UncheckedSignedUnit<H, D, S>>::decode(input) {
+            Ok(unit) if crc32(payload) == checksum && input.is_empty() => result.push(unit),
+            _ => return Ok((result, Some(LoaderError::CorruptRecord(offset)))),
+        }
+        offset += FRAME_PREFIX_LEN + len;
+    }
+    Ok((result, None))
+}
+
+/// A summary of the units restored from the backup.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BackupStats {
+    /// How many units were loaded.
+    pub units_loaded: usize,
+    /// The highest round of our own loaded units, creation resumes from the next one.
+    pub highest_round: Option<Round>,
+}
+
+impl BackupStats {
+    pub(crate) fn new<H: Hasher, D: Data, S: Signature>(
+        index: NodeIndex,
+        units: &[UncheckedSignedUnit<H, D, S>],
+    ) -> Self {
+        BackupStats {
+            units_loaded: units.len(),
+            highest_round: units
+                .iter()
+                .filter(|u| u.as_signable().creator() == index)
+                .map(|u| u.as_signable().round())
+                .max(),
+        }
+    }
+}
 
-    for unit in loaded_units.iter() {
-        let full_unit = unit.as_signable();
-        let coord = full_unit.coord();
+/// Parameters of loading the backup.
+pub struct LoadingConfig {
+    pub index: NodeIndex,
+    pub session_id: SessionId,
+    pub gap_policy: BackupGapPolicy,
+    pub load_policy: BackupLoadPolicy,
+    /// How many rounds ahead of the backup the collection may place our newest unit before its
+    /// result is deemed implausible and ignored, if there is such a limit.
+    pub collection_round_leeway: Option<Round>,
+    pub metrics: Arc<dyn Metrics>,
+}
+
+fn check_unit<H: Hasher, D: Data, S: Signature>(
+    unit: &UncheckedSignedUnit<H, D, S>,
+    session_id: SessionId,
+    already_loaded: &HashMap<UnitCoord, Vec<H::Hash>>,
+) -> Result<(), LoaderError> {
+    let full_unit = unit.as_signable();
+    let coord = full_unit.coord();
+
+    if full_unit.session_id() != session_id {
+        return Err(LoaderError::WrongSession(
+            coord,
+            session_id,
+            full_unit.session_id(),
+        ));
+    }
+
+    let parent_ids = &full_unit.as_pre_unit().control_hash().parents_mask;
 
-        if full_unit.session_id() != session_id {
-            return Err(LoaderError::WrongSession(
-                coord,
-                session_id,
-                full_unit.session_id(),
-            ));
+    // Sanity check: verify that all unit's parents appeared in backup before it.
+    for parent_id in parent_ids.elements() {
+        let parent = UnitCoord::new(coord.round() - 1, parent_id);
+        if !already_loaded.contains_key(&parent) {
+            return Err(LoaderError::InconsistentData(coord));
         }
+    }
 
-        let parent_ids = &full_unit.as_pre_unit().control_hash().parents_mask;
+    Ok(())
+}
+
+/// Verifies that our own unit is built on our unit of the previous round, by recomputing its
+/// control hash from the parents in the backup. Forks among the parents make it impossible to
+/// tell which of them the unit refers to, such units are accepted.
+fn check_own_chain<H: Hasher, D: Data, S: Signature>(
+    unit: &UncheckedSignedUnit<H, D, S>,
+    already_loaded: &HashMap<UnitCoord, Vec<H::Hash>>,
+) -> Result<(), LoaderError> {
+    let full_unit = unit.as_signable();
+    let coord = full_unit.coord();
+    if coord.round() == 0 {
+        return Ok(());
+    }
+    let control_hash = full_unit.control_hash();
+    if !control_hash.parents_mask[coord.creator()] {
+        return Err(LoaderError::BrokenOwnChain(coord));
+    }
+    let mut parents = NodeMap::with_size(control_hash.n_members());
+    for parent_id in control_hash.parents() {
+        let parent = UnitCoord::new(coord.round() - 1, parent_id);
+        match already_loaded.get(&parent).map(Vec::as_slice) {
+            Some([hash]) => parents.insert(parent_id, *hash),
+            _ => return Ok(()),
+        }
+    }
+    if ControlHash::<H>::combine_hashes(&parents) != control_hash.combined_hash {
+        return Err(LoaderError::BrokenOwnChain(coord));
+    }
+    Ok(())
+}
+
+fn load_backup<H: Hasher, D: Data, S: Signature, R: Read>(
+    unit_loader: UnitLoader<R, H, D, S>,
+    config: &LoadingConfig,
+) -> Result<Vec<UncheckedSignedUnit<H, D, S>>, LoaderError> {
+    let LoadingConfig {
+        index,
+        session_id,
+        gap_policy,
+        load_policy,
+        ..
+    } = *config;
+    let (loaded_units, damaged) = unit_loader.load()?;
+    if let Some(e) = damaged {
+        match load_policy {
+            BackupLoadPolicy::FailFast => return Err(e),
+            BackupLoadPolicy::BestEffort => {
+                warn!(target: "AlephBFT-unit-backup", "Loading only the units before the damaged record: {}", e)
+            }
+        }
+    }
+    let mut already_loaded = HashMap::new();
+    let mut own_rounds = BTreeSet::new();
+    let mut valid_units = Vec::with_capacity(loaded_units.len());
+
+    for unit in loaded_units {
+        if let Err(e) = check_unit(&unit, session_id, &already_loaded) {
+            match load_policy {
+                BackupLoadPolicy::FailFast => return Err(e),
+                BackupLoadPolicy::BestEffort => {
+                    warn!(target: "AlephBFT-unit-backup", "Skipping a unit from backup: {}", e);
+                    continue;
+                }
+            }
+        }
 
-        // Sanity check: verify that all unit's parents appeared in backup before it.
-        for parent_id in parent_ids.elements() {
-            let parent = UnitCoord::new(coord.round() - 1, parent_id);
-            if !already_loaded_coords.contains(&parent) {
-                return Err(LoaderError::InconsistentData(coord));
+        let coord = unit.as_signable().coord();
+        if coord.creator() == index {
+            if let Err(e) = check_own_chain(&unit, &already_loaded) {
+                match gap_policy {
+                    BackupGapPolicy::Warn => warn!(target: "AlephBFT-unit-backup", "{}", e),
+                    BackupGapPolicy::Refuse => return Err(e),
+                }
             }
+            own_rounds.insert(coord.round());
         }
+        already_loaded
+            .entry(coord)
+            .or_insert_with(Vec::new)
+            .push(unit.as_signable().hash());
+        valid_units.push(unit);
+    }
 
-        already_loaded_coords.insert(coord);
+    // Own units should form a chain, a gap would make the creator stall on a missing own parent.
+    if let Some((missing_round, _)) = (0..)
+        .zip(own_rounds)
+        .find(|(expected, round)| expected != round)
+    {
+        let coord = UnitCoord::new(missing_round, index);
+        match gap_policy {
+            BackupGapPolicy::Warn => {
+                warn!(target: "AlephBFT-unit-backup", "{}", LoaderError::MissingOwnUnit(coord))
+            }
+            BackupGapPolicy::Refuse => return Err(LoaderError::MissingOwnUnit(coord)),
+        }
     }
 
-    Ok(loaded_units)
+    Ok(valid_units)
 }
 
 fn on_shutdown(starting_round_tx: oneshot::Sender<Option<Round>>) {
@@ -159,16 +486,18 @@ fn on_shutdown(starting_round_tx: oneshot::Sender<Option<Round>>) {
 /// It sends all loaded units by `loaded_unit_tx`.
 /// If loaded Units are compatible with the unit collection result (meaning the highest unit is from at least
 /// round from unit collection + 1) it sends `Some(starting_round)` by
-/// `starting_round_tx`. If Units are not compatible it sends `None` by `starting_round_tx`
+/// `starting_round_tx`. If Units are not compatible it sends `None` by `starting_round_tx`.
+/// A gap or a break in the chain of own loaded units and units failing validation are handled
+/// according to the policies in `config`.
 pub async fn run_loading_mechanism<'a, H: Hasher, D: Data, S: Signature, R: Read>(
     unit_loader: UnitLoader<R, H, D, S>,
-    index: NodeIndex,
-    session_id: SessionId,
+    config: LoadingConfig,
     loaded_unit_tx: oneshot::Sender<Vec<UncheckedSignedUnit<H, D, S>>>,
     starting_round_tx: oneshot::Sender<Option<Round>>,
     next_round_collection_rx: oneshot::Receiver<Round>,
 ) {
-    let units = match load_backup(unit_loader, session_id) {
+    let index = config.index;
+    let units = match load_backup(unit_loader, &config) {
         Ok(units) => units,
         Err(e) => {
             error!(target: "AlephBFT-unit-backup", "unable to load unit backup: {}", e);
@@ -177,17 +506,12 @@ pub async fn run_loading_mechanism<'a, H: Hasher, D: Data, S: Signature, R: Read
         }
     };
 
-    let next_round_backup: Round = units
-        .iter()
-        .filter(|u| u.as_signable().creator() == index)
-        .map(|u| u.as_signable().round())
-        .max()
-        .map(|round| round + 1)
-        .unwrap_or(0);
+    let stats = BackupStats::new(index, &units);
+    let next_round_backup: Round = stats.highest_round.map(|round| round + 1).unwrap_or(0);
 
     info!(
         target: "AlephBFT-unit-backup", "Loaded {:?} units from backup. Able to continue from round: {:?}.",
-        units.len(),
+        stats.units_loaded,
         next_round_backup
     );
 
@@ -208,6 +532,21 @@ pub async fn run_loading_mechanism<'a, H: Hasher, D: Data, S: Signature, R: Read
 
     info!(target: "AlephBFT-unit-backup", "Next round inferred from collection: {:?}", next_round_collection);
 
+    let next_round_collection = match config.collection_round_leeway {
+        Some(leeway) if next_round_collection > next_round_backup.saturating_add(leeway) => {
+            // Responses claiming we created units far beyond our backup are more likely forged than
+            // lost, so they shouldn't make us skip rounds or give up the session.
+            warn!(
+                target: "AlephBFT-unit-backup", "Ignoring next round inferred from collection: {:?}, it is more than {:?} rounds ahead of the backup: {:?}",
+                next_round_collection,
+                leeway,
+                next_round_backup,
+            );
+            next_round_backup
+        }
+        _ => next_round_collection,
+    };
+
     if next_round_backup < next_round_collection {
         // Our newest unit doesn't appear in the backup. This indicates a serious issue, for example
         // a different node running with the same pair of keys. It's safer not to continue.
@@ -230,6 +569,7 @@ pub async fn run_loading_mechanism<'a, H: Hasher, D: Data, S: Signature, R: Read
         );
     }
 
+    config.metrics.report_starting_round(next_round_backup);
     if let Err(e) = starting_round_tx.send(Some(next_round_backup)) {
         error!(target: "AlephBFT-unit-backup", "Could not send starting round: {:?}", e);
     }
@@ -237,8 +577,11 @@ pub async fn run_loading_mechanism<'a, H: Hasher, D: Data, S: Signature, R: Read
 
 /// A task responsible for saving units into backup.
 /// It waits for units to appear in `backup_units_from_runway`, and writes them to backup.
-/// It announces a successful write through `backup_units_for_runway`.
+/// It announces a successful write through `backup_units_for_runway`, for our own units only once
+/// the backup is synced, as those must never be lost.
+/// Before exiting it flushes the backup one last time, so no saved unit is lost on shutdown.
 pub async fn run_saving_mechanism<'a, H: Hasher, D: Data, S: Signature, W: Write>(
+    node_index: NodeIndex,
     mut unit_saver: UnitSaver<W, H, D, S>,
     mut backup_units_from_runway: Receiver<UncheckedSignedUnit<H, D, S>>,
     backup_units_for_runway: Sender<UncheckedSignedUnit<H, D, S>>,
@@ -260,6 +603,12 @@ pub async fn run_saving_mechanism<'a, H: Hasher, D: Data, S: Signature, W: Write
                     error!(target: "AlephBFT-backup-saver", "Couldn't save unit to backup: {:?}", e);
                     break;
                 }
+                if unit_to_save.as_signable().creator() == node_index {
+                    if let Err(e) = unit_saver.sync() {
+                        error!(target: "AlephBFT-backup-saver", "Couldn't sync backup: {:?}", e);
+                        break;
+                    }
+                }
                 if backup_units_for_runway.unbounded_send(unit_to_save).is_err() {
                     error!(target: "AlephBFT-backup-saver", "Couldn't respond with saved unit.");
                     break;
@@ -272,26 +621,41 @@ pub async fn run_saving_mechanism<'a, H: Hasher, D: Data, S: Signature, W: Write
         }
 
         if terminator_exit {
-            debug!(target: "AlephBFT-backup-saver", "Backup saver decided to exit.");
-            terminator.terminate_sync().await;
             break;
         }
     }
+
+    if let Err(e) = unit_saver.flush() {
+        error!(target: "AlephBFT-backup-saver", "Couldn't flush backup on exit: {:?}", e);
+    }
+    debug!(target: "AlephBFT-backup-saver", "Backup saver decided to exit.");
+    terminator.terminate_sync().await;
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{run_loading_mechanism, UnitLoader};
+    use super::{
+        run_loading_mechanism, run_saving_mechanism, BackupStats, BackupSync, LoaderError,
+        LoadingConfig, UnitLoader, UnitSaver,
+    };
     use crate::{
         units::{
             create_units, creator_set, preunit_to_unchecked_signed_unit, preunit_to_unit,
-            UncheckedSignedUnit as GenericUncheckedSignedUnit,
+            ControlHash, PreUnit, UncheckedSignedUnit as GenericUncheckedSignedUnit,
         },
-        NodeCount, NodeIndex, Round, SessionId,
+        BackupGapPolicy, BackupLoadPolicy, Metrics, NodeCount, NodeIndex, NodeMap, NoopMetrics,
+        Round, SessionId, Terminator,
     };
     use aleph_bft_mock::{Data, Hasher64, Keychain, Loader, Signature};
     use codec::Encode;
-    use futures::channel::oneshot;
+    use futures::{
+        channel::{mpsc, oneshot},
+        StreamExt,
+    };
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
 
     type UncheckedSignedUnit = GenericUncheckedSignedUnit<Hasher64, Data, Signature>;
 
@@ -353,6 +717,45 @@ mod tests {
         oneshot::Receiver<Vec<UncheckedSignedUnit>>,
         oneshot::Sender<Round>,
         oneshot::Receiver<Option<Round>>,
+    ) {
+        prepare_test_with_policies(
+            encoded_units,
+            BackupGapPolicy::default(),
+            BackupLoadPolicy::default(),
+        )
+    }
+
+    fn prepare_test_with_policies(
+        encoded_units: Vec<u8>,
+        gap_policy: BackupGapPolicy,
+        load_policy: BackupLoadPolicy,
+    ) -> (
+        impl futures::Future,
+        oneshot::Receiver<Vec<UncheckedSignedUnit>>,
+        oneshot::Sender<Round>,
+        oneshot::Receiver<Option<Round>>,
+    ) {
+        prepare_test_with_config(
+            encoded_units,
+            LoadingConfig {
+                index: NODE_ID,
+                session_id: SESSION_ID,
+                gap_policy,
+                load_policy,
+                collection_round_leeway: None,
+                metrics: Arc::new(NoopMetrics),
+            },
+        )
+    }
+
+    fn prepare_test_with_config(
+        encoded_units: Vec<u8>,
+        config: LoadingConfig,
+    ) -> (
+        impl futures::Future,
+        oneshot::Receiver<Vec<UncheckedSignedUnit>>,
+        oneshot::Sender<Round>,
+        oneshot::Receiver<Option<Round>>,
     ) {
         let unit_loader = UnitLoader::new(Loader::new(encoded_units));
         let (loaded_unit_tx, loaded_unit_rx) = oneshot::channel();
@@ -362,8 +765,7 @@ mod tests {
         (
             run_loading_mechanism(
                 unit_loader,
-                NODE_ID,
-                SESSION_ID,
+                config,
                 loaded_unit_tx,
                 starting_round_tx,
                 highest_response_rx,
@@ -411,6 +813,140 @@ mod tests {
         assert_eq!(loaded_unit_rx.await, Ok(units));
     }
 
+    #[tokio::test]
+    async fn reports_stats_of_reloaded_units() {
+        let units: Vec<_> = produce_units(1, SESSION_ID)[0][..3].to_vec();
+        let mut backup = Vec::new();
+        {
+            let mut unit_saver = UnitSaver::<_, Hasher64, Data, Signature>::new(&mut backup);
+            for unit in &units {
+                unit_saver.save(unit.clone()).unwrap();
+            }
+            unit_saver.flush().unwrap();
+        }
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) = prepare_test(backup);
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(starting_round_rx.await, Ok(Some(1)));
+        let loaded_units = loaded_unit_rx.await.unwrap();
+        assert_eq!(loaded_units, units);
+        assert_eq!(
+            BackupStats::new(NODE_ID, &loaded_units),
+            BackupStats {
+                units_loaded: 3,
+                highest_round: Some(0),
+            }
+        );
+    }
+
+    /// Saves the units with a fresh saver, returning the backup and the offsets of all the frames.
+    fn save_units(units: &[UncheckedSignedUnit]) -> (Vec<u8>, Vec<usize>) {
+        let mut backup = Vec::new();
+        let mut offsets = Vec::new();
+        {
+            let mut unit_saver = UnitSaver::<_, Hasher64, Data, Signature>::new(&mut backup);
+            for unit in units {
+                unit_saver.save(unit.clone()).unwrap();
+                offsets.push(unit_saver.inner.len() - unit.encode().len() - 8);
+            }
+        }
+        (backup, offsets)
+    }
+
+    fn load_units(backup: Vec<u8>) -> Result<Vec<UncheckedSignedUnit>, LoaderError> {
+        match UnitLoader::<_, Hasher64, Data, Signature>::new(Loader::new(backup)).load()? {
+            (units, None) => Ok(units),
+            (_, Some(damaged)) => Err(damaged),
+        }
+    }
+
+    #[test]
+    fn loads_cleanly_saved_units() {
+        let units: Vec<_> = produce_units(2, SESSION_ID).into_iter().flatten().collect();
+        let (backup, _) = save_units(&units);
+
+        assert_eq!(load_units(backup).unwrap(), units);
+    }
+
+    #[test]
+    fn loads_backup_appended_to_after_restart() {
+        let units: Vec<_> = produce_units(2, SESSION_ID).into_iter().flatten().collect();
+        let (mut backup, _) = save_units(&units[..4]);
+        let (appended, _) = save_units(&units[4..]);
+        backup.extend(appended);
+
+        assert_eq!(load_units(backup).unwrap(), units);
+    }
+
+    #[test]
+    fn reports_truncated_tail_record() {
+        let units: Vec<_> = produce_units(1, SESSION_ID).into_iter().flatten().collect();
+        let (mut backup, offsets) = save_units(&units);
+        backup.truncate(backup.len() - 3);
+
+        match load_units(backup) {
+            Err(LoaderError::TruncatedRecord(offset)) => assert_eq!(offset, offsets[3]),
+            result => panic!("Unexpected result {:?}.", result),
+        }
+    }
+
+    #[test]
+    fn reports_corrupt_record() {
+        let units: Vec<_> = produce_units(1, SESSION_ID).into_iter().flatten().collect();
+        let (mut backup, offsets) = save_units(&units);
+        backup[offsets[1] + 10] ^= 0x01;
+
+        match load_units(backup) {
+            Err(LoaderError::CorruptRecord(offset)) => assert_eq!(offset, offsets[1]),
+            result => panic!("Unexpected result {:?}.", result),
+        }
+    }
+
+    #[test]
+    fn starts_backup_with_versioned_header() {
+        let units: Vec<_> = produce_units(1, SESSION_ID).into_iter().flatten().collect();
+        let (backup, offsets) = save_units(&units);
+
+        assert_eq!(&backup[..8], b"ALEPHBFT");
+        assert_eq!(backup[8..10], 1u16.to_le_bytes());
+        assert_eq!(offsets[0], 10);
+        assert_eq!(load_units(backup).unwrap(), units);
+    }
+
+    #[test]
+    fn rejects_unknown_format_version() {
+        let units: Vec<_> = produce_units(1, SESSION_ID).into_iter().flatten().collect();
+        let (mut backup, _) = save_units(&units);
+        backup[8..10].copy_from_slice(&258u16.to_le_bytes());
+
+        assert!(matches!(
+            load_units(backup),
+            Err(LoaderError::UnsupportedVersion(258))
+        ));
+    }
+
+    #[test]
+    fn rejects_unknown_format_version_appended_after_restart() {
+        let units: Vec<_> = produce_units(2, SESSION_ID).into_iter().flatten().collect();
+        let (mut backup, _) = save_units(&units[..4]);
+        let (mut appended, _) = save_units(&units[4..]);
+        appended[8..10].copy_from_slice(&2u16.to_le_bytes());
+        backup.extend(appended);
+
+        assert!(matches!(
+            load_units(backup),
+            Err(LoaderError::UnsupportedVersion(2))
+        ));
+    }
+
     #[tokio::test]
     async fn something_loaded_something_collected_succeeds() {
         let units: Vec<_> = produce_units(5, SESSION_ID).into_iter().flatten().collect();
@@ -431,6 +967,60 @@ mod tests {
         assert_eq!(loaded_unit_rx.await, Ok(units));
     }
 
+    #[derive(Default)]
+    struct StartingRoundMetrics {
+        starting_rounds: Mutex<Vec<Round>>,
+    }
+
+    impl Metrics for StartingRoundMetrics {
+        fn report_starting_round(&self, round: Round) {
+            self.starting_rounds.lock().unwrap().push(round);
+        }
+    }
+
+    #[tokio::test]
+    async fn reports_starting_round_once_decided() {
+        // (rounds in the backup, next round inferred from collection, reported starting round)
+        let cases = [
+            (0, 0, Some(0)),
+            (5, 5, Some(5)),
+            (5, 0, Some(5)),
+            (3, 4, None),
+        ];
+        for (backup_rounds, next_round_collection, expected) in cases {
+            let units: Vec<_> = produce_units(backup_rounds, SESSION_ID)
+                .into_iter()
+                .flatten()
+                .collect();
+            let encoded_units = encode_all(units).into_iter().flatten().collect();
+            let metrics = Arc::new(StartingRoundMetrics::default());
+
+            let (task, _loaded_unit_rx, highest_response_tx, starting_round_rx) =
+                prepare_test_with_config(
+                    encoded_units,
+                    LoadingConfig {
+                        index: NODE_ID,
+                        session_id: SESSION_ID,
+                        gap_policy: BackupGapPolicy::default(),
+                        load_policy: BackupLoadPolicy::default(),
+                        collection_round_leeway: None,
+                        metrics: metrics.clone(),
+                    },
+                );
+            let handle = tokio::spawn(async {
+                task.await;
+            });
+            highest_response_tx.send(next_round_collection).unwrap();
+            handle.await.unwrap();
+
+            assert_eq!(starting_round_rx.await, Ok(expected));
+            assert_eq!(
+                *metrics.starting_rounds.lock().unwrap(),
+                expected.into_iter().collect::<Vec<_>>()
+            );
+        }
+    }
+
     #[tokio::test]
     async fn nothing_loaded_something_collected_fails() {
         let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
@@ -468,6 +1058,36 @@ mod tests {
         assert_eq!(loaded_unit_rx.await, Ok(units));
     }
 
+    #[tokio::test]
+    async fn implausibly_high_collected_round_is_ignored() {
+        // (next round inferred from collection, starting round)
+        let cases = [(50, Some(3)), (6, Some(3)), (5, None), (4, None)];
+        for (next_round_collection, expected) in cases {
+            let units: Vec<_> = produce_units(3, SESSION_ID).into_iter().flatten().collect();
+            let encoded_units = encode_all(units).into_iter().flatten().collect();
+
+            let (task, _loaded_unit_rx, highest_response_tx, starting_round_rx) =
+                prepare_test_with_config(
+                    encoded_units,
+                    LoadingConfig {
+                        index: NODE_ID,
+                        session_id: SESSION_ID,
+                        gap_policy: BackupGapPolicy::default(),
+                        load_policy: BackupLoadPolicy::default(),
+                        collection_round_leeway: Some(2),
+                        metrics: Arc::new(NoopMetrics),
+                    },
+                );
+            let handle = tokio::spawn(async {
+                task.await;
+            });
+            highest_response_tx.send(next_round_collection).unwrap();
+            handle.await.unwrap();
+
+            assert_eq!(starting_round_rx.await, Ok(expected));
+        }
+    }
+
     #[tokio::test]
     async fn dropped_collection_fails() {
         let units: Vec<_> = produce_units(3, SESSION_ID).into_iter().flatten().collect();
@@ -594,4 +1214,352 @@ mod tests {
         assert_eq!(starting_round_rx.await, Ok(None));
         assert!(loaded_unit_rx.await.is_err());
     }
+
+    #[tokio::test]
+    async fn backup_with_invalid_unit_fails_fast() {
+        let mut units: Vec<_> = produce_units(5, SESSION_ID).into_iter().flatten().collect();
+        units[19] = produce_units(5, SESSION_ID + 1)[4][3].clone();
+        let encoded_units = encode_all(units).into_iter().flatten().collect();
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
+            prepare_test_with_policies(
+                encoded_units,
+                BackupGapPolicy::default(),
+                BackupLoadPolicy::FailFast,
+            );
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(starting_round_rx.await, Ok(None));
+        assert!(loaded_unit_rx.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn backup_with_invalid_unit_loads_best_effort() {
+        let mut units: Vec<_> = produce_units(5, SESSION_ID).into_iter().flatten().collect();
+        units[19] = produce_units(5, SESSION_ID + 1)[4][3].clone();
+        let encoded_units = encode_all(units.clone()).into_iter().flatten().collect();
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
+            prepare_test_with_policies(
+                encoded_units,
+                BackupGapPolicy::default(),
+                BackupLoadPolicy::BestEffort,
+            );
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        units.remove(19);
+        assert_eq!(starting_round_rx.await, Ok(Some(5)));
+        assert_eq!(loaded_unit_rx.await, Ok(units));
+    }
+
+    #[tokio::test]
+    async fn backup_with_torn_tail_loads_best_effort() {
+        let mut units: Vec<_> = produce_units(5, SESSION_ID).into_iter().flatten().collect();
+        let (mut backup, _) = save_units(&units);
+        backup.truncate(backup.len() - 3);
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
+            prepare_test_with_policies(
+                backup,
+                BackupGapPolicy::default(),
+                BackupLoadPolicy::BestEffort,
+            );
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        units.pop();
+        assert_eq!(starting_round_rx.await, Ok(Some(5)));
+        assert_eq!(loaded_unit_rx.await, Ok(units));
+    }
+
+    #[tokio::test]
+    async fn backup_with_torn_tail_fails_fast() {
+        let units: Vec<_> = produce_units(5, SESSION_ID).into_iter().flatten().collect();
+        let (mut backup, _) = save_units(&units);
+        backup.truncate(backup.len() - 3);
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
+            prepare_test_with_policies(
+                backup,
+                BackupGapPolicy::default(),
+                BackupLoadPolicy::FailFast,
+            );
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(starting_round_rx.await, Ok(None));
+        assert!(loaded_unit_rx.await.is_err());
+    }
+
+    /// Units of rounds 0 and 1 with the own unit of round 1 missing, and an own unit of round 2
+    /// built on top of the remaining units of round 1.
+    fn units_with_own_gap() -> Vec<UncheckedSignedUnit> {
+        let mut units = produce_units(2, SESSION_ID);
+        units[1].remove(NODE_ID.0);
+        let keychain = Keychain::new(N_MEMBERS, NODE_ID);
+        let mut parents = NodeMap::with_size(N_MEMBERS);
+        for unit in &units[1] {
+            let full_unit = unit.as_signable();
+            parents.insert(full_unit.creator(), full_unit.hash());
+        }
+        let pre_unit = PreUnit::new(NODE_ID, 2, ControlHash::new(&parents));
+        let mut units: Vec<_> = units.into_iter().flatten().collect();
+        units.push(preunit_to_unchecked_signed_unit(
+            pre_unit, SESSION_ID, &keychain,
+        ));
+        units
+    }
+
+    #[tokio::test]
+    async fn backup_with_own_gap_warns_by_default() {
+        let units = units_with_own_gap();
+        let encoded_units = encode_all(units.clone()).into_iter().flatten().collect();
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
+            prepare_test_with_policies(
+                encoded_units,
+                BackupGapPolicy::Warn,
+                BackupLoadPolicy::default(),
+            );
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(starting_round_rx.await, Ok(Some(3)));
+        assert_eq!(loaded_unit_rx.await, Ok(units));
+    }
+
+    #[tokio::test]
+    async fn backup_with_own_gap_refused_fails() {
+        let encoded_units = encode_all(units_with_own_gap())
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
+            prepare_test_with_policies(
+                encoded_units,
+                BackupGapPolicy::Refuse,
+                BackupLoadPolicy::default(),
+            );
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(starting_round_rx.await, Ok(None));
+        assert!(loaded_unit_rx.await.is_err());
+    }
+
+    /// Units of rounds 0 and 1, with the own unit of round 1 claiming the unit of another creator
+    /// in place of our unit of round 0.
+    fn units_with_broken_own_chain() -> Vec<UncheckedSignedUnit> {
+        let mut units = produce_units(2, SESSION_ID);
+        let keychain = Keychain::new(N_MEMBERS, NODE_ID);
+        let mut parents = NodeMap::with_size(N_MEMBERS);
+        for unit in &units[0] {
+            parents.insert(unit.as_signable().creator(), unit.as_signable().hash());
+        }
+        parents.insert(NODE_ID, units[0][1].as_signable().hash());
+        let pre_unit = PreUnit::new(NODE_ID, 1, ControlHash::new(&parents));
+        units[1][NODE_ID.0] = preunit_to_unchecked_signed_unit(pre_unit, SESSION_ID, &keychain);
+        units.into_iter().flatten().collect()
+    }
+
+    #[tokio::test]
+    async fn backup_with_broken_own_chain_warns_by_default() {
+        let units = units_with_broken_own_chain();
+        let encoded_units = encode_all(units.clone()).into_iter().flatten().collect();
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
+            prepare_test_with_policies(
+                encoded_units,
+                BackupGapPolicy::Warn,
+                BackupLoadPolicy::default(),
+            );
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(starting_round_rx.await, Ok(Some(2)));
+        assert_eq!(loaded_unit_rx.await, Ok(units));
+    }
+
+    #[tokio::test]
+    async fn backup_with_broken_own_chain_refused_fails() {
+        let encoded_units = encode_all(units_with_broken_own_chain())
+            .into_iter()
+            .flatten()
+            .collect();
+
+        let (task, loaded_unit_rx, highest_response_tx, starting_round_rx) =
+            prepare_test_with_policies(
+                encoded_units,
+                BackupGapPolicy::Refuse,
+                BackupLoadPolicy::default(),
+            );
+
+        let handle = tokio::spawn(async {
+            task.await;
+        });
+
+        highest_response_tx.send(0).unwrap();
+
+        handle.await.unwrap();
+
+        assert_eq!(starting_round_rx.await, Ok(None));
+        assert!(loaded_unit_rx.await.is_err());
+    }
+
+    #[derive(Clone, Default)]
+    struct FlushTrackingSaver {
+        data: Arc<Mutex<Vec<u8>>>,
+        flushes: Arc<Mutex<usize>>,
+    }
+
+    impl FlushTrackingSaver {
+        fn flush_count(&self) -> usize {
+            *self.flushes.lock().unwrap()
+        }
+    }
+
+    impl Write for FlushTrackingSaver {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.data.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            *self.flushes.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn saver_flushes_on_shutdown() {
+        let saver = FlushTrackingSaver::default();
+        let (units_for_saver, units_from_runway) = mpsc::unbounded();
+        let (units_for_runway, mut units_from_saver) = mpsc::unbounded();
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let terminator = Terminator::create_root(exit_rx, "AlephBFT-backup-saver");
+
+        let handle = tokio::spawn(run_saving_mechanism(
+            NodeIndex(0),
+            UnitSaver::<_, Hasher64, Data, Signature>::new(saver.clone()),
+            units_from_runway,
+            units_for_runway,
+            terminator,
+        ));
+
+        let unit = produce_units(1, SESSION_ID)[0][0].clone();
+        units_for_saver.unbounded_send(unit.clone()).unwrap();
+        assert_eq!(units_from_saver.next().await, Some(unit.clone()));
+        let flushes_before_exit = saver.flush_count();
+
+        exit_tx.send(()).unwrap();
+        handle.await.unwrap();
+
+        assert!(saver.flush_count() > flushes_before_exit);
+        let saved = saver.data.lock().unwrap().clone();
+        assert_eq!(load_units(saved).unwrap(), vec![unit]);
+    }
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    enum WriterOperation {
+        Write,
+        Flush,
+        Sync,
+    }
+
+    #[derive(Clone, Default)]
+    struct OperationRecordingSaver {
+        operations: Arc<Mutex<Vec<WriterOperation>>>,
+    }
+
+    impl Write for OperationRecordingSaver {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.operations.lock().unwrap().push(WriterOperation::Write);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            self.operations.lock().unwrap().push(WriterOperation::Flush);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn saver_syncs_only_own_units_before_handing_them_back() {
+        let saver = OperationRecordingSaver::default();
+        let sync: BackupSync<OperationRecordingSaver> = Arc::new(|saver| {
+            saver.operations.lock().unwrap().push(WriterOperation::Sync);
+            Ok(())
+        });
+        let (units_for_saver, units_from_runway) = mpsc::unbounded();
+        let (units_for_runway, mut units_from_saver) = mpsc::unbounded();
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let terminator = Terminator::create_root(exit_rx, "AlephBFT-backup-saver");
+
+        let handle = tokio::spawn(run_saving_mechanism(
+            NodeIndex(0),
+            UnitSaver::<_, Hasher64, Data, Signature>::new(saver.clone()).with_sync(Some(sync)),
+            units_from_runway,
+            units_for_runway,
+            terminator,
+        ));
+
+        for unit in produce_units(1, SESSION_ID).remove(0) {
+            let own = unit.as_signable().creator() == NodeIndex(0);
+            units_for_saver.unbounded_send(unit.clone()).unwrap();
+            assert_eq!(units_from_saver.next().await, Some(unit));
+            let operations = std::mem::take(&mut *saver.operations.lock().unwrap());
+            let mut expected = vec![WriterOperation::Write, WriterOperation::Flush];
+            if own {
+                expected.push(WriterOperation::Sync);
+            }
+            assert_eq!(operations, expected);
+        }
+
+        exit_tx.send(()).unwrap();
+        handle.await.unwrap();
+    }
 }
@@ -0,0 +1,159 @@
+use crate::SessionId;
+use log::debug;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Cursor},
+    path::PathBuf,
+};
+
+const BACKUP_FILE_PREFIX: &str = "session-";
+const BACKUP_FILE_SUFFIX: &str = ".units";
+const ARCHIVE_DIR: &str = "archive";
+const DEFAULT_RETAINED_SESSIONS: usize = 2;
+
+/// Keeps the unit backups of consecutive sessions in separate files of a single directory, so that
+/// a corrupted file compromises only one session. The backups of old sessions are moved to the
+/// `archive` subdirectory, only the ones of the most recent sessions are kept in place.
+#[derive(Clone, Debug)]
+pub struct BackupDirectory {
+    path: PathBuf,
+    retained_sessions: usize,
+}
+
+impl BackupDirectory {
+    /// Backups kept in the given directory, which gets created if needed.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        BackupDirectory {
+            path: path.into(),
+            retained_sessions: DEFAULT_RETAINED_SESSIONS,
+        }
+    }
+
+    /// Sets how many of the most recent sessions, including the one being opened, keep their
+    /// backups in place. At least the backup of the current session is always kept.
+    pub fn with_retained_sessions(mut self, retained_sessions: usize) -> Self {
+        self.retained_sessions = retained_sessions.max(1);
+        self
+    }
+
+    /// The name of the file holding the backup of the given session.
+    pub fn file_name(session_id: SessionId) -> String {
+        format!("{}{}{}", BACKUP_FILE_PREFIX, session_id, BACKUP_FILE_SUFFIX)
+    }
+
+    /// The path of the backup of the given session, while it is retained.
+    pub fn session_path(&self, session_id: SessionId) -> PathBuf {
+        self.path.join(Self::file_name(session_id))
+    }
+
+    /// The path of the backup of the given session, once it got archived.
+    pub fn archived_session_path(&self, session_id: SessionId) -> PathBuf {
+        self.path
+            .join(ARCHIVE_DIR)
+            .join(Self::file_name(session_id))
+    }
+
+    /// Opens the backup of the given session, archiving the backups of sessions that are no
+    /// longer retained first. Returns a writer appending to the backup and a reader of everything
+    /// saved in it so far, to be passed to [`LocalIO`](crate::LocalIO).
+    pub fn open_session(&self, session_id: SessionId) -> io::Result<(File, Cursor<Vec<u8>>)> {
+        fs::create_dir_all(&self.path)?;
+        self.archive_old_sessions(session_id)?;
+        let path = self.session_path(session_id);
+        let loader = if path.exists() {
+            Cursor::new(fs::read(&path)?)
+        } else {
+            Cursor::new(Vec::new())
+        };
+        let saver = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok((saver, loader))
+    }
+
+    fn archive_old_sessions(&self, session_id: SessionId) -> io::Result<()> {
+        for entry in fs::read_dir(&self.path)? {
+            let entry = entry?;
+            let old_session_id = match entry.file_name().to_str().and_then(parse_session_id) {
+                Some(old_session_id) => old_session_id,
+                None => continue,
+            };
+            if old_session_id.saturating_add(self.retained_sessions as SessionId) > session_id {
+                continue;
+            }
+            fs::create_dir_all(self.path.join(ARCHIVE_DIR))?;
+            debug!(target: "AlephBFT-unit-backup", "Archiving the backup of session {}.", old_session_id);
+            fs::rename(entry.path(), self.archived_session_path(old_session_id))?;
+        }
+        Ok(())
+    }
+}
+
+/// The session of the backup with the given file name, if it is one.
+fn parse_session_id(file_name: &str) -> Option<SessionId> {
+    file_name
+        .strip_prefix(BACKUP_FILE_PREFIX)?
+        .strip_suffix(BACKUP_FILE_SUFFIX)?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BackupDirectory;
+    use std::{fs, io::Write, path::PathBuf};
+
+    fn test_directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "aleph-bft-backup-directory-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        path
+    }
+
+    #[test]
+    fn reopened_session_reads_what_was_saved() {
+        let path = test_directory("reopen");
+        let directory = BackupDirectory::new(&path);
+
+        let (mut saver, loader) = directory.open_session(7).unwrap();
+        assert!(loader.into_inner().is_empty());
+        saver.write_all(&[1, 2, 3]).unwrap();
+        drop(saver);
+        let (mut saver, loader) = directory.open_session(7).unwrap();
+        assert_eq!(loader.into_inner(), vec![1, 2, 3]);
+        saver.write_all(&[4]).unwrap();
+
+        assert_eq!(
+            fs::read(path.join("session-7.units")).unwrap(),
+            vec![1, 2, 3, 4]
+        );
+        fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn archives_sessions_beyond_retention() {
+        let path = test_directory("archive");
+        let directory = BackupDirectory::new(&path).with_retained_sessions(2);
+
+        for session_id in 0..4 {
+            let (mut saver, _) = directory.open_session(session_id).unwrap();
+            saver.write_all(&[session_id as u8]).unwrap();
+        }
+
+        for session_id in 0..2 {
+            assert!(!directory.session_path(session_id).exists());
+            assert_eq!(
+                fs::read(directory.archived_session_path(session_id)).unwrap(),
+                vec![session_id as u8]
+            );
+        }
+        for session_id in 2..4 {
+            assert_eq!(
+                fs::read(directory.session_path(session_id)).unwrap(),
+                vec![session_id as u8]
+            );
+        }
+        fs::remove_dir_all(path).unwrap();
+    }
+}
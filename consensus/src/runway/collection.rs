@@ -1,18 +1,20 @@
 use crate::{
-    runway::Request,
+    metrics::TrackedSender,
+    runway::{interval_due, Request, RequestResolved, RunwayNotificationOut},
+    signing::SigningDomain,
     units::{UncheckedSignedUnit, ValidationError, Validator},
-    Data, Hasher, Keychain, NodeCount, NodeIndex, NodeMap, Receiver, Round, Sender, Signable,
-    Signature, SignatureError, UncheckedSigned,
+    Clock, Data, Hasher, Index, Keychain, NodeCount, NodeIndex, NodeMap, Receiver, Round, Sender,
+    Signable, Signature, SignatureError, SystemClock, UncheckedSigned,
 };
 use codec::{Decode, Encode};
 use futures::{channel::oneshot, FutureExt, StreamExt};
-use futures_timer::Delay;
 use log::{debug, error, info, warn};
 use std::{
     cmp::max,
     collections::hash_map::DefaultHasher,
     fmt::{Display, Formatter, Result as FmtResult},
     hash::{Hash, Hasher as _},
+    sync::Arc,
     time::Duration,
 };
 
@@ -38,7 +40,7 @@ impl<H: Hasher, D: Data, S: Signature> Signable for NewestUnitResponse<H, D, S>
     type Hash = Vec<u8>;
 
     fn hash(&self) -> Self::Hash {
-        self.encode()
+        SigningDomain::NewestUnitResponse.message(&self.encode())
     }
 }
 
@@ -76,6 +78,11 @@ impl<H: Hasher, D: Data, S: Signature> NewestUnitResponse<H, D, S> {
     pub fn requester(&self) -> NodeIndex {
         self.requester
     }
+
+    /// The newest unit of the responder, if any.
+    pub(crate) fn unit(&self) -> Option<&UncheckedSignedUnit<H, D, S>> {
+        self.unit.as_ref()
+    }
 }
 
 /// Ways in which a newest unit response might be wrong.
@@ -83,6 +90,7 @@ impl<H: Hasher, D: Data, S: Signature> NewestUnitResponse<H, D, S> {
 pub enum Error<H: Hasher, D: Data, S: Signature> {
     WrongSignature,
     SaltMismatch(Salt, Salt),
+    WrongRequester(NodeIndex),
     InvalidUnit(ValidationError<H, D, S>),
     ForeignUnit(NodeIndex),
 }
@@ -95,6 +103,7 @@ impl<H: Hasher, D: Data, S: Signature> Display for Error<H, D, S> {
             SaltMismatch(expected, got) => {
                 write!(f, "mismatched salt, expected {}, got {}", expected, got)
             }
+            WrongRequester(id) => write!(f, "response to a request of node {:?}", id),
             InvalidUnit(e) => write!(f, "invalid unit: {}", e),
             ForeignUnit(id) => write!(f, "unit from node {:?}", id),
         }
@@ -128,21 +137,21 @@ pub enum Status {
 
 /// Initial unit collection to figure out at which round we should start unit production.
 /// Unfortunately this isn't quite BFT, but it's good enough in many situations.
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
-pub struct Collection<'a, MK: Keychain> {
+#[derive(Clone, Debug)]
+pub struct Collection<'a, MK: Keychain, D: Data> {
     keychain: &'a MK,
-    validator: &'a Validator<MK>,
+    validator: &'a Validator<MK, D>,
     collected_starting_rounds: NodeMap<Round>,
     threshold: NodeCount,
     salt: Salt,
 }
 
-impl<'a, MK: Keychain> Collection<'a, MK> {
+impl<'a, MK: Keychain, D: Data> Collection<'a, MK, D> {
     /// Create a new collection instance ready to collect responses.
     /// The returned salt should be used to initiate newest unit requests.
     pub fn new(
         keychain: &'a MK,
-        validator: &'a Validator<MK>,
+        validator: &'a Validator<MK, D>,
         threshold: NodeCount,
     ) -> (Self, Salt) {
         let salt = generate_salt();
@@ -161,11 +170,15 @@ impl<'a, MK: Keychain> Collection<'a, MK> {
     }
 
     /// Process a response to a newest unit request.
-    pub fn on_newest_response<H: Hasher, D: Data>(
+    pub fn on_newest_response<H: Hasher>(
         &mut self,
         unchecked_response: UncheckedSigned<NewestUnitResponse<H, D, MK::Signature>, MK::Signature>,
     ) -> Result<Status, Error<H, D, MK::Signature>> {
         let response = unchecked_response.check(self.keychain)?.into_signable();
+        // Responses to our earlier requests, or to requests of others, must not be replayed to us.
+        if response.requester != self.keychain.index() {
+            return Err(Error::WrongRequester(response.requester));
+        }
         if response.salt != self.salt {
             return Err(Error::SaltMismatch(self.salt, response.salt));
         }
@@ -197,11 +210,26 @@ impl<'a, MK: Keychain> Collection<'a, MK> {
         self.salt
     }
 
+    /// The smallest number of responses, counting the trivial self-response, from which the
+    /// starting round may be decided once waiting for more times out, `N/3 + 1`. At least one of
+    /// them comes from an honest node then.
+    pub fn minimal_quorum(&self) -> NodeCount {
+        self.keychain.node_count() / 3 + NodeCount(1)
+    }
+
+    fn responders(&self) -> NodeCount {
+        NodeCount(self.collected_starting_rounds.item_count())
+    }
+
+    fn starting_round(&self) -> Round {
+        *self.collected_starting_rounds.values().max().unwrap_or(&0)
+    }
+
     /// The current status of the collection.
     pub fn status(&self) -> Status {
         use Status::*;
-        let responders = NodeCount(self.collected_starting_rounds.item_count());
-        let starting_round: Round = *self.collected_starting_rounds.values().max().unwrap_or(&0);
+        let responders = self.responders();
+        let starting_round = self.starting_round();
         if responders == self.keychain.node_count() {
             return Finished(starting_round);
         }
@@ -217,12 +245,20 @@ type ResponsesFromNetwork<H, D, MK> = UncheckedSigned<
     <MK as Keychain>::Signature,
 >;
 
+type RequestsForNetwork<H, D, MK> =
+    TrackedSender<RunwayNotificationOut<H, D, <MK as Keychain>::Signature>>;
+
 /// A runnable wrapper around initial unit collection.
 pub struct IO<'a, H: Hasher, D: Data, MK: Keychain> {
     round_for_creator: oneshot::Sender<Round>,
     responses_from_network: Receiver<ResponsesFromNetwork<H, D, MK>>,
-    resolved_requests: Sender<Request<H>>,
-    collection: Collection<'a, MK>,
+    resolved_requests: Sender<RequestResolved<H>>,
+    requests_for_network: RequestsForNetwork<H, D, MK>,
+    collection: Collection<'a, MK, D>,
+    clock: Arc<dyn Clock>,
+    status_report_interval: Option<Duration>,
+    timeout: Option<Duration>,
+    resend_interval: Option<Duration>,
 }
 
 impl<'a, H: Hasher, D: Data, MK: Keychain> IO<'a, H, D, MK> {
@@ -230,25 +266,80 @@ impl<'a, H: Hasher, D: Data, MK: Keychain> IO<'a, H, D, MK> {
     pub fn new(
         round_for_creator: oneshot::Sender<Round>,
         responses_from_network: Receiver<ResponsesFromNetwork<H, D, MK>>,
-        resolved_requests: Sender<Request<H>>,
-        collection: Collection<'a, MK>,
+        resolved_requests: Sender<RequestResolved<H>>,
+        requests_for_network: RequestsForNetwork<H, D, MK>,
+        collection: Collection<'a, MK, D>,
     ) -> Self {
         IO {
             round_for_creator,
             responses_from_network,
             resolved_requests,
+            requests_for_network,
             collection,
+            clock: Arc::new(SystemClock),
+            status_report_interval: Some(Duration::from_secs(10)),
+            timeout: None,
+            resend_interval: None,
         }
     }
 
-    fn finish(self, round: Round) {
+    /// Sets the source of time for the catch up delay, [`SystemClock`] by default.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Sets how often the status of the collection is reported, every 10 seconds by default.
+    /// `None` disables the reports.
+    pub fn with_status_report_interval(mut self, status_report_interval: Option<Duration>) -> Self {
+        self.status_report_interval = status_report_interval;
+        self
+    }
+
+    /// Sets how long to wait for responses before deciding the starting round from the ones
+    /// that arrived, if there are at least [`Collection::minimal_quorum`] of them, or giving up
+    /// otherwise. Waits indefinitely by default.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how often to broadcast the newest unit request again, with the same salt, while too
+    /// few responses arrived, never by default.
+    pub fn with_resend_interval(mut self, resend_interval: Option<Duration>) -> Self {
+        self.resend_interval = resend_interval;
+        self
+    }
+
+    fn resend_request(&self) {
+        debug!(target: "AlephBFT-runway", "Broadcasting the newest unit request again, status: {:?}.", self.collection.status());
+        let request = RunwayNotificationOut::Request(Request::NewestUnit(self.collection.salt()));
+        if let Err(e) = self.requests_for_network.unbounded_send(request) {
+            warn!(target: "AlephBFT-runway", "Unable to resend the newest unit request: {}", e);
+        }
+    }
+
+    fn on_timeout(self) {
+        let responders = self.collection.responders();
+        let minimal_quorum = self.collection.minimal_quorum();
+        if responders < minimal_quorum {
+            error!(target: "AlephBFT-runway", "Initial unit collection timed out with responses from {} nodes, at least {} needed to decide the starting round. Giving up.", responders.0, minimal_quorum.0);
+            return;
+        }
+        warn!(target: "AlephBFT-runway", "Initial unit collection timed out, deciding the starting round from responses of {} nodes.", responders.0);
+        let starting_round = self.collection.starting_round();
+        self.finish(starting_round, None);
+    }
+
+    fn finish(self, round: Round, satisfied_by: Option<NodeIndex>) {
         if self.round_for_creator.send(round).is_err() {
             error!(target: "AlephBFT-runway", "unable to send starting round to creator");
         }
-        if let Err(e) = self
-            .resolved_requests
-            .unbounded_send(Request::NewestUnit(self.collection.salt()))
-        {
+        if let Err(e) = self.resolved_requests.unbounded_send(RequestResolved {
+            request: Request::NewestUnit(self.collection.salt()),
+            satisfied_by,
+            abandoned: false,
+        }) {
             warn!(target: "AlephBFT-runway", "unable to send resolved request:  {}", e);
         }
         info!(target: "AlephBFT-runway", "Finished initial unit collection with status: {:?}", self.collection.status());
@@ -258,14 +349,15 @@ impl<'a, H: Hasher, D: Data, MK: Keychain> IO<'a, H, D, MK> {
         info!(target: "AlephBFT-runway", "Initial unit collection status report: status - {:?}, collected starting rounds - {}", self.collection.status(), self.collection.collected_starting_rounds);
     }
 
-    /// Run the initial unit collection until it sends the initial round.
+    /// Run the initial unit collection until it sends the initial round or times out.
     pub async fn run(mut self) {
         use Status::*;
-        let mut catch_up_delay = futures_timer::Delay::new(Duration::from_secs(5)).fuse();
+        let mut catch_up_delay = self.clock.sleep(Duration::from_secs(5)).fuse();
         let mut delay_passed = false;
 
-        let status_ticker_delay = Duration::from_secs(10);
-        let mut status_ticker = Delay::new(status_ticker_delay).fuse();
+        let mut status_ticker = interval_due(self.clock.as_ref(), self.status_report_interval);
+        let mut timeout = interval_due(self.clock.as_ref(), self.timeout);
+        let mut resend_ticker = interval_due(self.clock.as_ref(), self.resend_interval);
 
         loop {
             futures::select! {
@@ -278,17 +370,18 @@ impl<'a, H: Hasher, D: Data, MK: Keychain> IO<'a, H, D, MK> {
                             return;
                         }
                     };
+                    let responder = response.as_signable().index();
                     match self.collection.on_newest_response(response) {
                         Ok(Pending) => (),
                         Ok(Ready(round)) => if delay_passed {
-                            self.finish(round);
+                            self.finish(round, Some(responder));
                             return;
                         },
                         Ok(Finished(round)) => {
-                            self.finish(round);
+                            self.finish(round, Some(responder));
                             return;
                         },
-                        Err(e) => warn!(target: "AlephBFT-runway", "Received wrong newest unit response: {}", e),
+                        Err(e) => warn!(target: "AlephBFT-runway", "Ignoring wrong newest unit response from {:?}: {}", responder, e),
                     }
                 },
                 _ = catch_up_delay => match self.collection.status() {
@@ -298,13 +391,23 @@ impl<'a, H: Hasher, D: Data, MK: Keychain> IO<'a, H, D, MK> {
                         self.status_report();
                     },
                     Ready(round) | Finished(round)  => {
-                        self.finish(round);
+                        self.finish(round, None);
                         return;
                     },
                 },
                 _ = &mut status_ticker => {
                     self.status_report();
-                    status_ticker = Delay::new(status_ticker_delay).fuse();
+                    status_ticker = interval_due(self.clock.as_ref(), self.status_report_interval);
+                },
+                _ = &mut resend_ticker => {
+                    if self.collection.status() == Pending {
+                        self.resend_request();
+                        resend_ticker = interval_due(self.clock.as_ref(), self.resend_interval);
+                    }
+                },
+                _ = timeout => {
+                    self.on_timeout();
+                    return;
                 },
             }
         }
@@ -315,10 +418,12 @@ impl<'a, H: Hasher, D: Data, MK: Keychain> IO<'a, H, D, MK> {
 mod tests {
     use super::{
         Collection as GenericCollection, Error, NewestUnitResponse as GenericNewestUnitResponse,
-        Salt, Status::*,
+        Salt, Status::*, IO,
     };
     use crate::{
-        creation::Creator as GenericCreator,
+        creation::{default_threshold, Creator as GenericCreator},
+        metrics::tracked_unbounded,
+        runway::{Request, RequestResolved, RunwayNotificationOut},
         units::{
             FullUnit as GenericFullUnit, PreUnit as GenericPreUnit,
             UncheckedSignedUnit as GenericUncheckedSignedUnit, Validator as GenericValidator,
@@ -326,10 +431,17 @@ mod tests {
         Index, NodeCount, NodeIndex, SessionId, Signed, UncheckedSigned,
     };
     use aleph_bft_mock::{Data, Hasher64, Keychain, Signature};
-    use std::iter::{once, repeat};
+    use futures::{
+        channel::{mpsc, oneshot},
+        StreamExt,
+    };
+    use std::{
+        iter::{once, repeat},
+        time::Duration,
+    };
 
-    type Collection<'a> = GenericCollection<'a, Keychain>;
-    type Validator = GenericValidator<Keychain>;
+    type Collection<'a> = GenericCollection<'a, Keychain, Data>;
+    type Validator = GenericValidator<Keychain, Data>;
     type Creator = GenericCreator<Hasher64>;
     type PreUnit = GenericPreUnit<Hasher64>;
     type FullUnit = GenericFullUnit<Hasher64, Data>;
@@ -451,6 +563,35 @@ mod tests {
         assert_eq!(collection.status(), Ready(0));
     }
 
+    #[test]
+    fn ready_with_exactly_custom_threshold_messages() {
+        let n_members = NodeCount(7);
+        let threshold = NodeCount(5);
+        let collection_threshold = NodeCount(3);
+        let creator_id = NodeIndex(0);
+        let session_id = 0;
+        let max_round = 2;
+        let keychains = keychain_set(n_members);
+        let keychain = &keychains[0];
+        let validator = Validator::new(session_id, *keychain, max_round, threshold);
+        let (mut collection, salt) = Collection::new(keychain, &validator, collection_threshold);
+        let responses = create_responses(
+            keychains.iter().skip(1).take(2).zip(repeat(None)),
+            salt,
+            creator_id,
+        );
+        // Our own trivial response is counted as well.
+        assert_eq!(
+            collection.on_newest_response(responses[0].clone()),
+            Ok(Pending)
+        );
+        assert_eq!(
+            collection.on_newest_response(responses[1].clone()),
+            Ok(Ready(0))
+        );
+        assert_eq!(collection.status(), Ready(0));
+    }
+
     #[test]
     fn finished_and_higher_starting_round_with_last_message() {
         let n_members = NodeCount(7);
@@ -460,7 +601,7 @@ mod tests {
         let max_round = 2;
         let keychains = keychain_set(n_members);
         let keychain = &keychains[0];
-        let creator = Creator::new(creator_id, n_members);
+        let creator = Creator::new(creator_id, n_members, default_threshold(n_members));
         let validator = Validator::new(session_id, *keychain, max_round, threshold);
         let (mut collection, salt) = Collection::new(keychain, &validator, threshold);
         let (preunit, _) = creator.create_unit(0).expect("Creation should succeed.");
@@ -515,6 +656,46 @@ mod tests {
         assert_eq!(collection.status(), Pending);
     }
 
+    #[test]
+    fn stale_response_does_not_count_toward_threshold() {
+        let n_members = NodeCount(7);
+        let threshold = NodeCount(5);
+        let creator_id = NodeIndex(0);
+        let session_id = 0;
+        let max_round = 2;
+        let keychains = keychain_set(n_members);
+        let keychain = &keychains[0];
+        let validator = Validator::new(session_id, *keychain, max_round, threshold);
+        let (mut collection, salt) = Collection::new(keychain, &validator, threshold);
+        let stale_salt = salt.wrapping_sub(1);
+        let responses = create_responses(
+            keychains.iter().skip(1).take(4).zip(repeat(None)),
+            salt,
+            creator_id,
+        );
+        let stale_response =
+            create_responses(once((&keychains[4], None)), stale_salt, creator_id).remove(0);
+        let foreign_response =
+            create_responses(once((&keychains[4], None)), salt, NodeIndex(1)).remove(0);
+
+        for response in responses.iter().take(3) {
+            assert_eq!(collection.on_newest_response(response.clone()), Ok(Pending));
+        }
+        assert_eq!(
+            collection.on_newest_response(stale_response),
+            Err(Error::SaltMismatch(salt, stale_salt))
+        );
+        assert_eq!(
+            collection.on_newest_response(foreign_response),
+            Err(Error::WrongRequester(NodeIndex(1)))
+        );
+        assert_eq!(collection.status(), Pending);
+        assert_eq!(
+            collection.on_newest_response(responses[3].clone()),
+            Ok(Ready(0))
+        );
+    }
+
     #[test]
     fn detects_invalid_unit() {
         let n_members = NodeCount(7);
@@ -525,7 +706,7 @@ mod tests {
         let max_round = 2;
         let keychains = keychain_set(n_members);
         let keychain = &keychains[0];
-        let creator = Creator::new(creator_id, n_members);
+        let creator = Creator::new(creator_id, n_members, default_threshold(n_members));
         let validator = Validator::new(session_id, *keychain, max_round, threshold);
         let (mut collection, salt) = Collection::new(keychain, &validator, threshold);
         let (preunit, _) = creator.create_unit(0).expect("Creation should succeed.");
@@ -554,7 +735,7 @@ mod tests {
         let max_round = 2;
         let keychains = keychain_set(n_members);
         let keychain = &keychains[0];
-        let creator = Creator::new(other_creator_id, n_members);
+        let creator = Creator::new(other_creator_id, n_members, default_threshold(n_members));
         let validator = Validator::new(session_id, *keychain, max_round, threshold);
         let (mut collection, salt) = Collection::new(keychain, &validator, threshold);
         let (preunit, _) = creator.create_unit(0).expect("Creation should succeed.");
@@ -572,4 +753,166 @@ mod tests {
         }
         assert_eq!(collection.status(), Pending);
     }
+
+    #[tokio::test]
+    async fn resolved_request_names_the_final_responder() {
+        let n_members = NodeCount(7);
+        let threshold = NodeCount(5);
+        let creator_id = NodeIndex(0);
+        let session_id = 0;
+        let max_round = 2;
+        let keychains = keychain_set(n_members);
+        let keychain = &keychains[0];
+        let validator = Validator::new(session_id, *keychain, max_round, threshold);
+        let (collection, salt) = Collection::new(keychain, &validator, threshold);
+        let (round_for_creator, round_from_collection) = oneshot::channel();
+        let (responses_for_collection, responses_from_network) = mpsc::unbounded();
+        let (resolved_requests, mut resolved_requests_rx) = mpsc::unbounded();
+        let (requests_for_network, _requests_for_network_rx) = tracked_unbounded();
+        let io = IO::new(
+            round_for_creator,
+            responses_from_network,
+            resolved_requests,
+            requests_for_network,
+            collection,
+        );
+        let responses =
+            create_responses(keychains.iter().skip(1).zip(repeat(None)), salt, creator_id);
+        for response in responses {
+            responses_for_collection
+                .unbounded_send(response)
+                .expect("channel should be open");
+        }
+        io.run().await;
+        assert_eq!(round_from_collection.await, Ok(0));
+        match resolved_requests_rx.next().await {
+            Some(RequestResolved {
+                request: Request::NewestUnit(resolved_salt),
+                satisfied_by,
+                ..
+            }) => {
+                assert_eq!(resolved_salt, salt);
+                assert_eq!(satisfied_by, Some(NodeIndex(6)));
+            }
+            _ => panic!("expected the newest unit request to be resolved"),
+        }
+    }
+
+    #[tokio::test]
+    async fn decides_starting_round_after_timeout_with_minimal_quorum() {
+        let n_members = NodeCount(4);
+        let threshold = NodeCount(3);
+        let creator_id = NodeIndex(0);
+        let session_id = 0;
+        let max_round = 2;
+        let keychains = keychain_set(n_members);
+        let keychain = &keychains[0];
+        let validator = Validator::new(session_id, *keychain, max_round, threshold);
+        let (collection, salt) = Collection::new(keychain, &validator, threshold);
+        let (round_for_creator, round_from_collection) = oneshot::channel();
+        let (responses_for_collection, responses_from_network) = mpsc::unbounded();
+        let (resolved_requests, _resolved_requests_rx) = mpsc::unbounded();
+        let (requests_for_network, _requests_for_network_rx) = tracked_unbounded();
+        let io = IO::new(
+            round_for_creator,
+            responses_from_network,
+            resolved_requests,
+            requests_for_network,
+            collection,
+        )
+        .with_timeout(Some(Duration::from_millis(50)));
+        // Together with our own trivial response this is just the minimal quorum.
+        let responses = create_responses(
+            keychains.iter().skip(1).take(1).zip(repeat(None)),
+            salt,
+            creator_id,
+        );
+        for response in responses {
+            responses_for_collection
+                .unbounded_send(response)
+                .expect("channel should be open");
+        }
+
+        io.run().await;
+
+        assert_eq!(round_from_collection.await, Ok(0));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_timeout_without_minimal_quorum() {
+        let n_members = NodeCount(4);
+        let threshold = NodeCount(3);
+        let creator_id = NodeIndex(0);
+        let session_id = 0;
+        let max_round = 2;
+        let keychain = Keychain::new(n_members, creator_id);
+        let validator = Validator::new(session_id, keychain, max_round, threshold);
+        let (collection, _) = Collection::new(&keychain, &validator, threshold);
+        let (round_for_creator, round_from_collection) = oneshot::channel();
+        let (_responses_for_collection, responses_from_network) = mpsc::unbounded();
+        let (resolved_requests, _resolved_requests_rx) =
+            mpsc::unbounded::<RequestResolved<Hasher64>>();
+        let (requests_for_network, _requests_for_network_rx) = tracked_unbounded();
+        let io = IO::new(
+            round_for_creator,
+            responses_from_network,
+            resolved_requests,
+            requests_for_network,
+            collection,
+        )
+        .with_timeout(Some(Duration::from_millis(50)));
+
+        io.run().await;
+
+        assert!(round_from_collection.await.is_err());
+    }
+
+    #[tokio::test]
+    async fn broadcasts_request_again_while_pending() {
+        let n_members = NodeCount(7);
+        let threshold = NodeCount(5);
+        let creator_id = NodeIndex(0);
+        let session_id = 0;
+        let max_round = 2;
+        let keychains = keychain_set(n_members);
+        let keychain = &keychains[0];
+        let validator = Validator::new(session_id, *keychain, max_round, threshold);
+        let (collection, salt) = Collection::new(keychain, &validator, threshold);
+        let (round_for_creator, round_from_collection) = oneshot::channel();
+        let (responses_for_collection, responses_from_network) = mpsc::unbounded();
+        let (resolved_requests, _resolved_requests_rx) = mpsc::unbounded();
+        let (requests_for_network, mut requests_for_network_rx) = tracked_unbounded();
+        let io = IO::new(
+            round_for_creator,
+            responses_from_network,
+            resolved_requests,
+            requests_for_network,
+            collection,
+        )
+        .with_resend_interval(Some(Duration::from_millis(10)));
+        let mut responses =
+            create_responses(keychains.iter().skip(1).zip(repeat(None)), salt, creator_id)
+                .into_iter();
+        // Only the first peer got the initial broadcast.
+        responses_for_collection
+            .unbounded_send(responses.next().expect("there should be responses"))
+            .expect("channel should be open");
+
+        let network = async {
+            match requests_for_network_rx.next().await {
+                Some(RunwayNotificationOut::Request(Request::NewestUnit(resent_salt))) => {
+                    assert_eq!(resent_salt, salt)
+                }
+                _ => panic!("expected the newest unit request to be broadcast again"),
+            }
+            for response in responses {
+                responses_for_collection
+                    .unbounded_send(response)
+                    .expect("channel should be open");
+            }
+        };
+        futures::join!(io.run(), network);
+
+        assert_eq!(round_from_collection.await, Ok(0));
+    }
 }
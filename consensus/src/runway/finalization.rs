@@ -0,0 +1,26 @@
+use crate::{Data, FinalizationHandler, FinalizedItem, Hasher, Receiver};
+use futures::StreamExt;
+use log::debug;
+
+/// Passes the data of a single ordered batch to the handler, in the order of finalization.
+pub(crate) fn finalize_batch<H: Hasher, D: Data, FH: FinalizationHandler<D>>(
+    finalization_handler: &mut FH,
+    finalized_items: Vec<FinalizedItem<H, D>>,
+) {
+    finalization_handler.items_finalized::<H>(finalized_items);
+}
+
+/// Hands the data finalized by the runway to the application, batch by batch. Runs until the
+/// runway drops its end of the channel, so the batches it finalizes while exiting are handed over
+/// as well.
+pub async fn run_finalization_mechanism<H: Hasher, D: Data, FH: FinalizationHandler<D>>(
+    mut finalization_handler: FH,
+    mut finalized_items_from_runway: Receiver<Vec<FinalizedItem<H, D>>>,
+) {
+    let mut batches = 0;
+    while let Some(finalized_items) = finalized_items_from_runway.next().await {
+        finalize_batch(&mut finalization_handler, finalized_items);
+        batches += 1;
+    }
+    debug!(target: "AlephBFT-finalization", "Finalization task exiting after {} batches.", batches);
+}
@@ -1,37 +1,65 @@
 use crate::{
-    alerts::{Alert, AlertConfig, ForkProof, ForkingNotification, NetworkMessage},
-    consensus, handle_task_termination,
+    alerts::{
+        Alert, AlertConfig, ForkDetectionHandler, ForkEvidence, ForkProof, ForkingNotification,
+        NetworkMessage,
+    },
+    consensus,
+    creation::{RoundSnapshot, SnapshotIO},
+    extender::{OrderedBatch, HEAD_ACCEPTANCE_DISTANCE},
+    handle_task_termination,
     member::UnitMessage,
+    metrics::{tracked_channel, tracked_unbounded, RunwayCounters, TrackedReceiver, TrackedSender},
     units::{
-        ControlHash, PreUnit, SignedUnit, UncheckedSignedUnit, Unit, UnitCoord, UnitStore,
-        UnitStoreStatus, Validator,
+        ControlHash, PreUnit, SignedUnit, UncheckedSignedUnit, Unit, UnitCoord, UnitInspector,
+        UnitStore, UnitStoreStatus, ValidationError, ValidationErrorKind, Validator,
     },
-    Config, Data, DataProvider, FinalizationHandler, Hasher, Index, Keychain, MultiKeychain,
-    NodeCount, NodeIndex, NodeMap, Receiver, Round, Sender, Signature, Signed, SpawnHandle,
+    AlertCommitment, AlerterFailurePolicy, Clock, Config, Data, DataProvider, FinalizationHandler,
+    FinalizedItem, Hasher, Index, InternalChannel, Keychain, Metrics, MultiKeychain, NodeCount,
+    NodeIndex, NodeMap, NodeSubset, ParentVerification, Receiver, RequestHashing,
+    ResponseRateLimit, Round, Sender, SessionId, Signature, Signed, SpawnHandle, TaskHandle,
     Terminator, UncheckedSigned,
 };
 use aleph_bft_types::Recipient;
+use codec::Decode;
+use derivative::Derivative;
 use futures::{
     channel::{mpsc, oneshot},
-    pin_mut, Future, FutureExt, StreamExt,
+    future::{self, BoxFuture, Either, Fuse, FusedFuture},
+    pin_mut,
+    stream::FusedStream,
+    FutureExt, Stream, StreamExt,
 };
-use futures_timer::Delay;
-use log::{debug, error, info, trace, warn};
+use log::{debug, error, info, log_enabled, trace, warn, Level};
 use std::{
-    collections::HashSet,
+    collections::{hash_map::Entry, BTreeMap, HashMap, HashSet, VecDeque},
     convert::TryFrom,
     fmt,
-    io::{Read, Write},
+    io::{self, Read, Write},
     marker::PhantomData,
-    time::Duration,
+    sync::Arc,
+    time::{Duration, Instant},
 };
 
 mod backup;
+mod backup_directory;
 mod collection;
+mod finalization;
 mod packer;
 
-use backup::{UnitLoader, UnitSaver};
-#[cfg(feature = "initial_unit_collection")]
+#[cfg(test)]
+pub(crate) use backup::decode_backup;
+pub use backup::{BackupStats, BackupSync};
+use backup::{LoadingConfig, UnitLoader, UnitSaver};
+pub use backup_directory::BackupDirectory;
+
+type MissingCoords = HashMap<UnitCoord, Instant, RequestHashing>;
+type MissingParents<H> = HashSet<<H as Hasher>::Hash, RequestHashing>;
+type PendingRequests<H> = HashMap<Request<H>, Instant, RequestHashing>;
+type ForkDetectionCallback<H, D, S> = Box<dyn FnMut(NodeIndex, ForkProof<H, D, S>) + Send>;
+
+/// How many units failing validation transiently are kept for another try at most, so that
+/// a flood of them cannot exhaust memory.
+const MAX_DEFERRED_UNITS: usize = 1000;
 use collection::{Collection, IO as CollectionIO};
 pub use collection::{NewestUnitResponse, Salt};
 use packer::Packer;
@@ -44,6 +72,8 @@ pub(crate) enum NotificationIn<H: Hasher> {
     NewUnits(Vec<Unit<H>>),
     /// Response to a request to decode parents when the control hash is wrong.
     UnitParents(H::Hash, Vec<H::Hash>),
+    /// Our own unit restored from backup, as the creator has been missing it for too long.
+    RestoredOwnUnit(Unit<H>),
 }
 
 /// Type for outgoing notifications: Consensus to Runway.
@@ -59,18 +89,126 @@ pub(crate) enum NotificationOut<H: Hasher> {
     WrongControlHash(H::Hash),
     /// Notification that a new unit has been added to the DAG, list of decoded parents provided
     AddedToDag(H::Hash, Vec<H::Hash>),
+    /// Notification that the creator has been missing our own unit of the given round for too
+    /// long, so it should be restored from backup.
+    MissingOwnParent(Round),
+}
+
+/// Notable events in the life of the runway, which can be observed by the application.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RunwayEvent {
+    /// Consensus incorporated the unit with the given coord into its DAG.
+    UnitAddedToDag(UnitCoord),
+    /// A valid fork proof implicates this very node, so it stopped creating units.
+    OwnForkDetected,
+    /// A fork proof revealing a new forker was verified, either by us or by the alerter.
+    ForkEvidence(ForkEvidence),
+    /// The node sent as many parents responses bringing nothing new as
+    /// [`Config::with_duplicate_parents_response_limit`] allows, which points to a faulty or
+    /// malicious peer. Reported once per node, trusting the index it declared in the responses.
+    ExcessiveDuplicateResponses(NodeIndex),
+    /// Units were restored from the backup, reported before anything else happens in the session.
+    BackupLoaded(BackupStats),
+    /// We created our last unit, just below the maximal round, and every round that can still be
+    /// decided has been finalized, so the session will make no further progress and can be
+    /// torn down. The finalization handler might still be processing the last batches.
+    SessionComplete,
+    /// All the units ordered so far, as requested with [`RunwayControl::DumpOrderedUnits`]. One
+    /// unit per line in the order of finalization, as the position, round, creator and hex
+    /// encoded hash separated by spaces, so that the dumps of different nodes can be diffed.
+    OrderedUnits(String),
+    /// Whether the unit with the given encoded hash has been finalized, as requested with
+    /// [`RunwayControl::QueryFinalized`]. Only the most recently finalized units are remembered,
+    /// as many as [`Config::finalized_hashes_retention`] says, so older ones are reported as not
+    /// finalized.
+    FinalizationStatus { hash: Vec<u8>, finalized: bool },
+}
+
+/// The state of the runway, taken every time it reports its status.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StatusSnapshot {
+    /// The session the runway operates in.
+    pub session_id: SessionId,
+    /// The highest round of units the runway accepts.
+    pub max_round: Round,
+    /// How many units are held in the store.
+    pub units_held: usize,
+    /// The highest round of the units held, if any.
+    pub height: Option<Round>,
+    /// The contents of the unit store.
+    pub store: UnitStoreStatus,
+    /// The coords of the units we requested and still wait for, sorted.
+    pub missing_coords: Vec<UnitCoord>,
+    /// The encoded hashes of the units whose parents we requested and still wait for, sorted.
+    pub missing_parents: Vec<Vec<u8>>,
+    /// How far behind the rest of the committee the node is.
+    pub catch_up: CatchUpEstimate,
+    /// How many peers are known to have received our newest broadcast unit, if its propagation
+    /// is tracked.
+    pub newest_own_unit_reached: Option<usize>,
+    /// The peers known to have received each of our tracked broadcast units, by the rounds of
+    /// the units, oldest first. Empty if their propagation is not tracked.
+    pub own_units_reached: Vec<(Round, Vec<NodeIndex>)>,
+}
+
+/// A unit received from the network which failed validation for good, reported so that abuse can
+/// be detected by watching the rate of such failures. Units which might pass validation later,
+/// see [`ValidationErrorKind::DeferredByInspector`], are not reported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ValidationFailure {
+    /// The node that sent the unit, if known. Only parents responses identify their sender,
+    /// broadcast units and other responses do not.
+    pub sender: Option<NodeIndex>,
+    /// What was wrong with the unit.
+    pub error: ValidationErrorKind,
+}
+
+/// Commands through which the application can steer a running session.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RunwayControl {
+    /// Stop processing unit messages from the network, leaving them buffered. All the other work,
+    /// including answering requests already received, continues.
+    PauseUnitIntake,
+    /// Resume processing unit messages from the network, starting with the buffered ones.
+    ResumeUnitIntake,
+    /// Emit a [`RunwayEvent::OrderedUnits`] with all the units ordered so far.
+    DumpOrderedUnits,
+    /// Emit a [`RunwayEvent::FinalizationStatus`] telling whether the unit with the given encoded
+    /// hash has been finalized.
+    QueryFinalized(Vec<u8>),
 }
 
 /// Possible requests for information from other nodes.
+#[derive(Derivative)]
+#[derivative(
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Debug(bound = ""),
+    Eq(bound = ""),
+    PartialEq(bound = ""),
+    Hash(bound = "")
+)]
 pub enum Request<H: Hasher> {
     Coord(UnitCoord),
     Parents(H::Hash),
     NewestUnit(Salt),
 }
 
+/// A notification that a request has been satisfied or given up on, so it should no longer be
+/// sent out.
+pub struct RequestResolved<H: Hasher> {
+    /// The request that was satisfied or given up on.
+    pub request: Request<H>,
+    /// The node whose response satisfied the request, if it is known.
+    pub satisfied_by: Option<NodeIndex>,
+    /// Whether the request was given up on without being satisfied.
+    pub abandoned: bool,
+}
+
 pub(crate) enum Response<H: Hasher, D: Data, S: Signature> {
     Coord(UncheckedSignedUnit<H, D, S>),
-    Parents(H::Hash, Vec<UncheckedSignedUnit<H, D, S>>),
+    /// The responder, the unit and its parents.
+    Parents(NodeIndex, H::Hash, Vec<UncheckedSignedUnit<H, D, S>>),
     NewestUnit(UncheckedSigned<NewestUnitResponse<H, D, S>, S>),
 }
 
@@ -104,8 +242,8 @@ impl<H: Hasher, D: Data, S: Signature> TryFrom<UnitMessage<H, D, S>>
                 RunwayNotificationIn::Request(Request::Parents(u_hash), node_id)
             }
             UnitMessage::ResponseCoord(u) => RunwayNotificationIn::Response(Response::Coord(u)),
-            UnitMessage::ResponseParents(u_hash, parents) => {
-                RunwayNotificationIn::Response(Response::Parents(u_hash, parents))
+            UnitMessage::ResponseParents(responder, u_hash, parents) => {
+                RunwayNotificationIn::Response(Response::Parents(responder, u_hash, parents))
             }
             UnitMessage::RequestNewest(node_id, salt) => {
                 RunwayNotificationIn::Request(Request::NewestUnit(salt), node_id)
@@ -123,106 +261,333 @@ type CollectionResponse<H, D, MK> = UncheckedSigned<
     <MK as Keychain>::Signature,
 >;
 
-struct Runway<H, D, FH, MK>
+struct Runway<H, D, MK>
 where
     H: Hasher,
     D: Data,
-    FH: FinalizationHandler<D>,
     MK: MultiKeychain,
 {
-    missing_coords: HashSet<UnitCoord>,
-    missing_parents: HashSet<H::Hash>,
+    missing_coords: MissingCoords,
+    // Coords given up on, with the time they were abandoned, so that late responses to their
+    // requests are still accepted for another `missing_coord_lifetime`.
+    abandoned_coords: MissingCoords,
+    missing_parents: MissingParents<H>,
+    // Requests sent to the network and not resolved yet, with the time they were first sent.
+    // Which nodes get asked, and how often, is up to the member.
+    pending_requests: PendingRequests<H>,
     store: UnitStore<H, D, MK>,
     keychain: MK,
-    validator: Validator<MK>,
+    validator: Validator<MK, D>,
     alerts_for_alerter: Sender<Alert<H, D, MK::Signature>>,
     notifications_from_alerter: Receiver<ForkingNotification<H, D, MK::Signature>>,
-    unit_messages_from_network: Receiver<RunwayNotificationIn<H, D, MK::Signature>>,
-    unit_messages_for_network: Sender<RunwayNotificationOut<H, D, MK::Signature>>,
+    unit_messages_from_network: TrackedReceiver<RunwayNotificationIn<H, D, MK::Signature>>,
+    unit_messages_for_network: TrackedSender<RunwayNotificationOut<H, D, MK::Signature>>,
     responses_for_collection: Sender<CollectionResponse<H, D, MK>>,
-    resolved_requests: Sender<Request<H>>,
-    tx_consensus: Sender<NotificationIn<H>>,
+    resolved_requests: Sender<RequestResolved<H>>,
+    tx_consensus: TrackedSender<NotificationIn<H>>,
     rx_consensus: Receiver<NotificationOut<H>>,
-    ordered_batch_rx: Receiver<Vec<H::Hash>>,
-    finalization_handler: FH,
+    ordered_batch_rx: Receiver<OrderedBatch<H>>,
+    consensus_restarts: Option<ConsensusRestarts<H>>,
+    consensus_restart_attempts: usize,
+    catch_up_burst: Option<Round>,
+    consensus_down: bool,
+    ordered_before_restart: HashSet<H::Hash>,
+    newest_created_round: Option<Round>,
+    finalized_items_for_handler: Sender<Vec<FinalizedItem<H, D>>>,
     backup_units_for_saver: Sender<UncheckedSignedUnit<H, D, MK::Signature>>,
     backup_units_from_saver: Receiver<UncheckedSignedUnit<H, D, MK::Signature>>,
     preunits_for_packer: Sender<PreUnit<H>>,
     signed_units_from_packer: Receiver<SignedUnit<H, D, MK>>,
+    metrics: Arc<dyn Metrics>,
+    counters: RunwayCounters,
+    events_for_observer: Option<Sender<RunwayEvent>>,
+    status_for_observer: Option<Sender<StatusSnapshot>>,
+    validation_failures_for_observer: Option<Sender<ValidationFailure>>,
+    fork_detection_handler: Option<ForkDetectionCallback<H, D, MK::Signature>>,
+    control_from_application: Option<Receiver<RunwayControl>>,
+    unit_intake_paused: bool,
+    alert_commitment: AlertCommitment,
+    parent_verification: ParentVerification,
+    alerter_failure_policy: AlerterFailurePolicy,
+    alerter_down: bool,
+    confirmed_units: RecentHashes<H>,
+    highest_observed_round: Option<Round>,
+    finalized_round: Option<Round>,
+    ordered_units: Vec<(UnitCoord, H::Hash)>,
+    finalized_hashes: RecentHashes<H>,
+    unit_propagation: Option<UnitPropagation>,
+    wrong_control_hash_limit: usize,
+    duplicate_parents_response_limit: usize,
+    duplicate_parents_responses: NodeMap<usize>,
+    control_hash_failures: HashMap<H::Hash, usize>,
+    max_units_per_forker_round: usize,
+    alerted_units_per_coord: HashMap<UnitCoord, usize>,
+    missing_coord_lifetime: Duration,
+    response_rate_limit: Option<ResponseRateLimit>,
+    response_window: Option<(Instant, usize)>,
+    status_report_interval: Option<Duration>,
+    abandoned_units: HashSet<H::Hash>,
+    validation_retry_interval: Option<Duration>,
+    // Units which failed validation only transiently, waiting to be validated again.
+    deferred_units: HashMap<H::Hash, UncheckedSignedUnit<H, D, MK::Signature>>,
+    newest_backed_up_own_unit: Option<Unit<H>>,
+    // Our units that were created, but are not durably backed up yet, so nothing can use them.
+    own_units_awaiting_backup: HashMap<H::Hash, SignedUnit<H, D, MK>>,
+    // Our units backed up right after creation, which need not be saved again once in the DAG.
+    own_units_backed_up: HashSet<H::Hash>,
+    creator_snapshots: Option<CreatorSnapshots>,
+    clock: Arc<dyn Clock>,
+    creation_halted: bool,
+    session_complete: bool,
     exiting: bool,
 }
 
-struct RunwayStatus<'a, H: Hasher> {
-    status: UnitStoreStatus<'a>,
-    missing_coords: &'a HashSet<UnitCoord>,
-    missing_parents: &'a HashSet<H::Hash>,
+/// The ends of the channels connecting the runway with a running consensus.
+struct ConsensusIO<H: Hasher> {
+    tx_consensus: TrackedSender<NotificationIn<H>>,
+    rx_consensus: Receiver<NotificationOut<H>>,
+    ordered_batch_rx: Receiver<OrderedBatch<H>>,
+    starting_round: oneshot::Sender<Option<Round>>,
 }
 
-impl<'a, H: Hasher> RunwayStatus<'a, H> {
-    fn new(
-        status: UnitStoreStatus<'a>,
-        missing_coords: &'a HashSet<UnitCoord>,
-        missing_parents: &'a HashSet<H::Hash>,
-    ) -> Self {
-        Self {
-            status,
-            missing_coords,
-            missing_parents,
+/// How the runway asks for a stopped consensus to be restarted, and receives the connections to
+/// the new one.
+struct ConsensusRestarts<H: Hasher> {
+    requests: Sender<()>,
+    restarted: Receiver<ConsensusIO<H>>,
+}
+
+/// An estimate of how much the node still needs to catch up with the rest of the committee.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CatchUpEstimate {
+    /// The highest round of a unit created by another node that we have seen.
+    pub highest_observed_round: Option<Round>,
+    /// The highest round of a unit finalized by this node.
+    pub finalized_round: Option<Round>,
+}
+
+impl CatchUpEstimate {
+    /// The number of rounds that still have to be finalized to reach the highest observed round.
+    pub fn remaining_rounds(&self) -> Round {
+        match (self.highest_observed_round, self.finalized_round) {
+            (None, _) => 0,
+            (Some(observed), None) => observed + 1,
+            (Some(observed), Some(finalized)) => observed.saturating_sub(finalized),
+        }
+    }
+}
+
+/// The peers known to have requested our recently broadcast units, which shows they received
+/// them. Only the given number of our newest units is tracked.
+struct UnitPropagation {
+    capacity: usize,
+    n_members: NodeCount,
+    requested_by: BTreeMap<Round, NodeSubset>,
+}
+
+impl UnitPropagation {
+    fn new(capacity: usize, n_members: NodeCount) -> Self {
+        UnitPropagation {
+            capacity,
+            n_members,
+            requested_by: BTreeMap::new(),
+        }
+    }
+
+    fn on_broadcast(&mut self, round: Round) {
+        self.requested_by
+            .entry(round)
+            .or_insert_with(|| NodeSubset::with_size(self.n_members));
+        while self.requested_by.len() > self.capacity {
+            self.requested_by.pop_first();
+        }
+    }
+
+    fn on_requested_by(&mut self, round: Round, node_id: NodeIndex) {
+        if let Some(requested_by) = self.requested_by.get_mut(&round) {
+            requested_by.insert(node_id);
+        }
+    }
+}
+
+/// Creator snapshots held back until our unit of their round is backed up, as restoring a
+/// snapshot taken after a unit the backup lacks would stall creation.
+struct CreatorSnapshots {
+    from_creator: Receiver<RoundSnapshot>,
+    for_application: Sender<Vec<u8>>,
+    pending: BTreeMap<Round, Vec<u8>>,
+}
+
+impl CreatorSnapshots {
+    fn new(from_creator: Receiver<RoundSnapshot>, for_application: Sender<Vec<u8>>) -> Self {
+        CreatorSnapshots {
+            from_creator,
+            for_application,
+            pending: BTreeMap::new(),
+        }
+    }
+
+    /// The newest snapshot taken no later than after our unit of `backed_up_round`, forgetting
+    /// all the older ones.
+    fn release(&mut self, backed_up_round: Round) -> Option<Vec<u8>> {
+        let round = *self.pending.range(..=backed_up_round).next_back()?.0;
+        let snapshot = self.pending.remove(&round);
+        self.pending
+            .retain(|pending_round, _| *pending_round > round);
+        snapshot
+    }
+}
+
+/// A bounded set of recently inserted hashes, forgetting the oldest ones first. With the capacity
+/// of `usize::MAX` it remembers all of them.
+struct RecentHashes<H: Hasher> {
+    capacity: usize,
+    hashes: HashSet<H::Hash>,
+    order: VecDeque<H::Hash>,
+}
+
+impl<H: Hasher> RecentHashes<H> {
+    fn new(capacity: usize) -> Self {
+        RecentHashes {
+            capacity,
+            hashes: HashSet::new(),
+            order: VecDeque::new(),
         }
     }
+
+    fn insert(&mut self, hash: H::Hash) {
+        if self.capacity == 0 || !self.hashes.insert(hash) {
+            return;
+        }
+        self.order.push_back(hash);
+        if self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.hashes.remove(&oldest);
+            }
+        }
+    }
+
+    fn contains(&self, hash: &H::Hash) -> bool {
+        self.hashes.contains(hash)
+    }
+}
+
+/// The state of the runway at the time of a status report, logged through its `Display` and
+/// passed on to the observer as a [`StatusSnapshot`].
+#[derive(Clone, Debug)]
+pub(crate) struct RunwayStatusSnapshot<H: Hasher> {
+    /// The contents of the unit store.
+    pub store: UnitStoreStatus,
+    /// The coords of the units we requested and still wait for, sorted.
+    pub missing_coords: Vec<UnitCoord>,
+    /// The hashes of the units whose parents we requested and still wait for, sorted.
+    pub missing_parents: Vec<H::Hash>,
+    /// How far behind the rest of the committee the node is.
+    pub catch_up: CatchUpEstimate,
+}
+
+/// The parents a unit claims in its control hash, compared with the units of the previous round
+/// we hold locally.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ParentsDiff {
+    claimed: Vec<NodeIndex>,
+    available: Vec<NodeIndex>,
+}
+
+impl fmt::Display for ParentsDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let missing: Vec<_> = self
+            .claimed
+            .iter()
+            .filter(|creator| !self.available.contains(creator))
+            .collect();
+        write!(
+            f,
+            "claimed parents {:?}, available parents {:?}, missing {:?}",
+            self.claimed, self.available, missing
+        )
+    }
 }
 
-impl<'a, H: Hasher> fmt::Display for RunwayStatus<'a, H> {
+impl<H: Hasher> fmt::Display for RunwayStatusSnapshot<H> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "Runway status report: ")?;
-        write!(f, "{}", self.status)?;
+        write!(f, "{}", self.store)?;
         if !self.missing_coords.is_empty() {
-            let mut v_coords: Vec<(usize, Round)> = self
+            let v_coords: Vec<(usize, Round)> = self
                 .missing_coords
                 .iter()
                 .map(|uc| (uc.creator().into(), uc.round()))
                 .collect();
-            v_coords.sort();
             write!(f, "; missing coords - {:?}", v_coords)?;
         }
         if !self.missing_parents.is_empty() {
             write!(f, "; missing parents - {:?}", self.missing_parents)?;
         }
+        let rounds_to_catch_up = self.catch_up.remaining_rounds();
+        if rounds_to_catch_up > 0 {
+            write!(f, "; rounds to catch up - {}", rounds_to_catch_up)?;
+        }
         write!(f, ".")?;
         Ok(())
     }
 }
 
-struct RunwayConfig<H: Hasher, D: Data, FH: FinalizationHandler<D>, MK: MultiKeychain> {
+struct RunwayConfig<H: Hasher, D: Data, MK: MultiKeychain> {
     max_round: Round,
-    finalization_handler: FH,
+    finalized_items_for_handler: Sender<Vec<FinalizedItem<H, D>>>,
     backup_units_for_saver: Sender<UncheckedSignedUnit<H, D, MK::Signature>>,
     backup_units_from_saver: Receiver<UncheckedSignedUnit<H, D, MK::Signature>>,
     alerts_for_alerter: Sender<Alert<H, D, MK::Signature>>,
     notifications_from_alerter: Receiver<ForkingNotification<H, D, MK::Signature>>,
-    tx_consensus: Sender<NotificationIn<H>>,
+    tx_consensus: TrackedSender<NotificationIn<H>>,
     rx_consensus: Receiver<NotificationOut<H>>,
-    unit_messages_from_network: Receiver<RunwayNotificationIn<H, D, MK::Signature>>,
-    unit_messages_for_network: Sender<RunwayNotificationOut<H, D, MK::Signature>>,
+    unit_messages_from_network: TrackedReceiver<RunwayNotificationIn<H, D, MK::Signature>>,
+    unit_messages_for_network: TrackedSender<RunwayNotificationOut<H, D, MK::Signature>>,
     responses_for_collection: Sender<CollectionResponse<H, D, MK>>,
-    ordered_batch_rx: Receiver<Vec<H::Hash>>,
-    resolved_requests: Sender<Request<H>>,
+    ordered_batch_rx: Receiver<OrderedBatch<H>>,
+    consensus_restarts: Option<ConsensusRestarts<H>>,
+    consensus_restart_attempts: usize,
+    catch_up_burst: Option<Round>,
+    resolved_requests: Sender<RequestResolved<H>>,
     preunits_for_packer: Sender<PreUnit<H>>,
     signed_units_from_packer: Receiver<SignedUnit<H, D, MK>>,
+    metrics: Arc<dyn Metrics>,
+    counters: RunwayCounters,
+    events_for_observer: Option<Sender<RunwayEvent>>,
+    status_for_observer: Option<Sender<StatusSnapshot>>,
+    validation_failures_for_observer: Option<Sender<ValidationFailure>>,
+    fork_detection_handler: Option<ForkDetectionCallback<H, D, MK::Signature>>,
+    control_from_application: Option<Receiver<RunwayControl>>,
+    alert_commitment: AlertCommitment,
+    parent_verification: ParentVerification,
+    alerter_failure_policy: AlerterFailurePolicy,
+    finalized_hashes_retention: usize,
+    confirmed_hashes_retention: usize,
+    unit_propagation_tracking: Option<usize>,
+    wrong_control_hash_limit: usize,
+    duplicate_parents_response_limit: usize,
+    max_units_per_forker_round: usize,
+    missing_coord_lifetime: Duration,
+    response_rate_limit: Option<ResponseRateLimit>,
+    status_report_interval: Option<Duration>,
+    validation_retry_interval: Option<Duration>,
+    request_hashing: RequestHashing,
+    creator_snapshots: Option<CreatorSnapshots>,
+    clock: Arc<dyn Clock>,
 }
 
-impl<H, D, FH, MK> Runway<H, D, FH, MK>
+impl<H, D, MK> Runway<H, D, MK>
 where
     H: Hasher,
     D: Data,
-    FH: FinalizationHandler<D>,
     MK: MultiKeychain,
 {
-    fn new(config: RunwayConfig<H, D, FH, MK>, keychain: MK, validator: Validator<MK>) -> Self {
+    fn new(config: RunwayConfig<H, D, MK>, keychain: MK, validator: Validator<MK, D>) -> Self {
         let n_members = keychain.node_count();
         let RunwayConfig {
             max_round,
-            finalization_handler,
+            finalized_items_for_handler,
             backup_units_for_saver,
             backup_units_from_saver,
             alerts_for_alerter,
@@ -233,9 +598,35 @@ where
             unit_messages_for_network,
             responses_for_collection,
             ordered_batch_rx,
+            consensus_restarts,
+            consensus_restart_attempts,
+            catch_up_burst,
             resolved_requests,
             preunits_for_packer,
             signed_units_from_packer,
+            metrics,
+            counters,
+            events_for_observer,
+            status_for_observer,
+            validation_failures_for_observer,
+            fork_detection_handler,
+            control_from_application,
+            alert_commitment,
+            parent_verification,
+            alerter_failure_policy,
+            finalized_hashes_retention,
+            confirmed_hashes_retention,
+            unit_propagation_tracking,
+            wrong_control_hash_limit,
+            duplicate_parents_response_limit,
+            max_units_per_forker_round,
+            missing_coord_lifetime,
+            response_rate_limit,
+            status_report_interval,
+            validation_retry_interval,
+            request_hashing,
+            creator_snapshots,
+            clock,
         } = config;
         let store = UnitStore::new(n_members, max_round);
 
@@ -243,8 +634,10 @@ where
             store,
             keychain,
             validator,
-            missing_coords: HashSet::new(),
-            missing_parents: HashSet::new(),
+            missing_coords: HashMap::with_hasher(request_hashing.clone()),
+            abandoned_coords: HashMap::with_hasher(request_hashing.clone()),
+            missing_parents: HashSet::with_hasher(request_hashing.clone()),
+            pending_requests: HashMap::with_hasher(request_hashing),
             resolved_requests,
             alerts_for_alerter,
             notifications_from_alerter,
@@ -253,12 +646,57 @@ where
             tx_consensus,
             rx_consensus,
             ordered_batch_rx,
-            finalization_handler,
+            consensus_restarts,
+            consensus_restart_attempts,
+            catch_up_burst,
+            consensus_down: false,
+            ordered_before_restart: HashSet::new(),
+            newest_created_round: None,
+            finalized_items_for_handler,
             backup_units_for_saver,
             backup_units_from_saver,
             responses_for_collection,
             preunits_for_packer,
             signed_units_from_packer,
+            metrics,
+            counters,
+            events_for_observer,
+            status_for_observer,
+            validation_failures_for_observer,
+            fork_detection_handler,
+            control_from_application,
+            unit_intake_paused: false,
+            alert_commitment,
+            parent_verification,
+            alerter_failure_policy,
+            alerter_down: false,
+            confirmed_units: RecentHashes::new(confirmed_hashes_retention),
+            highest_observed_round: None,
+            finalized_round: None,
+            ordered_units: Vec::new(),
+            finalized_hashes: RecentHashes::new(finalized_hashes_retention),
+            unit_propagation: unit_propagation_tracking
+                .map(|capacity| UnitPropagation::new(capacity, n_members)),
+            wrong_control_hash_limit,
+            duplicate_parents_response_limit,
+            duplicate_parents_responses: NodeMap::with_size(n_members),
+            control_hash_failures: HashMap::new(),
+            max_units_per_forker_round,
+            alerted_units_per_coord: HashMap::new(),
+            missing_coord_lifetime,
+            response_rate_limit,
+            response_window: None,
+            status_report_interval,
+            abandoned_units: HashSet::new(),
+            validation_retry_interval,
+            deferred_units: HashMap::new(),
+            newest_backed_up_own_unit: None,
+            own_units_awaiting_backup: HashMap::new(),
+            own_units_backed_up: HashSet::new(),
+            creator_snapshots,
+            clock,
+            creation_halted: false,
+            session_complete: false,
             exiting: false,
         }
     }
@@ -271,11 +709,21 @@ where
         self.keychain.node_count()
     }
 
+    /// The session the runway operates in.
+    fn session_id(&self) -> SessionId {
+        self.validator.session_id()
+    }
+
+    /// The highest round of units the runway accepts.
+    fn max_round(&self) -> Round {
+        self.validator.max_round()
+    }
+
     fn on_unit_message(&mut self, message: RunwayNotificationIn<H, D, MK::Signature>) {
         match message {
             RunwayNotificationIn::NewUnit(u) => {
                 trace!(target: "AlephBFT-runway", "{:?} New unit received {:?}.", self.index(), &u);
-                self.on_unit_received(u, false)
+                self.on_unit_received(u)
             }
 
             RunwayNotificationIn::Request(request, node_id) => match request {
@@ -296,14 +744,15 @@ where
             RunwayNotificationIn::Response(res) => match res {
                 Response::Coord(u) => {
                     trace!(target: "AlephBFT-runway", "{:?} Fetch response received {:?}.", self.index(), &u);
-                    self.on_unit_received(u, false)
+                    self.on_coord_response(u)
                 }
-                Response::Parents(u_hash, parents) => {
+                Response::Parents(responder, u_hash, parents) => {
                     trace!(target: "AlephBFT-runway", "{:?} Response parents received {:?}.", self.index(), u_hash);
-                    self.on_parents_response(u_hash, parents)
+                    self.on_parents_response(responder, u_hash, parents)
                 }
                 Response::NewestUnit(response) => {
                     trace!(target: "AlephBFT-runway", "{:?} Response newest unit received from {:?}.", self.index(), response.index());
+                    self.request_catch_up_coords(&response);
                     let res = self.responses_for_collection.unbounded_send(response);
                     if res.is_err() {
                         debug!(target: "AlephBFT-runway", "{:?} Could not send response to collection ({:?}).", self.index(), res)
@@ -313,27 +762,159 @@ where
         }
     }
 
-    fn on_unit_received(&mut self, uu: UncheckedSignedUnit<H, D, MK::Signature>, alert: bool) {
+    /// Requests the units of the rounds right above our newest one, up to the unit in the
+    /// response, instead of waiting for consensus to find them missing one by one.
+    fn request_catch_up_coords(&mut self, response: &CollectionResponse<H, D, MK>) {
+        let burst = match self.catch_up_burst {
+            Some(burst) if burst > 0 => burst,
+            _ => return,
+        };
+        let unit = match response.as_signable().unit() {
+            Some(unit) => unit.clone(),
+            None => return,
+        };
+        let round = match self.validator.validate_unit(unit) {
+            Ok(su) => su.as_signable().round(),
+            Err(e) => {
+                warn!(target: "AlephBFT-runway", "{:?} Received an invalid unit in a newest unit response from {:?}: {}", self.index(), response.index(), e);
+                return;
+            }
+        };
+        let first_round = self.store.height().map_or(0, |height| height + 1);
+        if round < first_round {
+            return;
+        }
+        let last_round = round.min(first_round.saturating_add(burst - 1));
+        debug!(target: "AlephBFT-runway", "{:?} Requesting units of rounds {} to {} to catch up with round {}.", self.index(), first_round, last_round, round);
+        let n_members = self.node_count().0;
+        let coords = (first_round..=last_round)
+            .flat_map(|round| (0..n_members).map(move |id| UnitCoord::new(round, NodeIndex(id))))
+            .collect();
+        self.on_missing_coords(coords);
+    }
+
+    /// Only accepts units at coords we are still missing or recently gave up on. Late answers to
+    /// requests resolved in the meantime are dropped quietly, units at coords we never asked for
+    /// are reported.
+    fn on_coord_response(&mut self, uu: UncheckedSignedUnit<H, D, MK::Signature>) {
+        let coord = uu.as_signable().coord();
+        if self.missing_coords.contains_key(&coord) {
+            self.on_unit_received(uu);
+            return;
+        }
+        if self.abandoned_coords.remove(&coord).is_some() {
+            debug!(target: "AlephBFT-runway", "{:?} Received abandoned unit {:?} after all.", self.index(), coord);
+            self.on_unit_received(uu);
+            return;
+        }
+        if self.store.unit_by_coord(coord).is_none() {
+            warn!(target: "AlephBFT-runway", "{:?} Received unit {:?} in response to a request, but its coord {:?} was never requested.", self.index(), uu.as_signable().hash(), coord);
+            self.metrics.report_unrequested_unit();
+        }
+    }
+
+    fn on_unit_received(&mut self, uu: UncheckedSignedUnit<H, D, MK::Signature>) {
+        self.metrics.report_unit_received();
+        self.counters.count(|counters| counters.units_received += 1);
+        self.validate_and_add_unit(uu);
+    }
+
+    /// Validates the unit and adds it to the store, without counting it as received again.
+    fn validate_and_add_unit(&mut self, uu: UncheckedSignedUnit<H, D, MK::Signature>) {
+        let u_hash = uu.as_signable().hash();
+        if self.is_finalized(&u_hash) {
+            trace!(target: "AlephBFT-runway", "{:?} Dropping already finalized unit {:?}.", self.index(), u_hash);
+            return;
+        }
+        // Only kept if it might be needed for another try at validation.
+        let retained = self.validation_retry_interval.map(|_| uu.clone());
         match self.validator.validate_unit(uu) {
             Ok(su) => {
                 self.resolve_missing_coord(&su.as_signable().coord());
-                if alert {
-                    // Units from alerts explicitly come from forkers, and we want them anyway.
-                    self.store.add_unit(su, true);
-                } else {
-                    self.add_unit_to_store_unless_fork(su);
+                self.add_unit_to_store_unless_fork(su);
+            }
+            Err(ValidationError::UnknownCreator(fu)) => {
+                warn!(target: "AlephBFT-runway", "{:?} Received unit from creator {:?} outside of the committee of {:?}, the sender is misconfigured or malicious.", self.index(), fu.creator(), self.node_count());
+                self.metrics.report_unknown_creator();
+                self.report_validation_failure(None, ValidationErrorKind::UnknownCreator);
+            }
+            Err(e) if e.is_transient() => match retained {
+                Some(uu) => self.defer_unit(u_hash, uu),
+                None => {
+                    warn!(target: "AlephBFT-member", "Received unit failing validation: {}", e);
+                    self.report_validation_failure(None, e.kind());
                 }
+            },
+            Err(e) => {
+                warn!(target: "AlephBFT-member", "Received unit failing validation: {}", e);
+                self.report_validation_failure(None, e.kind());
+            }
+        }
+    }
+
+    fn report_validation_failure(&mut self, sender: Option<NodeIndex>, error: ValidationErrorKind) {
+        if let Some(validation_failures) = &self.validation_failures_for_observer {
+            let failure = ValidationFailure { sender, error };
+            if validation_failures.unbounded_send(failure).is_err() {
+                debug!(target: "AlephBFT-runway", "{:?} Validation failure observer is gone, no longer reporting validation failures.", self.index());
+                self.validation_failures_for_observer = None;
             }
-            Err(e) => warn!(target: "AlephBFT-member", "Received unit failing validation: {}", e),
+        }
+    }
+
+    /// Keeps a unit which failed validation transiently, to validate it again later.
+    fn defer_unit(&mut self, u_hash: H::Hash, uu: UncheckedSignedUnit<H, D, MK::Signature>) {
+        if self.deferred_units.len() >= MAX_DEFERRED_UNITS
+            && !self.deferred_units.contains_key(&u_hash)
+        {
+            warn!(target: "AlephBFT-runway", "{:?} Too many units waiting for another validation, dropping {:?}.", self.index(), u_hash);
+            return;
+        }
+        debug!(target: "AlephBFT-runway", "{:?} Unit {:?} failed validation transiently, will try again.", self.index(), u_hash);
+        self.deferred_units.insert(u_hash, uu);
+    }
+
+    /// Validates again all the units which previously failed validation transiently.
+    fn retry_deferred_units(&mut self) {
+        if self.deferred_units.is_empty() {
+            return;
+        }
+        trace!(target: "AlephBFT-runway", "{:?} Validating {} deferred units again.", self.index(), self.deferred_units.len());
+        for (_, uu) in std::mem::take(&mut self.deferred_units) {
+            self.validate_and_add_unit(uu);
         }
     }
 
     fn resolve_missing_coord(&mut self, coord: &UnitCoord) {
-        if self.missing_coords.remove(coord) {
+        if self.missing_coords.remove(coord).is_some() {
             self.send_resolved_request_notification(Request::Coord(*coord));
         }
     }
 
+    /// Stops requesting units missing for longer than `missing_coord_lifetime`, as they might not
+    /// exist at all. Should such a unit arrive within another `missing_coord_lifetime`, it is
+    /// still accepted.
+    fn abandon_expired_missing_coords(&mut self) {
+        let now = self.clock.now();
+        let lifetime = self.missing_coord_lifetime;
+        self.abandoned_coords
+            .retain(|_, since| now.saturating_duration_since(*since) < lifetime);
+        let expired: Vec<_> = self
+            .missing_coords
+            .iter()
+            .filter(|(_, since)| {
+                now.saturating_duration_since(**since) >= self.missing_coord_lifetime
+            })
+            .map(|(coord, _)| *coord)
+            .collect();
+        for coord in expired {
+            debug!(target: "AlephBFT-runway", "{:?} Giving up on missing unit {:?}, nobody provided it for {:?}.", self.index(), coord, self.missing_coord_lifetime);
+            self.missing_coords.remove(&coord);
+            self.abandoned_coords.insert(coord, now);
+            self.send_abandoned_request_notification(Request::Coord(coord));
+        }
+    }
+
     fn add_unit_to_store_unless_fork(&mut self, su: SignedUnit<H, D, MK>) {
         let full_unit = su.as_signable();
         trace!(target: "AlephBFT-member", "{:?} Adding member unit to store {:?}", self.index(), full_unit);
@@ -354,16 +935,118 @@ where
             return;
         }
 
-        self.store.add_unit(su, false);
+        self.add_unit_to_store(su, false);
+    }
+
+    fn add_unit_to_store(&mut self, su: SignedUnit<H, D, MK>, alert: bool) {
+        let round = su.as_signable().round();
+        let creator = su.as_signable().creator();
+        if creator != self.index() {
+            self.highest_observed_round = self.highest_observed_round.max(Some(round));
+        }
+        self.store.add_unit(su, alert);
+        if let Some(height) = self.store.height() {
+            self.metrics.report_dag_height(height);
+        }
+        self.metrics
+            .report_dag_width(round, self.store.width(round));
+    }
+
+    /// The peers known to have received each of our tracked units, oldest first: the ones that
+    /// requested it and the creators of the units in the DAG built directly on top of it.
+    fn own_units_reached(&self) -> Vec<(Round, Vec<NodeIndex>)> {
+        let unit_propagation = match &self.unit_propagation {
+            Some(unit_propagation) => unit_propagation,
+            None => return Vec::new(),
+        };
+        unit_propagation
+            .requested_by
+            .iter()
+            .map(|(round, requested_by)| {
+                let mut reached = requested_by.clone();
+                let children = self
+                    .store
+                    .unit_by_coord(UnitCoord::new(*round, self.index()))
+                    .map(|su| self.store.children_of(&su.as_signable().hash()))
+                    .unwrap_or_default();
+                for child in children {
+                    if let Some(child) = self.store.unit_by_hash(&child) {
+                        reached.insert(child.as_signable().creator());
+                    }
+                }
+                (*round, reached.elements().collect())
+            })
+            .collect()
     }
 
     fn on_new_forker_detected(&mut self, forker: NodeIndex, proof: ForkProof<H, D, MK::Signature>) {
+        if forker == self.index() {
+            self.halt_creation();
+        }
+        self.emit_event(RunwayEvent::ForkEvidence(ForkEvidence::new(&proof)));
+        self.metrics.report_forker_detected();
+        self.counters
+            .count(|counters| counters.forkers_detected += 1);
+        self.notify_fork_detection_handler(forker, &proof);
         let alerted_units = self.store.mark_forker(forker);
+        self.alert_about_forker(proof, alerted_units);
+    }
+
+    /// Marks all the forkers at once, forming an alert about each of them.
+    fn on_new_forkers_detected(&mut self, proofs: Vec<ForkProof<H, D, MK::Signature>>) {
+        let forkers: Vec<_> = proofs.iter().map(|proof| proof.0.index()).collect();
+        if forkers.contains(&self.index()) {
+            self.halt_creation();
+        }
+        for proof in &proofs {
+            self.emit_event(RunwayEvent::ForkEvidence(ForkEvidence::new(proof)));
+            self.metrics.report_forker_detected();
+            self.counters
+                .count(|counters| counters.forkers_detected += 1);
+            self.notify_fork_detection_handler(proof.0.index(), proof);
+        }
+        let alerted_units = self.store.mark_forkers(forkers);
+        for (proof, (_, alerted_units)) in proofs.into_iter().zip(alerted_units) {
+            self.alert_about_forker(proof, alerted_units);
+        }
+    }
+
+    fn notify_fork_detection_handler(
+        &mut self,
+        forker: NodeIndex,
+        proof: &ForkProof<H, D, MK::Signature>,
+    ) {
+        if let Some(fork_detection_handler) = &mut self.fork_detection_handler {
+            fork_detection_handler(forker, proof.clone());
+        }
+    }
+
+    fn alert_about_forker(
+        &mut self,
+        proof: ForkProof<H, D, MK::Signature>,
+        mut alerted_units: Vec<SignedUnit<H, D, MK>>,
+    ) {
+        if self.alert_commitment == AlertCommitment::SkipConfirmed {
+            alerted_units.retain(|su| !self.confirmed_units.contains(&su.as_signable().hash()));
+        }
         let alert = self.form_alert(proof, alerted_units);
         if self.alerts_for_alerter.unbounded_send(alert).is_err() {
             warn!(target: "AlephBFT-runway", "{:?} Channel to alerter should be open", self.index());
-            self.exiting = true;
+            if !self.on_alerter_down() {
+                self.exiting = true;
+            }
+        }
+    }
+
+    /// A fork proof against ourselves means our key is used by someone else or something is
+    /// seriously broken, creating more units could only make it worse.
+    fn halt_creation(&mut self) {
+        if self.creation_halted {
+            return;
         }
+        error!(target: "AlephBFT-runway", "{:?} We have been proven to be a forker, halting unit creation. Is our key used by another node?", self.index());
+        self.creation_halted = true;
+        self.emit_event(RunwayEvent::OwnForkDetected);
     }
 
     fn form_alert(
@@ -378,11 +1061,39 @@ where
         )
     }
 
+    /// Whether we can still afford to respond to a request, counting the response if so.
+    fn response_allowed(&mut self) -> bool {
+        let limit = match self.response_rate_limit {
+            Some(limit) => limit,
+            None => return true,
+        };
+        let now = self.clock.now();
+        let (window_start, responses) = self.response_window.get_or_insert((now, 0));
+        if now.saturating_duration_since(*window_start) >= limit.interval {
+            *window_start = now;
+            *responses = 0;
+        }
+        if *responses >= limit.max_responses {
+            return false;
+        }
+        *responses += 1;
+        true
+    }
+
     fn on_request_coord(&mut self, node_id: NodeIndex, coord: UnitCoord) {
         debug!(target: "AlephBFT-runway", "{:?} Received fetch request for coord {:?} from {:?}.", self.index(), coord, node_id);
+        if coord.creator() == self.index() {
+            if let Some(unit_propagation) = &mut self.unit_propagation {
+                unit_propagation.on_requested_by(coord.round(), node_id);
+            }
+        }
         let maybe_su = (self.store.unit_by_coord(coord)).cloned();
 
         if let Some(su) = maybe_su {
+            if !self.response_allowed() {
+                debug!(target: "AlephBFT-runway", "{:?} Dropping fetch request for coord {:?} from {:?}, response rate limit reached.", self.index(), coord, node_id);
+                return;
+            }
             trace!(target: "AlephBFT-runway", "{:?} Answering fetch request for coord {:?} from {:?}.", self.index(), coord, node_id);
             self.send_message_for_network(RunwayNotificationOut::Response(
                 Response::Coord(su.into()),
@@ -413,8 +1124,12 @@ where
                     return;
                 }
             }
+            if !self.response_allowed() {
+                debug!(target: "AlephBFT-runway", "{:?} Dropping parents request for hash {:?} from {:?}, response rate limit reached.", self.index(), u_hash, node_id);
+                return;
+            }
             self.send_message_for_network(RunwayNotificationOut::Response(
-                Response::Parents(u_hash, full_units),
+                Response::Parents(self.index(), u_hash, full_units),
                 node_id,
             ));
         } else {
@@ -427,30 +1142,46 @@ where
         let response = NewestUnitResponse::new(requester, self.index(), unit, salt);
 
         let signed_response = Signed::sign(response, &self.keychain).into_unchecked();
+        let notification =
+            RunwayNotificationOut::Response(Response::NewestUnit(signed_response), requester);
 
-        if let Err(e) =
-            self.unit_messages_for_network
-                .unbounded_send(RunwayNotificationOut::Response(
-                    Response::NewestUnit(signed_response),
-                    requester,
-                ))
-        {
+        if self.unit_messages_for_network.is_full() {
+            debug!(target: "AlephBFT-runway", "{:?} Too many messages waiting for the network, dropping the newest unit response for {:?}.", self.index(), requester);
+            return;
+        }
+        self.count_message_for_network(&notification);
+        if let Err(e) = self.unit_messages_for_network.unbounded_send(notification) {
             error!(target: "AlephBFT-runway", "Unable to send response to network: {}", e);
         }
     }
 
     fn on_parents_response(
         &mut self,
+        responder: NodeIndex,
         u_hash: H::Hash,
         parents: Vec<UncheckedSignedUnit<H, D, MK::Signature>>,
     ) {
+        if self.abandoned_units.contains(&u_hash) {
+            trace!(target: "AlephBFT-runway", "{:?} We got parents response for an abandoned unit {:?}. Ignoring.", self.index(), u_hash);
+            return;
+        }
+        if parents.len() > self.node_count().0 {
+            warn!(target: "AlephBFT-runway", "{:?} Received parent response with {} parents, more than there are nodes, for unit {:?}.", self.index(), parents.len(), u_hash);
+            return;
+        }
         if self.store.get_parents(u_hash).is_some() {
-            trace!(target: "AlephBFT-runway", "{:?} We got parents response but already know the parents.", self.index());
+            trace!(target: "AlephBFT-runway", "{:?} We got parents response from {:?} but already know the parents.", self.index(), responder);
+            self.on_duplicate_parents_response(responder);
             return;
         }
         let (u_round, u_control_hash, parent_ids) = match self.store.unit_by_hash(&u_hash) {
             Some(su) => {
                 let full_unit = su.as_signable();
+                let n_parents = full_unit.as_pre_unit().n_parents();
+                if parents.len() != n_parents.0 {
+                    warn!(target: "AlephBFT-runway", "{:?} In received parent response expected {} parents got {} for unit {:?}.", self.index(), n_parents.0, parents.len(), u_hash);
+                    return;
+                }
                 let parent_ids: Vec<_> = full_unit.control_hash().parents().collect();
                 (
                     full_unit.round(),
@@ -464,32 +1195,94 @@ where
             }
         };
 
-        if parent_ids.len() != parents.len() {
-            warn!(target: "AlephBFT-runway", "{:?} In received parent response expected {} parents got {} for unit {:?}.", self.index(), parents.len(), parent_ids.len(), u_hash);
+        let p_hashes = match self.parent_verification {
+            ParentVerification::Eager => self.verify_parents_eagerly(
+                responder,
+                u_round,
+                u_control_hash,
+                &parent_ids,
+                parents,
+            ),
+            ParentVerification::Deferred => self.verify_parents_deferred(
+                responder,
+                u_round,
+                u_control_hash,
+                &parent_ids,
+                parents,
+            ),
+        };
+        let p_hashes = match p_hashes {
+            Some(p_hashes) => p_hashes,
+            None => {
+                self.on_control_hash_failure(u_hash);
+                return;
+            }
+        };
+        self.control_hash_failures.remove(&u_hash);
+        self.store.add_parents(u_hash, p_hashes.clone());
+        trace!(target: "AlephBFT-runway", "{:?} Succesful parents response for {:?}.", self.index(), u_hash);
+        self.send_consensus_notification(NotificationIn::UnitParents(u_hash, p_hashes));
+    }
+
+    /// Counts a parents response that brought nothing new, flags the responder once it sent as
+    /// many as the limit allows.
+    fn on_duplicate_parents_response(&mut self, responder: NodeIndex) {
+        if responder.0 >= self.node_count().0 {
             return;
         }
+        self.metrics.report_duplicate_parents_response(responder);
+        let duplicates = self
+            .duplicate_parents_responses
+            .get(responder)
+            .map_or(1, |duplicates| duplicates + 1);
+        self.duplicate_parents_responses
+            .insert(responder, duplicates);
+        if duplicates == self.duplicate_parents_response_limit {
+            warn!(target: "AlephBFT-runway", "{:?} Node {:?} sent {} parents responses for units whose parents we already knew, flagging it.", self.index(), responder, duplicates);
+            self.emit_event(RunwayEvent::ExcessiveDuplicateResponses(responder));
+        }
+    }
+
+    /// Parents in a response are matched with the control hash by their creators, so a response
+    /// listing them in any order is fine, as long as every expected creator appears exactly once.
+    fn is_expected_parent(
+        creator: NodeIndex,
+        parent_ids: &[NodeIndex],
+        p_hashes_node_map: &NodeMap<H::Hash>,
+    ) -> bool {
+        parent_ids.contains(&creator) && p_hashes_node_map.get(creator).is_none()
+    }
 
+    fn verify_parents_eagerly(
+        &mut self,
+        responder: NodeIndex,
+        u_round: Round,
+        u_control_hash: H::Hash,
+        parent_ids: &[NodeIndex],
+        parents: Vec<UncheckedSignedUnit<H, D, MK::Signature>>,
+    ) -> Option<Vec<H::Hash>> {
         let mut p_hashes_node_map = NodeMap::with_size(self.node_count());
-        for (i, uu) in parents.into_iter().enumerate() {
+        for uu in parents {
             let su = match self.validator.validate_unit(uu) {
                 Ok(su) => su,
                 Err(e) => {
                     warn!(target: "AlephBFT-runway", "{:?} In received parent response received a unit that does not pass validation: {}", self.index(), e);
-                    return;
+                    if !e.is_transient() {
+                        self.report_validation_failure(Some(responder), e.kind());
+                    }
+                    return None;
                 }
             };
             let full_unit = su.as_signable();
-            if full_unit.round() + 1 != u_round {
+            if u_round.checked_sub(1) != Some(full_unit.round()) {
                 warn!(target: "AlephBFT-runway", "{:?} In received parent response received a unit with wrong round.", self.index());
-                return;
+                return None;
             }
-            if full_unit.creator() != parent_ids[i] {
+            if !Self::is_expected_parent(full_unit.creator(), parent_ids, &p_hashes_node_map) {
                 warn!(target: "AlephBFT-runway", "{:?} In received parent response received a unit with wrong creator.", self.index());
-                return;
+                return None;
             }
-            let p_hash = full_unit.hash();
-            let ix = full_unit.creator();
-            p_hashes_node_map.insert(ix, p_hash);
+            p_hashes_node_map.insert(full_unit.creator(), full_unit.hash());
             // There might be some optimization possible here to not validate twice, but overall
             // this piece of code should be executed extremely rarely.
             self.resolve_missing_coord(&su.as_signable().coord());
@@ -498,12 +1291,60 @@ where
 
         if ControlHash::<H>::combine_hashes(&p_hashes_node_map) != u_control_hash {
             warn!(target: "AlephBFT-runway", "{:?} In received parent response the control hash is incorrect {:?}.", self.index(), p_hashes_node_map);
-            return;
+            return None;
         }
-        let p_hashes: Vec<_> = p_hashes_node_map.into_values().collect();
-        self.store.add_parents(u_hash, p_hashes.clone());
-        trace!(target: "AlephBFT-runway", "{:?} Succesful parents response for {:?}.", self.index(), u_hash);
-        self.send_consensus_notification(NotificationIn::UnitParents(u_hash, p_hashes));
+        Some(p_hashes_node_map.into_values().collect())
+    }
+
+    fn verify_parents_deferred(
+        &mut self,
+        responder: NodeIndex,
+        u_round: Round,
+        u_control_hash: H::Hash,
+        parent_ids: &[NodeIndex],
+        parents: Vec<UncheckedSignedUnit<H, D, MK::Signature>>,
+    ) -> Option<Vec<H::Hash>> {
+        let mut p_hashes_node_map = NodeMap::with_size(self.node_count());
+        for uu in parents.iter() {
+            let full_unit = uu.as_signable();
+            if u_round.checked_sub(1) != Some(full_unit.round()) {
+                warn!(target: "AlephBFT-runway", "{:?} In received parent response received a unit with wrong round.", self.index());
+                return None;
+            }
+            if !Self::is_expected_parent(full_unit.creator(), parent_ids, &p_hashes_node_map) {
+                warn!(target: "AlephBFT-runway", "{:?} In received parent response received a unit with wrong creator.", self.index());
+                return None;
+            }
+            p_hashes_node_map.insert(full_unit.creator(), full_unit.hash());
+        }
+        if ControlHash::<H>::combine_hashes(&p_hashes_node_map) != u_control_hash {
+            warn!(target: "AlephBFT-runway", "{:?} In received parent response the control hash is incorrect {:?}.", self.index(), p_hashes_node_map);
+            return None;
+        }
+
+        // Only now that the response is known to be consistent are the parents we do not hold
+        // yet verified, as they are about to be used. The ones in the store are verified already.
+        let mut verified_parents = Vec::new();
+        for uu in parents {
+            if self.store.unit_by_hash(&uu.as_signable().hash()).is_some() {
+                continue;
+            }
+            match self.validator.validate_unit(uu) {
+                Ok(su) => verified_parents.push(su),
+                Err(e) => {
+                    warn!(target: "AlephBFT-runway", "{:?} In received parent response received a unit that does not pass validation: {}", self.index(), e);
+                    if !e.is_transient() {
+                        self.report_validation_failure(Some(responder), e.kind());
+                    }
+                    return None;
+                }
+            }
+        }
+        for su in verified_parents {
+            self.resolve_missing_coord(&su.as_signable().coord());
+            self.add_unit_to_store_unless_fork(su);
+        }
+        Some(p_hashes_node_map.into_values().collect())
     }
 
     fn resolve_missing_parents(&mut self, u_hash: &H::Hash) {
@@ -512,9 +1353,37 @@ where
         }
     }
 
+    /// Our new unit is only used once it is backed up, otherwise after a crash we could create
+    /// another unit of the same round. It gets added to the store when the saver is done with it.
     fn on_packed(&mut self, signed_unit: SignedUnit<H, D, MK>) {
         debug!(target: "AlephBFT-runway", "{:?} On create notification.", self.index());
-        self.store.add_unit(signed_unit, false);
+        let hash = signed_unit.as_signable().hash();
+        if self
+            .backup_units_for_saver
+            .unbounded_send(signed_unit.clone().into())
+            .is_err()
+        {
+            error!(target: "AlephBFT-runway", "{:?} A created unit couldn't be sent to backup: {:?}.", self.index(), hash);
+            self.exiting = true;
+            return;
+        }
+        self.own_units_awaiting_backup.insert(hash, signed_unit);
+    }
+
+    /// Reacts to the alerter stopping according to the configured policy, returns whether
+    /// the runway should keep going.
+    fn on_alerter_down(&mut self) -> bool {
+        match self.alerter_failure_policy {
+            AlerterFailurePolicy::Halt => false,
+            AlerterFailurePolicy::Degrade => {
+                if !self.alerter_down {
+                    self.alerter_down = true;
+                    error!(target: "AlephBFT-runway", "{:?} The alerter is down, continuing WITHOUT protection against forks.", self.index());
+                    self.metrics.report_alerter_down();
+                }
+                true
+            }
+        }
     }
 
     fn on_alert_notification(&mut self, notification: ForkingNotification<H, D, MK::Signature>) {
@@ -527,18 +1396,73 @@ where
                 }
             }
 
-            Units(units) => {
-                for uu in units {
-                    self.on_unit_received(uu, true);
+            Units(units) => self.on_alerted_units(units),
+            KnownForkers(proofs) => self.on_known_forkers(proofs),
+        }
+    }
+
+    fn on_known_forkers(&mut self, proofs: Vec<ForkProof<H, D, MK::Signature>>) {
+        let alerter_forkers: HashSet<_> = proofs.iter().map(|proof| proof.0.index()).collect();
+        let missed: Vec<_> = proofs
+            .into_iter()
+            .filter(|proof| !self.store.is_forker(proof.0.index()))
+            .collect();
+        if !missed.is_empty() {
+            warn!(target: "AlephBFT-runway", "{:?} Reconciliation found {} forkers known to the alerter but not to us.", self.index(), missed.len());
+            self.metrics.report_forker_discrepancy(missed.len());
+            self.on_new_forkers_detected(missed);
+        }
+        // Our own alerts might still be in flight to the alerter, so this is not necessarily
+        // a discrepancy.
+        for forker in (0..self.keychain.node_count().0).map(NodeIndex) {
+            if self.store.is_forker(forker) && !alerter_forkers.contains(&forker) {
+                debug!(target: "AlephBFT-runway", "{:?} Forker {:?} not yet known to the alerter.", self.index(), forker);
+            }
+        }
+    }
+
+    fn on_alerted_units(&mut self, units: Vec<UncheckedSignedUnit<H, D, MK::Signature>>) {
+        // Alerted units come from a forker, but an alert should still contain at most one variant
+        // per coord. Otherwise the alert itself equivocates and we only keep the first variant.
+        let mut alerted_hashes = HashMap::new();
+        for uu in units {
+            let su = match self.validator.validate_unit(uu) {
+                Ok(su) => su,
+                Err(e) => {
+                    warn!(target: "AlephBFT-member", "Received alerted unit failing validation: {}", e);
+                    continue;
+                }
+            };
+            let full_unit = su.as_signable();
+            let (coord, hash) = (full_unit.coord(), full_unit.hash());
+            if *alerted_hashes.entry(coord).or_insert(hash) != hash {
+                warn!(target: "AlephBFT-runway", "{:?} Alert contains conflicting units for coord {:?}, ignoring {:?}.", self.index(), coord, hash);
+                continue;
+            }
+            if !self.store.contains_hash(&hash) {
+                let accepted = self.alerted_units_per_coord.entry(coord).or_insert(0);
+                if *accepted >= self.max_units_per_forker_round {
+                    warn!(target: "AlephBFT-runway", "{:?} Too many alerted units for coord {:?}, dropping {:?}.", self.index(), coord, hash);
+                    continue;
                 }
+                *accepted += 1;
+            }
+            self.resolve_missing_coord(&coord);
+            if self.alert_commitment == AlertCommitment::SkipConfirmed {
+                self.confirmed_units.insert(hash);
             }
+            // Units from alerts explicitly come from forkers, and we want them anyway.
+            self.add_unit_to_store(su, true);
         }
     }
 
     fn on_consensus_notification(&mut self, notification: NotificationOut<H>) {
         match notification {
             NotificationOut::CreatedPreUnit(pu, _) => {
-                if self.preunits_for_packer.unbounded_send(pu).is_err() {
+                self.newest_created_round = self.newest_created_round.max(Some(pu.round()));
+                if self.creation_halted {
+                    debug!(target: "AlephBFT-runway", "{:?} Dropping created preunit, unit creation is halted.", self.index());
+                } else if self.preunits_for_packer.unbounded_send(pu).is_err() {
                     warn!(target: "AlephBFT-runway", "{:?} preunits_for_packer channel should be open", self.index());
                     self.exiting = true;
                 }
@@ -549,11 +1473,18 @@ where
             NotificationOut::WrongControlHash(h) => {
                 self.on_wrong_control_hash(h);
             }
+            NotificationOut::MissingOwnParent(round) => {
+                self.on_missing_own_parent(round);
+            }
             NotificationOut::AddedToDag(h, p_hashes) => {
                 self.store.add_parents(h, p_hashes);
                 self.resolve_missing_parents(&h);
                 if let Some(su) = self.store.unit_by_hash(&h).cloned() {
-                    if self
+                    self.emit_event(RunwayEvent::UnitAddedToDag(su.as_signable().coord()));
+                    self.check_session_complete();
+                    if self.own_units_backed_up.remove(&h) {
+                        self.on_unit_backup_saved(su.into());
+                    } else if self
                         .backup_units_for_saver
                         .unbounded_send(su.into())
                         .is_err()
@@ -568,19 +1499,91 @@ where
     }
 
     fn on_unit_backup_saved(&mut self, unit: UncheckedSignedUnit<H, D, MK::Signature>) {
+        self.remember_backed_up_unit(&unit);
+        self.release_creator_snapshot();
+        let hash = unit.as_signable().hash();
+        if let Some(signed_unit) = self.own_units_awaiting_backup.remove(&hash) {
+            trace!(target: "AlephBFT-runway", "{:?} Created unit {:?} backed up, adding it to the store.", self.index(), hash);
+            self.own_units_backed_up.insert(hash);
+            self.add_unit_to_store(signed_unit, false);
+            return;
+        }
         self.send_message_for_network(RunwayNotificationOut::NewAnyUnit(unit.clone()));
 
         if unit.as_signable().creator() == self.index() {
             trace!(target: "AlephBFT-runway", "{:?} Sending a unit {:?}.", self.index(), unit.as_signable().hash());
+            if let Some(unit_propagation) = &mut self.unit_propagation {
+                unit_propagation.on_broadcast(unit.as_signable().round());
+            }
             self.send_message_for_network(RunwayNotificationOut::NewSelfUnit(unit));
         }
     }
 
+    fn remember_backed_up_unit(&mut self, unit: &UncheckedSignedUnit<H, D, MK::Signature>) {
+        let full_unit = unit.as_signable();
+        if full_unit.creator() != self.index() {
+            return;
+        }
+        let newest_round = self
+            .newest_backed_up_own_unit
+            .as_ref()
+            .map(|unit| unit.round());
+        if newest_round < Some(full_unit.round()) {
+            self.newest_backed_up_own_unit = Some(full_unit.unit());
+        }
+    }
+
+    fn on_creator_snapshot(&mut self, round: Round, snapshot: Vec<u8>) {
+        if let Some(creator_snapshots) = &mut self.creator_snapshots {
+            creator_snapshots.pending.insert(round, snapshot);
+        }
+        self.release_creator_snapshot();
+    }
+
+    fn release_creator_snapshot(&mut self) {
+        let backed_up_round = match &self.newest_backed_up_own_unit {
+            Some(unit) => unit.round(),
+            None => return,
+        };
+        let creator_snapshots = match &mut self.creator_snapshots {
+            Some(creator_snapshots) => creator_snapshots,
+            None => return,
+        };
+        if let Some(snapshot) = creator_snapshots.release(backed_up_round) {
+            if creator_snapshots
+                .for_application
+                .unbounded_send(snapshot)
+                .is_err()
+            {
+                debug!(target: "AlephBFT-runway", "{:?} Creator snapshot receiver gone.", self.index());
+                self.creator_snapshots = None;
+            }
+        }
+    }
+
+    fn on_missing_own_parent(&mut self, round: Round) {
+        // Restoring anything but the newest backed up unit could let the creator build a second
+        // unit for a round we already have one in.
+        match &self.newest_backed_up_own_unit {
+            Some(unit) if unit.round() == round => {
+                warn!(target: "AlephBFT-runway", "{:?} Restoring own unit of round {} from backup.", self.index(), round);
+                let notification = NotificationIn::RestoredOwnUnit(unit.clone());
+                self.send_consensus_notification(notification);
+            }
+            newest => {
+                let newest_round = newest.as_ref().map(|unit| unit.round());
+                warn!(target: "AlephBFT-runway", "{:?} Cannot restore own unit of round {}, the newest backed up one is of round {:?}.", self.index(), round, newest_round);
+            }
+        }
+    }
+
     fn on_missing_coords(&mut self, mut coords: Vec<UnitCoord>) {
         trace!(target: "AlephBFT-runway", "{:?} Dealing with missing coords notification {:?}.", self.index(), coords);
         coords.retain(|coord| !self.store.contains_coord(coord));
+        let now = self.clock.now();
         for coord in coords {
-            if self.missing_coords.insert(coord) {
+            if let Entry::Vacant(entry) = self.missing_coords.entry(coord) {
+                entry.insert(now);
                 self.send_message_for_network(RunwayNotificationOut::Request(Request::Coord(
                     coord,
                 )));
@@ -588,8 +1591,32 @@ where
         }
     }
 
+    fn parents_diff(&self, u_hash: &H::Hash) -> Option<ParentsDiff> {
+        let claimed = self.store.claimed_parents(u_hash)?;
+        let round = self.store.unit_by_hash(u_hash)?.as_signable().round();
+        let available = match round.checked_sub(1) {
+            Some(parent_round) => (0..self.node_count().0)
+                .map(NodeIndex)
+                .filter(|creator| {
+                    self.store
+                        .contains_coord(&UnitCoord::new(parent_round, *creator))
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        Some(ParentsDiff { claimed, available })
+    }
+
     fn on_wrong_control_hash(&mut self, u_hash: H::Hash) {
         trace!(target: "AlephBFT-runway", "{:?} Dealing with wrong control hash notification {:?}.", self.index(), u_hash);
+        if self.abandoned_units.contains(&u_hash) || self.on_control_hash_failure(u_hash) {
+            return;
+        }
+        if log_enabled!(target: "AlephBFT-runway", Level::Trace) {
+            if let Some(diff) = self.parents_diff(&u_hash) {
+                trace!(target: "AlephBFT-runway", "{:?} Unit {:?} has a wrong control hash: {}.", self.index(), u_hash, diff);
+            }
+        }
         if let Some(p_hashes) = self.store.get_parents(u_hash) {
             // We have the parents by some strange reason (someone sent us parents
             // without us requesting them).
@@ -602,31 +1629,151 @@ where
         }
     }
 
-    fn on_ordered_batch(&mut self, batch: Vec<H::Hash>) {
-        let data_iter: Vec<_> = batch
-            .iter()
-            .filter_map(|h| {
-                self.store
-                    .unit_by_hash(h)
-                    .expect("Ordered units must be in store")
-                    .as_signable()
-                    .data()
-                    .clone()
-            })
-            .collect();
+    /// Counts a failure to reconcile the control hash of the unit with its parents, gives up on
+    /// the unit once the limit is reached. Returns whether the unit got abandoned.
+    fn on_control_hash_failure(&mut self, u_hash: H::Hash) -> bool {
+        let failures = self.control_hash_failures.entry(u_hash).or_insert(0);
+        *failures += 1;
+        if *failures < self.wrong_control_hash_limit {
+            return false;
+        }
+        let failures = *failures;
+        self.control_hash_failures.remove(&u_hash);
+        warn!(target: "AlephBFT-runway", "{:?} Giving up on unit {:?} after {} failures to reconstruct its parents.", self.index(), u_hash, failures);
+        self.abandoned_units.insert(u_hash);
+        if self.missing_parents.remove(&u_hash) {
+            self.send_resolved_request_notification(Request::Parents(u_hash));
+        }
+        self.metrics.report_abandoned_unit();
+        true
+    }
 
-        for d in data_iter {
-            self.finalization_handler.data_finalized(d);
+    fn on_ordered_batch(&mut self, batch: OrderedBatch<H>) {
+        let batch_round = batch.iter().map(|(_, round)| *round).max();
+        let mut finalized_items = Vec::new();
+        let mut finalized_count = 0;
+        for (h, _) in batch {
+            if self.ordered_before_restart.remove(&h) {
+                continue;
+            }
+            finalized_count += 1;
+            let full_unit = self
+                .store
+                .unit_by_hash(&h)
+                .expect("Ordered units must be in store")
+                .as_signable();
+            let position = self.ordered_units.len();
+            self.ordered_units.push((full_unit.coord(), h));
+            self.finalized_hashes.insert(h);
+            if let Some(d) = full_unit.data() {
+                finalized_items.push(FinalizedItem {
+                    data: d.clone(),
+                    creator: full_unit.creator(),
+                    round: full_unit.round(),
+                    hash: h,
+                    position,
+                });
+            }
+        }
+        self.finalized_round = self.finalized_round.max(batch_round);
+        self.metrics.report_finalized_units(finalized_count);
+
+        // The handler runs in its own task, so that a slow application does not hold up the loop.
+        if !finalized_items.is_empty()
+            && self
+                .finalized_items_for_handler
+                .unbounded_send(finalized_items)
+                .is_err()
+        {
+            error!(target: "AlephBFT-runway", "{:?} Finalization task gone, dropping finalized batch.", self.index());
+        }
+        self.check_session_complete();
+    }
+
+    /// Finalizes the batches ordered by consensus that are still waiting in the channel, so that
+    /// they are not lost when exiting.
+    fn drain_ordered_batches(&mut self) {
+        let mut drained = 0;
+        while let Some(Some(batch)) = self.ordered_batch_rx.next().now_or_never() {
+            self.on_ordered_batch(batch);
+            drained += 1;
+        }
+        if drained > 0 {
+            debug!(target: "AlephBFT-runway", "{:?} Finalized {} ordered batches while exiting.", self.index(), drained);
+        }
+    }
+
+    /// Emits [`RunwayEvent::SessionComplete`] once we have our own last unit and we finalized
+    /// the last round whose head can be decided with the units below the maximal round.
+    fn check_session_complete(&mut self) {
+        if self.session_complete {
+            return;
+        }
+        // The creator stops just below the maximal round.
+        let last_round = self.max_round().saturating_sub(1);
+        let ceiling_reached = self
+            .store
+            .contains_coord(&UnitCoord::new(last_round, self.index()));
+        let last_decidable_round = last_round.saturating_sub(HEAD_ACCEPTANCE_DISTANCE);
+        if ceiling_reached && self.finalized_round >= Some(last_decidable_round) {
+            info!(target: "AlephBFT-runway", "{:?} Session {} complete, finalized round {}.", self.index(), self.session_id(), last_decidable_round);
+            self.session_complete = true;
+            self.emit_event(RunwayEvent::SessionComplete);
+        }
+    }
+
+    /// Whether the unit with the given hash has been finalized. Only the most recently finalized
+    /// units are remembered, as many as [`Config::finalized_hashes_retention`] says.
+    fn is_finalized(&self, hash: &H::Hash) -> bool {
+        self.finalized_hashes.contains(hash)
+    }
+
+    /// Writes all the units ordered so far, one per line in the order of finalization, as
+    /// the position, round, creator and hex encoded hash separated by spaces.
+    fn dump_ordered_units<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        for (position, (coord, hash)) in self.ordered_units.iter().enumerate() {
+            write!(
+                writer,
+                "{} {} {} ",
+                position,
+                coord.round(),
+                coord.creator().0
+            )?;
+            for byte in hash.as_ref() {
+                write!(writer, "{:02x}", byte)?;
+            }
+            writeln!(writer)?;
         }
+        Ok(())
     }
 
     fn send_message_for_network(
         &mut self,
         notification: RunwayNotificationOut<H, D, MK::Signature>,
     ) {
+        // Only rebroadcasts and responses are dropped under backpressure, requests and our own
+        // units always go out, as nothing else would resend them in time.
+        let droppable = matches!(
+            notification,
+            RunwayNotificationOut::NewAnyUnit(_) | RunwayNotificationOut::Response(..)
+        );
+        if droppable && self.unit_messages_for_network.is_full() {
+            debug!(target: "AlephBFT-runway", "{:?} Too many messages waiting for the network, dropping one.", self.index());
+            return;
+        }
+        if let RunwayNotificationOut::Request(request @ (Request::Coord(_) | Request::Parents(_))) =
+            &notification
+        {
+            if let Some(since) = self.pending_requests.get(request) {
+                trace!(target: "AlephBFT-runway", "{:?} Not sending {:?}, pending since {:?}.", self.index(), request, since);
+                return;
+            }
+            self.pending_requests.insert(*request, self.clock.now());
+        }
+        self.count_message_for_network(&notification);
         if self
             .unit_messages_for_network
-            .unbounded_send(notification)
+            .send_past_capacity(notification)
             .is_err()
         {
             warn!(target: "AlephBFT-runway", "{:?} unit_messages_for_network channel should be open", self.index());
@@ -634,20 +1781,152 @@ where
         }
     }
 
-    fn send_resolved_request_notification(&mut self, notification: Request<H>) {
+    fn count_message_for_network(&self, notification: &RunwayNotificationOut<H, D, MK::Signature>) {
+        self.counters.count(|counters| match notification {
+            RunwayNotificationOut::NewSelfUnit(_) => counters.units_broadcast += 1,
+            RunwayNotificationOut::NewAnyUnit(_) => {}
+            RunwayNotificationOut::Request(_) => counters.requests_sent += 1,
+            RunwayNotificationOut::Response(..) => counters.responses_sent += 1,
+        });
+    }
+
+    fn send_resolved_request_notification(&mut self, request: Request<H>) {
+        self.send_request_notification(RequestResolved {
+            request,
+            satisfied_by: None,
+            abandoned: false,
+        });
+    }
+
+    fn send_abandoned_request_notification(&mut self, request: Request<H>) {
+        self.send_request_notification(RequestResolved {
+            request,
+            satisfied_by: None,
+            abandoned: true,
+        });
+    }
+
+    fn send_request_notification(&mut self, notification: RequestResolved<H>) {
+        self.pending_requests.remove(&notification.request);
         if self.resolved_requests.unbounded_send(notification).is_err() {
             warn!(target: "AlephBFT-runway", "{:?} resolved_requests channel should be open", self.index());
             self.exiting = true;
         }
     }
 
+    fn emit_event(&mut self, event: RunwayEvent) {
+        if let Some(events_for_observer) = &self.events_for_observer {
+            if events_for_observer.unbounded_send(event).is_err() {
+                debug!(target: "AlephBFT-runway", "{:?} Runway event observer is gone, no longer emitting events.", self.index());
+                self.events_for_observer = None;
+            }
+        }
+    }
+
+    fn on_control(&mut self, control: RunwayControl) {
+        debug!(target: "AlephBFT-runway", "{:?} Received control command {:?}.", self.index(), control);
+        match control {
+            RunwayControl::PauseUnitIntake => self.unit_intake_paused = true,
+            RunwayControl::ResumeUnitIntake => self.unit_intake_paused = false,
+            RunwayControl::DumpOrderedUnits => {
+                let mut dump = Vec::new();
+                self.dump_ordered_units(&mut dump)
+                    .expect("writing to a vector should succeed");
+                let dump = String::from_utf8(dump).expect("the dump should be text");
+                self.emit_event(RunwayEvent::OrderedUnits(dump));
+            }
+            RunwayControl::QueryFinalized(hash) => {
+                let finalized = H::Hash::decode(&mut &hash[..])
+                    .map(|hash| self.is_finalized(&hash))
+                    .unwrap_or(false);
+                self.emit_event(RunwayEvent::FinalizationStatus { hash, finalized });
+            }
+        }
+    }
+
     fn send_consensus_notification(&mut self, notification: NotificationIn<H>) {
+        if self.consensus_down {
+            // A restarted consensus gets all the units we hold anyway.
+            return;
+        }
         if self.tx_consensus.unbounded_send(notification).is_err() {
             warn!(target: "AlephBFT-runway", "{:?} Channel to consensus should be open", self.index());
-            self.exiting = true;
+            if !self.on_consensus_down() {
+                self.exiting = true;
+            }
+        }
+    }
+
+    /// Asks for a restart of consensus that stopped, if any attempts are left. Returns whether we
+    /// should wait for the restart rather than exit.
+    fn on_consensus_down(&mut self) -> bool {
+        if self.consensus_down {
+            return true;
+        }
+        let restarts = match &self.consensus_restarts {
+            Some(restarts) if self.consensus_restart_attempts > 0 => restarts,
+            _ => return false,
+        };
+        if restarts.requests.unbounded_send(()).is_err() {
+            warn!(target: "AlephBFT-runway", "{:?} Unable to request a restart of consensus.", self.index());
+            return false;
+        }
+        self.consensus_restart_attempts -= 1;
+        self.consensus_down = true;
+        warn!(target: "AlephBFT-runway", "{:?} Consensus stopped, requested a restart, {} more attempts left.", self.index(), self.consensus_restart_attempts);
+        true
+    }
+
+    fn on_consensus_restarted(&mut self, consensus_io: ConsensusIO<H>) {
+        let ConsensusIO {
+            tx_consensus,
+            rx_consensus,
+            ordered_batch_rx,
+            starting_round,
+        } = consensus_io;
+        self.tx_consensus = tx_consensus;
+        self.rx_consensus = rx_consensus;
+        self.ordered_batch_rx = ordered_batch_rx;
+        self.consensus_down = false;
+        // The new consensus orders the units in the same way, so it repeats all the batches
+        // finalized so far, which we must not finalize again.
+        self.ordered_before_restart = self.ordered_units.iter().map(|(_, h)| *h).collect();
+        let mut units: Vec<_> = self
+            .store
+            .units()
+            .map(|su| su.as_signable().unit())
+            .collect();
+        units.sort_by_key(|unit| unit.round());
+        info!(target: "AlephBFT-runway", "{:?} Consensus restarted, passing it {} units.", self.index(), units.len());
+        self.send_consensus_notification(NotificationIn::NewUnits(units));
+        // Never create a second unit of a round, even if the first one did not reach the store.
+        let newest_own_round = self
+            .store
+            .newest_unit(self.index())
+            .map(|unit| unit.as_signable().round());
+        let next_round = newest_own_round
+            .max(self.newest_created_round)
+            .map_or(0, |round| round + 1);
+        if starting_round.send(Some(next_round)).is_err() {
+            warn!(target: "AlephBFT-runway", "{:?} Unable to send the starting round to the restarted consensus.", self.index());
         }
     }
 
+    fn report_channel_depths(&self) {
+        self.metrics.report_channel_depth(
+            InternalChannel::NotificationsForConsensus,
+            self.tx_consensus.depth(),
+        );
+        self.metrics.report_channel_depth(
+            InternalChannel::UnitMessagesForNetwork,
+            self.unit_messages_for_network.depth(),
+        );
+        self.metrics.report_channel_depth(
+            InternalChannel::OrderedBatches,
+            self.ordered_batch_rx.size_hint().0,
+        );
+    }
+
     fn move_units_to_consensus(&mut self) {
         let units_to_move = self
             .store
@@ -658,13 +1937,60 @@ where
         self.send_consensus_notification(NotificationIn::NewUnits(units_to_move))
     }
 
-    fn status_report(&self) {
-        let runway_status: RunwayStatus<H> = RunwayStatus::new(
-            self.store.get_status(),
-            &self.missing_coords,
-            &self.missing_parents,
-        );
+    fn catch_up_estimate(&self) -> CatchUpEstimate {
+        CatchUpEstimate {
+            highest_observed_round: self.highest_observed_round,
+            finalized_round: self.finalized_round,
+        }
+    }
+
+    /// The state of the store and of the units we still wait for.
+    fn runway_status_snapshot(&self) -> RunwayStatusSnapshot<H> {
+        let mut missing_coords: Vec<_> = self.missing_coords.keys().copied().collect();
+        missing_coords.sort_by_key(|coord| (coord.creator(), coord.round()));
+        let mut missing_parents: Vec<_> = self.missing_parents.iter().copied().collect();
+        missing_parents.sort();
+        RunwayStatusSnapshot {
+            store: self.store.get_status(),
+            missing_coords,
+            missing_parents,
+            catch_up: self.catch_up_estimate(),
+        }
+    }
+
+    /// Statistics of the store and of the units we still wait for.
+    fn status_snapshot(&self, runway_status: &RunwayStatusSnapshot<H>) -> StatusSnapshot {
+        let own_units_reached = self.own_units_reached();
+        StatusSnapshot {
+            session_id: self.session_id(),
+            max_round: self.max_round(),
+            units_held: runway_status.store.size(),
+            height: runway_status.store.height(),
+            store: runway_status.store.clone(),
+            missing_coords: runway_status.missing_coords.clone(),
+            missing_parents: runway_status
+                .missing_parents
+                .iter()
+                .map(|hash| hash.as_ref().to_vec())
+                .collect(),
+            catch_up: runway_status.catch_up,
+            newest_own_unit_reached: own_units_reached.last().map(|(_, reached)| reached.len()),
+            own_units_reached,
+        }
+    }
+
+    fn status_report(&mut self) {
+        let runway_status = self.runway_status_snapshot();
         info!(target: "AlephBFT-runway", "{}", runway_status);
+        self.metrics
+            .report_missing_coords(runway_status.missing_coords.len());
+        let snapshot = self.status_snapshot(&runway_status);
+        if let Some(status_for_observer) = &self.status_for_observer {
+            if status_for_observer.unbounded_send(snapshot).is_err() {
+                debug!(target: "AlephBFT-runway", "{:?} Status observer is gone, no longer emitting status snapshots.", self.index());
+                self.status_for_observer = None;
+            }
+        }
     }
 
     async fn run(
@@ -676,13 +2002,21 @@ where
         let units_from_backup = units_from_backup.fuse();
         pin_mut!(units_from_backup);
 
-        let status_ticker_delay = Duration::from_secs(10);
-        let mut status_ticker = Delay::new(status_ticker_delay).fuse();
+        let mut status_ticker = interval_due(self.clock.as_ref(), self.status_report_interval);
+        let expiry_ticker_delay = Duration::from_secs(10);
+        let mut expiry_ticker = self.clock.sleep(expiry_ticker_delay).fuse();
+        let mut validation_retry_ticker =
+            interval_due(self.clock.as_ref(), self.validation_retry_interval);
 
+        // Units arriving from the network while the backup is being loaded wait in their channel
+        // and get handled once the loop below starts. The initial unit collection only finishes
+        // while the loop is running, as it is the loop that passes the responses on to it.
         match units_from_backup.await {
             Ok(units) => {
+                self.emit_event(RunwayEvent::BackupLoaded(BackupStats::new(index, &units)));
                 for u in units {
-                    self.on_unit_received(u, false);
+                    self.remember_backed_up_unit(&u);
+                    self.on_unit_received(u);
                 }
             }
             Err(e) => {
@@ -691,14 +2025,27 @@ where
             }
         }
 
-        debug!(target: "AlephBFT-runway", "{:?} Runway started.", index);
+        debug!(target: "AlephBFT-runway", "{:?} Runway started in session {} with max round {}.", index, self.session_id(), self.max_round());
         loop {
             futures::select! {
-                notification = self.rx_consensus.next() => match notification {
+                notification = next_unless_paused(&mut self.rx_consensus, self.consensus_down) => match notification {
                     Some(notification) => self.on_consensus_notification(notification),
                     None => {
-                        error!(target: "AlephBFT-runway", "{:?} Consensus notification stream closed.", index);
-                        break;
+                        if !self.on_consensus_down() {
+                            error!(target: "AlephBFT-runway", "{:?} Consensus notification stream closed.", index);
+                            break;
+                        }
+                    }
+                },
+
+                consensus_io = next_if_present(self.consensus_restarts.as_mut().map(|restarts| &mut restarts.restarted)) => match consensus_io {
+                    Some(consensus_io) => self.on_consensus_restarted(consensus_io),
+                    None => {
+                        debug!(target: "AlephBFT-runway", "{:?} Consensus restart stream closed.", index);
+                        self.consensus_restarts = None;
+                        if self.consensus_down {
+                            break;
+                        }
                     }
                 },
 
@@ -716,11 +2063,13 @@ where
                     },
                     None => {
                         error!(target: "AlephBFT-runway", "{:?} Alert notification stream closed.", index);
-                        break;
+                        if !self.on_alerter_down() {
+                            break;
+                        }
                     }
                 },
 
-                event = self.unit_messages_from_network.next() => match event {
+                event = next_unless_paused(&mut self.unit_messages_from_network, self.unit_intake_paused) => match event {
                     Some(event) => self.on_unit_message(event),
                     None => {
                         error!(target: "AlephBFT-runway", "{:?} Unit message stream closed.", index);
@@ -728,6 +2077,14 @@ where
                     }
                 },
 
+                snapshot = next_if_present(self.creator_snapshots.as_mut().map(|snapshots| &mut snapshots.from_creator)) => match snapshot {
+                    Some((round, snapshot)) => self.on_creator_snapshot(round, snapshot),
+                    None => {
+                        debug!(target: "AlephBFT-runway", "{:?} Creator snapshot stream closed.", index);
+                        self.creator_snapshots = None;
+                    }
+                },
+
                 message = self.backup_units_from_saver.next() => match message {
                     Some(unit) => self.on_unit_backup_saved(unit),
                     None => {
@@ -735,17 +2092,38 @@ where
                     }
                 },
 
-                batch = self.ordered_batch_rx.next() => match batch {
+                batch = next_unless_paused(&mut self.ordered_batch_rx, self.consensus_down) => match batch {
                     Some(batch) => self.on_ordered_batch(batch),
                     None => {
-                        error!(target: "AlephBFT-runway", "{:?} Ordered batch stream closed.", index);
-                        break;
+                        if !self.on_consensus_down() {
+                            error!(target: "AlephBFT-runway", "{:?} Ordered batch stream closed.", index);
+                            break;
+                        }
+                    }
+                },
+
+                control = next_if_present(self.control_from_application.as_mut()) => match control {
+                    Some(control) => self.on_control(control),
+                    None => {
+                        debug!(target: "AlephBFT-runway", "{:?} Control stream closed.", index);
+                        self.control_from_application = None;
                     }
                 },
 
                 _ = &mut status_ticker => {
                     self.status_report();
-                    status_ticker = Delay::new(status_ticker_delay).fuse();
+                    self.report_channel_depths();
+                    status_ticker = interval_due(self.clock.as_ref(), self.status_report_interval);
+                },
+
+                _ = &mut expiry_ticker => {
+                    self.abandon_expired_missing_coords();
+                    expiry_ticker = self.clock.sleep(expiry_ticker_delay).fuse();
+                },
+
+                _ = &mut validation_retry_ticker => {
+                    self.retry_deferred_units();
+                    validation_retry_ticker = interval_due(self.clock.as_ref(), self.validation_retry_interval);
                 },
 
                 _ = terminator.get_exit().fuse() => {
@@ -757,33 +2135,176 @@ where
 
             if self.exiting {
                 debug!(target: "AlephBFT-runway", "{:?} Runway decided to exit.", index);
+                self.drain_ordered_batches();
                 terminator.terminate_sync().await;
                 break;
             }
         }
 
+        if log_enabled!(target: "AlephBFT-runway", Level::Trace) {
+            let mut dump = Vec::new();
+            match self.dump_ordered_units(&mut dump) {
+                Ok(()) => {
+                    trace!(target: "AlephBFT-runway", "{:?} Ordered units:\n{}", index, String::from_utf8_lossy(&dump))
+                }
+                Err(e) => {
+                    debug!(target: "AlephBFT-runway", "{:?} Unable to dump ordered units: {}", index, e)
+                }
+            }
+        }
         debug!(target: "AlephBFT-runway", "{:?} Run ended.", index);
     }
 }
 
+/// The next item of the stream, unless `paused`, in which case the future never resolves.
+fn next_unless_paused<S: FusedStream + Unpin>(
+    stream: &mut S,
+    paused: bool,
+) -> impl FusedFuture<Output = Option<S::Item>> + '_ {
+    next_if_present((!paused).then_some(stream))
+}
+
+/// The next item of the stream, if there is one, otherwise the future never resolves.
+fn next_if_present<S: FusedStream + Unpin>(
+    stream: Option<&mut S>,
+) -> impl FusedFuture<Output = Option<S::Item>> + '_ {
+    match stream {
+        Some(stream) => Either::Left(stream.next()),
+        None => Either::Right(future::pending()),
+    }
+}
+
+/// Resolves once the given interval passes, never if there is none.
+pub(crate) fn interval_due(
+    clock: &dyn Clock,
+    interval: Option<Duration>,
+) -> Fuse<BoxFuture<'static, ()>> {
+    match interval {
+        Some(interval) => clock.sleep(interval),
+        None => Box::pin(future::pending()),
+    }
+    .fuse()
+}
+
+/// The ends of the channels between the runway and the network layer that belong to the runway,
+/// created with a [`NetworkIOBuilder`].
 pub(crate) struct NetworkIO<H: Hasher, D: Data, MK: MultiKeychain> {
     pub(crate) alert_messages_for_network: Sender<(NetworkMessage<H, D, MK>, Recipient)>,
     pub(crate) alert_messages_from_network: Receiver<NetworkMessage<H, D, MK>>,
-    pub(crate) unit_messages_for_network: Sender<RunwayNotificationOut<H, D, MK::Signature>>,
-    pub(crate) unit_messages_from_network: Receiver<RunwayNotificationIn<H, D, MK::Signature>>,
-    pub(crate) resolved_requests: Sender<Request<H>>,
+    pub(crate) unit_messages_for_network: TrackedSender<RunwayNotificationOut<H, D, MK::Signature>>,
+    pub(crate) unit_messages_from_network:
+        TrackedReceiver<RunwayNotificationIn<H, D, MK::Signature>>,
+    pub(crate) resolved_requests: Sender<RequestResolved<H>>,
+    pub(crate) validation_failures: Option<Sender<ValidationFailure>>,
+}
+
+/// The ends of the [`NetworkIO`] channels that belong to the network layer.
+pub(crate) struct NetworkEnds<H: Hasher, D: Data, MK: MultiKeychain> {
+    pub(crate) alert_messages_for_alerter: Sender<NetworkMessage<H, D, MK>>,
+    pub(crate) alert_messages_from_alerter: Receiver<(NetworkMessage<H, D, MK>, Recipient)>,
+    pub(crate) unit_messages_for_runway: TrackedSender<RunwayNotificationIn<H, D, MK::Signature>>,
+    pub(crate) unit_messages_from_runway:
+        TrackedReceiver<RunwayNotificationOut<H, D, MK::Signature>>,
+    pub(crate) resolved_requests: Receiver<RequestResolved<H>>,
+}
+
+/// Configures the capacities of the channels between the runway and the network handling of a
+/// session, passed with
+/// [`LocalIO::with_network_io_builder`](crate::LocalIO::with_network_io_builder). The channels
+/// themselves are internal, network layers are plugged in through the
+/// [`Network`](crate::Network) trait.
+///
+/// The channels carrying unit messages can be given capacities, so that a flood of messages
+/// cannot exhaust memory when either side falls behind. Once as many messages as the capacity
+/// allows are waiting, further rebroadcasts and responses are dropped, relying on the repeated
+/// requests of the protocol. Requests, our own units and alerts are never dropped.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NetworkIOBuilder {
+    incoming_unit_capacity: Option<usize>,
+    outgoing_unit_capacity: Option<usize>,
+}
+
+impl NetworkIOBuilder {
+    /// A builder of channels without capacities.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many unit messages from the network may wait for the runway, unlimited by
+    /// default.
+    pub fn with_incoming_unit_capacity(mut self, incoming_unit_capacity: Option<usize>) -> Self {
+        self.incoming_unit_capacity = incoming_unit_capacity;
+        self
+    }
+
+    /// Sets how many unit messages from the runway may wait for the network, unlimited by
+    /// default.
+    pub fn with_outgoing_unit_capacity(mut self, outgoing_unit_capacity: Option<usize>) -> Self {
+        self.outgoing_unit_capacity = outgoing_unit_capacity;
+        self
+    }
+
+    /// Creates the channels, returning the ends for the runway and for the session's network
+    /// handling.
+    pub(crate) fn build<H: Hasher, D: Data, MK: MultiKeychain>(
+        &self,
+    ) -> (NetworkIO<H, D, MK>, NetworkEnds<H, D, MK>) {
+        let (alert_messages_for_alerter, alert_messages_from_network) = mpsc::unbounded();
+        let (alert_messages_for_network, alert_messages_from_alerter) = mpsc::unbounded();
+        let (unit_messages_for_runway, unit_messages_from_network) =
+            tracked_channel(self.incoming_unit_capacity);
+        let (unit_messages_for_network, unit_messages_from_runway) =
+            tracked_channel(self.outgoing_unit_capacity);
+        let (resolved_requests_tx, resolved_requests_rx) = mpsc::unbounded();
+        let network_io = NetworkIO {
+            alert_messages_for_network,
+            alert_messages_from_network,
+            unit_messages_for_network,
+            unit_messages_from_network,
+            resolved_requests: resolved_requests_tx,
+            validation_failures: None,
+        };
+        let network_ends = NetworkEnds {
+            alert_messages_for_alerter,
+            alert_messages_from_alerter,
+            unit_messages_for_runway,
+            unit_messages_from_runway,
+            resolved_requests: resolved_requests_rx,
+        };
+        (network_io, network_ends)
+    }
+}
+
+impl<H: Hasher, D: Data, MK: MultiKeychain> NetworkIO<H, D, MK> {
+    /// Sets a channel on which units received from the network and failing validation are
+    /// reported.
+    pub(crate) fn with_validation_failures(
+        mut self,
+        validation_failures: Option<Sender<ValidationFailure>>,
+    ) -> Self {
+        self.validation_failures = validation_failures;
+        self
+    }
+}
+
+/// Whether a session started with the given config begins with collecting our newest unit from
+/// other members, which needs responses from a threshold of them before any unit is created.
+/// This defaults to the `initial_unit_collection` feature, and can be overridden for every
+/// session with [`Config::with_initial_unit_collection`]. The backup is always checked against
+/// the collection result rather than replacing it.
+pub fn collects_initial_units(config: &Config) -> bool {
+    config.initial_unit_collection()
 }
 
-#[cfg(feature = "initial_unit_collection")]
 fn initial_unit_collection<'a, H: Hasher, D: Data, MK: MultiKeychain>(
     keychain: &'a MK,
-    validator: &'a Validator<MK>,
+    validator: &'a Validator<MK, D>,
     threshold: NodeCount,
-    unit_messages_for_network: &Sender<RunwayNotificationOut<H, D, MK::Signature>>,
+    unit_messages_for_network: &TrackedSender<RunwayNotificationOut<H, D, MK::Signature>>,
     unit_collection_sender: oneshot::Sender<Round>,
     responses_from_runway: Receiver<CollectionResponse<H, D, MK>>,
-    resolved_requests: Sender<Request<H>>,
-) -> Result<impl Future<Output = ()> + 'a, ()> {
+    resolved_requests: Sender<RequestResolved<H>>,
+) -> Result<CollectionIO<'a, H, D, MK>, ()> {
     let (collection, salt) = Collection::new(keychain, validator, threshold);
     let notification = RunwayNotificationOut::Request(Request::NewestUnit(salt));
 
@@ -792,24 +2313,21 @@ fn initial_unit_collection<'a, H: Hasher, D: Data, MK: MultiKeychain>(
         return Err(());
     };
 
-    let collection = CollectionIO::new(
+    Ok(CollectionIO::new(
         unit_collection_sender,
         responses_from_runway,
         resolved_requests,
+        unit_messages_for_network.clone(),
         collection,
-    );
-    Ok(collection.run())
+    ))
 }
 
-#[cfg(not(feature = "initial_unit_collection"))]
-fn trivial_start(
-    starting_round_sender: oneshot::Sender<Round>,
-) -> Result<impl Future<Output = ()>, ()> {
+fn trivial_start(starting_round_sender: oneshot::Sender<Round>) -> Result<future::Ready<()>, ()> {
     if let Err(e) = starting_round_sender.send(0) {
         error!(target: "AlephBFT-runway", "Unable to send the starting round: {}", e);
         return Err(());
     }
-    Ok(async {})
+    Ok(future::ready(()))
 }
 
 pub struct RunwayIO<
@@ -825,6 +2343,14 @@ pub struct RunwayIO<
     pub finalization_handler: FH,
     pub unit_saver: UnitSaver<US, H, D, S>,
     pub unit_loader: UnitLoader<UL, H, D, S>,
+    pub event_sender: Option<Sender<RunwayEvent>>,
+    pub status_sender: Option<Sender<StatusSnapshot>>,
+    pub control_receiver: Option<Receiver<RunwayControl>>,
+    pub unit_inspector: Option<Arc<dyn UnitInspector<D>>>,
+    pub genesis_data: Option<D>,
+    pub fork_detection_handler: Option<ForkDetectionCallback<H, D, S>>,
+    pub initial_creator_snapshot: Option<Vec<u8>>,
+    pub creator_snapshot_sender: Option<Sender<Vec<u8>>>,
     _phantom: PhantomData<(H, D, S)>,
 }
 
@@ -849,9 +2375,113 @@ impl<
             finalization_handler,
             unit_saver: UnitSaver::new(unit_saver),
             unit_loader: UnitLoader::new(unit_loader),
+            event_sender: None,
+            status_sender: None,
+            control_receiver: None,
+            unit_inspector: None,
+            genesis_data: None,
+            fork_detection_handler: None,
+            initial_creator_snapshot: None,
+            creator_snapshot_sender: None,
             _phantom: PhantomData,
         }
     }
+
+    pub fn with_event_sender(mut self, event_sender: Option<Sender<RunwayEvent>>) -> Self {
+        self.event_sender = event_sender;
+        self
+    }
+
+    pub fn with_status_sender(mut self, status_sender: Option<Sender<StatusSnapshot>>) -> Self {
+        self.status_sender = status_sender;
+        self
+    }
+
+    pub fn with_control_receiver(
+        mut self,
+        control_receiver: Option<Receiver<RunwayControl>>,
+    ) -> Self {
+        self.control_receiver = control_receiver;
+        self
+    }
+
+    pub fn with_unit_inspector(
+        mut self,
+        unit_inspector: Option<Arc<dyn UnitInspector<D>>>,
+    ) -> Self {
+        self.unit_inspector = unit_inspector;
+        self
+    }
+
+    pub fn with_genesis_data(mut self, genesis_data: Option<D>) -> Self {
+        self.genesis_data = genesis_data;
+        self
+    }
+
+    pub fn with_creator_snapshots(
+        mut self,
+        initial_snapshot: Option<Vec<u8>>,
+        snapshot_sender: Option<Sender<Vec<u8>>>,
+    ) -> Self {
+        self.initial_creator_snapshot = initial_snapshot;
+        self.creator_snapshot_sender = snapshot_sender;
+        self
+    }
+
+    pub fn with_backup_sync(mut self, backup_sync: Option<BackupSync<US>>) -> Self {
+        self.unit_saver = self.unit_saver.with_sync(backup_sync);
+        self
+    }
+
+    pub fn with_fork_detection_handler<FDH: ForkDetectionHandler<D>>(
+        mut self,
+        mut fork_detection_handler: FDH,
+    ) -> Self {
+        self.fork_detection_handler = Some(Box::new(move |forker, proof| {
+            fork_detection_handler.fork_detected::<H, S>(forker, proof)
+        }));
+        self
+    }
+}
+
+fn spawn_consensus<H: Hasher>(
+    config: &Config,
+    spawn_handle: &impl SpawnHandle,
+    snapshot_io: SnapshotIO,
+    terminator: &mut Terminator,
+) -> (TaskHandle, ConsensusIO<H>) {
+    let (tx_consensus, consensus_stream) = tracked_unbounded();
+    let (consensus_sink, rx_consensus) = mpsc::unbounded();
+    let (ordered_batch_tx, ordered_batch_rx) = mpsc::unbounded();
+    let (starting_round_sender, starting_round) = oneshot::channel();
+    let consensus_terminator = terminator.add_offspring_connection("AlephBFT-consensus");
+    let consensus_config = config.clone();
+    let consensus_spawner = spawn_handle.clone();
+
+    let consensus_handle = spawn_handle.spawn_essential_with_hint(
+        "runway/consensus",
+        async move {
+            consensus::run(
+                consensus_config,
+                consensus_stream,
+                consensus_sink,
+                ordered_batch_tx,
+                consensus_spawner,
+                starting_round,
+                snapshot_io,
+                consensus_terminator,
+            )
+            .await
+        },
+        config.consensus_scheduling_hint(),
+    );
+    let consensus_io = ConsensusIO {
+        tx_consensus,
+        rx_consensus,
+        ordered_batch_rx,
+        starting_round: starting_round_sender,
+    };
+    (consensus_handle, consensus_io)
 }
 
 pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
@@ -871,16 +2501,15 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
     MK: MultiKeychain,
     SH: SpawnHandle,
 {
-    let (tx_consensus, consensus_stream) = mpsc::unbounded();
-    let (consensus_sink, rx_consensus) = mpsc::unbounded();
-    let (ordered_batch_tx, ordered_batch_rx) = mpsc::unbounded();
-
     let (alert_notifications_for_units, notifications_from_alerter) = mpsc::unbounded();
     let (alerts_for_alerter, alerts_from_units) = mpsc::unbounded();
     let alert_config = AlertConfig {
         session_id: config.session_id(),
         n_members: config.n_members(),
+        max_units_per_forker_round: config.max_units_per_forker_round(),
+        confirmation_threshold: config.alert_confirmation_threshold(),
     };
+    let max_units_per_forker_round = alert_config.max_units_per_forker_round;
     let alerter_terminator = terminator.add_offspring_connection("AlephBFT-alerter");
     let alerter_keychain = keychain.clone();
     let alert_messages_for_network = network_io.alert_messages_for_network;
@@ -893,41 +2522,59 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
         alert_notifications_for_units,
         alerts_from_units,
         alert_config.n_members,
-    );
-    let alerter_handler = crate::alerts::Handler::new(alerter_keychain, alert_config);
+        config.clock(),
+    )
+    .with_reconciliation_interval(config.forker_reconciliation_interval())
+    .with_verification_spawner(spawn_handle.clone());
+    let alerter_handler = crate::alerts::Handler::new(alerter_keychain, alert_config)
+        .with_observer(config.alert_observer())
+        .with_metrics(config.metrics());
 
-    let alerter_handle = spawn_handle.spawn_essential("runway/alerter", async move {
-        alerter_service
-            .run(alerter_handler, alerter_terminator)
-            .await;
-    });
+    let alerter_handle = spawn_handle.spawn_essential_with_hint(
+        "runway/alerter",
+        async move {
+            alerter_service
+                .run(alerter_handler, alerter_terminator)
+                .await;
+        },
+        config.consensus_scheduling_hint(),
+    );
     let mut alerter_handle = alerter_handle.fuse();
 
-    let consensus_terminator = terminator.add_offspring_connection("AlephBFT-consensus");
-    let consensus_config = config.clone();
-    let consensus_spawner = spawn_handle.clone();
-    let (starting_round_sender, starting_round) = oneshot::channel();
-
-    let consensus_handle = spawn_handle.spawn_essential("runway/consensus", async move {
-        consensus::run(
-            consensus_config,
-            consensus_stream,
-            consensus_sink,
-            ordered_batch_tx,
-            consensus_spawner,
-            starting_round,
-            consensus_terminator,
-        )
-        .await
-    });
+    let (creator_snapshots, held_creator_snapshots) = match runway_io.creator_snapshot_sender {
+        Some(for_application) => {
+            let (creator_snapshots, from_creator) = mpsc::unbounded();
+            (
+                Some(creator_snapshots),
+                Some(CreatorSnapshots::new(from_creator, for_application)),
+            )
+        }
+        None => (None, None),
+    };
+    let snapshot_io = SnapshotIO {
+        initial: runway_io.initial_creator_snapshot,
+        snapshots: creator_snapshots.clone(),
+    };
+    let (consensus_handle, consensus_io) =
+        spawn_consensus(&config, &spawn_handle, snapshot_io, &mut terminator);
     let mut consensus_handle = consensus_handle.fuse();
+    let ConsensusIO {
+        tx_consensus,
+        rx_consensus,
+        ordered_batch_rx,
+        starting_round: starting_round_sender,
+    } = consensus_io;
+    let (restart_requests_for_runway, mut restart_requests) = mpsc::unbounded();
+    let (restarted_consensus_for_runway, restarted_consensus) = mpsc::unbounded();
 
     let (backup_units_for_saver, backup_units_from_runway) = mpsc::unbounded();
     let (backup_units_for_runway, backup_units_from_saver) = mpsc::unbounded();
 
+    let index = keychain.index();
     let backup_saver_terminator = terminator.add_offspring_connection("AlephBFT-backup-saver");
     let backup_saver_handle = spawn_handle.spawn_essential("runway/backup_saver", async move {
         backup::run_saving_mechanism(
+            index,
             runway_io.unit_saver,
             backup_units_from_runway,
             backup_units_for_runway,
@@ -937,25 +2584,31 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
     });
     let mut backup_saver_handle = backup_saver_handle.fuse();
 
-    let index = keychain.index();
     let threshold = (keychain.node_count() * 2) / 3 + NodeCount(1);
     let validator = Validator::new(
         config.session_id(),
         keychain.clone(),
         config.max_round(),
         threshold,
-    );
+    )
+    .with_inspector(runway_io.unit_inspector);
     let (responses_for_collection, responses_from_runway) = mpsc::unbounded();
     let (unit_collections_sender, unit_collection_result) = oneshot::channel();
     let (loaded_units_tx, loaded_units_rx) = oneshot::channel();
-    let session_id = config.session_id();
+    let loading_config = LoadingConfig {
+        index,
+        session_id: config.session_id(),
+        gap_policy: config.backup_gap_policy(),
+        load_policy: config.backup_load_policy(),
+        collection_round_leeway: config.collection_round_leeway(),
+        metrics: config.metrics(),
+    };
 
     let backup_loading_handle = spawn_handle
         .spawn_essential("runway/loading", async move {
             backup::run_loading_mechanism(
                 runway_io.unit_loader,
-                index,
-                session_id,
+                loading_config,
                 loaded_units_tx,
                 starting_round_sender,
                 unit_collection_result,
@@ -965,38 +2618,62 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
         .fuse();
     pin_mut!(backup_loading_handle);
 
-    #[cfg(feature = "initial_unit_collection")]
-    let starting_round_handle = match initial_unit_collection(
-        keychain,
-        &validator,
-        threshold,
-        &network_io.unit_messages_for_network,
-        unit_collections_sender,
-        responses_from_runway,
-        network_io.resolved_requests.clone(),
-    ) {
-        Ok(handle) => handle.fuse(),
-        Err(_) => return,
-    };
-    #[cfg(not(feature = "initial_unit_collection"))]
-    let starting_round_handle = match trivial_start(unit_collections_sender) {
-        Ok(handle) => handle.fuse(),
-        Err(_) => return,
-    };
-    pin_mut!(starting_round_handle);
+    let mut starting_round_handle = Box::pin(if collects_initial_units(&config) {
+        match initial_unit_collection(
+            keychain,
+            &validator,
+            config.collection_threshold().unwrap_or(threshold),
+            &network_io.unit_messages_for_network,
+            unit_collections_sender,
+            responses_from_runway,
+            network_io.resolved_requests.clone(),
+        ) {
+            Ok(collection) => Either::Left(
+                collection
+                    .with_clock(config.clock())
+                    .with_status_report_interval(config.status_report_interval())
+                    .with_timeout(config.collection_timeout())
+                    .with_resend_interval(config.collection_resend_interval())
+                    .run(),
+            ),
+            Err(_) => return,
+        }
+    } else {
+        match trivial_start(unit_collections_sender) {
+            Ok(handle) => Either::Right(handle),
+            Err(_) => return,
+        }
+    })
+    .fuse();
 
     let RunwayIO {
         data_provider,
         finalization_handler,
+        event_sender,
+        status_sender,
+        control_receiver,
+        fork_detection_handler,
         ..
     } = runway_io;
     let (preunits_for_packer, preunits_from_runway) = mpsc::unbounded();
     let (signed_units_for_runway, signed_units_from_packer) = mpsc::unbounded();
+    let (finalized_items_for_handler, finalized_items_from_runway) = mpsc::unbounded();
+
+    let finalization_handle = spawn_handle
+        .spawn_essential("runway/finalization", async move {
+            finalization::run_finalization_mechanism(
+                finalization_handler,
+                finalized_items_from_runway,
+            )
+            .await
+        })
+        .fuse();
+    pin_mut!(finalization_handle);
 
     let runway_handle = spawn_handle
         .spawn_essential("runway", {
             let runway_config = RunwayConfig {
-                finalization_handler,
+                finalized_items_for_handler,
                 backup_units_for_saver,
                 backup_units_from_saver,
                 alerts_for_alerter,
@@ -1011,6 +2688,35 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
                 max_round: config.max_round(),
                 preunits_for_packer,
                 signed_units_from_packer,
+                metrics: config.metrics(),
+                counters: config.runway_counters(),
+                events_for_observer: event_sender,
+                status_for_observer: status_sender,
+                validation_failures_for_observer: network_io.validation_failures,
+                fork_detection_handler,
+                control_from_application: control_receiver,
+                alert_commitment: config.alert_commitment(),
+                parent_verification: config.parent_verification(),
+                alerter_failure_policy: config.alerter_failure_policy(),
+                finalized_hashes_retention: config.finalized_hashes_retention(),
+                confirmed_hashes_retention: config.confirmed_hashes_retention(),
+                unit_propagation_tracking: config.unit_propagation_tracking(),
+                wrong_control_hash_limit: config.wrong_control_hash_limit(),
+                duplicate_parents_response_limit: config.duplicate_parents_response_limit(),
+                max_units_per_forker_round,
+                missing_coord_lifetime: config.missing_coord_lifetime(),
+                validation_retry_interval: config.validation_retry_interval(),
+                response_rate_limit: config.response_rate_limit(),
+                consensus_restarts: Some(ConsensusRestarts {
+                    requests: restart_requests_for_runway,
+                    restarted: restarted_consensus,
+                }),
+                consensus_restart_attempts: config.consensus_restart_attempts(),
+                catch_up_burst: config.catch_up_burst(),
+                status_report_interval: config.status_report_interval(),
+                request_hashing: config.request_hashing(),
+                creator_snapshots: held_creator_snapshots,
+                clock: config.clock(),
             };
             let runway_terminator = terminator.add_offspring_connection("AlephBFT-runway");
             let validator = validator.clone();
@@ -1031,7 +2737,11 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
                 signed_units_for_runway,
                 keychain.clone(),
                 config.session_id(),
-            );
+            )
+            .with_repeated_data_policy(config.repeated_data_policy())
+            .with_genesis_data(runway_io.genesis_data)
+            .with_data_provider_timeout(config.data_provider_timeout())
+            .with_clock(config.clock());
 
             async move {
                 match packer.run(packer_terminator).await {
@@ -1051,13 +2761,37 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
                 debug!(target: "AlephBFT-runway", "{:?} Runway task terminated early.", index);
                 break;
             },
-            _ = alerter_handle => {
-                debug!(target: "AlephBFT-runway", "{:?} Alerter task terminated early.", index);
-                break;
+            _ = alerter_handle => match config.alerter_failure_policy() {
+                AlerterFailurePolicy::Halt => {
+                    debug!(target: "AlephBFT-runway", "{:?} Alerter task terminated early.", index);
+                    break;
+                },
+                AlerterFailurePolicy::Degrade => {
+                    error!(target: "AlephBFT-runway", "{:?} Alerter task terminated early, continuing in degraded mode.", index);
+                },
             },
             _ = consensus_handle => {
                 debug!(target: "AlephBFT-runway", "{:?} Consensus task terminated early.", index);
-                break;
+                if config.consensus_restart_attempts() == 0 {
+                    break;
+                }
+            },
+            request = restart_requests.next() => {
+                if request.is_none() {
+                    continue;
+                }
+                debug!(target: "AlephBFT-runway", "{:?} Restarting consensus.", index);
+                // The restarted consensus is passed all the units we hold, no need for a snapshot.
+                let snapshot_io = SnapshotIO {
+                    initial: None,
+                    snapshots: creator_snapshots.clone(),
+                };
+                let (handle, consensus_io) =
+                    spawn_consensus(&config, &spawn_handle, snapshot_io, &mut terminator);
+                consensus_handle = handle.fuse();
+                if restarted_consensus_for_runway.unbounded_send(consensus_io).is_err() {
+                    debug!(target: "AlephBFT-runway", "{:?} Runway gone before consensus restarted.", index);
+                }
             },
             _ = packer_handle => {
                 debug!(target: "AlephBFT-runway", "{:?} Packer task terminated early.", index);
@@ -1067,6 +2801,10 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
                 debug!(target: "AlephBFT-runway", "{:?} Backup saving task terminated early.", index);
                 break;
             },
+            _ = finalization_handle => {
+                debug!(target: "AlephBFT-runway", "{:?} Finalization task terminated early.", index);
+                break;
+            },
             _ = starting_round_handle => {
                 debug!(target: "AlephBFT-runway", "{:?} Starting round task terminated.", index);
             },
@@ -1085,6 +2823,14 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
     handle_task_termination(consensus_handle, "AlephBFT-runway", "Consensus", index).await;
     handle_task_termination(alerter_handle, "AlephBFT-runway", "Alerter", index).await;
     handle_task_termination(runway_handle, "AlephBFT-runway", "Runway", index).await;
+    // Only ends once the runway is gone, after handing over everything it finalized.
+    handle_task_termination(
+        finalization_handle,
+        "AlephBFT-runway",
+        "Finalization",
+        index,
+    )
+    .await;
     handle_task_termination(packer_handle, "AlephBFT-runway", "Packer", index).await;
     handle_task_termination(
         backup_saver_handle,
@@ -1093,6 +2839,2455 @@ pub(crate) async fn run<H, D, US, UL, MK, DP, FH, SH>(
         index,
     )
     .await;
+    // The collection holds the sender the loading task might still be waiting on.
+    drop(starting_round_handle);
+    handle_task_termination(
+        backup_loading_handle,
+        "AlephBFT-unit-backup",
+        "BackupLoading",
+        index,
+    )
+    .await;
 
     debug!(target: "AlephBFT-runway", "{:?} Runway ended.", index);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        collects_initial_units,
+        finalization::{finalize_batch, run_finalization_mechanism},
+        spawn_consensus, BackupStats, CatchUpEstimate, ConsensusIO, ConsensusRestarts,
+        CreatorSnapshots, NetworkIO, NetworkIOBuilder, NewestUnitResponse, NotificationIn,
+        NotificationOut, ParentsDiff, RecentHashes, Request, RequestResolved, Response, Runway,
+        RunwayConfig, RunwayControl, RunwayEvent, RunwayIO, RunwayNotificationIn,
+        RunwayNotificationOut, RunwayStatusSnapshot, StatusSnapshot, UnitPropagation,
+        ValidationFailure,
+    };
+    use crate::{
+        alerts::{
+            Alert, AlertMessage, ForkDetectionHandler, ForkEvidence, ForkProof, ForkingNotification,
+        },
+        metrics::{tracked_channel, tracked_unbounded, TrackedReceiver, TrackedSendError},
+        testing::{clock::VirtualClock, gen_config, gen_delay_config},
+        units::{
+            create_units, creator_set, preunit_to_unchecked_signed_unit, preunit_to_unit,
+            ControlHash, FullUnit, Inspection, PreUnit,
+            UncheckedSignedUnit as GenericUncheckedSignedUnit, UnitCoord, UnitInspector,
+            ValidationError, ValidationErrorKind, Validator,
+        },
+        AlertCommitment, AlerterFailurePolicy, FinalizationHandler, FinalizedItem, Hasher,
+        InternalChannel, Metrics, NodeCount, NodeIndex, NodeMap, NoopMetrics, ParentVerification,
+        Receiver, Recipient, RequestHashing, ResponseRateLimit, Round, RunwayCounters,
+        RunwayCountersSnapshot, SchedulingHint, Sender, SessionId, Signed, SpawnHandle,
+        SystemClock, TaskHandle, Terminator,
+    };
+    use aleph_bft_mock::{BadSigning, Data, Hash64, Hasher64, Keychain, Signature};
+    use codec::Encode;
+    use futures::{
+        channel::{mpsc, oneshot},
+        future::pending,
+        Future, FutureExt, StreamExt,
+    };
+    use parking_lot::Mutex;
+    use std::{
+        collections::{hash_map::DefaultHasher, HashMap, HashSet},
+        io,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        time::{Duration, Instant},
+    };
+
+    type TestRunway = Runway<Hasher64, Data, Keychain>;
+    type UncheckedSignedUnit = GenericUncheckedSignedUnit<Hasher64, Data, Signature>;
+
+    const SESSION_ID: SessionId = 0;
+    const NODE_ID: NodeIndex = NodeIndex(0);
+    const N_MEMBERS: NodeCount = NodeCount(4);
+    const MAX_ROUND: Round = 100;
+
+    /// The other ends of the channels of the runway, kept so that they stay open.
+    struct RunwayEndpoints {
+        backup_units_for_saver: Receiver<UncheckedSignedUnit>,
+        _backup_units_from_saver: Sender<UncheckedSignedUnit>,
+        alerts_for_alerter: Receiver<Alert<Hasher64, Data, Signature>>,
+        notifications_from_alerter:
+            Sender<crate::alerts::ForkingNotification<Hasher64, Data, Signature>>,
+        notifications_for_consensus: TrackedReceiver<super::NotificationIn<Hasher64>>,
+        _notifications_from_consensus: Sender<super::NotificationOut<Hasher64>>,
+        unit_messages_from_network: Sender<super::RunwayNotificationIn<Hasher64, Data, Signature>>,
+        unit_messages_for_network:
+            TrackedReceiver<super::RunwayNotificationOut<Hasher64, Data, Signature>>,
+        _responses_for_collection: Receiver<super::CollectionResponse<Hasher64, Data, Keychain>>,
+        ordered_batch_tx: Sender<Vec<(Hash64, Round)>>,
+        finalized_items: Receiver<Vec<FinalizedItem<Hasher64, Data>>>,
+        resolved_requests: Receiver<RequestResolved<Hasher64>>,
+        preunits_for_packer: Receiver<crate::units::PreUnit<Hasher64>>,
+        _signed_units_from_packer: Sender<crate::units::SignedUnit<Hasher64, Data, Keychain>>,
+        events: Receiver<RunwayEvent>,
+        status_snapshots: Receiver<StatusSnapshot>,
+        validation_failures: Receiver<ValidationFailure>,
+    }
+
+    /// The creator, round and hash of a unit, together with the data it finalized.
+    type FinalizedUnit = (NodeIndex, Round, Vec<u8>, Data);
+
+    #[derive(Default)]
+    struct RecordingFinalizationHandler {
+        finalized_units: Vec<FinalizedUnit>,
+        finalized_data: Vec<Data>,
+    }
+
+    impl FinalizationHandler<Data> for RecordingFinalizationHandler {
+        fn data_finalized(&mut self, data: Data) {
+            self.finalized_data.push(data);
+        }
+
+        fn unit_finalized<H: Hasher>(
+            &mut self,
+            creator: NodeIndex,
+            round: Round,
+            hash: H::Hash,
+            data: &Data,
+        ) {
+            self.finalized_units
+                .push((creator, round, hash.as_ref().to_vec(), *data));
+        }
+    }
+
+    #[derive(Default)]
+    struct BatchRecordingFinalizationHandler {
+        finalized_batches: Vec<Vec<Data>>,
+    }
+
+    impl FinalizationHandler<Data> for BatchRecordingFinalizationHandler {
+        fn data_finalized(&mut self, _data: Data) {
+            panic!("Data should be finalized in whole batches.");
+        }
+
+        fn data_finalized_batch(&mut self, batch: Vec<Data>) {
+            self.finalized_batches.push(batch);
+        }
+    }
+
+    #[derive(Default)]
+    struct ItemRecordingFinalizationHandler {
+        finalized_items: Vec<(usize, FinalizedUnit)>,
+    }
+
+    impl FinalizationHandler<Data> for ItemRecordingFinalizationHandler {
+        fn data_finalized(&mut self, _data: Data) {}
+
+        fn item_finalized<H: Hasher>(&mut self, item: &FinalizedItem<H, Data>) {
+            self.finalized_items.push((
+                item.position,
+                (
+                    item.creator,
+                    item.round,
+                    item.hash.as_ref().to_vec(),
+                    item.data,
+                ),
+            ));
+        }
+    }
+
+    /// Passes everything the runway finalized so far to the handler, as the finalization task
+    /// would.
+    fn finalize_pending<FH: FinalizationHandler<Data>>(
+        endpoints: &mut RunwayEndpoints,
+        finalization_handler: &mut FH,
+    ) {
+        while let Ok(Some(finalized_items)) = endpoints.finalized_items.try_next() {
+            finalize_batch(finalization_handler, finalized_items);
+        }
+    }
+
+    /// A call to one of the methods of [`Metrics`] recorded by [`RecordingMetrics`].
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    enum MetricsCall {
+        DagHeight(Round),
+        DagWidth(Round, usize),
+        ForkerDiscrepancy(usize),
+        AlerterDown,
+        AbandonedUnit,
+        UnknownCreator,
+        UnrequestedUnit,
+        DuplicateParentsResponse(NodeIndex),
+        ChannelDepth(InternalChannel, usize),
+    }
+
+    /// Records the calls to the methods of [`Metrics`] checked by the tests, in order.
+    #[derive(Default)]
+    struct RecordingMetrics {
+        calls: Mutex<Vec<MetricsCall>>,
+    }
+
+    impl RecordingMetrics {
+        fn record(&self, call: MetricsCall) {
+            self.calls.lock().push(call);
+        }
+
+        /// How many of the recorded calls match.
+        fn count(&self, matching: impl Fn(&MetricsCall) -> bool) -> usize {
+            self.calls
+                .lock()
+                .iter()
+                .filter(|call| matching(call))
+                .count()
+        }
+
+        /// The arguments of the recorded calls picked by `pick`, in order.
+        fn arguments<T>(&self, pick: impl FnMut(&MetricsCall) -> Option<T>) -> Vec<T> {
+            self.calls.lock().iter().filter_map(pick).collect()
+        }
+    }
+
+    impl Metrics for RecordingMetrics {
+        fn report_dag_height(&self, height: Round) {
+            self.record(MetricsCall::DagHeight(height));
+        }
+
+        fn report_dag_width(&self, round: Round, width: usize) {
+            self.record(MetricsCall::DagWidth(round, width));
+        }
+
+        fn report_forker_discrepancy(&self, missed: usize) {
+            self.record(MetricsCall::ForkerDiscrepancy(missed));
+        }
+
+        fn report_alerter_down(&self) {
+            self.record(MetricsCall::AlerterDown);
+        }
+
+        fn report_abandoned_unit(&self) {
+            self.record(MetricsCall::AbandonedUnit);
+        }
+
+        fn report_unknown_creator(&self) {
+            self.record(MetricsCall::UnknownCreator);
+        }
+
+        fn report_unrequested_unit(&self) {
+            self.record(MetricsCall::UnrequestedUnit);
+        }
+
+        fn report_duplicate_parents_response(&self, responder: NodeIndex) {
+            self.record(MetricsCall::DuplicateParentsResponse(responder));
+        }
+
+        fn report_channel_depth(&self, channel: InternalChannel, depth: usize) {
+            self.record(MetricsCall::ChannelDepth(channel, depth));
+        }
+    }
+
+    fn setup_runway(metrics: Arc<dyn Metrics>) -> (TestRunway, RunwayEndpoints) {
+        let keychain = Keychain::new(N_MEMBERS, NODE_ID);
+        let threshold = (N_MEMBERS * 2) / 3 + NodeCount(1);
+        let validator = Validator::new(SESSION_ID, keychain, MAX_ROUND, threshold);
+        let (backup_units_for_saver, backup_units_for_saver_rx) = mpsc::unbounded();
+        let (backup_units_from_saver_tx, backup_units_from_saver) = mpsc::unbounded();
+        let (alerts_for_alerter, alerts_for_alerter_rx) = mpsc::unbounded();
+        let (notifications_from_alerter_tx, notifications_from_alerter) = mpsc::unbounded();
+        let (tx_consensus, notifications_for_consensus) = tracked_unbounded();
+        let (notifications_from_consensus, rx_consensus) = mpsc::unbounded();
+        let (unit_messages_from_network_tx, unit_messages_from_network) = mpsc::unbounded();
+        let (unit_messages_for_network, unit_messages_for_network_rx) = tracked_unbounded();
+        let (responses_for_collection, responses_for_collection_rx) = mpsc::unbounded();
+        let (ordered_batch_tx, ordered_batch_rx) = mpsc::unbounded();
+        let (finalized_items_for_handler, finalized_items) = mpsc::unbounded();
+        let (resolved_requests, resolved_requests_rx) = mpsc::unbounded();
+        let (preunits_for_packer, preunits_for_packer_rx) = mpsc::unbounded();
+        let (signed_units_from_packer_tx, signed_units_from_packer) = mpsc::unbounded();
+        let (events_for_observer, events) = mpsc::unbounded();
+        let (status_for_observer, status_snapshots) = mpsc::unbounded();
+        let (validation_failures_for_observer, validation_failures) = mpsc::unbounded();
+
+        let config = RunwayConfig {
+            max_round: MAX_ROUND,
+            finalized_items_for_handler,
+            backup_units_for_saver,
+            backup_units_from_saver,
+            alerts_for_alerter,
+            notifications_from_alerter,
+            tx_consensus,
+            rx_consensus,
+            unit_messages_from_network: unit_messages_from_network.into(),
+            unit_messages_for_network,
+            responses_for_collection,
+            ordered_batch_rx,
+            resolved_requests,
+            preunits_for_packer,
+            signed_units_from_packer,
+            metrics,
+            counters: RunwayCounters::default(),
+            events_for_observer: Some(events_for_observer),
+            status_for_observer: Some(status_for_observer),
+            validation_failures_for_observer: Some(validation_failures_for_observer),
+            fork_detection_handler: None,
+            control_from_application: None,
+            alert_commitment: AlertCommitment::default(),
+            parent_verification: ParentVerification::default(),
+            alerter_failure_policy: AlerterFailurePolicy::default(),
+            finalized_hashes_retention: 100,
+            confirmed_hashes_retention: 100,
+            unit_propagation_tracking: None,
+            wrong_control_hash_limit: 10,
+            duplicate_parents_response_limit: 10,
+            max_units_per_forker_round: N_MEMBERS.0,
+            missing_coord_lifetime: Duration::from_secs(600),
+            validation_retry_interval: None,
+            response_rate_limit: None,
+            consensus_restarts: None,
+            consensus_restart_attempts: 0,
+            catch_up_burst: None,
+            status_report_interval: Some(Duration::from_secs(10)),
+            request_hashing: RequestHashing::default(),
+            creator_snapshots: None,
+            clock: Arc::new(SystemClock),
+        };
+        let endpoints = RunwayEndpoints {
+            backup_units_for_saver: backup_units_for_saver_rx,
+            _backup_units_from_saver: backup_units_from_saver_tx,
+            alerts_for_alerter: alerts_for_alerter_rx,
+            notifications_from_alerter: notifications_from_alerter_tx,
+            notifications_for_consensus,
+            _notifications_from_consensus: notifications_from_consensus,
+            unit_messages_from_network: unit_messages_from_network_tx,
+            unit_messages_for_network: unit_messages_for_network_rx,
+            _responses_for_collection: responses_for_collection_rx,
+            ordered_batch_tx,
+            finalized_items,
+            resolved_requests: resolved_requests_rx,
+            preunits_for_packer: preunits_for_packer_rx,
+            _signed_units_from_packer: signed_units_from_packer_tx,
+            events,
+            status_snapshots,
+            validation_failures,
+        };
+        (Runway::new(config, keychain, validator), endpoints)
+    }
+
+    /// Honest units of all members, units[i][j] is the unit produced in round i by creator j.
+    fn produce_units(rounds: Round) -> Vec<Vec<UncheckedSignedUnit>> {
+        let mut creators = creator_set(N_MEMBERS);
+        let keychains: Vec<_> = (0..N_MEMBERS.0)
+            .map(|id| Keychain::new(N_MEMBERS, NodeIndex(id)))
+            .collect();
+        (0..rounds)
+            .map(|round| {
+                let pre_units = create_units(creators.iter(), round);
+                let units: Vec<_> = pre_units
+                    .iter()
+                    .map(|(pre_unit, _)| preunit_to_unit(pre_unit.clone(), SESSION_ID))
+                    .collect();
+                for creator in creators.iter_mut() {
+                    creator.add_units(&units);
+                }
+                pre_units
+                    .into_iter()
+                    .zip(keychains.iter())
+                    .map(|((pre_unit, _), keychain)| {
+                        preunit_to_unchecked_signed_unit(pre_unit, SESSION_ID, keychain)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reports_dag_height_and_width() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, _endpoints) = setup_runway(metrics.clone());
+        let units = produce_units(3);
+
+        for unit in units[0].iter().chain(units[1].iter().take(3)) {
+            runway.on_unit_received(unit.clone());
+        }
+        runway.on_unit_received(units[2][1].clone());
+
+        let heights = metrics.arguments(|call| match call {
+            MetricsCall::DagHeight(height) => Some(*height),
+            _ => None,
+        });
+        assert_eq!(heights.last(), Some(&2));
+        let widths: HashMap<_, _> = metrics
+            .arguments(|call| match call {
+                MetricsCall::DagWidth(round, width) => Some((*round, *width)),
+                _ => None,
+            })
+            .into_iter()
+            .collect();
+        assert_eq!(widths, HashMap::from([(0, 4), (1, 3), (2, 1)]));
+    }
+
+    fn fork_unit(creator: NodeIndex, round: Round, data: Data) -> UncheckedSignedUnit {
+        let control_hash = ControlHash::new(&NodeMap::with_size(N_MEMBERS));
+        let pre_unit = PreUnit::new(creator, round, control_hash);
+        let full_unit = FullUnit::new(pre_unit, Some(data), SESSION_ID);
+        Signed::sign(full_unit, &Keychain::new(N_MEMBERS, creator)).into()
+    }
+
+    #[test]
+    fn ignores_conflicting_units_in_alert() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let forker = NodeIndex(1);
+        let variant_a = fork_unit(forker, 0, 1);
+        let variant_b = fork_unit(forker, 0, 2);
+        let hash_a = variant_a.as_signable().hash();
+        let hash_b = variant_b.as_signable().hash();
+
+        runway.on_alert_notification(ForkingNotification::Forker((
+            variant_a.clone(),
+            variant_b.clone(),
+        )));
+        runway.on_alert_notification(ForkingNotification::Units(vec![variant_a, variant_b]));
+
+        assert!(runway.store.contains_hash(&hash_a));
+        assert!(!runway.store.contains_hash(&hash_b));
+        let stored = runway
+            .store
+            .unit_by_coord(UnitCoord::new(0, forker))
+            .expect("the first variant should be stored");
+        assert_eq!(stored.as_signable().hash(), hash_a);
+    }
+
+    #[test]
+    fn drops_alerted_units_beyond_the_cap() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        runway.max_units_per_forker_round = 2;
+        runway.alert_commitment = AlertCommitment::SkipConfirmed;
+        let forker = NodeIndex(1);
+        let variants: Vec<_> = (0..5).map(|data| fork_unit(forker, 0, data)).collect();
+        let hashes: Vec<_> = variants.iter().map(|v| v.as_signable().hash()).collect();
+
+        runway.on_alert_notification(ForkingNotification::Forker((
+            variants[0].clone(),
+            variants[1].clone(),
+        )));
+        // Every alert commits to a different variant, so they do not conflict within an alert.
+        for variant in variants {
+            runway.on_alert_notification(ForkingNotification::Units(vec![variant]));
+        }
+
+        assert!(runway.store.contains_hash(&hashes[0]));
+        assert!(runway.store.contains_hash(&hashes[1]));
+        for hash in &hashes[2..] {
+            assert!(!runway.store.contains_hash(hash));
+            assert!(!runway.confirmed_units.contains(hash));
+        }
+
+        // Resending an accepted unit does not count against the cap.
+        let resent = fork_unit(forker, 0, 0);
+        runway.on_alert_notification(ForkingNotification::Units(vec![resent]));
+        assert_eq!(
+            runway.alerted_units_per_coord[&UnitCoord::new(0, forker)],
+            2
+        );
+    }
+
+    #[test]
+    fn estimates_rounds_to_catch_up() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(6);
+        assert_eq!(runway.catch_up_estimate().remaining_rounds(), 0);
+
+        for unit in units.iter().flatten() {
+            runway.on_unit_received(unit.clone());
+        }
+        assert_eq!(runway.catch_up_estimate().remaining_rounds(), 6);
+
+        let batch = units[..2]
+            .iter()
+            .flatten()
+            .map(|unit| (unit.as_signable().hash(), unit.as_signable().round()))
+            .collect();
+        runway.on_ordered_batch(batch);
+
+        assert_eq!(
+            runway.catch_up_estimate(),
+            CatchUpEstimate {
+                highest_observed_round: Some(5),
+                finalized_round: Some(1),
+            }
+        );
+        assert_eq!(runway.catch_up_estimate().remaining_rounds(), 4);
+        let snapshot = runway.status_snapshot(&runway.runway_status_snapshot());
+        assert_eq!(snapshot.catch_up, runway.catch_up_estimate());
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn prometheus_metrics_count_finalized_units() {
+        let metrics = crate::PrometheusMetrics::register(
+            &mut prometheus_client::registry::Registry::default(),
+        );
+        let (mut runway, _endpoints) = setup_runway(Arc::new(metrics.clone()));
+        let units = produce_units(3);
+        for unit in units.iter().flatten() {
+            runway.on_unit_received(unit.clone());
+        }
+        assert_eq!(metrics.units_received(), 3 * N_MEMBERS.0 as u64);
+        assert_eq!(metrics.units_finalized(), 0);
+
+        let batch = units[..2]
+            .iter()
+            .flatten()
+            .map(|unit| (unit.as_signable().hash(), unit.as_signable().round()))
+            .collect();
+        runway.on_ordered_batch(batch);
+
+        assert_eq!(metrics.units_finalized(), 2 * N_MEMBERS.0 as u64);
+    }
+
+    #[tokio::test]
+    async fn emits_event_when_unit_added_to_dag() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(2);
+        for unit in units.iter().flatten() {
+            runway.on_unit_received(unit.clone());
+        }
+        let unit = units[1][2].as_signable();
+        let parent_hashes = units[0]
+            .iter()
+            .map(|parent| parent.as_signable().hash())
+            .collect();
+
+        runway.on_consensus_notification(NotificationOut::AddedToDag(unit.hash(), parent_hashes));
+
+        assert_eq!(
+            endpoints.events.next().await,
+            Some(RunwayEvent::UnitAddedToDag(UnitCoord::new(1, NodeIndex(2))))
+        );
+    }
+
+    #[tokio::test]
+    async fn reconciliation_recovers_dropped_forker_notification() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, mut endpoints) = setup_runway(metrics.clone());
+        let forker = NodeIndex(1);
+        let proof = (fork_unit(forker, 0, 1), fork_unit(forker, 0, 2));
+
+        // The alerter learned about the forker, but the notification never reached us.
+        assert!(!runway.store.is_forker(forker));
+        runway.on_alert_notification(ForkingNotification::KnownForkers(vec![proof.clone()]));
+
+        assert!(runway.store.is_forker(forker));
+        assert_eq!(
+            metrics.arguments(|call| match call {
+                MetricsCall::ForkerDiscrepancy(missed) => Some(*missed),
+                _ => None,
+            }),
+            vec![1]
+        );
+        assert_eq!(
+            endpoints.alerts_for_alerter.next().await,
+            Some(Alert::new(NODE_ID, proof.clone(), Vec::new()))
+        );
+
+        // Once the views agree, further reconciliations find nothing.
+        runway.on_alert_notification(ForkingNotification::KnownForkers(vec![proof]));
+        assert_eq!(
+            metrics.arguments(|call| match call {
+                MetricsCall::ForkerDiscrepancy(missed) => Some(*missed),
+                _ => None,
+            }),
+            vec![1]
+        );
+    }
+
+    #[tokio::test]
+    async fn network_io_builder_sets_unit_channel_capacities() {
+        let (mut network_io, network_ends): (NetworkIO<Hasher64, Data, Keychain>, _) =
+            NetworkIOBuilder::new()
+                .with_incoming_unit_capacity(Some(2))
+                .with_outgoing_unit_capacity(None)
+                .build();
+        let unit = produce_units(1)[0][1].clone();
+
+        for _ in 0..2 {
+            assert!(!network_ends.unit_messages_for_runway.is_full());
+            network_ends
+                .unit_messages_for_runway
+                .unbounded_send(RunwayNotificationIn::NewUnit(unit.clone()))
+                .expect("channel should be open");
+        }
+        assert!(network_ends.unit_messages_for_runway.is_full());
+        assert!(matches!(
+            network_ends
+                .unit_messages_for_runway
+                .unbounded_send(RunwayNotificationIn::NewUnit(unit.clone())),
+            Err(TrackedSendError::Full(_))
+        ));
+        assert!(network_io.unit_messages_from_network.next().await.is_some());
+        assert!(!network_ends.unit_messages_for_runway.is_full());
+
+        for _ in 0..10 {
+            network_io
+                .unit_messages_for_network
+                .unbounded_send(RunwayNotificationOut::NewSelfUnit(unit.clone()))
+                .expect("channel should be open");
+        }
+        assert!(!network_io.unit_messages_for_network.is_full());
+    }
+
+    #[tokio::test]
+    async fn network_io_connects_runway_with_network() {
+        let (mut network_io, mut network_ends): (NetworkIO<Hasher64, Data, Keychain>, _) =
+            NetworkIOBuilder::new().build();
+        let coord = UnitCoord::new(3, NodeIndex(1));
+        let unit = produce_units(1)[0][1].clone();
+
+        let alert_message = AlertMessage::AlertRequest(NodeIndex(2), [1; 8]);
+        network_ends
+            .alert_messages_for_alerter
+            .unbounded_send(alert_message.clone())
+            .expect("channel should be open");
+        assert_eq!(
+            network_io.alert_messages_from_network.next().await,
+            Some(alert_message.clone())
+        );
+        network_io
+            .alert_messages_for_network
+            .unbounded_send((alert_message.clone(), Recipient::Everyone))
+            .expect("channel should be open");
+        assert_eq!(
+            network_ends.alert_messages_from_alerter.next().await,
+            Some((alert_message, Recipient::Everyone))
+        );
+
+        network_ends
+            .unit_messages_for_runway
+            .unbounded_send(RunwayNotificationIn::NewUnit(unit.clone()))
+            .expect("channel should be open");
+        assert!(matches!(
+            network_io.unit_messages_from_network.next().await,
+            Some(RunwayNotificationIn::NewUnit(received)) if received == unit
+        ));
+        network_io
+            .unit_messages_for_network
+            .unbounded_send(RunwayNotificationOut::NewSelfUnit(unit.clone()))
+            .expect("channel should be open");
+        assert!(matches!(
+            network_ends.unit_messages_from_runway.next().await,
+            Some(RunwayNotificationOut::NewSelfUnit(received)) if received == unit
+        ));
+
+        network_io
+            .resolved_requests
+            .unbounded_send(RequestResolved {
+                request: Request::Coord(coord),
+                satisfied_by: None,
+                abandoned: false,
+            })
+            .expect("channel should be open");
+        assert!(matches!(
+            network_ends.resolved_requests.next().await,
+            Some(RequestResolved {
+                request: Request::Coord(received),
+                satisfied_by: None,
+                abandoned: false,
+            }) if received == coord
+        ));
+    }
+
+    #[tokio::test]
+    async fn halts_creation_when_proven_forker() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let proof = (fork_unit(NODE_ID, 0, 1), fork_unit(NODE_ID, 0, 2));
+
+        runway.on_alert_notification(ForkingNotification::Forker(proof));
+        let control_hash = ControlHash::new(&NodeMap::with_size(N_MEMBERS));
+        let pre_unit = PreUnit::new(NODE_ID, 1, control_hash);
+        runway.on_consensus_notification(NotificationOut::CreatedPreUnit(pre_unit, Vec::new()));
+
+        assert_eq!(
+            endpoints.events.next().await,
+            Some(RunwayEvent::OwnForkDetected)
+        );
+        drop(runway);
+        assert!(endpoints.preunits_for_packer.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn emits_evidence_of_detected_fork() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let forker = NodeIndex(2);
+        let variant_a = fork_unit(forker, 0, 1);
+        let variant_b = fork_unit(forker, 0, 2);
+
+        runway.on_unit_received(variant_a.clone());
+        runway.on_unit_received(variant_b.clone());
+
+        assert_eq!(
+            endpoints.events.next().await,
+            Some(RunwayEvent::ForkEvidence(ForkEvidence {
+                forker,
+                round: 0,
+                units: (variant_b.encode(), variant_a.encode()),
+            }))
+        );
+    }
+
+    #[derive(Clone, Default)]
+    struct RecordingForkDetectionHandler {
+        forkers: Arc<Mutex<Vec<NodeIndex>>>,
+    }
+
+    impl ForkDetectionHandler<Data> for RecordingForkDetectionHandler {
+        fn fork_detected<H: Hasher, S: crate::Signature>(
+            &mut self,
+            forker: NodeIndex,
+            proof: ForkProof<H, Data, S>,
+        ) {
+            assert_eq!(proof.0.as_signable().creator(), forker);
+            assert_eq!(proof.1.as_signable().creator(), forker);
+            self.forkers.lock().push(forker);
+        }
+    }
+
+    #[test]
+    fn notifies_fork_detection_handler_once_per_forker() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let handler = RecordingForkDetectionHandler::default();
+        let forkers = handler.forkers.clone();
+        let mut fork_detection_handler = handler;
+        runway.fork_detection_handler = Some(Box::new(move |forker, proof| {
+            fork_detection_handler.fork_detected::<Hasher64, Signature>(forker, proof)
+        }));
+        let forker = NodeIndex(3);
+
+        runway.on_unit_received(fork_unit(forker, 0, 7));
+        runway.on_unit_received(fork_unit(forker, 0, 8));
+        assert_eq!(*forkers.lock(), vec![forker]);
+
+        // Further units of the forker, and alerts about it, do not trigger the handler again.
+        runway.on_unit_received(fork_unit(forker, 0, 9));
+        runway.on_unit_received(fork_unit(forker, 0, 10));
+        runway.on_alert_notification(ForkingNotification::Forker((
+            fork_unit(forker, 0, 11),
+            fork_unit(forker, 0, 12),
+        )));
+        assert_eq!(*forkers.lock(), vec![forker]);
+    }
+
+    #[tokio::test]
+    async fn uses_own_unit_only_once_backed_up() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let control_hash = ControlHash::new(&NodeMap::with_size(N_MEMBERS));
+        let full_unit = FullUnit::new(PreUnit::new(NODE_ID, 0, control_hash), Some(0), SESSION_ID);
+        let hash = full_unit.hash();
+        let signed_unit = Signed::sign(full_unit, &Keychain::new(N_MEMBERS, NODE_ID));
+
+        runway.on_packed(signed_unit);
+        assert!(!runway.store.contains_hash(&hash));
+        let saved = endpoints
+            .backup_units_for_saver
+            .next()
+            .await
+            .expect("the unit should be sent to backup");
+        assert_eq!(saved.as_signable().hash(), hash);
+
+        runway.on_unit_backup_saved(saved);
+        assert!(runway.store.contains_hash(&hash));
+
+        // Once in the DAG it is broadcast, without being saved again.
+        runway.on_consensus_notification(NotificationOut::AddedToDag(hash, Vec::new()));
+        assert!(endpoints.backup_units_for_saver.try_next().is_err());
+        assert!(matches!(
+            endpoints.unit_messages_for_network.next().await,
+            Some(RunwayNotificationOut::NewAnyUnit(unit)) if unit.as_signable().hash() == hash
+        ));
+        assert!(matches!(
+            endpoints.unit_messages_for_network.next().await,
+            Some(RunwayNotificationOut::NewSelfUnit(unit)) if unit.as_signable().hash() == hash
+        ));
+    }
+
+    #[test]
+    fn predicts_initial_unit_collection() {
+        let config = gen_config(NODE_ID, N_MEMBERS, gen_delay_config());
+        assert_eq!(
+            collects_initial_units(&config),
+            cfg!(feature = "initial_unit_collection")
+        );
+        assert!(collects_initial_units(
+            &config.clone().with_initial_unit_collection(true)
+        ));
+        assert!(!collects_initial_units(
+            &config.with_initial_unit_collection(false)
+        ));
+    }
+
+    /// Runs a whole runway with an empty backup, returning the first message it sends out.
+    async fn first_message_of_session(
+        initial_unit_collection: bool,
+    ) -> RunwayNotificationOut<Hasher64, Data, Signature> {
+        let config = gen_config(NODE_ID, N_MEMBERS, gen_delay_config())
+            .with_initial_unit_collection(initial_unit_collection);
+        let keychain = Keychain::new(N_MEMBERS, NODE_ID);
+        let (finalization_handler, _finalized) = aleph_bft_mock::FinalizationHandler::new();
+        let runway_io = RunwayIO::new(
+            aleph_bft_mock::DataProvider::new(),
+            finalization_handler,
+            aleph_bft_mock::Saver::new(),
+            io::empty(),
+        );
+        let (network_io, mut network_ends) = NetworkIOBuilder::new().build();
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let runway_handle = tokio::spawn(async move {
+            super::run(
+                config,
+                runway_io,
+                &keychain,
+                aleph_bft_mock::Spawner::new(),
+                network_io,
+                Terminator::create_root(exit_rx, "AlephBFT-runway"),
+            )
+            .await
+        });
+
+        let message = network_ends
+            .unit_messages_from_runway
+            .next()
+            .await
+            .expect("the runway should send a message");
+        exit_tx.send(()).expect("channel should be open");
+        runway_handle.await.expect("runway should exit cleanly");
+        message
+    }
+
+    #[tokio::test]
+    async fn starts_with_collection_only_if_configured() {
+        assert!(matches!(
+            first_message_of_session(true).await,
+            RunwayNotificationOut::Request(Request::NewestUnit(_))
+        ));
+        // The unit is announced as any other unit before it is announced as our own.
+        assert!(matches!(
+            first_message_of_session(false).await,
+            RunwayNotificationOut::NewAnyUnit(unit)
+                if unit.as_signable().round() == 0 && unit.as_signable().creator() == NODE_ID
+        ));
+    }
+
+    #[test]
+    fn dumps_ordered_units() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(2);
+        for unit in units.iter().flatten() {
+            runway.on_unit_received(unit.clone());
+        }
+        let batch_of = |coords: &[(usize, usize)]| {
+            coords
+                .iter()
+                .map(|(round, creator)| {
+                    let unit = units[*round][*creator].as_signable();
+                    (unit.hash(), unit.round())
+                })
+                .collect()
+        };
+        runway.on_ordered_batch(batch_of(&[(0, 2)]));
+        runway.on_ordered_batch(batch_of(&[(0, 0), (0, 1), (0, 3), (1, 1)]));
+
+        let mut dump = Vec::new();
+        runway
+            .dump_ordered_units(&mut dump)
+            .expect("writing to a vector should succeed");
+        let expected = "0 0 2 d5d14dedaabe1685\n\
+                        1 0 0 fc92bfe92aca4489\n\
+                        2 0 1 258e610d9ec3f53d\n\
+                        3 0 3 2eab76f4525d7600\n\
+                        4 1 1 4afd5408840f33ec\n";
+        assert_eq!(
+            String::from_utf8(dump).expect("the dump should be text"),
+            expected
+        );
+
+        runway.on_control(RunwayControl::DumpOrderedUnits);
+        assert_eq!(
+            endpoints.events.try_next().expect("channel should be open"),
+            Some(RunwayEvent::OrderedUnits(expected.to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn alert_skips_units_of_confirmed_alerts() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        runway.alert_commitment = AlertCommitment::SkipConfirmed;
+        let forker = NodeIndex(1);
+        let units = produce_units(6);
+        for unit in units.iter().flatten() {
+            runway.on_unit_received(unit.clone());
+        }
+        // An alert committing to the early units of the forker got confirmed before we formed ours.
+        runway.store.mark_forker(forker);
+        runway.on_alerted_units(units[..4].iter().map(|round| round[1].clone()).collect());
+
+        let proof = (fork_unit(forker, 0, 1), fork_unit(forker, 0, 2));
+        runway.on_new_forker_detected(forker, proof.clone());
+
+        let expected_units = units[4..].iter().map(|round| round[1].clone()).collect();
+        assert_eq!(
+            endpoints.alerts_for_alerter.next().await,
+            Some(Alert::new(NODE_ID, proof, expected_units))
+        );
+    }
+
+    #[tokio::test]
+    async fn alert_commits_again_to_forgotten_confirmed_units() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        runway.alert_commitment = AlertCommitment::SkipConfirmed;
+        runway.confirmed_units = RecentHashes::new(2);
+        let forker = NodeIndex(1);
+        let units = produce_units(6);
+        for unit in units.iter().flatten() {
+            runway.on_unit_received(unit.clone());
+        }
+        runway.store.mark_forker(forker);
+        runway.on_alerted_units(units[..4].iter().map(|round| round[1].clone()).collect());
+
+        let proof = (fork_unit(forker, 0, 1), fork_unit(forker, 0, 2));
+        runway.on_new_forker_detected(forker, proof.clone());
+
+        // Only the two most recently confirmed units are remembered and skipped.
+        let expected_units = units[..2]
+            .iter()
+            .chain(&units[4..])
+            .map(|round| round[1].clone())
+            .collect();
+        assert_eq!(
+            endpoints.alerts_for_alerter.next().await,
+            Some(Alert::new(NODE_ID, proof, expected_units))
+        );
+    }
+
+    /// Waits for the unit with the given hash to be passed to consensus, for at most `timeout`.
+    async fn unit_passed_to_consensus(
+        notifications: &mut TrackedReceiver<NotificationIn<Hasher64>>,
+        hash: Hash64,
+        timeout: Duration,
+    ) -> bool {
+        let passed = async {
+            while let Some(notification) = notifications.next().await {
+                if let NotificationIn::NewUnits(units) = notification {
+                    if units.iter().any(|unit| unit.hash() == hash) {
+                        return;
+                    }
+                }
+            }
+            pending::<()>().await
+        };
+        tokio::time::timeout(timeout, passed).await.is_ok()
+    }
+
+    #[tokio::test]
+    async fn reports_units_loaded_from_backup() {
+        let (runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let (units_from_backup_tx, units_from_backup) = oneshot::channel();
+        units_from_backup_tx
+            .send(produce_units(1)[0][..3].to_vec())
+            .expect("channel should be open");
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let runway_handle = tokio::spawn(runway.run(
+            units_from_backup,
+            Terminator::create_root(exit_rx, "AlephBFT-runway"),
+        ));
+
+        assert_eq!(
+            endpoints.events.next().await,
+            Some(RunwayEvent::BackupLoaded(BackupStats {
+                units_loaded: 3,
+                highest_round: Some(0),
+            }))
+        );
+
+        exit_tx.send(()).expect("channel should be open");
+        runway_handle.await.expect("runway should exit cleanly");
+    }
+
+    #[tokio::test]
+    async fn incorporates_units_arriving_before_the_loop_starts() {
+        let (runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(1);
+        let send_unit = |unit: &UncheckedSignedUnit| {
+            endpoints
+                .unit_messages_from_network
+                .unbounded_send(RunwayNotificationIn::NewUnit(unit.clone()))
+                .expect("channel should be open");
+        };
+        // One unit arrives before the runway even starts, another while it waits for the backup.
+        send_unit(&units[0][1]);
+        let (units_from_backup_tx, units_from_backup) = oneshot::channel();
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let runway_handle = tokio::spawn(runway.run(
+            units_from_backup,
+            Terminator::create_root(exit_rx, "AlephBFT-runway"),
+        ));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        send_unit(&units[0][2]);
+        units_from_backup_tx
+            .send(Vec::new())
+            .expect("channel should be open");
+
+        for unit in [&units[0][1], &units[0][2]] {
+            assert!(
+                unit_passed_to_consensus(
+                    &mut endpoints.notifications_for_consensus,
+                    unit.as_signable().hash(),
+                    Duration::from_secs(5)
+                )
+                .await
+            );
+        }
+
+        exit_tx.send(()).expect("channel should be open");
+        runway_handle.await.expect("runway should exit cleanly");
+    }
+
+    #[tokio::test]
+    async fn paused_unit_intake_resumes_with_buffered_units() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let (control_tx, control_rx) = mpsc::unbounded();
+        runway.control_from_application = Some(control_rx);
+        let (units_from_backup_tx, units_from_backup) = oneshot::channel();
+        units_from_backup_tx
+            .send(Vec::new())
+            .expect("channel should be open");
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let runway_handle = tokio::spawn(runway.run(
+            units_from_backup,
+            Terminator::create_root(exit_rx, "AlephBFT-runway"),
+        ));
+
+        control_tx
+            .unbounded_send(RunwayControl::PauseUnitIntake)
+            .expect("channel should be open");
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let unit = produce_units(1)[0][1].clone();
+        let hash = unit.as_signable().hash();
+        endpoints
+            .unit_messages_from_network
+            .unbounded_send(RunwayNotificationIn::NewUnit(unit))
+            .expect("channel should be open");
+        assert!(
+            !unit_passed_to_consensus(
+                &mut endpoints.notifications_for_consensus,
+                hash,
+                Duration::from_millis(200)
+            )
+            .await
+        );
+
+        control_tx
+            .unbounded_send(RunwayControl::ResumeUnitIntake)
+            .expect("channel should be open");
+        assert!(
+            unit_passed_to_consensus(
+                &mut endpoints.notifications_for_consensus,
+                hash,
+                Duration::from_secs(5)
+            )
+            .await
+        );
+
+        exit_tx.send(()).expect("channel should be open");
+        runway_handle.await.expect("runway should exit cleanly");
+    }
+
+    #[test]
+    fn deferred_parent_verification_rejects_invalid_parent() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        runway.parent_verification = ParentVerification::Deferred;
+        let units = produce_units(2);
+        let child = units[1][0].clone();
+        let child_hash = child.as_signable().hash();
+        runway.on_unit_received(child);
+        runway.on_unit_received(units[0][0].clone());
+
+        let mut parents = units[0].clone();
+        let bad_keychain: BadSigning<Keychain> = Keychain::new(N_MEMBERS, NodeIndex(2)).into();
+        parents[2] = Signed::sign(parents[2].as_signable().clone(), &bad_keychain).into();
+        runway.on_parents_response(NodeIndex(1), child_hash, parents);
+        assert!(runway.store.get_parents(child_hash).is_none());
+        assert!(!runway
+            .store
+            .contains_hash(&units[0][1].as_signable().hash()));
+
+        runway.on_parents_response(NodeIndex(1), child_hash, units[0].clone());
+        assert!(runway.store.get_parents(child_hash).is_some());
+        for parent in &units[0] {
+            assert!(runway.store.contains_hash(&parent.as_signable().hash()));
+        }
+    }
+
+    #[test]
+    fn reports_units_failing_validation() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(2);
+        let bad_keychain: BadSigning<Keychain> = Keychain::new(N_MEMBERS, NodeIndex(2)).into();
+        let badly_signed = |unit: &UncheckedSignedUnit| -> UncheckedSignedUnit {
+            Signed::sign(unit.as_signable().clone(), &bad_keychain).into()
+        };
+
+        runway.on_unit_received(badly_signed(&units[0][2]));
+        assert_eq!(
+            endpoints
+                .validation_failures
+                .try_next()
+                .expect("channel should be open"),
+            Some(ValidationFailure {
+                sender: None,
+                error: ValidationErrorKind::WrongSignature,
+            })
+        );
+
+        let child = units[1][0].clone();
+        let child_hash = child.as_signable().hash();
+        runway.on_unit_received(child);
+        let mut parents = units[0].clone();
+        parents[2] = badly_signed(&parents[2]);
+        runway.on_parents_response(NodeIndex(3), child_hash, parents);
+        assert_eq!(
+            endpoints
+                .validation_failures
+                .try_next()
+                .expect("channel should be open"),
+            Some(ValidationFailure {
+                sender: Some(NodeIndex(3)),
+                error: ValidationErrorKind::WrongSignature,
+            })
+        );
+
+        // Valid units are not reported.
+        runway.on_parents_response(NodeIndex(3), child_hash, units[0].clone());
+        assert!(runway.store.get_parents(child_hash).is_some());
+        assert!(endpoints.validation_failures.try_next().is_err());
+    }
+
+    #[test]
+    fn accepts_parents_in_any_order() {
+        for parent_verification in [ParentVerification::Eager, ParentVerification::Deferred] {
+            let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+            runway.parent_verification = parent_verification;
+            let units = produce_units(2);
+            let child = units[1][0].clone();
+            let child_hash = child.as_signable().hash();
+            runway.on_unit_received(child);
+
+            // The same parent twice, in place of another one, does not pass.
+            let mut duplicated = units[0].clone();
+            duplicated[3] = duplicated[1].clone();
+            runway.on_parents_response(NodeIndex(1), child_hash, duplicated);
+            assert!(runway.store.get_parents(child_hash).is_none());
+
+            let mut shuffled = units[0].clone();
+            shuffled.reverse();
+            shuffled.swap(0, 2);
+            runway.on_parents_response(NodeIndex(1), child_hash, shuffled);
+            let expected: Vec<_> = units[0]
+                .iter()
+                .map(|parent| parent.as_signable().hash())
+                .collect();
+            assert_eq!(runway.store.get_parents(child_hash), Some(&expected));
+            for parent in &units[0] {
+                assert!(runway.store.contains_hash(&parent.as_signable().hash()));
+            }
+        }
+    }
+
+    #[test]
+    fn flags_peer_repeating_parents_responses() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, mut endpoints) = setup_runway(metrics.clone());
+        runway.duplicate_parents_response_limit = 3;
+        let units = produce_units(2);
+        let child = units[1][0].clone();
+        let child_hash = child.as_signable().hash();
+        runway.on_unit_received(child);
+        runway.on_parents_response(NodeIndex(1), child_hash, units[0].clone());
+        assert!(runway.store.get_parents(child_hash).is_some());
+
+        let mut flagged = Vec::new();
+        for _ in 0..5 {
+            runway.on_parents_response(NodeIndex(2), child_hash, units[0].clone());
+            while let Ok(Some(event)) = endpoints.events.try_next() {
+                if let RunwayEvent::ExcessiveDuplicateResponses(node_id) = event {
+                    flagged.push((
+                        node_id,
+                        runway.duplicate_parents_responses.get(node_id).copied(),
+                    ));
+                }
+            }
+        }
+        runway.on_parents_response(NodeIndex(3), child_hash, units[0].clone());
+
+        assert_eq!(flagged, vec![(NodeIndex(2), Some(3))]);
+        assert_eq!(
+            metrics.arguments(|call| match call {
+                MetricsCall::DuplicateParentsResponse(responder) => Some(*responder),
+                _ => None,
+            }),
+            vec![
+                NodeIndex(2),
+                NodeIndex(2),
+                NodeIndex(2),
+                NodeIndex(2),
+                NodeIndex(2),
+                NodeIndex(3)
+            ]
+        );
+        assert!(endpoints.events.try_next().is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_parents_response() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(2);
+        let child = units[1][0].clone();
+        let child_hash = child.as_signable().hash();
+        runway.on_unit_received(child);
+
+        let parents: Vec<_> = units[0].iter().chain(units[0].iter()).cloned().collect();
+        assert_eq!(parents.len(), 2 * N_MEMBERS.0);
+        runway.on_parents_response(NodeIndex(1), child_hash, parents);
+        assert!(runway.store.get_parents(child_hash).is_none());
+        for parent in &units[0] {
+            assert!(!runway.store.contains_hash(&parent.as_signable().hash()));
+        }
+    }
+
+    /// Runs the runway after the alerter went away, returns whether the runway stopped on its own
+    /// and how many times the alerter was reported down.
+    async fn run_with_dead_alerter(policy: AlerterFailurePolicy) -> (bool, usize) {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, endpoints) = setup_runway(metrics.clone());
+        runway.alerter_failure_policy = policy;
+        drop(endpoints.notifications_from_alerter);
+        let (units_from_backup_tx, units_from_backup) = oneshot::channel();
+        units_from_backup_tx
+            .send(Vec::new())
+            .expect("channel should be open");
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let runway_handle = tokio::spawn(runway.run(
+            units_from_backup,
+            Terminator::create_root(exit_rx, "AlephBFT-runway"),
+        ));
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let stopped = runway_handle.is_finished();
+        // The runway might be gone already, in which case there is no one to receive this.
+        let _ = exit_tx.send(());
+        runway_handle.await.expect("runway should exit cleanly");
+        let reports = metrics.count(|call| *call == MetricsCall::AlerterDown);
+        (stopped, reports)
+    }
+
+    #[tokio::test]
+    async fn halts_when_alerter_dies() {
+        assert_eq!(
+            run_with_dead_alerter(AlerterFailurePolicy::Halt).await,
+            (true, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn degrades_when_alerter_dies() {
+        assert_eq!(
+            run_with_dead_alerter(AlerterFailurePolicy::Degrade).await,
+            (false, 1)
+        );
+    }
+
+    #[test]
+    fn describes_parents_of_unit_with_wrong_control_hash() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(2);
+        let child = units[1][0].clone();
+        let child_hash = child.as_signable().hash();
+        runway.on_unit_received(child);
+        for unit in units[0].iter().skip(1).take(2) {
+            runway.on_unit_received(unit.clone());
+        }
+
+        runway.on_consensus_notification(NotificationOut::WrongControlHash(child_hash));
+
+        let diff = runway
+            .parents_diff(&child_hash)
+            .expect("the unit should be in store");
+        assert_eq!(
+            diff,
+            ParentsDiff {
+                claimed: (0..4).map(NodeIndex).collect(),
+                available: vec![NodeIndex(1), NodeIndex(2)],
+            }
+        );
+        assert_eq!(
+            diff.to_string(),
+            "claimed parents [NodeIndex(0), NodeIndex(1), NodeIndex(2), NodeIndex(3)], available parents [NodeIndex(1), NodeIndex(2)], missing [NodeIndex(0), NodeIndex(3)]"
+        );
+    }
+
+    #[test]
+    fn tells_whether_a_unit_was_finalized() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(1);
+        for unit in &units[0][1..3] {
+            runway.on_unit_received(unit.clone());
+        }
+        let finalized = units[0][1].as_signable();
+        let unfinalized = units[0][2].as_signable();
+
+        runway.on_ordered_batch(vec![(finalized.hash(), finalized.round())]);
+
+        assert!(runway.is_finalized(&finalized.hash()));
+        assert!(!runway.is_finalized(&unfinalized.hash()));
+
+        for (hash, finalized) in [(finalized.hash(), true), (unfinalized.hash(), false)] {
+            let hash = hash.encode();
+            runway.on_control(RunwayControl::QueryFinalized(hash.clone()));
+            assert_eq!(
+                endpoints.events.try_next().expect("channel should be open"),
+                Some(RunwayEvent::FinalizationStatus { hash, finalized })
+            );
+        }
+    }
+
+    #[test]
+    fn drops_already_finalized_units() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        runway.finalized_hashes = RecentHashes::new(1);
+        let units = produce_units(1);
+        let finalize = |runway: &mut TestRunway, unit: &UncheckedSignedUnit| {
+            runway.on_unit_received(unit.clone());
+            let unit = unit.as_signable();
+            runway.on_ordered_batch(vec![(unit.hash(), unit.round())]);
+        };
+        let unit = &units[0][1];
+        let coord = unit.as_signable().coord();
+        finalize(&mut runway, unit);
+
+        // A unit reaching the store would resolve the request for its coord.
+        runway.missing_coords.insert(coord, Instant::now());
+        runway.on_unit_received(unit.clone());
+        assert!(runway.missing_coords.contains_key(&coord));
+
+        // Finalizing another unit pushes the first one out of the bounded set.
+        finalize(&mut runway, &units[0][2]);
+        runway.on_unit_received(unit.clone());
+        assert!(!runway.missing_coords.contains_key(&coord));
+    }
+
+    #[test]
+    fn finalizes_data_along_with_the_unit_carrying_it() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(2);
+        let batch: Vec<_> = [&units[0][2], &units[0][1], &units[1][1]]
+            .into_iter()
+            .map(|unit| {
+                runway.on_unit_received(unit.clone());
+                let unit = unit.as_signable();
+                (unit.hash(), unit.round())
+            })
+            .collect();
+
+        runway.on_ordered_batch(batch.clone());
+        let mut handler = RecordingFinalizationHandler::default();
+        finalize_pending(&mut endpoints, &mut handler);
+
+        let expected: Vec<_> = [NodeIndex(2), NodeIndex(1), NodeIndex(1)]
+            .into_iter()
+            .zip(batch)
+            .map(|(creator, (hash, round))| (creator, round, hash.to_vec(), 0))
+            .collect();
+        assert_eq!(handler.finalized_units, expected);
+    }
+
+    #[tokio::test]
+    async fn finalizes_queued_batches_on_exit() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(1);
+        let batch: Vec<_> = units[0][1..3]
+            .iter()
+            .map(|unit| {
+                runway.on_unit_received(unit.clone());
+                let unit = unit.as_signable();
+                (unit.hash(), unit.round())
+            })
+            .collect();
+        endpoints
+            .ordered_batch_tx
+            .unbounded_send(batch)
+            .expect("channel should be open");
+        // With consensus down the batch is never picked up in the main loop, so only draining
+        // the channel on exit can finalize it.
+        runway.consensus_down = true;
+        let (units_from_backup_tx, units_from_backup) = oneshot::channel();
+        units_from_backup_tx
+            .send(Vec::new())
+            .expect("channel should be open");
+        let (exit_tx, exit_rx) = oneshot::channel();
+        exit_tx.send(()).expect("channel should be open");
+
+        runway
+            .run(
+                units_from_backup,
+                Terminator::create_root(exit_rx, "AlephBFT-runway"),
+            )
+            .await;
+
+        let mut handler = BatchRecordingFinalizationHandler::default();
+        finalize_pending(&mut endpoints, &mut handler);
+        assert_eq!(handler.finalized_batches, vec![vec![0, 0]]);
+    }
+
+    #[test]
+    fn finalizes_whole_batches_in_the_order_of_single_units() {
+        let units: Vec<UncheckedSignedUnit> = produce_units(1)[0]
+            .iter()
+            .map(|unit| {
+                let pre_unit = unit.as_signable().as_pre_unit().clone();
+                let creator = pre_unit.creator();
+                let keychain = Keychain::new(N_MEMBERS, creator);
+                let full_unit = FullUnit::new(pre_unit, Some(creator.0 as Data), SESSION_ID);
+                Signed::sign(full_unit, &keychain).into()
+            })
+            .collect();
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let batch: Vec<_> = [&units[2], &units[3], &units[1]]
+            .into_iter()
+            .map(|unit| {
+                runway.on_unit_received(unit.clone());
+                let unit = unit.as_signable();
+                (unit.hash(), unit.round())
+            })
+            .collect();
+
+        runway.on_ordered_batch(batch);
+        let finalized_items = endpoints
+            .finalized_items
+            .try_next()
+            .expect("a batch should be finalized")
+            .expect("channel should be open");
+        let mut handler = RecordingFinalizationHandler::default();
+        let mut batched_handler = BatchRecordingFinalizationHandler::default();
+        finalize_batch(&mut handler, finalized_items.clone());
+        finalize_batch(&mut batched_handler, finalized_items);
+
+        let finalized_data: Vec<_> = handler
+            .finalized_units
+            .iter()
+            .map(|(_, _, _, data)| *data)
+            .collect();
+        assert_eq!(finalized_data, vec![2, 3, 1]);
+        assert_eq!(handler.finalized_data, finalized_data);
+        assert_eq!(batched_handler.finalized_batches, vec![finalized_data]);
+        // Handlers can still be used as trait objects.
+        let _: &dyn FinalizationHandler<Data> = &batched_handler;
+    }
+
+    #[test]
+    fn finalizes_items_with_provenance() {
+        let units: Vec<UncheckedSignedUnit> = produce_units(1)[0]
+            .iter()
+            .map(|unit| {
+                let pre_unit = unit.as_signable().as_pre_unit().clone();
+                let creator = pre_unit.creator();
+                let keychain = Keychain::new(N_MEMBERS, creator);
+                // The unit of the first creator carries no data, but still takes a position.
+                let data = (creator.0 != 0).then_some(creator.0 as Data);
+                let full_unit = FullUnit::new(pre_unit, data, SESSION_ID);
+                Signed::sign(full_unit, &keychain).into()
+            })
+            .collect();
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        for unit in &units {
+            runway.on_unit_received(unit.clone());
+        }
+        let ordered = |indices: &[usize]| -> Vec<_> {
+            indices
+                .iter()
+                .map(|i| {
+                    let unit = units[*i].as_signable();
+                    (unit.hash(), unit.round())
+                })
+                .collect()
+        };
+
+        runway.on_ordered_batch(ordered(&[0]));
+        // Batches without any data are not passed on.
+        assert!(endpoints.finalized_items.try_next().is_err());
+        runway.on_ordered_batch(ordered(&[2, 3, 1]));
+        let mut handler = ItemRecordingFinalizationHandler::default();
+        finalize_pending(&mut endpoints, &mut handler);
+
+        let expected: Vec<_> = [2, 3, 1]
+            .into_iter()
+            .zip(1..)
+            .map(|(i, position)| {
+                let unit = units[i].as_signable();
+                (
+                    position,
+                    (NodeIndex(i), 0, unit.hash().as_ref().to_vec(), i as Data),
+                )
+            })
+            .collect();
+        assert_eq!(handler.finalized_items, expected);
+    }
+
+    /// Finalizes data only once the test opens the gate.
+    struct GatedFinalizationHandler {
+        gate: Option<oneshot::Receiver<()>>,
+        finalized_data: Arc<Mutex<Vec<Data>>>,
+    }
+
+    impl FinalizationHandler<Data> for GatedFinalizationHandler {
+        fn data_finalized(&mut self, data: Data) {
+            if let Some(gate) = self.gate.take() {
+                futures::executor::block_on(gate).expect("the gate should be opened");
+            }
+            self.finalized_data.lock().push(data);
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn keeps_servicing_network_while_finalization_is_slow() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(1);
+        let batch: Vec<_> = units[0][1..3]
+            .iter()
+            .map(|unit| {
+                runway.on_unit_received(unit.clone());
+                let unit = unit.as_signable();
+                (unit.hash(), unit.round())
+            })
+            .collect();
+        let (open_gate, gate) = oneshot::channel();
+        let finalized_data = Arc::new(Mutex::new(Vec::new()));
+        let handler = GatedFinalizationHandler {
+            gate: Some(gate),
+            finalized_data: finalized_data.clone(),
+        };
+        let finalization_handle = tokio::spawn(run_finalization_mechanism(
+            handler,
+            endpoints.finalized_items,
+        ));
+        let (units_from_backup_tx, units_from_backup) = oneshot::channel();
+        units_from_backup_tx
+            .send(Vec::new())
+            .expect("channel should be open");
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let runway_handle = tokio::spawn(runway.run(
+            units_from_backup,
+            Terminator::create_root(exit_rx, "AlephBFT-runway"),
+        ));
+
+        endpoints
+            .ordered_batch_tx
+            .unbounded_send(batch)
+            .expect("channel should be open");
+        let unit = units[0][3].clone();
+        let hash = unit.as_signable().hash();
+        endpoints
+            .unit_messages_from_network
+            .unbounded_send(RunwayNotificationIn::NewUnit(unit))
+            .expect("channel should be open");
+        // The handler is stuck on the batch, yet the unit from the network goes through.
+        assert!(
+            unit_passed_to_consensus(
+                &mut endpoints.notifications_for_consensus,
+                hash,
+                Duration::from_secs(5)
+            )
+            .await
+        );
+        assert!(finalized_data.lock().is_empty());
+
+        open_gate
+            .send(())
+            .expect("the handler should wait for the gate");
+        exit_tx.send(()).expect("channel should be open");
+        runway_handle.await.expect("runway should exit cleanly");
+        finalization_handle
+            .await
+            .expect("finalization should exit cleanly");
+        assert_eq!(*finalized_data.lock(), vec![0, 0]);
+    }
+
+    #[tokio::test]
+    async fn limits_the_rate_of_responses() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let clock = Arc::new(VirtualClock::new());
+        runway.clock = clock.clone();
+        let interval = Duration::from_secs(1);
+        runway.response_rate_limit = Some(ResponseRateLimit {
+            max_responses: 3,
+            interval,
+        });
+        let units = produce_units(2);
+        for unit in units.iter().flatten() {
+            runway.on_unit_received(unit.clone());
+        }
+        let parents = units[0]
+            .iter()
+            .map(|unit| unit.as_signable().hash())
+            .collect();
+        let child = units[1][1].as_signable().hash();
+        runway.store.add_parents(child, parents);
+        let requester = NodeIndex(2);
+        let count_responses = |endpoints: &mut RunwayEndpoints| {
+            let mut responses = 0;
+            while let Some(Some(message)) =
+                endpoints.unit_messages_for_network.next().now_or_never()
+            {
+                if matches!(
+                    message,
+                    RunwayNotificationOut::Response(Response::Parents(..), _)
+                ) {
+                    responses += 1;
+                }
+            }
+            responses
+        };
+
+        for _ in 0..10 {
+            runway.on_unit_message(RunwayNotificationIn::Request(
+                Request::Parents(child),
+                requester,
+            ));
+        }
+        assert_eq!(count_responses(&mut endpoints), 3);
+
+        // Our own units still go out.
+        let own_unit = units[1][0].clone();
+        runway.on_unit_backup_saved(own_unit.clone());
+        assert!(matches!(
+            endpoints.unit_messages_for_network.next().await,
+            Some(RunwayNotificationOut::NewAnyUnit(sent)) if sent == own_unit
+        ));
+        assert!(matches!(
+            endpoints.unit_messages_for_network.next().await,
+            Some(RunwayNotificationOut::NewSelfUnit(sent)) if sent == own_unit
+        ));
+
+        clock.advance(interval);
+        for _ in 0..10 {
+            runway.on_unit_message(RunwayNotificationIn::Request(
+                Request::Parents(child),
+                requester,
+            ));
+        }
+        assert_eq!(count_responses(&mut endpoints), 3);
+    }
+
+    #[tokio::test]
+    async fn abandons_missing_coord_after_its_lifetime() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let clock = Arc::new(VirtualClock::new());
+        runway.clock = clock.clone();
+        let coord = UnitCoord::new(3, NodeIndex(1));
+
+        runway.on_missing_coords(vec![coord]);
+        assert!(matches!(
+            endpoints.unit_messages_for_network.next().await,
+            Some(RunwayNotificationOut::Request(Request::Coord(requested))) if requested == coord
+        ));
+
+        clock.advance(runway.missing_coord_lifetime - Duration::from_secs(1));
+        runway.abandon_expired_missing_coords();
+        assert!(runway.missing_coords.contains_key(&coord));
+        assert!(endpoints.resolved_requests.next().now_or_never().is_none());
+
+        clock.advance(Duration::from_secs(1));
+        runway.abandon_expired_missing_coords();
+        assert!(!runway.missing_coords.contains_key(&coord));
+        // Marking the request as abandoned stops the member from repeating it.
+        assert!(matches!(
+            endpoints.resolved_requests.next().await,
+            Some(RequestResolved {
+                request: Request::Coord(resolved),
+                satisfied_by: None,
+                abandoned: true,
+            }) if resolved == coord
+        ));
+        assert!(endpoints
+            .unit_messages_for_network
+            .next()
+            .now_or_never()
+            .is_none());
+    }
+
+    fn requested_coords(endpoints: &mut RunwayEndpoints) -> Vec<UnitCoord> {
+        let mut coords = Vec::new();
+        while let Some(Some(message)) = endpoints.unit_messages_for_network.next().now_or_never() {
+            if let RunwayNotificationOut::Request(Request::Coord(coord)) = message {
+                coords.push(coord);
+            }
+        }
+        coords
+    }
+
+    #[tokio::test]
+    async fn requests_coords_to_catch_up_with_newest_unit_response() {
+        let units = produce_units(5);
+        let responder = NodeIndex(1);
+        let response =
+            NewestUnitResponse::new(NODE_ID, responder, Some(units[4][NODE_ID.0].clone()), 0);
+        let response =
+            Signed::sign(response, &Keychain::new(N_MEMBERS, responder)).into_unchecked();
+        for catch_up_burst in [None, Some(2)] {
+            let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+            runway.catch_up_burst = catch_up_burst;
+            for unit in units[0].iter() {
+                runway.on_unit_received(unit.clone());
+            }
+            requested_coords(&mut endpoints);
+
+            runway.on_unit_message(RunwayNotificationIn::Response(Response::NewestUnit(
+                response.clone(),
+            )));
+            let expected: HashSet<_> = match catch_up_burst {
+                // Only the rounds right above the ones we hold, up to the burst size.
+                Some(_) => (1..=2)
+                    .flat_map(|round| {
+                        (0..N_MEMBERS.0).map(move |id| UnitCoord::new(round, NodeIndex(id)))
+                    })
+                    .collect(),
+                None => HashSet::new(),
+            };
+            let requested = requested_coords(&mut endpoints);
+            assert_eq!(requested.len(), expected.len());
+            assert_eq!(requested.into_iter().collect::<HashSet<_>>(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_a_pending_request_only_once() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let coord = UnitCoord::new(3, NodeIndex(1));
+
+        runway.on_missing_coords(vec![coord]);
+        runway.on_missing_coords(vec![coord]);
+        // Any other path requesting the same coord is suppressed as well.
+        runway.send_message_for_network(RunwayNotificationOut::Request(Request::Coord(coord)));
+        assert!(matches!(
+            endpoints.unit_messages_for_network.next().await,
+            Some(RunwayNotificationOut::Request(Request::Coord(requested))) if requested == coord
+        ));
+        assert!(endpoints
+            .unit_messages_for_network
+            .next()
+            .now_or_never()
+            .is_none());
+
+        // Once resolved, the coord can be requested again.
+        runway.resolve_missing_coord(&coord);
+        runway.on_missing_coords(vec![coord]);
+        assert!(matches!(
+            endpoints.unit_messages_for_network.next().await,
+            Some(RunwayNotificationOut::Request(Request::Coord(requested))) if requested == coord
+        ));
+    }
+
+    #[tokio::test]
+    async fn drops_only_rebroadcasts_and_responses_under_backpressure() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let (unit_messages_for_network, mut unit_messages_from_runway) = tracked_channel(Some(1));
+        runway.unit_messages_for_network = unit_messages_for_network;
+        let unit = produce_units(1)[0][1].clone();
+        let coord = UnitCoord::new(3, NodeIndex(1));
+
+        runway.send_message_for_network(RunwayNotificationOut::NewAnyUnit(unit.clone()));
+        assert!(runway.unit_messages_for_network.is_full());
+        runway.send_message_for_network(RunwayNotificationOut::NewAnyUnit(unit.clone()));
+        runway.on_request_newest(NodeIndex(1), 0);
+        runway.on_missing_coords(vec![coord]);
+        runway.send_message_for_network(RunwayNotificationOut::NewSelfUnit(unit.clone()));
+
+        assert!(matches!(
+            unit_messages_from_runway.next().await,
+            Some(RunwayNotificationOut::NewAnyUnit(_))
+        ));
+        assert!(matches!(
+            unit_messages_from_runway.next().await,
+            Some(RunwayNotificationOut::Request(Request::Coord(requested))) if requested == coord
+        ));
+        assert!(matches!(
+            unit_messages_from_runway.next().await,
+            Some(RunwayNotificationOut::NewSelfUnit(_))
+        ));
+        assert!(unit_messages_from_runway.next().now_or_never().is_none());
+    }
+
+    fn runway_with_deterministic_request_hashing() -> (TestRunway, RunwayEndpoints) {
+        let (mut runway, endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let request_hashing = RequestHashing::new(|| Box::new(DefaultHasher::new()));
+        runway.missing_coords = HashMap::with_hasher(request_hashing.clone());
+        runway.missing_parents = HashSet::with_hasher(request_hashing);
+        (runway, endpoints)
+    }
+
+    #[tokio::test]
+    async fn deduplicates_missing_coords_with_deterministic_hashing() {
+        let coords: Vec<_> = (1..20)
+            .flat_map(|round| {
+                (1..N_MEMBERS.0).map(move |creator| UnitCoord::new(round, NodeIndex(creator)))
+            })
+            .collect();
+        let mut orders = Vec::new();
+        for _ in 0..2 {
+            let (mut runway, mut endpoints) = runway_with_deterministic_request_hashing();
+            runway.on_missing_coords(coords.clone());
+            runway.on_missing_coords(coords.iter().rev().cloned().collect());
+
+            let mut requested = Vec::new();
+            while let Some(Some(RunwayNotificationOut::Request(Request::Coord(coord)))) =
+                endpoints.unit_messages_for_network.next().now_or_never()
+            {
+                requested.push(coord);
+            }
+            assert_eq!(requested, coords);
+            orders.push(runway.missing_coords.keys().cloned().collect::<Vec<_>>());
+        }
+        assert_eq!(orders[0].len(), coords.len());
+        assert_eq!(orders[0], orders[1]);
+    }
+
+    #[test]
+    fn reports_units_from_creators_outside_of_the_committee() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, _endpoints) = setup_runway(metrics.clone());
+        let creator = NodeIndex(N_MEMBERS.0);
+        let pre_unit = PreUnit::new(creator, 0, ControlHash::new(&NodeMap::with_size(N_MEMBERS)));
+        let unit: UncheckedSignedUnit = Signed::sign(
+            FullUnit::new(pre_unit, Some(0), SESSION_ID),
+            &Keychain::new(N_MEMBERS, creator),
+        )
+        .into();
+
+        assert!(matches!(
+            runway.validator.validate_unit(unit.clone()),
+            Err(ValidationError::UnknownCreator(_))
+        ));
+        runway.on_unit_received(unit);
+        assert_eq!(
+            metrics.count(|call| *call == MetricsCall::UnknownCreator),
+            1
+        );
+        assert_eq!(runway.store.height(), None);
+    }
+
+    #[test]
+    fn drops_coord_response_with_unrequested_unit() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, _endpoints) = setup_runway(metrics.clone());
+        let units = produce_units(1);
+        let requested = units[0][1].as_signable().coord();
+        runway.missing_coords.insert(requested, Instant::now());
+        let mismatched = units[0][2].clone();
+        let mismatched_coord = mismatched.as_signable().coord();
+
+        runway.on_unit_message(RunwayNotificationIn::Response(Response::Coord(mismatched)));
+
+        assert!(runway.missing_coords.contains_key(&requested));
+        assert!(runway.store.unit_by_coord(mismatched_coord).is_none());
+        assert_eq!(
+            metrics.count(|call| *call == MetricsCall::UnrequestedUnit),
+            1
+        );
+
+        runway.on_unit_message(RunwayNotificationIn::Response(Response::Coord(
+            units[0][1].clone(),
+        )));
+        assert!(!runway.missing_coords.contains_key(&requested));
+        assert_eq!(
+            metrics.count(|call| *call == MetricsCall::UnrequestedUnit),
+            1
+        );
+    }
+
+    #[test]
+    fn accepts_late_coord_response_for_abandoned_coord() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, _endpoints) = setup_runway(metrics.clone());
+        let clock = Arc::new(VirtualClock::new());
+        runway.clock = clock.clone();
+        let unit = produce_units(1)[0][1].clone();
+        let coord = unit.as_signable().coord();
+
+        runway.on_missing_coords(vec![coord]);
+        clock.advance(runway.missing_coord_lifetime);
+        runway.abandon_expired_missing_coords();
+        assert!(!runway.missing_coords.contains_key(&coord));
+
+        runway.on_unit_message(RunwayNotificationIn::Response(Response::Coord(unit)));
+        assert!(runway.store.unit_by_coord(coord).is_some());
+        assert_eq!(
+            metrics.count(|call| *call == MetricsCall::UnrequestedUnit),
+            0
+        );
+    }
+
+    #[test]
+    fn forgets_abandoned_coords_after_their_lifetime() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, _endpoints) = setup_runway(metrics.clone());
+        let clock = Arc::new(VirtualClock::new());
+        runway.clock = clock.clone();
+        let unit = produce_units(1)[0][1].clone();
+        let coord = unit.as_signable().coord();
+
+        runway.on_missing_coords(vec![coord]);
+        clock.advance(runway.missing_coord_lifetime);
+        runway.abandon_expired_missing_coords();
+        clock.advance(runway.missing_coord_lifetime);
+        runway.abandon_expired_missing_coords();
+        assert!(runway.abandoned_coords.is_empty());
+
+        runway.on_unit_message(RunwayNotificationIn::Response(Response::Coord(unit)));
+        assert!(runway.store.unit_by_coord(coord).is_none());
+        assert_eq!(
+            metrics.count(|call| *call == MetricsCall::UnrequestedUnit),
+            1
+        );
+    }
+
+    /// Defers all the units until told to accept them.
+    struct UpdatingInspector {
+        updating: AtomicBool,
+    }
+
+    impl UnitInspector<Data> for UpdatingInspector {
+        fn accepts(&self, _coord: UnitCoord, _data: Option<&Data>) -> bool {
+            true
+        }
+
+        fn inspect(&self, _coord: UnitCoord, _data: Option<&Data>) -> Inspection {
+            match self.updating.load(Ordering::SeqCst) {
+                true => Inspection::Deferred,
+                false => Inspection::Accepted,
+            }
+        }
+    }
+
+    #[test]
+    fn retries_units_failing_validation_transiently() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let inspector = Arc::new(UpdatingInspector {
+            updating: AtomicBool::new(true),
+        });
+        runway.validator = runway
+            .validator
+            .clone()
+            .with_inspector(Some(inspector.clone()));
+        runway.validation_retry_interval = Some(Duration::from_secs(1));
+        let counters = runway.counters.clone();
+        let unit = produce_units(1)[0][1].clone();
+        let coord = unit.as_signable().coord();
+
+        assert!(matches!(
+            runway.validator.validate_unit(unit.clone()),
+            Err(ValidationError::DeferredByInspector(_))
+        ));
+        runway.on_unit_received(unit);
+        assert!(runway.store.unit_by_coord(coord).is_none());
+        assert_eq!(runway.deferred_units.len(), 1);
+
+        // Still deferred, so kept for another try.
+        runway.retry_deferred_units();
+        assert_eq!(runway.deferred_units.len(), 1);
+
+        inspector.updating.store(false, Ordering::SeqCst);
+        runway.retry_deferred_units();
+        assert!(runway.deferred_units.is_empty());
+        assert!(runway.store.unit_by_coord(coord).is_some());
+        // Retries do not count as receiving the unit again.
+        assert_eq!(counters.snapshot_and_reset().units_received, 1);
+    }
+
+    #[test]
+    fn reports_transient_validation_failures_without_retries() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let inspector = Arc::new(UpdatingInspector {
+            updating: AtomicBool::new(true),
+        });
+        runway.validator = runway.validator.clone().with_inspector(Some(inspector));
+        let unit = produce_units(1)[0][1].clone();
+
+        runway.on_unit_received(unit);
+        assert!(runway.deferred_units.is_empty());
+        assert_eq!(
+            endpoints
+                .validation_failures
+                .try_next()
+                .expect("channel should be open"),
+            Some(ValidationFailure {
+                sender: None,
+                error: ValidationErrorKind::DeferredByInspector,
+            })
+        );
+    }
+
+    #[test]
+    fn drops_units_failing_validation_permanently() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        runway.validation_retry_interval = Some(Duration::from_secs(1));
+        let unit = produce_units(1)[0][2].clone();
+        let bad_keychain: BadSigning<Keychain> = Keychain::new(N_MEMBERS, NodeIndex(2)).into();
+        let badly_signed: UncheckedSignedUnit =
+            Signed::sign(unit.as_signable().clone(), &bad_keychain).into();
+
+        let error = runway
+            .validator
+            .validate_unit(badly_signed.clone())
+            .expect_err("the unit is badly signed");
+        assert!(!error.is_transient());
+        runway.on_unit_received(badly_signed);
+        assert!(runway.deferred_units.is_empty());
+        assert!(runway
+            .store
+            .unit_by_coord(unit.as_signable().coord())
+            .is_none());
+    }
+
+    #[test]
+    fn gives_up_on_unit_with_unresolvable_parents() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, _endpoints) = setup_runway(metrics.clone());
+        runway.wrong_control_hash_limit = 3;
+        let units = produce_units(2);
+        let child = units[1][0].clone();
+        let child_hash = child.as_signable().hash();
+        runway.on_unit_received(child);
+
+        let mut wrong_parents = units[0].clone();
+        wrong_parents[3] = fork_unit(NodeIndex(3), 0, 7);
+        runway.on_consensus_notification(NotificationOut::WrongControlHash(child_hash));
+        assert!(runway.missing_parents.contains(&child_hash));
+        runway.on_parents_response(NodeIndex(1), child_hash, wrong_parents.clone());
+        assert!(runway.abandoned_units.is_empty());
+        runway.on_parents_response(NodeIndex(1), child_hash, wrong_parents);
+
+        assert!(runway.abandoned_units.contains(&child_hash));
+        assert!(!runway.missing_parents.contains(&child_hash));
+        assert_eq!(metrics.count(|call| *call == MetricsCall::AbandonedUnit), 1);
+
+        // An abandoned unit is not reconsidered, even with the right parents.
+        runway.on_parents_response(NodeIndex(1), child_hash, units[0].clone());
+        runway.on_consensus_notification(NotificationOut::WrongControlHash(child_hash));
+        assert!(runway.store.get_parents(child_hash).is_none());
+        assert!(!runway.missing_parents.contains(&child_hash));
+        assert_eq!(metrics.count(|call| *call == MetricsCall::AbandonedUnit), 1);
+    }
+
+    #[test]
+    fn reports_session_id_and_max_round() {
+        let (runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        assert_eq!(runway.session_id(), SESSION_ID);
+        assert_eq!(runway.max_round(), MAX_ROUND);
+
+        let snapshot = runway.status_snapshot(&runway.runway_status_snapshot());
+        assert_eq!(snapshot.session_id, SESSION_ID);
+        assert_eq!(snapshot.max_round, MAX_ROUND);
+    }
+
+    /// The most recently reported depth of every channel.
+    fn channel_depths(metrics: &RecordingMetrics) -> HashMap<InternalChannel, usize> {
+        metrics
+            .arguments(|call| match call {
+                MetricsCall::ChannelDepth(channel, depth) => Some((*channel, *depth)),
+                _ => None,
+            })
+            .into_iter()
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn reports_backlog_of_internal_channels() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, mut endpoints) = setup_runway(metrics.clone());
+        let units = produce_units(1);
+
+        for unit in &units[0] {
+            let unit = preunit_to_unit(unit.as_signable().as_pre_unit().clone(), SESSION_ID);
+            runway.send_consensus_notification(NotificationIn::NewUnits(vec![unit]));
+        }
+        for unit in units[0].iter().take(2) {
+            runway.send_message_for_network(RunwayNotificationOut::NewAnyUnit(unit.clone()));
+        }
+        for _ in 0..3 {
+            endpoints
+                .ordered_batch_tx
+                .unbounded_send(Vec::new())
+                .expect("channel should be open");
+        }
+        runway.report_channel_depths();
+        assert_eq!(
+            channel_depths(&metrics),
+            HashMap::from([
+                (InternalChannel::NotificationsForConsensus, 4),
+                (InternalChannel::UnitMessagesForNetwork, 2),
+                (InternalChannel::OrderedBatches, 3),
+            ])
+        );
+
+        endpoints.notifications_for_consensus.next().await;
+        runway.report_channel_depths();
+        assert_eq!(
+            channel_depths(&metrics)[&InternalChannel::NotificationsForConsensus],
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn reports_status_at_the_configured_interval() {
+        let metrics = Arc::new(RecordingMetrics::default());
+        let (mut runway, _endpoints) = setup_runway(metrics.clone());
+        runway.status_report_interval = Some(Duration::from_millis(20));
+        let (units_from_backup_tx, units_from_backup) = oneshot::channel();
+        units_from_backup_tx
+            .send(Vec::new())
+            .expect("channel should be open");
+        let (exit_tx, exit_rx) = oneshot::channel();
+        let runway_handle = tokio::spawn(runway.run(
+            units_from_backup,
+            Terminator::create_root(exit_rx, "AlephBFT-runway"),
+        ));
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        exit_tx.send(()).expect("channel should be open");
+        runway_handle.await.expect("runway should exit cleanly");
+        assert!(
+            metrics.count(|call| matches!(
+                call,
+                MetricsCall::ChannelDepth(InternalChannel::NotificationsForConsensus, _)
+            )) > 1
+        );
+    }
+
+    #[test]
+    fn status_report_emits_a_snapshot() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(1);
+        for unit in &units[0][1..3] {
+            runway.on_unit_received(unit.clone());
+        }
+        runway.on_missing_coords(vec![
+            UnitCoord::new(1, NodeIndex(1)),
+            UnitCoord::new(1, NodeIndex(2)),
+            UnitCoord::new(2, NodeIndex(3)),
+        ]);
+
+        runway.status_report();
+
+        let snapshot = endpoints
+            .status_snapshots
+            .try_next()
+            .expect("channel should be open")
+            .expect("status snapshot should be emitted");
+        assert_eq!(snapshot.units_held, 2);
+        assert_eq!(snapshot.height, Some(0));
+        assert_eq!(snapshot.store.size(), 2);
+        assert_eq!(
+            snapshot.missing_coords,
+            vec![
+                UnitCoord::new(1, NodeIndex(1)),
+                UnitCoord::new(1, NodeIndex(2)),
+                UnitCoord::new(2, NodeIndex(3)),
+            ]
+        );
+        assert!(snapshot.missing_parents.is_empty());
+    }
+
+    #[test]
+    fn runway_counters_are_reset_by_snapshots() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let counters = runway.counters.clone();
+        let units = produce_units(1);
+        for unit in &units[0][1..4] {
+            runway.on_unit_received(unit.clone());
+        }
+        runway.send_message_for_network(RunwayNotificationOut::Request(Request::Coord(
+            UnitCoord::new(1, NodeIndex(1)),
+        )));
+        runway.on_request_newest(NodeIndex(2), 7);
+        runway.on_request_newest(NodeIndex(3), 7);
+
+        assert_eq!(
+            counters.snapshot_and_reset(),
+            RunwayCountersSnapshot {
+                units_received: 3,
+                units_broadcast: 0,
+                requests_sent: 1,
+                responses_sent: 2,
+                forkers_detected: 0,
+            }
+        );
+        assert_eq!(
+            counters.snapshot_and_reset(),
+            RunwayCountersSnapshot::default()
+        );
+
+        runway.on_unit_received(units[0][1].clone());
+        assert_eq!(counters.snapshot_and_reset().units_received, 1);
+    }
+
+    #[test]
+    fn status_snapshot_holds_what_gets_reported() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(2);
+        for unit in &units[0] {
+            runway.on_unit_received(unit.clone());
+        }
+        let unit_hash = units[1][1].as_signable().hash();
+        runway.on_unit_received(units[1][1].clone());
+        runway.on_missing_coords(vec![
+            UnitCoord::new(2, NodeIndex(3)),
+            UnitCoord::new(1, NodeIndex(2)),
+        ]);
+        runway.on_wrong_control_hash(unit_hash);
+
+        let snapshot: RunwayStatusSnapshot<Hasher64> = runway.runway_status_snapshot();
+
+        assert_eq!(snapshot.store.size(), N_MEMBERS.0 + 1);
+        assert_eq!(snapshot.store.height(), Some(1));
+        assert!(snapshot.store.forkers().is_empty());
+        assert_eq!(
+            snapshot.missing_coords,
+            vec![
+                UnitCoord::new(1, NodeIndex(2)),
+                UnitCoord::new(2, NodeIndex(3))
+            ]
+        );
+        assert_eq!(snapshot.missing_parents, vec![unit_hash]);
+        assert!(snapshot
+            .to_string()
+            .contains("missing coords - [(2, 1), (3, 2)]"));
+
+        let observed = runway.status_snapshot(&snapshot);
+        assert_eq!(observed.store, snapshot.store);
+        assert_eq!(observed.missing_coords, snapshot.missing_coords);
+        assert_eq!(observed.missing_parents, vec![unit_hash.as_ref().to_vec()]);
+    }
+
+    /// The peers known to have received our unit of the given round, as reported in the status
+    /// snapshot, if it is tracked.
+    fn own_unit_reached(runway: &TestRunway, round: Round) -> Option<Vec<NodeIndex>> {
+        runway
+            .status_snapshot(&runway.runway_status_snapshot())
+            .own_units_reached
+            .into_iter()
+            .find(|(unit_round, _)| *unit_round == round)
+            .map(|(_, reached)| reached)
+    }
+
+    #[test]
+    fn tracks_peers_that_received_own_unit() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        runway.unit_propagation = Some(UnitPropagation::new(2, N_MEMBERS));
+        let units = produce_units(2);
+        let own_coord = UnitCoord::new(0, NODE_ID);
+
+        runway.on_unit_backup_saved(units[0][NODE_ID.0].clone());
+        assert_eq!(own_unit_reached(&runway, 0), Some(Vec::new()));
+        runway.on_request_coord(NodeIndex(1), own_coord);
+        runway.on_request_coord(NodeIndex(2), own_coord);
+        runway.on_request_coord(NodeIndex(1), own_coord);
+        // Requests for units of others say nothing about ours.
+        runway.on_request_coord(NodeIndex(3), UnitCoord::new(0, NodeIndex(1)));
+        assert_eq!(
+            own_unit_reached(&runway, 0),
+            Some(vec![NodeIndex(1), NodeIndex(2)])
+        );
+
+        // A unit built on top of ours shows its creator received it as well, once its parents
+        // are known.
+        for unit in &units[0] {
+            runway.on_unit_received(unit.clone());
+        }
+        runway.on_unit_received(units[1][3].clone());
+        assert_eq!(
+            own_unit_reached(&runway, 0),
+            Some(vec![NodeIndex(1), NodeIndex(2)])
+        );
+        let parent_hashes = units[0]
+            .iter()
+            .map(|parent| parent.as_signable().hash())
+            .collect();
+        runway.on_consensus_notification(NotificationOut::AddedToDag(
+            units[1][3].as_signable().hash(),
+            parent_hashes,
+        ));
+        assert_eq!(
+            own_unit_reached(&runway, 0),
+            Some(vec![NodeIndex(1), NodeIndex(2), NodeIndex(3)])
+        );
+        assert_eq!(own_unit_reached(&runway, 1), None);
+
+        runway.status_report();
+        let snapshot = endpoints
+            .status_snapshots
+            .try_next()
+            .expect("channel should be open")
+            .expect("status snapshot should be emitted");
+        assert_eq!(snapshot.newest_own_unit_reached, Some(3));
+        assert_eq!(
+            snapshot.own_units_reached,
+            vec![(0, vec![NodeIndex(1), NodeIndex(2), NodeIndex(3)])]
+        );
+    }
+
+    #[tokio::test]
+    async fn restores_only_the_newest_backed_up_own_unit() {
+        let (mut runway, mut endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let units = produce_units(2);
+        let own_units: Vec<_> = units.iter().map(|round| round[NODE_ID.0].clone()).collect();
+
+        runway.on_unit_backup_saved(own_units[0].clone());
+        runway.on_consensus_notification(NotificationOut::MissingOwnParent(0));
+        match endpoints.notifications_for_consensus.next().await {
+            Some(NotificationIn::RestoredOwnUnit(unit)) => {
+                assert_eq!(unit, own_units[0].as_signable().unit())
+            }
+            _ => panic!("own unit of round 0 should be restored"),
+        }
+
+        // Restoring the unit of round 0 now would let the creator make a second unit of round 1.
+        runway.on_unit_backup_saved(own_units[1].clone());
+        runway.on_consensus_notification(NotificationOut::MissingOwnParent(0));
+        runway.on_consensus_notification(NotificationOut::MissingOwnParent(2));
+        assert_eq!(runway.tx_consensus.depth(), 0);
+    }
+
+    #[tokio::test]
+    async fn holds_creator_snapshots_until_own_unit_backed_up() {
+        let (mut runway, _endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let (_snapshots_from_creator, from_creator) = mpsc::unbounded();
+        let (for_application, mut snapshots) = mpsc::unbounded();
+        runway.creator_snapshots = Some(CreatorSnapshots::new(from_creator, for_application));
+        let units = produce_units(3);
+        let own_units: Vec<_> = units.iter().map(|round| round[NODE_ID.0].clone()).collect();
+
+        runway.on_creator_snapshot(0, vec![0]);
+        runway.on_creator_snapshot(1, vec![1]);
+        // A crash now must not leave a snapshot ahead of the backup.
+        assert!(snapshots.try_next().is_err());
+
+        runway.on_unit_backup_saved(own_units[0].clone());
+        assert_eq!(snapshots.try_next(), Ok(Some(vec![0])));
+        runway.on_unit_backup_saved(own_units[1].clone());
+        runway.on_unit_backup_saved(own_units[2].clone());
+        assert_eq!(snapshots.try_next(), Ok(Some(vec![1])));
+        assert!(snapshots.try_next().is_err());
+
+        // Snapshots of rounds already backed up go out right away.
+        runway.on_creator_snapshot(2, vec![2]);
+        assert_eq!(snapshots.try_next(), Ok(Some(vec![2])));
+    }
+
+    #[tokio::test]
+    async fn restarts_consensus_the_configured_number_of_times() {
+        let (mut runway, endpoints) = setup_runway(Arc::new(NoopMetrics));
+        let (restart_requests_tx, mut restart_requests) = mpsc::unbounded();
+        let (restarted_tx, restarted) = mpsc::unbounded();
+        runway.consensus_restarts = Some(ConsensusRestarts {
+            requests: restart_requests_tx,
+            restarted,
+        });
+        runway.consensus_restart_attempts = 2;
+        let units = produce_units(1);
+        for unit in units[0].iter() {
+            runway.on_unit_received(unit.clone());
+        }
+        let (units_from_backup_tx, units_from_backup) = oneshot::channel();
+        units_from_backup_tx
+            .send(Vec::new())
+            .expect("channel should be open");
+        let (_exit_tx, exit_rx) = oneshot::channel();
+        let runway_handle = tokio::spawn(runway.run(
+            units_from_backup,
+            Terminator::create_root(exit_rx, "AlephBFT-runway"),
+        ));
+
+        let mut consensus_ends = vec![(
+            endpoints._notifications_from_consensus,
+            endpoints.ordered_batch_tx,
+        )];
+        for _ in 0..2 {
+            // Dropping the ends held by consensus looks just like consensus stopping.
+            consensus_ends.clear();
+            assert_eq!(restart_requests.next().await, Some(()));
+            let (tx_consensus, mut notifications_for_consensus) = tracked_unbounded();
+            let (notifications_from_consensus, rx_consensus) = mpsc::unbounded();
+            let (ordered_batch_tx, ordered_batch_rx) = mpsc::unbounded();
+            let (starting_round_tx, starting_round) = oneshot::channel();
+            restarted_tx
+                .unbounded_send(ConsensusIO {
+                    tx_consensus,
+                    rx_consensus,
+                    ordered_batch_rx,
+                    starting_round: starting_round_tx,
+                })
+                .expect("channel should be open");
+            match notifications_for_consensus.next().await {
+                Some(NotificationIn::NewUnits(replayed)) => assert_eq!(replayed.len(), N_MEMBERS.0),
+                _ => panic!("units should be replayed to the restarted consensus"),
+            }
+            assert_eq!(starting_round.await, Ok(Some(1)));
+            consensus_ends.push((notifications_from_consensus, ordered_batch_tx));
+        }
+        consensus_ends.clear();
+
+        tokio::time::timeout(Duration::from_secs(5), runway_handle)
+            .await
+            .expect("runway should exit once out of restart attempts")
+            .expect("runway should exit cleanly");
+        assert_eq!(restart_requests.next().await, None);
+    }
+
+    /// Records the hints the tasks were spawned with, without running them.
+    #[derive(Clone, Default)]
+    struct HintRecordingSpawner {
+        hints: Arc<Mutex<Vec<(&'static str, SchedulingHint)>>>,
+    }
+
+    impl SpawnHandle for HintRecordingSpawner {
+        fn spawn(&self, _name: &'static str, _task: impl Future<Output = ()> + Send + 'static) {}
+
+        fn spawn_essential(
+            &self,
+            name: &'static str,
+            task: impl Future<Output = ()> + Send + 'static,
+        ) -> TaskHandle {
+            self.spawn_essential_with_hint(name, task, SchedulingHint::default())
+        }
+
+        fn spawn_essential_with_hint(
+            &self,
+            name: &'static str,
+            _task: impl Future<Output = ()> + Send + 'static,
+            hint: SchedulingHint,
+        ) -> TaskHandle {
+            self.hints.lock().push((name, hint));
+            Box::pin(pending())
+        }
+    }
+
+    #[test]
+    fn spawns_consensus_with_the_configured_hint() {
+        let config = gen_config(NODE_ID, N_MEMBERS, gen_delay_config())
+            .with_consensus_scheduling_hint(SchedulingHint::Critical);
+        let spawner = HintRecordingSpawner::default();
+        let (_exit_tx, exit_rx) = oneshot::channel();
+        let mut terminator = Terminator::create_root(exit_rx, "AlephBFT-runway");
+
+        let (_consensus_handle, _consensus_io) =
+            spawn_consensus::<Hasher64>(&config, &spawner, Default::default(), &mut terminator);
+
+        assert_eq!(
+            *spawner.hints.lock(),
+            vec![("runway/consensus", SchedulingHint::Critical)]
+        );
+    }
+}
@@ -1,11 +1,11 @@
 use crate::{
     units::{FullUnit, PreUnit, SignedUnit},
-    Data, DataProvider, Hasher, MultiKeychain, NodeIndex, Receiver, Sender, SessionId, Signed,
-    Terminator,
+    Clock, Data, DataProvider, Hasher, MultiKeychain, NodeIndex, Receiver, RepeatedDataPolicy,
+    Sender, SessionId, Signed, SystemClock, Terminator,
 };
 use futures::{pin_mut, FutureExt, StreamExt};
 use log::{debug, error};
-use std::marker::PhantomData;
+use std::{sync::Arc, time::Duration};
 
 /// The component responsible for packing Data from DataProvider into received PreUnits,
 /// and signing the outcome, thus creating SignedUnits that are sent back to Runway.
@@ -21,7 +21,11 @@ where
     signed_units_for_runway: Sender<SignedUnit<H, D, MK>>,
     keychain: MK,
     session_id: SessionId,
-    _phantom: PhantomData<D>,
+    repeated_data_policy: RepeatedDataPolicy,
+    genesis_data: Option<D>,
+    last_data: Option<D>,
+    data_provider_timeout: Option<Duration>,
+    clock: Arc<dyn Clock>,
 }
 
 impl<H, D, DP, MK> Packer<H, D, DP, MK>
@@ -44,14 +48,70 @@ where
             signed_units_for_runway,
             keychain,
             session_id,
-            _phantom: PhantomData,
+            repeated_data_policy: RepeatedDataPolicy::default(),
+            genesis_data: None,
+            last_data: None,
+            data_provider_timeout: None,
+            clock: Arc::new(SystemClock),
         }
     }
 
+    pub fn with_repeated_data_policy(mut self, repeated_data_policy: RepeatedDataPolicy) -> Self {
+        self.repeated_data_policy = repeated_data_policy;
+        self
+    }
+
+    /// Makes round-zero units carry the given data instead of asking the provider.
+    pub fn with_genesis_data(mut self, genesis_data: Option<D>) -> Self {
+        self.genesis_data = genesis_data;
+        self
+    }
+
+    /// Makes units carry no data whenever the provider does not come up with any within the
+    /// given time, instead of waiting for it indefinitely.
+    pub fn with_data_provider_timeout(mut self, data_provider_timeout: Option<Duration>) -> Self {
+        self.data_provider_timeout = data_provider_timeout;
+        self
+    }
+
+    /// Sets the source of time for the data provider timeout, [`SystemClock`] by default.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     fn index(&self) -> NodeIndex {
         self.keychain.index()
     }
 
+    /// Asks the provider for data, dropping the request once the timeout, if any, passes.
+    async fn get_data(&mut self) -> Option<D> {
+        let timeout = match self.data_provider_timeout {
+            Some(timeout) => timeout,
+            None => return self.data_provider.get_data().await,
+        };
+        let index = self.index();
+        futures::select! {
+            data = self.data_provider.get_data().fuse() => data,
+            _ = self.clock.sleep(timeout).fuse() => {
+                debug!(target: "AlephBFT-packer", "{:?} No data provided within {:?}, creating a unit without data.", index, timeout);
+                None
+            },
+        }
+    }
+
+    fn filter_repeated(&mut self, data: Option<D>) -> Option<D> {
+        let repeated = data.is_some() && data == self.last_data;
+        self.last_data = data.clone();
+        match self.repeated_data_policy {
+            RepeatedDataPolicy::Omit if repeated => {
+                debug!(target: "AlephBFT-packer", "{:?} Omitting data repeated from the previous unit.", self.index());
+                None
+            }
+            _ => data,
+        }
+    }
+
     /// The main loop.
     async fn pack(&mut self) {
         loop {
@@ -64,8 +124,18 @@ where
                 }
             };
             debug!(target: "AlephBFT-packer", "{:?} Received PreUnit.", self.index());
-            let data = self.data_provider.get_data().await;
-            debug!(target: "AlephBFT-packer", "{:?} Received data.", self.index());
+            let data = match (preunit.round(), &self.genesis_data) {
+                (0, Some(genesis_data)) => {
+                    debug!(target: "AlephBFT-packer", "{:?} Using genesis data.", self.index());
+                    Some(genesis_data.clone())
+                }
+                _ => {
+                    let data = self.get_data().await;
+                    debug!(target: "AlephBFT-packer", "{:?} Received data.", self.index());
+                    data
+                }
+            };
+            let data = self.filter_repeated(data);
             let full_unit = FullUnit::new(preunit, data, self.session_id);
             let signed_unit = Signed::sign(full_unit, &self.keychain);
             if self
@@ -100,14 +170,15 @@ mod tests {
     use super::Packer;
     use crate::{
         units::{ControlHash, PreUnit, SignedUnit},
-        NodeCount, NodeIndex, Receiver, Sender, SessionId, Terminator,
+        NodeCount, NodeIndex, Receiver, RepeatedDataPolicy, Sender, SessionId, Terminator,
     };
     use aleph_bft_mock::{Data, DataProvider, Hasher64, Keychain, StalledDataProvider};
-    use aleph_bft_types::NodeMap;
+    use aleph_bft_types::{DataProvider as DataProviderT, NodeMap};
     use futures::{
         channel::{mpsc, oneshot},
         pin_mut, FutureExt, StreamExt,
     };
+    use std::time::Duration;
 
     const SESSION_ID: SessionId = 43;
     const NODE_ID: NodeIndex = NodeIndex(0);
@@ -239,4 +310,113 @@ mod tests {
             .await
             .expect("Packer terminated with an error");
     }
+
+    #[tokio::test]
+    async fn packs_without_data_after_provider_timeout() {
+        let keychain = Keychain::new(N_MEMBERS, NODE_ID);
+        let (preunits_channel, preunits_from_runway) = mpsc::unbounded::<PreUnit<Hasher64>>();
+        let (signed_units_for_runway, mut signed_units_channel) = mpsc::unbounded();
+        let mut packer = Packer::new(
+            StalledDataProvider::new(),
+            preunits_from_runway,
+            signed_units_for_runway,
+            keychain,
+            SESSION_ID,
+        )
+        .with_data_provider_timeout(Some(Duration::from_millis(50)));
+        let (_exit_tx, exit_rx) = oneshot::channel();
+        let control_hash = ControlHash::new(&NodeMap::with_size(N_MEMBERS));
+        let preunit = PreUnit::new(NODE_ID, 0, control_hash);
+        preunits_channel
+            .unbounded_send(preunit.clone())
+            .expect("Packer PreUnit channel closed");
+        let packer_handle = packer
+            .run(Terminator::create_root(exit_rx, "AlephBFT-packer"))
+            .fuse();
+        pin_mut!(packer_handle);
+        let unit = futures::select! {
+            unit = signed_units_channel.next() => match unit {
+                Some(unit) => unit,
+                None => panic!("Packer SignedUnit channel closed"),
+            },
+            _ = packer_handle => panic!("Packer terminated early"),
+        }
+        .into_unchecked()
+        .into_signable();
+        assert_eq!(unit.as_pre_unit(), &preunit);
+        assert_eq!(unit.data(), &None);
+    }
+
+    struct RepeatingDataProvider(Data);
+
+    #[async_trait::async_trait]
+    impl DataProviderT<Data> for RepeatingDataProvider {
+        async fn get_data(&mut self) -> Option<Data> {
+            Some(self.0)
+        }
+    }
+
+    async fn pack_twice(
+        policy: RepeatedDataPolicy,
+        genesis_data: Option<Data>,
+    ) -> Vec<Option<Data>> {
+        let keychain = Keychain::new(N_MEMBERS, NODE_ID);
+        let (preunits_channel, preunits_from_runway) = mpsc::unbounded::<PreUnit<Hasher64>>();
+        let (signed_units_for_runway, mut signed_units_channel) = mpsc::unbounded();
+        let mut packer = Packer::new(
+            RepeatingDataProvider(7),
+            preunits_from_runway,
+            signed_units_for_runway,
+            keychain,
+            SESSION_ID,
+        )
+        .with_repeated_data_policy(policy)
+        .with_genesis_data(genesis_data);
+        let (_exit_tx, exit_rx) = oneshot::channel();
+        let control_hash = ControlHash::new(&NodeMap::with_size(N_MEMBERS));
+        for round in 0..2 {
+            preunits_channel
+                .unbounded_send(PreUnit::new(NODE_ID, round, control_hash.clone()))
+                .expect("Packer PreUnit channel closed");
+        }
+        let packer_handle = packer
+            .run(Terminator::create_root(exit_rx, "AlephBFT-packer"))
+            .fuse();
+        pin_mut!(packer_handle);
+        let mut data = Vec::new();
+        while data.len() < 2 {
+            futures::select! {
+                unit = signed_units_channel.next() => match unit {
+                    Some(unit) => data.push(*unit.into_unchecked().into_signable().data()),
+                    None => panic!("Packer SignedUnit channel closed"),
+                },
+                _ = packer_handle => panic!("Packer terminated early"),
+            }
+        }
+        data
+    }
+
+    #[tokio::test]
+    async fn repeated_data_kept_by_default() {
+        assert_eq!(
+            pack_twice(RepeatedDataPolicy::default(), None).await,
+            vec![Some(7), Some(7)]
+        );
+    }
+
+    #[tokio::test]
+    async fn repeated_data_omitted_when_configured() {
+        assert_eq!(
+            pack_twice(RepeatedDataPolicy::Omit, None).await,
+            vec![Some(7), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn round_zero_carries_genesis_data() {
+        assert_eq!(
+            pack_twice(RepeatedDataPolicy::default(), Some(42)).await,
+            vec![Some(42), Some(7)]
+        );
+    }
 }
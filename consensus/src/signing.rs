@@ -0,0 +1,98 @@
+/// Domains of the payloads signed during consensus. The tag of the domain is prepended to the hash
+/// of a payload before signing it, so that a signature over one kind of payload can never be
+/// passed off as a signature over another kind.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SigningDomain {
+    Unit,
+    Alert,
+    NewestUnitResponse,
+    Rmc,
+}
+
+impl SigningDomain {
+    fn tag(&self) -> u8 {
+        match self {
+            SigningDomain::Unit => 0,
+            SigningDomain::Alert => 1,
+            SigningDomain::NewestUnitResponse => 2,
+            SigningDomain::Rmc => 3,
+        }
+    }
+
+    /// The message actually signed for a payload with the given hash.
+    pub(crate) fn message(&self, hash: &[u8]) -> Vec<u8> {
+        let mut message = Vec::with_capacity(hash.len() + 1);
+        message.push(self.tag());
+        message.extend_from_slice(hash);
+        message
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SigningDomain;
+    use crate::{
+        alerts::RmcHash,
+        units::{ControlHash, FullUnit, PreUnit},
+        Keychain as _, NodeCount, NodeIndex, NodeMap, Signed,
+    };
+    use aleph_bft_mock::{Data, Hasher64, Keychain};
+
+    #[test]
+    fn unit_signature_is_not_an_alert_signature() {
+        let n_members = NodeCount(4);
+        let creator = NodeIndex(1);
+        let keychain = Keychain::new(n_members, creator);
+        let pre_unit = PreUnit::new(creator, 0, ControlHash::new(&NodeMap::with_size(n_members)));
+        let full_unit: FullUnit<Hasher64, Data> = FullUnit::new(pre_unit, Some(0), 0);
+        let unit_hash = full_unit.hash();
+        let signature = Signed::sign(full_unit, &keychain)
+            .into_unchecked()
+            .signature();
+
+        assert!(keychain.verify(
+            &SigningDomain::Unit.message(unit_hash.as_ref()),
+            &signature,
+            creator
+        ));
+        assert!(!keychain.verify(
+            &SigningDomain::Alert.message(unit_hash.as_ref()),
+            &signature,
+            creator
+        ));
+    }
+
+    #[test]
+    fn rmc_signature_is_valid_only_in_rmc() {
+        let n_members = NodeCount(4);
+        let creator = NodeIndex(1);
+        let keychain = Keychain::new(n_members, creator);
+        let pre_unit = PreUnit::new(creator, 0, ControlHash::new(&NodeMap::with_size(n_members)));
+        let full_unit: FullUnit<Hasher64, Data> = FullUnit::new(pre_unit, Some(0), 0);
+        let hash = full_unit.hash();
+        let rmc_signature = Signed::sign_with_index(RmcHash(hash), &keychain)
+            .into_unchecked()
+            .signature();
+        let unit_signature = Signed::sign(full_unit, &keychain)
+            .into_unchecked()
+            .signature();
+
+        assert!(keychain.verify(
+            &SigningDomain::Rmc.message(hash.as_ref()),
+            &rmc_signature,
+            creator
+        ));
+        for domain in [
+            SigningDomain::Unit,
+            SigningDomain::Alert,
+            SigningDomain::NewestUnitResponse,
+        ] {
+            assert!(!keychain.verify(&domain.message(hash.as_ref()), &rmc_signature, creator));
+        }
+        assert!(!keychain.verify(
+            &SigningDomain::Rmc.message(hash.as_ref()),
+            &unit_signature,
+            creator
+        ));
+    }
+}
@@ -1,7 +1,9 @@
+use crate::{Clock, SystemClock};
 use std::{
     cmp::Ordering,
     collections::{binary_heap::PeekMut, BinaryHeap},
     fmt::{Debug, Formatter},
+    sync::Arc,
     time,
     time::Duration,
 };
@@ -25,9 +27,16 @@ impl<T: Eq> Ord for ScheduledTask<T> {
     }
 }
 
-#[derive(Clone, Default)]
+#[derive(Clone)]
 pub struct TaskQueue<T: Eq + PartialEq> {
     queue: BinaryHeap<ScheduledTask<T>>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<T: Eq + PartialEq> Default for TaskQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T: Eq + PartialEq> Debug for TaskQueue<T> {
@@ -46,17 +55,24 @@ impl<T: Eq> TaskQueue<T> {
     pub fn new() -> Self {
         Self {
             queue: BinaryHeap::new(),
+            clock: Arc::new(SystemClock),
         }
     }
 
+    /// Sets the source of time deciding which tasks are due, [`SystemClock`] by default.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Schedules `task` for as soon as possible.
     pub fn schedule_now(&mut self, task: T) {
-        self.schedule(task, time::Instant::now());
+        self.schedule(task, self.clock.now());
     }
 
     /// Schedules `task` for execution after `delay`.
     pub fn schedule_in(&mut self, task: T, delay: Duration) {
-        self.schedule(task, time::Instant::now() + delay)
+        self.schedule(task, self.clock.now() + delay)
     }
 
     /// Schedules `task` for execution at `scheduled_time`.
@@ -72,7 +88,7 @@ impl<T: Eq> TaskQueue<T> {
     pub fn pop_due_task(&mut self) -> Option<T> {
         let scheduled_task = self.queue.peek_mut()?;
 
-        if scheduled_task.scheduled_time <= time::Instant::now() {
+        if scheduled_task.scheduled_time <= self.clock.now() {
             Some(PeekMut::pop(scheduled_task).task)
         } else {
             None
@@ -6,9 +6,10 @@ use std::{
 
 use crate::{
     extender::ExtenderUnit,
+    metrics::TrackedReceiver,
     runway::{NotificationIn, NotificationOut},
     units::{ControlHash, Unit, UnitCoord},
-    Hasher, NodeCount, NodeIndex, NodeMap, Receiver, Round, Sender, Terminator,
+    Hasher, NodeCount, NodeIndex, NodeMap, Round, Sender, Terminator,
 };
 use codec::{Decode, Encode};
 use log::{debug, trace, warn};
@@ -120,7 +121,7 @@ type SyncClosure<X, Y> = Box<dyn FnMut(X) -> Y + Sync + Send + 'static>;
 pub(crate) struct Terminal<H: Hasher> {
     node_id: NodeIndex,
     // A channel for receiving notifications (units mainly)
-    ntfct_rx: Receiver<NotificationIn<H>>,
+    ntfct_rx: TrackedReceiver<NotificationIn<H>>,
     // A channel to push outgoing notifications
     ntfct_tx: Sender<NotificationOut<H>>,
     // A Queue to handle events happening in the Terminal. The reason of this being a queue is because
@@ -128,6 +129,8 @@ pub(crate) struct Terminal<H: Hasher> {
     // in a FIFO order (as in BFS) and not recursively (as in DFS).
     event_queue: VecDeque<TerminalEvent<H>>,
     post_insert: Vec<SyncClosure<TerminalUnit<H>, ()>>,
+    // Called with our own units restored from backup which are already in the Dag.
+    on_restore: Vec<SyncClosure<TerminalUnit<H>, ()>>,
     // Here we store all the units -- the ones in Dag and the ones "hanging".
     unit_store: HashMap<H::Hash, TerminalUnit<H>>,
 
@@ -146,7 +149,7 @@ pub(crate) struct Terminal<H: Hasher> {
 impl<H: Hasher> Terminal<H> {
     pub(crate) fn new(
         node_id: NodeIndex,
-        ntfct_rx: Receiver<NotificationIn<H>>,
+        ntfct_rx: TrackedReceiver<NotificationIn<H>>,
         ntfct_tx: Sender<NotificationOut<H>>,
     ) -> Self {
         Terminal {
@@ -155,6 +158,7 @@ impl<H: Hasher> Terminal<H> {
             ntfct_tx,
             event_queue: VecDeque::new(),
             post_insert: Vec::new(),
+            on_restore: Vec::new(),
             unit_store: HashMap::new(),
             unit_by_coord: HashMap::new(),
             children_coord: HashMap::new(),
@@ -255,18 +259,16 @@ impl<H: Hasher> Terminal<H> {
             .get(u_hash)
             .expect("Unit to be added to dag must be in store")
             .clone();
+        // Notify before the creator can learn about the unit, so that any unit built on top of
+        // it is reported after it, which keeps parents ahead of children in the backup.
+        let parent_hashes = u.parents.clone().into_values().collect();
+        self.send_notification(NotificationOut::AddedToDag(*u_hash, parent_hashes));
         self.post_insert.iter_mut().for_each(|f| f(u.clone()));
         if let Some(children) = self.children_hash.remove(u_hash) {
             for v_hash in children {
                 self.new_parent_in_dag(&v_hash);
             }
         }
-        let mut parent_hashes = Vec::new();
-        for p_hash in u.parents.into_values() {
-            parent_hashes.push(p_hash);
-        }
-
-        self.send_notification(NotificationOut::AddedToDag(*u_hash, parent_hashes));
     }
 
     // We set the correct parent hashes for unit u.
@@ -357,6 +359,24 @@ impl<H: Hasher> Terminal<H> {
         self.post_insert.push(hook);
     }
 
+    pub(crate) fn register_restore_hook(&mut self, hook: SyncClosure<TerminalUnit<H>, ()>) {
+        self.on_restore.push(hook);
+    }
+
+    fn on_restored_own_unit(&mut self, u: Unit<H>) {
+        match self.unit_store.get(&u.hash()) {
+            Some(tu) if tu.status == UnitStatus::InDag => {
+                let tu = tu.clone();
+                self.on_restore.iter_mut().for_each(|f| f(tu.clone()));
+            }
+            // Otherwise it reaches the hooks once it is added to the Dag.
+            _ => {
+                self.add_to_store(u);
+                self.handle_events();
+            }
+        }
+    }
+
     fn send_notification(&mut self, notification: NotificationOut<H>) {
         if self.ntfct_tx.unbounded_send(notification).is_err() {
             warn!(target: "AlephBFT-terminal", "{:?} Notification channel should be open", self.node_id);
@@ -379,7 +399,10 @@ impl<H: Hasher> Terminal<H> {
                             self.update_on_wrong_hash_response(u_hash, p_hashes);
                             self.handle_events();
                         },
-                        _ => {}
+                        Some(NotificationIn::RestoredOwnUnit(u)) => {
+                            self.on_restored_own_unit(u);
+                        },
+                        None => {}
                     }
                 }
                 _ = terminator.get_exit().fuse() => {
@@ -1,10 +1,12 @@
 use crate::{
-    alerts::{Alert, AlertConfig, AlertMessage, ForkProof, ForkingNotification, Handler, Service},
+    alerts::{
+        Alert, AlertConfig, AlertMessage, ForkProof, ForkingNotification, Handler, RmcHash, Service,
+    },
     units::{ControlHash, FullUnit, PreUnit},
     Index, Indexed, Keychain as _, NodeCount, NodeIndex, NodeMap, Recipient, Round, Signable,
-    Signed, Terminator, UncheckedSigned,
+    Signed, SystemClock, Terminator, UncheckedSigned,
 };
-use aleph_bft_mock::{Data, Hasher64, Keychain, PartialMultisignature, Signature};
+use aleph_bft_mock::{Data, Hasher64, Keychain, PartialMultisignature, Signature, Spawner};
 use aleph_bft_rmc::Message as RmcMessage;
 use futures::{
     channel::{mpsc, oneshot},
@@ -15,6 +17,7 @@ use log::trace;
 use std::{
     collections::{HashMap, HashSet},
     hash::Hash,
+    sync::Arc,
     time::Duration,
 };
 
@@ -223,12 +226,16 @@ impl TestCase {
             notifications_for_units,
             alerts_from_units,
             n_members,
-        );
+            Arc::new(SystemClock),
+        )
+        .with_verification_spawner(Spawner);
         let alerter_handler = Handler::new(
             keychain,
             AlertConfig {
                 n_members,
                 session_id: 0,
+                max_units_per_forker_round: n_members.0,
+                confirmation_threshold: None,
             },
         );
 
@@ -309,7 +316,7 @@ async fn reacts_to_correctly_incoming_alert() {
     let mut test_case = TestCase::new(n_members);
     let fork_proof = test_case.fork_proof(forker, 0);
     let alert = test_case.alert(alerter_index, fork_proof.clone());
-    let signed_alert_hash = test_case.indexed_unchecked_signed(Signable::hash(&alert), own_index);
+    let signed_alert_hash = test_case.indexed_unchecked_signed(RmcHash(alert.hash()), own_index);
     let signed_alert = test_case.unchecked_signed(alert.clone(), alerter_index);
     test_case
         .incoming_message(AlertMessage::ForkAlert(signed_alert))
@@ -330,7 +337,7 @@ async fn notifies_about_finished_alert() {
     let mut test_case = TestCase::new(n_members);
     let fork_proof = test_case.fork_proof(forker, 0);
     let alert = test_case.alert(alerter_index, fork_proof.clone());
-    let alert_hash = Signable::hash(&alert);
+    let alert_hash = alert.hash();
     let signed_alert = test_case.unchecked_signed(alert.clone(), alerter_index);
     test_case
         .incoming_message(AlertMessage::ForkAlert(signed_alert))
@@ -338,7 +345,7 @@ async fn notifies_about_finished_alert() {
         .wait();
     for i in 1..n_members.0 - 1 {
         let node_id = NodeIndex(i);
-        let signed_alert_hash = test_case.indexed_unchecked_signed(alert_hash, node_id);
+        let signed_alert_hash = test_case.indexed_unchecked_signed(RmcHash(alert_hash), node_id);
         test_case.incoming_message(AlertMessage::RmcMessage(
             node_id,
             RmcMessage::SignedHash(signed_alert_hash),
@@ -357,8 +364,8 @@ async fn asks_about_unknown_alert() {
     let mut test_case = TestCase::new(n_members);
     let fork_proof = test_case.fork_proof(forker, 0);
     let alert = test_case.alert(alerter_index, fork_proof.clone());
-    let alert_hash = Signable::hash(&alert);
-    let signed_alert_hash = test_case.indexed_unchecked_signed(alert_hash, alerter_index);
+    let alert_hash = alert.hash();
+    let signed_alert_hash = test_case.indexed_unchecked_signed(RmcHash(alert_hash), alerter_index);
     test_case
         .incoming_message(AlertMessage::RmcMessage(
             alerter_index,
@@ -383,7 +390,7 @@ async fn ignores_wrong_alert() {
     let wrong_alert = test_case.alert(forker, wrong_fork_proof.clone());
     let signed_wrong_alert = test_case.unchecked_signed(wrong_alert.clone(), forker);
     let signed_wrong_alert_hash =
-        test_case.indexed_unchecked_signed(Signable::hash(&wrong_alert), own_index);
+        test_case.indexed_unchecked_signed(RmcHash(wrong_alert.hash()), own_index);
     test_case
         .incoming_message(AlertMessage::ForkAlert(signed_wrong_alert))
         .unexpected_notification(ForkingNotification::Forker(wrong_fork_proof));
@@ -406,6 +413,29 @@ async fn ignores_wrong_alert() {
     test_case.run(own_index).await;
 }
 
+#[tokio::test]
+async fn verifies_queued_alerts_concurrently() {
+    let n_members = NodeCount(7);
+    let own_index = NodeIndex(0);
+    let mut test_case = TestCase::new(n_members);
+    let valid_unit = test_case.unchecked_signed_unit(NodeIndex(1), 0, 0);
+    let wrong_fork_proof = (valid_unit.clone(), valid_unit);
+    let wrong_alert = test_case.alert(NodeIndex(1), wrong_fork_proof.clone());
+    let signed_wrong_alert = test_case.unchecked_signed(wrong_alert, NodeIndex(1));
+    test_case
+        .incoming_message(AlertMessage::ForkAlert(signed_wrong_alert))
+        .unexpected_notification(ForkingNotification::Forker(wrong_fork_proof));
+    for (alerter, forker) in [(1, 4), (2, 5), (3, 6)] {
+        let fork_proof = test_case.fork_proof(NodeIndex(forker), 0);
+        let alert = test_case.alert(NodeIndex(alerter), fork_proof.clone());
+        let signed_alert = test_case.unchecked_signed(alert, NodeIndex(alerter));
+        test_case
+            .incoming_message(AlertMessage::ForkAlert(signed_alert))
+            .outgoing_notification(ForkingNotification::Forker(fork_proof));
+    }
+    test_case.run(own_index).await;
+}
+
 #[tokio::test]
 async fn responds_to_alert_queries() {
     let n_members = NodeCount(7);
@@ -414,9 +444,9 @@ async fn responds_to_alert_queries() {
     let forker = NodeIndex(6);
     let mut test_case = TestCase::new(n_members);
     let alert = test_case.alert(own_index, test_case.fork_proof(forker, 0));
-    let alert_hash = Signable::hash(&alert);
+    let alert_hash = alert.hash();
     let signed_alert = test_case.unchecked_signed(alert.clone(), own_index);
-    let signed_alert_hash = test_case.indexed_unchecked_signed(alert_hash, own_index);
+    let signed_alert_hash = test_case.indexed_unchecked_signed(RmcHash(alert_hash), own_index);
     test_case
         .incoming_alert(alert.clone())
         .outgoing_message(
@@ -455,10 +485,10 @@ async fn notifies_only_about_multisigned_alert() {
     let mut test_case = TestCase::new(n_members);
     let fork_proof = test_case.fork_proof(forker, 0);
     let empty_alert = test_case.alert(double_committer, fork_proof.clone());
-    let empty_alert_hash = Signable::hash(&empty_alert);
+    let empty_alert_hash = empty_alert.hash();
     let signed_empty_alert = test_case.unchecked_signed(empty_alert.clone(), double_committer);
     let signed_empty_alert_hash =
-        test_case.indexed_unchecked_signed(empty_alert_hash, double_committer);
+        test_case.indexed_unchecked_signed(RmcHash(empty_alert_hash), double_committer);
     test_case
         .incoming_message(AlertMessage::ForkAlert(signed_empty_alert))
         .incoming_message(AlertMessage::RmcMessage(
@@ -474,11 +504,11 @@ async fn notifies_only_about_multisigned_alert() {
         fork_proof.clone(),
         vec![forker_unit.clone()],
     );
-    let nonempty_alert_hash = Signable::hash(&nonempty_alert);
+    let nonempty_alert_hash = nonempty_alert.hash();
     let signed_nonempty_alert =
         test_case.unchecked_signed(nonempty_alert.clone(), double_committer);
     let signed_nonempty_alert_hash =
-        test_case.indexed_unchecked_signed(nonempty_alert_hash, double_committer);
+        test_case.indexed_unchecked_signed(RmcHash(nonempty_alert_hash), double_committer);
     let keychain = test_case.keychain(double_committer);
     let mut multisigned_nonempty_alert_hash = signed_nonempty_alert_hash
         .check(keychain)
@@ -487,7 +517,7 @@ async fn notifies_only_about_multisigned_alert() {
     for i in 1..n_members.0 - 2 {
         let node_id = NodeIndex(i);
         let signed_nonempty_alert_hash =
-            test_case.indexed_unchecked_signed(nonempty_alert_hash, node_id);
+            test_case.indexed_unchecked_signed(RmcHash(nonempty_alert_hash), node_id);
         multisigned_nonempty_alert_hash = multisigned_nonempty_alert_hash.add_signature(
             signed_nonempty_alert_hash
                 .check(keychain)
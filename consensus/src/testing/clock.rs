@@ -0,0 +1,136 @@
+use crate::{
+    testing::{gen_config, gen_delay_config, init_log, spawn_honest_member_with_config},
+    Clock, NodeCount, SpawnHandle,
+};
+use aleph_bft_mock::{Router, Spawner};
+use futures::{
+    channel::oneshot,
+    future::{self, BoxFuture},
+    FutureExt, StreamExt,
+};
+use parking_lot::Mutex;
+use serial_test::serial;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+struct VirtualClockState {
+    elapsed: Duration,
+    sleepers: Vec<(Duration, oneshot::Sender<()>)>,
+}
+
+/// A clock standing still until advanced, waking up all the sleepers it passes by.
+pub struct VirtualClock {
+    start: Instant,
+    state: Mutex<VirtualClockState>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        VirtualClock {
+            start: Instant::now(),
+            state: Mutex::new(VirtualClockState {
+                elapsed: Duration::ZERO,
+                sleepers: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().elapsed
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let mut state = self.state.lock();
+        state.elapsed += duration;
+        let elapsed = state.elapsed;
+        let (due, pending): (Vec<_>, Vec<_>) = state
+            .sleepers
+            .drain(..)
+            .filter(|(_, sleeper)| !sleeper.is_canceled())
+            .partition(|(deadline, _)| *deadline <= elapsed);
+        state.sleepers = pending;
+        for (_, sleeper) in due {
+            let _ = sleeper.send(());
+        }
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        if duration.is_zero() {
+            return Box::pin(future::ready(()));
+        }
+        let (sleeper, wake_up) = oneshot::channel();
+        let mut state = self.state.lock();
+        let deadline = state.elapsed + duration;
+        state.sleepers.push((deadline, sleeper));
+        Box::pin(wake_up.map(|_| ()))
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn finalizes_in_simulated_time() {
+    init_log();
+    let n_members = NodeCount(4);
+    let n_batches = 3;
+    let unit_creation_delay = Duration::from_secs(60);
+    let clock = Arc::new(VirtualClock::new());
+    let spawner = Spawner::new();
+    let (net_hub, networks) = Router::new(n_members, 1.0);
+    spawner.spawn("network-hub", net_hub);
+
+    let mut members = Vec::new();
+    for (network, _) in networks {
+        let mut delay_config = gen_delay_config();
+        delay_config.unit_creation_delay = Arc::new(move |_| unit_creation_delay);
+        let config = gen_config(network.index(), n_members, delay_config).with_clock(clock.clone());
+        members.push(spawn_honest_member_with_config(
+            spawner,
+            config,
+            vec![],
+            network,
+        ));
+    }
+
+    let started = Instant::now();
+    let driver = tokio::spawn({
+        let clock = clock.clone();
+        async move {
+            loop {
+                clock.advance(Duration::from_secs(1));
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+    });
+
+    let mut batches = Vec::new();
+    for member in members.iter_mut() {
+        let mut member_batches = Vec::new();
+        for _ in 0..n_batches {
+            member_batches.push(member.finalization_rx.next().await.unwrap());
+        }
+        batches.push(member_batches);
+    }
+    let real_elapsed = started.elapsed();
+    let simulated_elapsed = clock.elapsed();
+    driver.abort();
+
+    for member_batches in &batches {
+        assert_eq!(member_batches, &batches[0]);
+    }
+    // Nothing gets created before the first creation delay passes, which the wall clock never got to.
+    assert!(simulated_elapsed >= unit_creation_delay);
+    assert!(real_elapsed < unit_creation_delay);
+
+    for member in members {
+        let _ = member.exit_tx.send(());
+        let _ = member.handle.await;
+    }
+}
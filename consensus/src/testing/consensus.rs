@@ -1,12 +1,13 @@
 use crate::{
     consensus,
+    creation::{CreatorSnapshot, SnapshotIO},
     runway::{NotificationIn, NotificationOut},
     testing::{complete_oneshot, gen_config, gen_delay_config, init_log},
     units::{ControlHash, PreUnit, Unit, UnitCoord},
     Hasher, NodeIndex, SpawnHandle, Terminator,
 };
 use aleph_bft_mock::{Hasher64, Spawner};
-use codec::Encode;
+use codec::{Decode, Encode};
 use futures::{
     channel::{
         mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
@@ -109,6 +110,9 @@ impl HonestHub {
                 // Safe to ignore in testing.
                 // Normally this is used in Member to answer parents requests.
             }
+            NotificationOut::MissingOwnParent(_round) => {
+                // Safe to ignore in testing, as no units get lost.
+            }
         }
     }
 }
@@ -171,11 +175,12 @@ async fn agree_on_first_batch() {
             "consensus",
             consensus::run(
                 conf,
-                rx,
+                rx.into(),
                 tx,
                 batch_tx,
                 spawner,
                 starting_round,
+                Default::default(),
                 Terminator::create_root(exit_rx, "AlephBFT-consensus"),
             ),
         ));
@@ -213,11 +218,12 @@ async fn catches_wrong_control_hash() {
         "consensus",
         consensus::run(
             conf,
-            rx_in,
+            rx_in.into(),
             tx_out,
             batch_tx,
             spawner,
             starting_round,
+            Default::default(),
             Terminator::create_root(exit_rx, "AlephBFT-consensus"),
         ),
     );
@@ -247,3 +253,51 @@ async fn catches_wrong_control_hash() {
 
     consensus_handle.await.expect("The node is honest.");
 }
+
+#[tokio::test]
+async fn reports_creator_snapshots() {
+    init_log();
+    let n_nodes = 4;
+    let spawner = Spawner::new();
+    let (_tx_in, rx_in) = unbounded();
+    let (tx_out, mut rx_out) = unbounded();
+    let (snapshots_tx, mut snapshots_rx) = unbounded();
+
+    let conf = gen_config(NodeIndex(0), n_nodes.into(), gen_delay_config());
+    let (exit_tx, exit_rx) = oneshot::channel();
+    let (batch_tx, _batch_rx) = unbounded();
+    let starting_round = complete_oneshot(Some(0));
+
+    let consensus_handle = spawner.spawn_essential(
+        "consensus",
+        consensus::run::<Hasher64>(
+            conf,
+            rx_in.into(),
+            tx_out,
+            batch_tx,
+            spawner,
+            starting_round,
+            SnapshotIO {
+                initial: None,
+                snapshots: Some(snapshots_tx),
+            },
+            Terminator::create_root(exit_rx, "AlephBFT-consensus"),
+        ),
+    );
+    loop {
+        if let NotificationOut::CreatedPreUnit(pu, _) = rx_out.next().await.unwrap() {
+            assert_eq!(pu.round(), 0);
+            break;
+        }
+    }
+    let (round, snapshot) = snapshots_rx
+        .next()
+        .await
+        .expect("snapshot should be reported");
+    assert_eq!(round, 0);
+    assert!(CreatorSnapshot::<Hasher64>::decode(&mut &snapshot[..]).is_ok());
+
+    let _ = exit_tx.send(());
+
+    consensus_handle.await.expect("The node is honest.");
+}
@@ -1,10 +1,10 @@
 use crate::{
+    runway::decode_backup,
     testing::{init_log, spawn_honest_member, HonestMember, Network, ReconnectSender},
-    units::{UncheckedSignedUnit, UnitCoord},
+    units::UnitCoord,
     NodeCount, NodeIndex, SpawnHandle, TaskHandle,
 };
 use aleph_bft_mock::{Data, Hasher64, Router, Signature, Spawner};
-use codec::Decode;
 use futures::{
     channel::{mpsc, oneshot},
     StreamExt,
@@ -67,6 +67,7 @@ fn connect_nodes(
                 saved_state,
                 exit_tx,
                 handle,
+                ..
             } = spawn_honest_member(*spawner, ix, n_members, vec![], network);
             (
                 ix,
@@ -109,6 +110,7 @@ async fn reconnect_nodes(
             saved_state,
             exit_tx,
             handle,
+            ..
         } = spawn_honest_member(*spawner, *node_id, n_members, saved_units.clone(), network);
         reconnected_nodes.push((
             *node_id,
@@ -125,11 +127,14 @@ async fn reconnect_nodes(
     reconnected_nodes
 }
 
-fn verify_backup(buf: &mut &[u8]) -> HashSet<UnitCoord> {
+fn verify_backup(buf: &[u8]) -> HashSet<UnitCoord> {
     let mut already_saved = HashSet::new();
 
-    while !buf.is_empty() {
-        let unit = UncheckedSignedUnit::<Hasher64, Data, Signature>::decode(buf).unwrap();
+    let units = match decode_backup::<Hasher64, Data, Signature>(buf) {
+        Ok((units, None)) => units,
+        result => panic!("backup should be valid, got {:?}", result),
+    };
+    for unit in units {
         let full_unit = unit.as_signable();
         let coord = full_unit.coord();
         let parent_ids = &full_unit.as_pre_unit().control_hash().parents_mask;
@@ -223,10 +228,10 @@ async fn crashed_nodes_recover(n_members: NodeCount, n_batches: usize) {
     }
 
     for (ix, (_, saved_units_before)) in killed {
-        let saved_before_coords = verify_backup(&mut &saved_units_before[..]);
+        let saved_before_coords = verify_backup(&saved_units_before);
         let NodeData { saved_units, .. } = node_data.get(&ix).expect("should contain killed node");
 
-        let saved_after_coords = verify_backup(&mut &saved_units.lock()[..]);
+        let saved_after_coords = verify_backup(&saved_units.lock());
         assert!(saved_before_coords.is_subset(&saved_after_coords));
     }
 
@@ -260,7 +265,7 @@ async fn saves_units_properly() {
     }
 
     for (_, saved_units) in killed {
-        let _ = verify_backup(&mut &saved_units[..]);
+        let _ = verify_backup(&saved_units);
     }
 }
 
@@ -2,14 +2,23 @@ use crate::{
     creation::{run, IO},
     runway::NotificationOut as GenericNotificationOut,
     testing::{gen_config, gen_delay_config},
-    units::{FullUnit as GenericFullUnit, PreUnit as GenericPreUnit, Unit as GenericUnit},
-    NodeCount, Receiver, Round, Sender, Terminator,
+    units::{
+        ControlHash, FullUnit as GenericFullUnit, PreUnit as GenericPreUnit, Unit as GenericUnit,
+    },
+    CreationDecision, CreationGate, NodeCount, NodeMap, Receiver, Round, Sender, Terminator,
 };
 use aleph_bft_mock::{Data, Hasher64};
 use futures::{
     channel::{mpsc, oneshot},
     FutureExt, StreamExt,
 };
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 type PreUnit = GenericPreUnit<Hasher64>;
 type Unit = GenericUnit<Hasher64>;
@@ -85,9 +94,14 @@ fn setup_test(n_members: NodeCount) -> TestSetup {
     for node_ix in 0..n_members.0 {
         let (parents_for_creator, parents_from_controller) = mpsc::unbounded();
 
+        // Nothing gets finalized here, and the default lag is never exceeded.
+        let (_, finalized_rounds) = mpsc::unbounded();
         let io = IO {
             incoming_parents: parents_from_controller,
             outgoing_units: notifications_for_controller.clone(),
+            finalized_rounds,
+            initial_snapshot: None,
+            snapshots: None,
         };
         let config = gen_config(node_ix.into(), n_members, gen_delay_config());
         let (starting_round_for_consensus, starting_round) = oneshot::channel();
@@ -304,3 +318,225 @@ async fn late_creators_should_create_dag() {
     );
     finish(killers, handles).await;
 }
+
+// Lost own parent test
+// A single creator never gets its own unit of round 0 back, so it asks for it to be restored and,
+// once it is, continues creating without ever creating a second unit for any round.
+#[tokio::test]
+async fn creator_recovers_lost_own_parent() {
+    let n_members = NodeCount(4);
+    let (parents_for_creator, incoming_parents) = mpsc::unbounded();
+    let (outgoing_units, mut units_from_creator) = mpsc::unbounded();
+    let (_finalized_rounds_tx, finalized_rounds) = mpsc::unbounded();
+    let io = IO {
+        incoming_parents,
+        outgoing_units,
+        finalized_rounds,
+        initial_snapshot: None,
+        snapshots: None,
+    };
+    let config = gen_config(0.into(), n_members, gen_delay_config())
+        .with_own_parent_recovery_delay(Duration::from_millis(100));
+    let (starting_round_for_consensus, starting_round) = oneshot::channel();
+    let (killer, exit) = oneshot::channel();
+    let handle = tokio::spawn(run(
+        config.into(),
+        io,
+        starting_round,
+        Terminator::create_root(exit, "AlephBFT-creator"),
+    ));
+    starting_round_for_consensus
+        .send(Some(0))
+        .expect("Sending the starting round should work.");
+
+    let own_unit = match units_from_creator.next().await {
+        Some(NotificationOut::CreatedPreUnit(preunit, _)) => preunit_to_unit(preunit),
+        _ => panic!("Creator should create a unit of round 0."),
+    };
+    for creator in 1..n_members.0 {
+        let preunit = PreUnit::new(
+            creator.into(),
+            0,
+            ControlHash::new(&NodeMap::with_size(n_members)),
+        );
+        parents_for_creator
+            .unbounded_send(preunit_to_unit(preunit))
+            .expect("Creator input channel isn't closed.");
+    }
+
+    assert_eq!(
+        units_from_creator.next().await,
+        Some(NotificationOut::MissingOwnParent(0))
+    );
+    parents_for_creator
+        .unbounded_send(own_unit.clone())
+        .expect("Creator input channel isn't closed.");
+    let mut created_rounds = Vec::new();
+    while let Some(notification) = units_from_creator.next().await {
+        if let NotificationOut::CreatedPreUnit(preunit, _) = notification {
+            created_rounds.push(preunit.round());
+            break;
+        }
+    }
+    assert_eq!(created_rounds, vec![1]);
+
+    // Restoring the unit again must not make the creator go back to an earlier round.
+    parents_for_creator
+        .unbounded_send(own_unit)
+        .expect("Creator input channel isn't closed.");
+    let mut later = units_from_creator.next();
+    futures::select! {
+        notification = later => panic!("Unexpected notification {:?}.", notification),
+        _ = futures_timer::Delay::new(Duration::from_millis(300)).fuse() => {},
+    }
+
+    killer.send(()).unwrap();
+    handle.await.unwrap();
+}
+
+/// Answers every unit created by a single creator with a full round of units including it,
+/// returning the rounds of the created units once the creator stops creating.
+async fn feed_creator_until_idle(
+    units_from_creator: &mut Receiver<NotificationOut>,
+    parents_for_creator: &Sender<Unit>,
+    n_members: NodeCount,
+) -> Vec<Round> {
+    let mut created_rounds = Vec::new();
+    loop {
+        let preunit = futures::select! {
+            notification = units_from_creator.next() => match notification {
+                Some(NotificationOut::CreatedPreUnit(preunit, _)) => preunit,
+                notification => panic!("Unexpected notification {:?}.", notification),
+            },
+            _ = futures_timer::Delay::new(Duration::from_millis(500)).fuse() => return created_rounds,
+        };
+        let round = preunit.round();
+        created_rounds.push(round);
+        parents_for_creator
+            .unbounded_send(preunit_to_unit(preunit))
+            .expect("Creator input channel isn't closed.");
+        for creator in 1..n_members.0 {
+            let preunit = PreUnit::new(
+                creator.into(),
+                round,
+                ControlHash::new(&NodeMap::with_size(n_members)),
+            );
+            parents_for_creator
+                .unbounded_send(preunit_to_unit(preunit))
+                .expect("Creator input channel isn't closed.");
+        }
+    }
+}
+
+// Finalization lag test
+// A single creator is given all the parents it needs, but nothing gets finalized, so it stops
+// after getting the allowed number of rounds ahead, and continues once finalization advances.
+#[tokio::test]
+async fn creator_pauses_too_far_ahead_of_finalization() {
+    let n_members = NodeCount(4);
+    let max_finalization_lag = 2;
+    let (parents_for_creator, incoming_parents) = mpsc::unbounded();
+    let (outgoing_units, mut units_from_creator) = mpsc::unbounded();
+    let (finalized_rounds_for_creator, finalized_rounds) = mpsc::unbounded();
+    let io = IO {
+        incoming_parents,
+        outgoing_units,
+        finalized_rounds,
+        initial_snapshot: None,
+        snapshots: None,
+    };
+    let config = gen_config(0.into(), n_members, gen_delay_config())
+        .with_max_finalization_lag(max_finalization_lag);
+    let (starting_round_for_consensus, starting_round) = oneshot::channel();
+    let (killer, exit) = oneshot::channel();
+    let handle = tokio::spawn(run(
+        config.into(),
+        io,
+        starting_round,
+        Terminator::create_root(exit, "AlephBFT-creator"),
+    ));
+    starting_round_for_consensus
+        .send(Some(0))
+        .expect("Sending the starting round should work.");
+
+    assert_eq!(
+        feed_creator_until_idle(&mut units_from_creator, &parents_for_creator, n_members).await,
+        vec![0, 1, 2]
+    );
+
+    finalized_rounds_for_creator
+        .unbounded_send(1)
+        .expect("Creator finalized rounds channel isn't closed.");
+    assert_eq!(
+        feed_creator_until_idle(&mut units_from_creator, &parents_for_creator, n_members).await,
+        vec![3]
+    );
+
+    killer.send(()).unwrap();
+    handle.await.unwrap();
+}
+
+/// Defers creating the unit of a single round for as long as it is told to.
+struct RoundGate {
+    round: Round,
+    closed: AtomicBool,
+}
+
+impl CreationGate for RoundGate {
+    fn decide(&self, round: Round) -> CreationDecision {
+        match round == self.round && self.closed.load(Ordering::SeqCst) {
+            true => CreationDecision::Defer(Duration::from_millis(50)),
+            false => CreationDecision::Create,
+        }
+    }
+}
+
+// Creation gate test
+// A single creator is given all the parents it needs, but the gate holds back its unit of round 2,
+// so nothing of that round gets created until the gate opens, after which creation resumes.
+#[tokio::test]
+async fn creator_waits_for_creation_gate() {
+    let n_members = NodeCount(4);
+    let gate = Arc::new(RoundGate {
+        round: 2,
+        closed: AtomicBool::new(true),
+    });
+    let (parents_for_creator, incoming_parents) = mpsc::unbounded();
+    let (outgoing_units, mut units_from_creator) = mpsc::unbounded();
+    let (_finalized_rounds_tx, finalized_rounds) = mpsc::unbounded();
+    let io = IO {
+        incoming_parents,
+        outgoing_units,
+        finalized_rounds,
+        initial_snapshot: None,
+        snapshots: None,
+    };
+    let config = gen_config(0.into(), n_members, gen_delay_config())
+        .with_max_finalization_lag(3)
+        .with_creation_gate(gate.clone());
+    let (starting_round_for_consensus, starting_round) = oneshot::channel();
+    let (killer, exit) = oneshot::channel();
+    let handle = tokio::spawn(run(
+        config.into(),
+        io,
+        starting_round,
+        Terminator::create_root(exit, "AlephBFT-creator"),
+    ));
+    starting_round_for_consensus
+        .send(Some(0))
+        .expect("Sending the starting round should work.");
+
+    assert_eq!(
+        feed_creator_until_idle(&mut units_from_creator, &parents_for_creator, n_members).await,
+        vec![0, 1]
+    );
+
+    gate.closed.store(false, Ordering::SeqCst);
+    assert_eq!(
+        feed_creator_until_idle(&mut units_from_creator, &parents_for_creator, n_members).await,
+        vec![2, 3]
+    );
+
+    killer.send(()).unwrap();
+    handle.await.unwrap();
+}
@@ -134,11 +134,12 @@ async fn run_consensus_on_dag(
         "consensus",
         consensus::run(
             conf,
-            rx_in,
+            rx_in.into(),
             tx_out,
             batch_tx,
             spawner,
             starting_round,
+            Default::default(),
             Terminator::create_root(exit_rx, "AlephBFT-consensus"),
         ),
     );
@@ -148,7 +149,7 @@ async fn run_consensus_on_dag(
     loop {
         futures::select! {
             batch = batch_rx.next() => {
-                batches.push(batch.unwrap());
+                batches.push(batch.unwrap().into_iter().map(|(hash, _)| hash).collect());
             },
             _ = &mut delay_fut => {
                 break;
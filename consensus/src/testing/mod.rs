@@ -1,22 +1,27 @@
 #![cfg(test)]
 mod alerts;
 mod byzantine;
+pub mod clock;
 mod consensus;
 mod crash;
 mod crash_recovery;
 mod creation;
 mod dag;
+mod session;
 mod unreliable;
 
 use crate::{
     create_config, run_session, Config, DelayConfig, LocalIO, Network as NetworkT, NodeCount,
-    NodeIndex, SpawnHandle, TaskHandle, Terminator,
+    NodeIndex, RunwayEvent, SpawnHandle, TaskHandle, Terminator,
 };
 use aleph_bft_mock::{
     Data, DataProvider, FinalizationHandler, Hasher64, Keychain, Loader, Network as MockNetwork,
     PartialMultisignature, ReconnectSender as ReconnectSenderGeneric, Saver, Signature, Spawner,
 };
-use futures::channel::{mpsc::UnboundedReceiver, oneshot};
+use futures::channel::{
+    mpsc::{self, UnboundedReceiver},
+    oneshot,
+};
 use parking_lot::Mutex;
 use std::{sync::Arc, time::Duration};
 
@@ -55,6 +60,8 @@ pub fn gen_delay_config() -> DelayConfig {
         parent_request_recipients: Arc::new(|_| 1),
         // 50, 50, 50, 50, ...
         newest_request_delay: Arc::new(|_| Duration::from_millis(50)),
+        // everyone, everyone, everyone, ...
+        newest_request_recipients: Arc::new(|_| usize::MAX),
     }
 }
 
@@ -65,6 +72,7 @@ pub fn gen_config(node_ix: NodeIndex, n_members: NodeCount, delay_config: DelayC
 
 pub struct HonestMember {
     finalization_rx: UnboundedReceiver<Data>,
+    events_rx: UnboundedReceiver<RunwayEvent>,
     saved_state: Arc<Mutex<Vec<u8>>>,
     exit_tx: oneshot::Sender<()>,
     handle: TaskHandle,
@@ -77,15 +85,28 @@ pub fn spawn_honest_member(
     units: Vec<u8>,
     network: impl 'static + NetworkT<NetworkData>,
 ) -> HonestMember {
+    let config = gen_config(node_index, n_members, gen_delay_config());
+    spawn_honest_member_with_config(spawner, config, units, network)
+}
+
+pub fn spawn_honest_member_with_config(
+    spawner: Spawner,
+    config: Config,
+    units: Vec<u8>,
+    network: impl 'static + NetworkT<NetworkData>,
+) -> HonestMember {
+    let node_index = config.node_ix();
+    let n_members = config.n_members();
     let data_provider = DataProvider::new();
     let (finalization_handler, finalization_rx) = FinalizationHandler::new();
-    let config = gen_config(node_index, n_members, gen_delay_config());
     let (exit_tx, exit_rx) = oneshot::channel();
     let spawner_inner = spawner;
     let unit_loader = Loader::new(units);
     let saved_state = Arc::new(Mutex::new(vec![]));
     let unit_saver: Saver = saved_state.clone().into();
-    let local_io = LocalIO::new(data_provider, finalization_handler, unit_saver, unit_loader);
+    let (events_tx, events_rx) = mpsc::unbounded();
+    let local_io = LocalIO::new(data_provider, finalization_handler, unit_saver, unit_loader)
+        .with_event_sender(events_tx);
     let member_task = async move {
         let keychain = Keychain::new(n_members, node_index);
         run_session(
@@ -101,6 +122,7 @@ pub fn spawn_honest_member(
     let handle = spawner.spawn_essential("member", member_task);
     HonestMember {
         finalization_rx,
+        events_rx,
         saved_state,
         exit_tx,
         handle,
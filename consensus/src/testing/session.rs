@@ -0,0 +1,228 @@
+use crate::{
+    create_config, run_session,
+    runway::decode_backup,
+    testing::{
+        gen_config, gen_delay_config, init_log, spawn_honest_member_with_config, NetworkData,
+    },
+    BackupDirectory, DataProvider, LocalIO, NodeCount, RunwayEvent, SessionId, SpawnHandle,
+    Terminator,
+};
+use aleph_bft_mock::{
+    Data, DataProvider as MockDataProvider, FinalizationHandler, Hasher64, Keychain, Loader,
+    Router, Saver, Signature, Spawner,
+};
+use futures::{
+    channel::{mpsc, oneshot},
+    future::pending,
+    StreamExt,
+};
+use parking_lot::Mutex;
+use serial_test::serial;
+use std::{fs, sync::Arc, time::Duration};
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn reports_session_complete_at_max_round() {
+    init_log();
+    let n_members = NodeCount(4);
+    let max_round = 10;
+    let spawner = Spawner::new();
+    let (net_hub, networks) = Router::new(n_members, 1.0);
+    spawner.spawn("network-hub", net_hub);
+
+    let mut members = Vec::new();
+    for (network, _) in networks {
+        let config = create_config(
+            n_members,
+            network.index(),
+            0,
+            max_round,
+            gen_delay_config(),
+            Duration::ZERO,
+        )
+        .expect("Should always succeed with Duration::ZERO");
+        members.push(spawn_honest_member_with_config(
+            spawner,
+            config,
+            vec![],
+            network,
+        ));
+    }
+
+    for member in members.iter_mut() {
+        loop {
+            match member.events_rx.next().await {
+                Some(RunwayEvent::SessionComplete) => break,
+                Some(_) => continue,
+                None => panic!("the member stopped without completing the session"),
+            }
+        }
+    }
+
+    for member in members {
+        let _ = member.exit_tx.send(());
+        let _ = member.handle.await;
+    }
+}
+
+/// Reports every request for data, and then never provides any.
+struct WaitingDataProvider {
+    requests: mpsc::UnboundedSender<()>,
+}
+
+#[async_trait::async_trait]
+impl DataProvider<Data> for WaitingDataProvider {
+    async fn get_data(&mut self) -> Option<Data> {
+        let _ = self.requests.unbounded_send(());
+        pending().await
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn exits_while_waiting_for_data() {
+    init_log();
+    let n_members = NodeCount(4);
+    let spawner = Spawner::new();
+    let (net_hub, mut networks) = Router::<NetworkData>::new(n_members, 1.0);
+    spawner.spawn("network-hub", net_hub);
+    let (network, _) = networks.remove(0);
+    let node_index = network.index();
+    // The others are needed for the session to start at all.
+    let others: Vec<_> = networks
+        .into_iter()
+        .map(|(network, _)| {
+            let config = gen_config(network.index(), n_members, gen_delay_config());
+            spawn_honest_member_with_config(spawner, config, vec![], network)
+        })
+        .collect();
+    let config = gen_config(node_index, n_members, gen_delay_config());
+    let (requests, mut data_requests) = mpsc::unbounded();
+    let (finalization_handler, _finalization_rx) = FinalizationHandler::new();
+    let unit_saver: Saver = Arc::new(Mutex::new(vec![])).into();
+    let local_io = LocalIO::new(
+        WaitingDataProvider { requests },
+        finalization_handler,
+        unit_saver,
+        Loader::new(vec![]),
+    );
+    let (exit_tx, exit_rx) = oneshot::channel();
+    let handle = spawner.spawn_essential(
+        "member",
+        run_session(
+            config,
+            local_io,
+            network,
+            Keychain::new(n_members, node_index),
+            spawner,
+            Terminator::create_root(exit_rx, "AlephBFT-member"),
+        ),
+    );
+
+    data_requests
+        .next()
+        .await
+        .expect("data should be requested for the first unit");
+    exit_tx.send(()).expect("the member should be running");
+    tokio::time::timeout(Duration::from_secs(5), handle)
+        .await
+        .expect("the member should exit promptly")
+        .expect("the member should exit cleanly");
+
+    for member in others {
+        let _ = member.exit_tx.send(());
+        let _ = member.handle.await;
+    }
+}
+
+/// Runs a session until the first member, backing up into the directory, finalizes some data.
+async fn run_session_backed_up_in(directory: &BackupDirectory, session_id: SessionId) {
+    let n_members = NodeCount(4);
+    let spawner = Spawner::new();
+    let (net_hub, mut networks) = Router::<NetworkData>::new(n_members, 1.0);
+    spawner.spawn("network-hub", net_hub);
+    let (network, _) = networks.remove(0);
+    let node_index = network.index();
+    let session_config = |node_index| {
+        create_config(
+            n_members,
+            node_index,
+            session_id,
+            5000,
+            gen_delay_config(),
+            Duration::ZERO,
+        )
+        .expect("Should always succeed with Duration::ZERO")
+    };
+    let others: Vec<_> = networks
+        .into_iter()
+        .map(|(network, _)| {
+            let config = session_config(network.index());
+            spawn_honest_member_with_config(spawner, config, vec![], network)
+        })
+        .collect();
+    let (unit_saver, unit_loader) = directory
+        .open_session(session_id)
+        .expect("the backup should open");
+    let (finalization_handler, mut finalization_rx) = FinalizationHandler::new();
+    let local_io = LocalIO::new(
+        MockDataProvider::new(),
+        finalization_handler,
+        unit_saver,
+        unit_loader,
+    );
+    let (exit_tx, exit_rx) = oneshot::channel();
+    let handle = spawner.spawn_essential(
+        "member",
+        run_session(
+            session_config(node_index),
+            local_io,
+            network,
+            Keychain::new(n_members, node_index),
+            spawner,
+            Terminator::create_root(exit_rx, "AlephBFT-member"),
+        ),
+    );
+
+    for _ in 0..5 {
+        finalization_rx
+            .next()
+            .await
+            .expect("the member should finalize data");
+    }
+    let _ = exit_tx.send(());
+    let _ = handle.await;
+    for member in others {
+        let _ = member.exit_tx.send(());
+        let _ = member.handle.await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[serial]
+async fn backs_up_sessions_in_separate_files() {
+    init_log();
+    let path =
+        std::env::temp_dir().join(format!("aleph-bft-session-backups-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    let directory = BackupDirectory::new(&path);
+
+    for session_id in 0..2 {
+        run_session_backed_up_in(&directory, session_id).await;
+    }
+
+    for session_id in 0..2 {
+        let session_path = path.join(format!("session-{}.units", session_id));
+        assert_eq!(session_path, directory.session_path(session_id));
+        let backup = fs::read(session_path).expect("the session should be backed up");
+        let units = match decode_backup::<Hasher64, Data, Signature>(&backup) {
+            Ok((units, None)) => units,
+            result => panic!("the backup should load on its own, got {:?}", result),
+        };
+        assert!(!units.is_empty());
+        for unit in units {
+            assert_eq!(unit.as_signable().session_id(), session_id);
+        }
+    }
+    fs::remove_dir_all(path).expect("the backups should be removable");
+}
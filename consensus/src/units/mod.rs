@@ -1,6 +1,6 @@
 use crate::{
-    Data, Hasher, Index, Keychain, NodeCount, NodeIndex, NodeMap, NodeSubset, Round, SessionId,
-    Signable, Signed, UncheckedSigned,
+    signing::SigningDomain, Data, Hasher, Index, Keychain, NodeCount, NodeIndex, NodeMap,
+    NodeSubset, Round, SessionId, Signable, Signed, UncheckedSigned,
 };
 use codec::{Decode, Encode};
 use derivative::Derivative;
@@ -11,10 +11,11 @@ mod store;
 #[cfg(test)]
 mod testing;
 mod validator;
+pub use store::UnitStoreStatus;
 pub(crate) use store::*;
 #[cfg(test)]
 pub use testing::{create_units, creator_set, preunit_to_unchecked_signed_unit, preunit_to_unit};
-pub use validator::{ValidationError, Validator};
+pub use validator::{Inspection, UnitInspector, ValidationError, ValidationErrorKind, Validator};
 
 /// The coordinates of a unit, i.e. creator and round. In the absence of forks this uniquely
 /// determines a unit within a session.
@@ -180,9 +181,9 @@ impl<H: Hasher, D: Data> FullUnit<H, D> {
 }
 
 impl<H: Hasher, D: Data> Signable for FullUnit<H, D> {
-    type Hash = H::Hash;
-    fn hash(&self) -> H::Hash {
-        self.hash()
+    type Hash = Vec<u8>;
+    fn hash(&self) -> Vec<u8> {
+        SigningDomain::Unit.message(self.hash().as_ref())
     }
 }
 
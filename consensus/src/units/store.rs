@@ -3,22 +3,28 @@ use itertools::Itertools;
 use log::{trace, warn};
 use std::{collections::HashSet, fmt};
 
-#[derive(Clone, Eq, PartialEq, Hash)]
-pub struct UnitStoreStatus<'a> {
-    forkers: &'a NodeSubset,
+/// A forker together with all its units in store, sorted w.r.t. increasing rounds.
+type ForkerUnits<H, D, K> = (NodeIndex, Vec<SignedUnit<H, D, K>>);
+
+/// The contents of the [`UnitStore`] at the time of a status report.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct UnitStoreStatus {
+    forkers: NodeSubset,
     size: usize,
     height: Option<Round>,
     top_row: NodeMap<Round>,
     first_missing_rounds: NodeMap<Round>,
+    buffered: Vec<UnitCoord>,
 }
 
-impl<'a> UnitStoreStatus<'a> {
+impl UnitStoreStatus {
     fn new(
-        forkers: &'a NodeSubset,
+        forkers: NodeSubset,
         size: usize,
         height: Option<Round>,
         top_row: NodeMap<Round>,
         first_missing_rounds: NodeMap<Round>,
+        buffered: Vec<UnitCoord>,
     ) -> Self {
         Self {
             forkers,
@@ -26,11 +32,43 @@ impl<'a> UnitStoreStatus<'a> {
             height,
             top_row,
             first_missing_rounds,
+            buffered,
         }
     }
+
+    /// The number of units in the store.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The highest round of the units in the store, if any.
+    pub fn height(&self) -> Option<Round> {
+        self.height
+    }
+
+    /// The nodes known to be forkers.
+    pub fn forkers(&self) -> Vec<NodeIndex> {
+        self.forkers.elements().collect()
+    }
+
+    /// The highest round of the units of every creator in the store.
+    pub fn top_row(&self) -> &NodeMap<Round> {
+        &self.top_row
+    }
+
+    /// The lowest round below its top one in which a creator has no unit in the store, for the
+    /// creators with such gaps.
+    pub fn first_missing_rounds(&self) -> &NodeMap<Round> {
+        &self.first_missing_rounds
+    }
+
+    /// The units in the store not yet passed to consensus.
+    pub fn buffered(&self) -> &[UnitCoord] {
+        &self.buffered
+    }
 }
 
-impl<'a> fmt::Display for UnitStoreStatus<'a> {
+impl fmt::Display for UnitStoreStatus {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "DAG size - {}", self.size)?;
         if let Some(r) = self.height {
@@ -47,6 +85,14 @@ impl<'a> fmt::Display for UnitStoreStatus<'a> {
         if !self.forkers.is_empty() {
             write!(f, "; forkers - {}", self.forkers)?;
         }
+        if !self.buffered.is_empty() {
+            let buffered: Vec<(usize, Round)> = self
+                .buffered
+                .iter()
+                .map(|uc| (uc.creator().into(), uc.round()))
+                .collect();
+            write!(f, "; units not yet passed to consensus - {:?}", buffered)?;
+        }
         Ok(())
     }
 }
@@ -59,10 +105,12 @@ pub(crate) struct UnitStore<H: Hasher, D: Data, K: Keychain> {
     by_coord: HashMap<UnitCoord, SignedUnit<H, D, K>>,
     by_hash: HashMap<H::Hash, SignedUnit<H, D, K>>,
     parents: HashMap<H::Hash, Vec<H::Hash>>,
+    children: HashMap<H::Hash, Vec<H::Hash>>,
     //the number of unique nodes that we hold units for a given round
     is_forker: NodeSubset,
     legit_buffer: Vec<SignedUnit<H, D, K>>,
     max_round: Round,
+    height: Option<Round>,
 }
 
 impl<H: Hasher, D: Data, K: Keychain> UnitStore<H, D, K> {
@@ -71,10 +119,12 @@ impl<H: Hasher, D: Data, K: Keychain> UnitStore<H, D, K> {
             by_coord: HashMap::new(),
             by_hash: HashMap::new(),
             parents: HashMap::new(),
+            children: HashMap::new(),
             // is_forker is initialized with default values for bool, i.e., false
             is_forker: NodeSubset::with_size(n_nodes),
             legit_buffer: Vec::new(),
             max_round,
+            height: None,
         }
     }
 
@@ -99,11 +149,15 @@ impl<H: Hasher, D: Data, K: Keychain> UnitStore<H, D, K> {
                 .collect(),
         );
         UnitStoreStatus::new(
-            &self.is_forker,
+            self.is_forker.clone(),
             self.by_coord.len(),
-            self.by_coord.keys().map(|k| k.round).max(),
+            self.height,
             top_row,
             first_missing_rounds,
+            self.peek_buffer()
+                .into_iter()
+                .map(|su| su.as_signable().coord())
+                .collect(),
         )
     }
 
@@ -115,6 +169,18 @@ impl<H: Hasher, D: Data, K: Keychain> UnitStore<H, D, K> {
         self.by_hash.get(hash)
     }
 
+    /// The highest round of a unit in the store, if there are any units.
+    pub(crate) fn height(&self) -> Option<Round> {
+        self.height
+    }
+
+    /// The number of creators we hold a unit of for the given round.
+    pub(crate) fn width(&self, round: Round) -> usize {
+        (0..self.is_forker.size())
+            .filter(|id| self.contains_coord(&UnitCoord::new(round, NodeIndex(*id))))
+            .count()
+    }
+
     pub(crate) fn contains_hash(&self, hash: &H::Hash) -> bool {
         self.by_hash.contains_key(hash)
     }
@@ -137,6 +203,16 @@ impl<H: Hasher, D: Data, K: Keychain> UnitStore<H, D, K> {
         )
     }
 
+    // All the units held, in no particular order.
+    pub(crate) fn units(&self) -> impl Iterator<Item = &SignedUnit<H, D, K>> {
+        self.by_hash.values()
+    }
+
+    // Outputs the legit units waiting to be sent to Consensus, without emptying the buffer.
+    pub(crate) fn peek_buffer(&self) -> Vec<&SignedUnit<H, D, K>> {
+        self.legit_buffer.iter().collect()
+    }
+
     // Outputs new legit units that are supposed to be sent to Consensus and empties the buffer.
     pub(crate) fn yield_buffer_units(&mut self) -> Vec<SignedUnit<H, D, K>> {
         std::mem::take(&mut self.legit_buffer)
@@ -157,13 +233,33 @@ impl<H: Hasher, D: Data, K: Keychain> UnitStore<H, D, K> {
     // Marks a node as a forker and outputs all units in store created by this node.
     // The returned vector is sorted w.r.t. increasing rounds.
     pub(crate) fn mark_forker(&mut self, forker: NodeIndex) -> Vec<SignedUnit<H, D, K>> {
-        if self.is_forker[forker] {
-            warn!(target: "AlephBFT-unit-store", "Trying to mark the node {:?} as forker for the second time.", forker);
+        self.mark_forkers(vec![forker])
+            .pop()
+            .map(|(_, units)| units)
+            .unwrap_or_default()
+    }
+
+    // Marks several nodes as forkers at once, outputting for each of them, in the given order,
+    // all units in store created by it, sorted w.r.t. increasing rounds.
+    pub(crate) fn mark_forkers(&mut self, forkers: Vec<NodeIndex>) -> Vec<ForkerUnits<H, D, K>> {
+        for forker in &forkers {
+            if self.is_forker[*forker] {
+                warn!(target: "AlephBFT-unit-store", "Trying to mark the node {:?} as forker for the second time.", forker);
+            }
+            self.is_forker.insert(*forker);
+        }
+        let mut alerted_units: Vec<_> = forkers
+            .into_iter()
+            .map(|forker| (forker, Vec::new()))
+            .collect();
+        for round in 0..=self.max_round {
+            for (forker, units) in alerted_units.iter_mut() {
+                if let Some(su) = self.unit_by_coord(UnitCoord::new(round, *forker)) {
+                    units.push(su.clone());
+                }
+            }
         }
-        self.is_forker.insert(forker);
-        (0..=self.max_round)
-            .filter_map(|r| self.unit_by_coord(UnitCoord::new(r, forker)).cloned())
-            .collect()
+        alerted_units
     }
 
     pub(crate) fn add_unit(&mut self, su: SignedUnit<H, D, K>, alert: bool) {
@@ -184,6 +280,7 @@ impl<H: Hasher, D: Data, K: Keychain> UnitStore<H, D, K> {
         }
         self.by_hash.insert(hash, su.clone());
         self.by_coord.insert(su.as_signable().coord(), su.clone());
+        self.height = self.height.max(Some(su.as_signable().round()));
 
         if alert || !self.is_forker[creator] {
             self.legit_buffer.push(su);
@@ -191,12 +288,44 @@ impl<H: Hasher, D: Data, K: Keychain> UnitStore<H, D, K> {
     }
 
     pub(crate) fn add_parents(&mut self, hash: H::Hash, parents: Vec<H::Hash>) {
+        for parent in &parents {
+            let children = self.children.entry(*parent).or_default();
+            if !children.contains(&hash) {
+                children.push(hash);
+            }
+        }
         self.parents.insert(hash, parents);
     }
 
+    /// Hashes of the units whose parents were added to the store and include the given unit.
+    pub(crate) fn children_of(&self, hash: &H::Hash) -> Vec<H::Hash> {
+        self.children.get(hash).cloned().unwrap_or_default()
+    }
+
+    /// Creators of the parents claimed in the control hash of the unit with the given hash.
+    pub(crate) fn claimed_parents(&self, hash: &H::Hash) -> Option<Vec<NodeIndex>> {
+        self.by_hash
+            .get(hash)
+            .map(|su| su.as_signable().control_hash().parents().collect())
+    }
+
     pub(crate) fn get_parents(&mut self, hash: H::Hash) -> Option<&Vec<H::Hash>> {
         self.parents.get(&hash)
     }
+
+    /// Rebuilds the combined hash of the parents of the unit from the units in the store, if all
+    /// of the claimed parents are present. A result differing from the claimed control hash means
+    /// either a forged control hash or a parent being a fork we do not hold.
+    #[cfg(test)]
+    pub(crate) fn recompute_control_hash(&self, fu: &FullUnit<H, D>) -> Option<H::Hash> {
+        let control_hash = fu.control_hash();
+        let mut parents = NodeMap::with_size(control_hash.n_members());
+        for creator in control_hash.parents() {
+            let coord = UnitCoord::new(fu.round().checked_sub(1)?, creator);
+            parents.insert(creator, self.unit_by_coord(coord)?.as_signable().hash());
+        }
+        Some(ControlHash::<H>::combine_hashes(&parents))
+    }
 }
 
 #[cfg(test)]
@@ -223,6 +352,83 @@ mod tests {
         Signed::sign(full_unit, keychain)
     }
 
+    #[test]
+    fn mark_forkers_in_batch() {
+        let n_nodes = NodeCount(7);
+        let mut store = UnitStore::<Hasher64, Data, Keychain>::new(n_nodes, 100);
+        let keychains: Vec<_> = n_nodes
+            .into_iterator()
+            .map(|node_id| Keychain::new(n_nodes, node_id))
+            .collect();
+        let forkers = vec![NodeIndex(5), NodeIndex(1), NodeIndex(3)];
+
+        for round in 0..5 {
+            for (i, keychain) in keychains.iter().enumerate() {
+                // One of the forkers stopped creating units early.
+                if i == 3 && round > 1 {
+                    continue;
+                }
+                store.add_unit(
+                    create_unit(round, NodeIndex(i), n_nodes, 0, keychain),
+                    false,
+                );
+            }
+        }
+
+        let alerted_rounds: Vec<_> = store
+            .mark_forkers(forkers.clone())
+            .into_iter()
+            .map(|(forker, units)| {
+                assert!(units
+                    .iter()
+                    .all(|unit| unit.as_signable().creator() == forker));
+                let rounds: Vec<_> = units
+                    .iter()
+                    .map(|unit| unit.as_signable().round())
+                    .collect();
+                (forker, rounds)
+            })
+            .collect();
+
+        assert_eq!(
+            alerted_rounds,
+            vec![
+                (NodeIndex(5), vec![0, 1, 2, 3, 4]),
+                (NodeIndex(1), vec![0, 1, 2, 3, 4]),
+                (NodeIndex(3), vec![0, 1]),
+            ]
+        );
+        for node_id in n_nodes.into_iterator() {
+            assert_eq!(store.is_forker(node_id), forkers.contains(&node_id));
+        }
+    }
+
+    #[test]
+    fn peek_buffer_without_draining() {
+        let n_nodes = NodeCount(4);
+        let mut store = UnitStore::<Hasher64, Data, Keychain>::new(n_nodes, 100);
+        let keychains: Vec<_> = n_nodes
+            .into_iterator()
+            .map(|node_id| Keychain::new(n_nodes, node_id))
+            .collect();
+        assert!(store.peek_buffer().is_empty());
+
+        let units: Vec<_> = keychains
+            .iter()
+            .enumerate()
+            .map(|(i, keychain)| create_unit(0, NodeIndex(i), n_nodes, 0, keychain))
+            .collect();
+        for unit in &units {
+            store.add_unit(unit.clone(), false);
+        }
+        let peeked: Vec<_> = store.peek_buffer().into_iter().cloned().collect();
+        assert_eq!(peeked, units);
+        assert_eq!(store.peek_buffer().len(), units.len());
+
+        assert_eq!(store.yield_buffer_units(), units);
+        assert!(store.peek_buffer().is_empty());
+    }
+
     #[test]
     fn mark_forker_restore_state() {
         let n_nodes = NodeCount(10);
@@ -282,4 +488,112 @@ mod tests {
             assert!(store.by_hash.contains_key(hash));
         }
     }
+
+    #[test]
+    fn height_and_width_follow_added_units() {
+        let n_nodes = NodeCount(4);
+        let mut store = UnitStore::<Hasher64, Data, Keychain>::new(n_nodes, 100);
+        assert_eq!(store.height(), None);
+        assert_eq!(store.width(0), 0);
+
+        let keychains: Vec<_> = (0..n_nodes.0)
+            .map(|i| Keychain::new(n_nodes, NodeIndex(i)))
+            .collect();
+        for (i, keychain) in keychains.iter().enumerate() {
+            store.add_unit(create_unit(0, NodeIndex(i), n_nodes, 0, keychain), false);
+        }
+        for (i, keychain) in keychains.iter().enumerate().take(2) {
+            store.add_unit(create_unit(1, NodeIndex(i), n_nodes, 0, keychain), false);
+        }
+        store.add_unit(
+            create_unit(5, NodeIndex(3), n_nodes, 0, &keychains[3]),
+            false,
+        );
+
+        assert_eq!(store.height(), Some(5));
+        assert_eq!(store.width(0), 4);
+        assert_eq!(store.width(1), 2);
+        assert_eq!(store.width(2), 0);
+        assert_eq!(store.width(5), 1);
+    }
+
+    #[test]
+    fn children_index_follows_added_parents() {
+        let n_nodes = NodeCount(4);
+        let mut store = UnitStore::<Hasher64, Data, Keychain>::new(n_nodes, 100);
+        let keychains: Vec<_> = (0..n_nodes.0)
+            .map(|i| Keychain::new(n_nodes, NodeIndex(i)))
+            .collect();
+        let hashes: Vec<Vec<_>> = (0..2)
+            .map(|round| {
+                keychains
+                    .iter()
+                    .enumerate()
+                    .map(|(i, keychain)| {
+                        let unit = create_unit(round, NodeIndex(i), n_nodes, 0, keychain);
+                        let hash = unit.as_signable().hash();
+                        store.add_unit(unit, false);
+                        hash
+                    })
+                    .collect()
+            })
+            .collect();
+        assert!(store.children_of(&hashes[0][0]).is_empty());
+
+        store.add_parents(hashes[1][0], hashes[0][..3].to_vec());
+        store.add_parents(hashes[1][1], hashes[0][1..].to_vec());
+
+        assert_eq!(store.children_of(&hashes[0][0]), vec![hashes[1][0]]);
+        assert_eq!(
+            store.children_of(&hashes[0][1]),
+            vec![hashes[1][0], hashes[1][1]]
+        );
+        assert_eq!(store.children_of(&hashes[0][3]), vec![hashes[1][1]]);
+        assert!(store.children_of(&hashes[1][0]).is_empty());
+    }
+
+    #[test]
+    fn recomputed_control_hash_exposes_forgery() {
+        let n_nodes = NodeCount(4);
+        let mut store = UnitStore::<Hasher64, Data, Keychain>::new(n_nodes, 100);
+        let keychains: Vec<_> = (0..n_nodes.0)
+            .map(|i| Keychain::new(n_nodes, NodeIndex(i)))
+            .collect();
+        let mut parents = NodeMap::with_size(n_nodes);
+        for (i, keychain) in keychains.iter().enumerate().take(3) {
+            let unit = create_unit(0, NodeIndex(i), n_nodes, 0, keychain);
+            parents.insert(NodeIndex(i), unit.as_signable().hash());
+            store.add_unit(unit, false);
+        }
+        let honest = FullUnit::<Hasher64, Data>::new(
+            PreUnit::new(NodeIndex(0), 1, ControlHash::new(&parents)),
+            Some(0),
+            0,
+        );
+        assert_eq!(
+            store.recompute_control_hash(&honest),
+            Some(honest.control_hash().combined_hash)
+        );
+
+        let mut forged_control_hash = ControlHash::new(&parents);
+        forged_control_hash.combined_hash = [7; 8];
+        let forged = FullUnit::<Hasher64, Data>::new(
+            PreUnit::new(NodeIndex(1), 1, forged_control_hash),
+            Some(0),
+            0,
+        );
+        let recomputed = store
+            .recompute_control_hash(&forged)
+            .expect("all the parents are in the store");
+        assert_ne!(recomputed, forged.control_hash().combined_hash);
+
+        let mut with_missing_parent = parents.clone();
+        with_missing_parent.insert(NodeIndex(3), [3; 8]);
+        let incomplete = FullUnit::<Hasher64, Data>::new(
+            PreUnit::new(NodeIndex(2), 1, ControlHash::new(&with_missing_parent)),
+            Some(0),
+            0,
+        );
+        assert_eq!(store.recompute_control_hash(&incomplete), None);
+    }
 }
@@ -1,5 +1,5 @@
 use crate::{
-    creation::Creator as GenericCreator,
+    creation::{default_threshold, Creator as GenericCreator},
     units::{
         FullUnit as GenericFullUnit, PreUnit as GenericPreUnit,
         UncheckedSignedUnit as GenericUncheckedSignedUnit, Unit as GenericUnit,
@@ -16,7 +16,7 @@ type UncheckedSignedUnit = GenericUncheckedSignedUnit<Hasher64, Data, Signature>
 
 pub fn creator_set(n_members: NodeCount) -> Vec<Creator> {
     (0..n_members.0)
-        .map(|i| Creator::new(NodeIndex(i), n_members))
+        .map(|i| Creator::new(NodeIndex(i), n_members, default_threshold(n_members)))
         .collect()
 }
 
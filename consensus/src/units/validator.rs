@@ -1,15 +1,46 @@
 use crate::{
-    units::{FullUnit, PreUnit, SignedUnit, UncheckedSignedUnit},
+    units::{FullUnit, PreUnit, SignedUnit, UncheckedSignedUnit, UnitCoord},
     Data, Hasher, Keychain, NodeCount, Round, SessionId, Signature, SignatureError,
 };
+use derivative::Derivative;
 use std::{
     fmt::{Display, Formatter, Result as FmtResult},
     result::Result as StdResult,
+    sync::Arc,
 };
 
+/// The outcome of application-specific checks of a unit.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Inspection {
+    /// The unit is fine.
+    Accepted,
+    /// The unit is invalid for good.
+    Rejected,
+    /// The unit cannot be judged yet, e.g. because the application is in the middle of an update,
+    /// so it might be accepted when checked again later.
+    Deferred,
+}
+
+/// Application-specific checks of incoming units, e.g. of the schema of the data they carry.
+/// They are applied after all the built-in checks passed.
+pub trait UnitInspector<D: Data>: Send + Sync + 'static {
+    /// Whether the unit with the given coordinates carrying the given data should be accepted.
+    fn accepts(&self, coord: UnitCoord, data: Option<&D>) -> bool;
+
+    /// The verdict on the unit with the given coordinates carrying the given data. By default
+    /// based on [`UnitInspector::accepts`], never deferring.
+    fn inspect(&self, coord: UnitCoord, data: Option<&D>) -> Inspection {
+        match self.accepts(coord, data) {
+            true => Inspection::Accepted,
+            false => Inspection::Rejected,
+        }
+    }
+}
+
 /// All that can be wrong with a unit except control hash issues.
 #[derive(Eq, PartialEq, Debug)]
 pub enum ValidationError<H: Hasher, D: Data, S: Signature> {
+    UnknownCreator(FullUnit<H, D>),
     WrongSignature(UncheckedSignedUnit<H, D, S>),
     WrongSession(FullUnit<H, D>),
     RoundTooHigh(FullUnit<H, D>),
@@ -17,12 +48,60 @@ pub enum ValidationError<H: Hasher, D: Data, S: Signature> {
     RoundZeroWithParents(PreUnit<H>),
     NotEnoughParents(PreUnit<H>),
     NotDescendantOfPreviousUnit(PreUnit<H>),
+    RejectedByInspector(FullUnit<H, D>),
+    DeferredByInspector(UncheckedSignedUnit<H, D, S>),
+}
+
+/// What was wrong with a unit failing validation, see [`ValidationError`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ValidationErrorKind {
+    UnknownCreator,
+    WrongSignature,
+    WrongSession,
+    RoundTooHigh,
+    WrongNumberOfMembers,
+    RoundZeroWithParents,
+    NotEnoughParents,
+    NotDescendantOfPreviousUnit,
+    RejectedByInspector,
+    DeferredByInspector,
+}
+
+impl<H: Hasher, D: Data, S: Signature> ValidationError<H, D, S> {
+    /// Whether the unit might pass validation when checked again later, as opposed to being
+    /// invalid for good.
+    pub fn is_transient(&self) -> bool {
+        matches!(self, ValidationError::DeferredByInspector(_))
+    }
+
+    /// What was wrong with the unit, without the unit itself.
+    pub fn kind(&self) -> ValidationErrorKind {
+        use ValidationError::*;
+        match self {
+            UnknownCreator(_) => ValidationErrorKind::UnknownCreator,
+            WrongSignature(_) => ValidationErrorKind::WrongSignature,
+            WrongSession(_) => ValidationErrorKind::WrongSession,
+            RoundTooHigh(_) => ValidationErrorKind::RoundTooHigh,
+            WrongNumberOfMembers(_) => ValidationErrorKind::WrongNumberOfMembers,
+            RoundZeroWithParents(_) => ValidationErrorKind::RoundZeroWithParents,
+            NotEnoughParents(_) => ValidationErrorKind::NotEnoughParents,
+            NotDescendantOfPreviousUnit(_) => ValidationErrorKind::NotDescendantOfPreviousUnit,
+            RejectedByInspector(_) => ValidationErrorKind::RejectedByInspector,
+            DeferredByInspector(_) => ValidationErrorKind::DeferredByInspector,
+        }
+    }
 }
 
 impl<H: Hasher, D: Data, S: Signature> Display for ValidationError<H, D, S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         use ValidationError::*;
         match self {
+            UnknownCreator(fu) => write!(
+                f,
+                "unit with creator {:?} outside of the committee: {:?}",
+                fu.creator(),
+                fu
+            ),
             WrongSignature(usu) => write!(f, "wrongly signed unit: {:?}", usu),
             WrongSession(fu) => write!(f, "unit from wrong session: {:?}", fu),
             RoundTooHigh(fu) => write!(f, "unit with too high round {}: {:?}", fu.round(), fu),
@@ -44,6 +123,12 @@ impl<H: Hasher, D: Data, S: Signature> Display for ValidationError<H, D, S> {
                 "nonzero round unit is not descendant of its creator's previous unit: {:?}",
                 pu
             ),
+            RejectedByInspector(fu) => write!(f, "unit rejected by the unit inspector: {:?}", fu),
+            DeferredByInspector(usu) => write!(
+                f,
+                "unit deferred by the unit inspector: {:?}",
+                usu.as_signable()
+            ),
         }
     }
 }
@@ -56,31 +141,58 @@ impl<H: Hasher, D: Data, S: Signature> From<SignatureError<FullUnit<H, D>, S>>
     }
 }
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
-pub struct Validator<K: Keychain> {
+/// Checks the units received from other nodes, also outside of a session with
+/// [`NetworkData::validate_units`](crate::NetworkData::validate_units).
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
+pub struct Validator<K: Keychain, D: Data> {
     session_id: SessionId,
     keychain: K,
     max_round: Round,
     threshold: NodeCount,
+    #[derivative(Debug = "ignore")]
+    inspector: Option<Arc<dyn UnitInspector<D>>>,
 }
 
 type Result<H, D, K> =
     StdResult<SignedUnit<H, D, K>, ValidationError<H, D, <K as Keychain>::Signature>>;
 
-impl<K: Keychain> Validator<K> {
+impl<K: Keychain, D: Data> Validator<K, D> {
+    /// Accepts units of the session up to `max_round`, signed by the members of the keychain and
+    /// with at least `threshold` parents above round zero.
     pub fn new(session_id: SessionId, keychain: K, max_round: Round, threshold: NodeCount) -> Self {
         Validator {
             session_id,
             keychain,
             max_round,
             threshold,
+            inspector: None,
         }
     }
 
-    pub fn validate_unit<H: Hasher, D: Data>(
+    pub fn session_id(&self) -> SessionId {
+        self.session_id
+    }
+
+    pub fn max_round(&self) -> Round {
+        self.max_round
+    }
+
+    /// Sets a [`UnitInspector`] applying application-specific checks to the units.
+    pub fn with_inspector(mut self, inspector: Option<Arc<dyn UnitInspector<D>>>) -> Self {
+        self.inspector = inspector;
+        self
+    }
+
+    pub fn validate_unit<H: Hasher>(
         &self,
         uu: UncheckedSignedUnit<H, D, K::Signature>,
     ) -> Result<H, D, K> {
+        let full_unit = uu.as_signable();
+        if full_unit.creator().0 >= self.keychain.node_count().0 {
+            // NOTE: this implies either a misconfigured peer or malicious behavior.
+            return Err(ValidationError::UnknownCreator(full_unit.clone()));
+        }
         let su = uu.check(&self.keychain)?;
         let full_unit = su.as_signable();
         if full_unit.session_id() != self.session_id {
@@ -91,13 +203,27 @@ impl<K: Keychain> Validator<K> {
         if full_unit.round() > self.max_round {
             return Err(ValidationError::RoundTooHigh(full_unit.clone()));
         }
-        self.validate_unit_parents(su)
+        let su = self.validate_unit_parents(su)?;
+        self.inspect_unit(su)
     }
 
-    fn validate_unit_parents<H: Hasher, D: Data>(
-        &self,
-        su: SignedUnit<H, D, K>,
-    ) -> Result<H, D, K> {
+    fn inspect_unit<H: Hasher>(&self, su: SignedUnit<H, D, K>) -> Result<H, D, K> {
+        if let Some(inspector) = &self.inspector {
+            let full_unit = su.as_signable();
+            match inspector.inspect(full_unit.coord(), full_unit.data().as_ref()) {
+                Inspection::Accepted => (),
+                Inspection::Rejected => {
+                    return Err(ValidationError::RejectedByInspector(full_unit.clone()))
+                }
+                Inspection::Deferred => {
+                    return Err(ValidationError::DeferredByInspector(su.into_unchecked()))
+                }
+            }
+        }
+        Ok(su)
+    }
+
+    fn validate_unit_parents<H: Hasher>(&self, su: SignedUnit<H, D, K>) -> Result<H, D, K> {
         // NOTE: at this point we cannot validate correctness of the control hash, in principle it could be
         // just a random hash, but we still would not be able to deduce that by looking at the unit only.
         let pre_unit = su.as_signable().as_pre_unit();
@@ -125,16 +251,23 @@ impl<K: Keychain> Validator<K> {
 
 #[cfg(test)]
 mod tests {
-    use super::{ValidationError::*, Validator as GenericValidator};
+    use super::{UnitInspector, ValidationError::*, Validator as GenericValidator};
     use crate::{
-        creation::Creator as GenericCreator,
-        units::{create_units, creator_set, preunit_to_unchecked_signed_unit, preunit_to_unit},
-        NodeCount, NodeIndex,
+        creation::{default_threshold, Creator as GenericCreator},
+        units::{
+            create_units, creator_set, preunit_to_unchecked_signed_unit, preunit_to_unit,
+            FullUnit as GenericFullUnit, UncheckedSignedUnit as GenericUncheckedSignedUnit,
+            UnitCoord,
+        },
+        NodeCount, NodeIndex, Signed,
     };
-    use aleph_bft_mock::{Hasher64, Keychain};
+    use aleph_bft_mock::{Data, Hasher64, Keychain, Signature};
+    use std::sync::Arc;
 
-    type Validator = GenericValidator<Keychain>;
+    type Validator = GenericValidator<Keychain, Data>;
     type Creator = GenericCreator<Hasher64>;
+    type FullUnit = GenericFullUnit<Hasher64, Data>;
+    type UncheckedSignedUnit = GenericUncheckedSignedUnit<Hasher64, Data, Signature>;
 
     #[test]
     fn validates_initial_unit() {
@@ -144,7 +277,7 @@ mod tests {
         let session_id = 0;
         let round = 0;
         let max_round = 2;
-        let creator = Creator::new(creator_id, n_members);
+        let creator = Creator::new(creator_id, n_members, default_threshold(n_members));
         let keychain = Keychain::new(n_members, creator_id);
         let validator = Validator::new(session_id, keychain, max_round, threshold);
         let (preunit, _) = creator
@@ -166,7 +299,7 @@ mod tests {
         let wrong_session_id = 43;
         let round = 0;
         let max_round = 2;
-        let creator = Creator::new(creator_id, n_members);
+        let creator = Creator::new(creator_id, n_members, default_threshold(n_members));
         let keychain = Keychain::new(n_members, creator_id);
         let validator = Validator::new(session_id, keychain, max_round, threshold);
         let (preunit, _) = creator
@@ -190,7 +323,7 @@ mod tests {
         let session_id = 0;
         let round = 0;
         let max_round = 2;
-        let creator = Creator::new(creator_id, n_members);
+        let creator = Creator::new(creator_id, n_members, default_threshold(n_members));
         let keychain = Keychain::new(n_plus_one_members, creator_id);
         let validator = Validator::new(session_id, keychain, max_round, threshold);
         let (preunit, _) = creator
@@ -271,4 +404,47 @@ mod tests {
         };
         assert_eq!(full_unit, unchecked_unit.into_signable());
     }
+
+    const SENTINEL: Data = 43;
+
+    struct SentinelInspector;
+
+    impl UnitInspector<Data> for SentinelInspector {
+        fn accepts(&self, _coord: UnitCoord, data: Option<&Data>) -> bool {
+            data != Some(&SENTINEL)
+        }
+    }
+
+    #[test]
+    fn inspector_rejects_units_with_sentinel_data() {
+        let n_members = NodeCount(7);
+        let threshold = NodeCount(5);
+        let creator_id = NodeIndex(0);
+        let session_id = 0;
+        let round = 0;
+        let max_round = 2;
+        let creator = Creator::new(creator_id, n_members, default_threshold(n_members));
+        let keychain = Keychain::new(n_members, creator_id);
+        let validator = Validator::new(session_id, keychain, max_round, threshold)
+            .with_inspector(Some(Arc::new(SentinelInspector)));
+        let (preunit, _) = creator
+            .create_unit(round)
+            .expect("Creation should succeed.");
+
+        let unchecked_unit =
+            preunit_to_unchecked_signed_unit(preunit.clone(), session_id, &keychain);
+        assert!(validator.validate_unit(unchecked_unit).is_ok());
+
+        let sentinel_unit: UncheckedSignedUnit = Signed::sign(
+            FullUnit::new(preunit, Some(SENTINEL), session_id),
+            &keychain,
+        )
+        .into();
+        let full_unit = match validator.validate_unit(sentinel_unit.clone()) {
+            Ok(_) => panic!("Validated unit with sentinel data."),
+            Err(RejectedByInspector(full_unit)) => full_unit,
+            Err(e) => panic!("Unexpected error from validator: {:?}", e),
+        };
+        assert_eq!(full_unit, sentinel_unit.into_signable());
+    }
 }
@@ -8,7 +8,9 @@ use dataio::{Data, DataProvider, FinalizationHandler};
 use futures::{channel::oneshot, StreamExt};
 use log::{debug, error, info};
 use network::Network;
-use std::{collections::HashMap, fs, fs::File, io, io::Write, path::Path, time::Duration};
+use std::{
+    collections::HashMap, fs, fs::File, io, io::Write, path::Path, sync::Arc, time::Duration,
+};
 use time::{macros::format_description, OffsetDateTime};
 
 /// Example node producing linear order.
@@ -110,7 +112,8 @@ async fn main() {
         finalization_handler,
         backup_saver,
         backup_loader,
-    );
+    )
+    .with_backup_sync(Arc::new(|file: &mut File| file.sync_data()));
 
     let (exit_tx, exit_rx) = oneshot::channel();
     let member_terminator = Terminator::create_root(exit_rx, "AlephBFT-member");
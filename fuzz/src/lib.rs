@@ -203,6 +203,8 @@ pub fn gen_delay_config() -> DelayConfig {
         parent_request_recipients: Arc::new(|_| 1),
         // 50, 50, 50, 50, ...
         newest_request_delay: Arc::new(|_| Duration::from_millis(50)),
+        // everyone, everyone, everyone, ...
+        newest_request_recipients: Arc::new(|_| usize::MAX),
     }
 }
 
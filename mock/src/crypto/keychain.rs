@@ -8,11 +8,23 @@ use aleph_bft_types::{
 pub struct Keychain {
     count: NodeCount,
     index: NodeIndex,
+    quorum: Option<usize>,
 }
 
 impl Keychain {
     pub fn new(count: NodeCount, index: NodeIndex) -> Self {
-        Keychain { count, index }
+        Keychain {
+            count,
+            index,
+            quorum: None,
+        }
+    }
+
+    /// Sets how many signatures make a multisignature complete, e.g. for modelling a different
+    /// fault assumption for alerts, more than 2/3 of the nodes by default.
+    pub fn with_quorum(mut self, quorum: usize) -> Self {
+        self.quorum = Some(quorum);
+        self
     }
 
     pub fn new_vec(node_count: NodeCount) -> Vec<Self> {
@@ -22,7 +34,7 @@ impl Keychain {
     }
 
     fn quorum(&self) -> usize {
-        2 * self.count.0 / 3 + 1
+        self.quorum.unwrap_or(2 * self.count.0 / 3 + 1)
     }
 }
 
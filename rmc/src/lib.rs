@@ -3,6 +3,7 @@ pub use aleph_bft_crypto::{
     Indexed, MultiKeychain, Multisigned, NodeCount, PartialMultisignature, PartiallyMultisigned,
     Signable, Signature, Signed, UncheckedSigned,
 };
+pub use aleph_bft_types::{Clock, SystemClock};
 use async_trait::async_trait;
 use codec::{Decode, Encode};
 use core::fmt::Debug;
@@ -10,13 +11,13 @@ use futures::{
     channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
     FutureExt, StreamExt,
 };
-use futures_timer::Delay;
 use log::{debug, warn};
 use std::{
     cmp::Reverse,
     collections::{BinaryHeap, HashMap},
     fmt::Formatter,
     hash::Hash,
+    sync::Arc,
     time,
     time::Duration,
 };
@@ -73,13 +74,6 @@ impl<T> ScheduledTask<T> {
 #[derive(Ord, PartialOrd, Eq, PartialEq)]
 struct IndexedInstant(time::Instant, usize);
 
-impl IndexedInstant {
-    fn now(i: usize) -> Self {
-        let curr_time = time::Instant::now();
-        IndexedInstant(curr_time, i)
-    }
-}
-
 /// A basic task scheduler scheduling tasks with an exponential slowdown
 ///
 /// A scheduler parameterized by a duration `initial_delay`. When a task is added to the scheduler
@@ -88,6 +82,7 @@ impl IndexedInstant {
 /// one.
 pub struct DoublingDelayScheduler<T> {
     initial_delay: time::Duration,
+    clock: Arc<dyn Clock>,
     scheduled_instants: BinaryHeap<Reverse<IndexedInstant>>,
     scheduled_tasks: Vec<ScheduledTask<T>>,
     on_new_task_tx: UnboundedSender<T>,
@@ -109,12 +104,19 @@ impl<T> DoublingDelayScheduler<T> {
         let (on_new_task_tx, on_new_task_rx) = unbounded();
         DoublingDelayScheduler {
             initial_delay,
+            clock: Arc::new(SystemClock),
             scheduled_instants: BinaryHeap::new(),
             scheduled_tasks: Vec::new(),
             on_new_task_tx,
             on_new_task_rx,
         }
     }
+
+    /// Sets the source of time for scheduling the tasks, [`SystemClock`] by default.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
 }
 
 #[async_trait]
@@ -128,11 +130,11 @@ impl<T: Send + Sync + Clone> TaskScheduler<T> for DoublingDelayScheduler<T> {
     async fn next_task(&mut self) -> Option<T> {
         let mut delay: futures::future::Fuse<_> = match self.scheduled_instants.peek() {
             Some(&Reverse(IndexedInstant(instant, _))) => {
-                let now = time::Instant::now();
+                let now = self.clock.now();
                 if now > instant {
-                    Delay::new(Duration::new(0, 0)).fuse()
+                    self.clock.sleep(Duration::new(0, 0)).fuse()
                 } else {
-                    Delay::new(instant - now).fuse()
+                    self.clock.sleep(instant - now).fuse()
                 }
             }
             None => futures::future::Fuse::terminated(),
@@ -143,7 +145,7 @@ impl<T: Send + Sync + Clone> TaskScheduler<T> for DoublingDelayScheduler<T> {
             task = self.on_new_task_rx.next() => {
                 if let Some(task) = task {
                     let i = self.scheduled_tasks.len();
-                    let indexed_instant = IndexedInstant::now(i);
+                    let indexed_instant = IndexedInstant(self.clock.now(), i);
                     self.scheduled_instants.push(Reverse(indexed_instant));
                     let scheduled_task = ScheduledTask::new(task, self.initial_delay);
                     self.scheduled_tasks.push(scheduled_task);
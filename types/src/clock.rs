@@ -0,0 +1,26 @@
+use futures::future::BoxFuture;
+use futures_timer::Delay;
+use std::time::{Duration, Instant};
+
+/// An abstraction for the source of time, governing all the delays and timeouts of the protocol.
+/// Replacing it with a virtual clock allows running accelerated simulations.
+pub trait Clock: Send + Sync + 'static {
+    /// The current time.
+    fn now(&self) -> Instant;
+    /// A future completing once `duration` passed according to this clock.
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()>;
+}
+
+/// A [`Clock`] following the system time.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> BoxFuture<'static, ()> {
+        Box::pin(Delay::new(duration))
+    }
+}
@@ -1,5 +1,7 @@
 use async_trait::async_trait;
 
+use crate::{Hasher, NodeIndex, Round};
+
 /// The source of data items that consensus should order.
 ///
 /// AlephBFT internally calls [`DataProvider::get_data`] whenever a new unit is created and data needs to be placed inside.
@@ -9,9 +11,28 @@ use async_trait::async_trait;
 #[async_trait]
 pub trait DataProvider<Data>: Sync + Send + 'static {
     /// Outputs a new data item to be ordered
+    ///
+    /// The returned future might be dropped before completing, e.g. when the session exits
+    /// while it is still waiting for data, so it should not leave the provider in a broken state.
     async fn get_data(&mut self) -> Option<Data>;
 }
 
+/// A finalized data item together with the unit that carried it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FinalizedItem<H: Hasher, Data> {
+    /// The finalized data.
+    pub data: Data,
+    /// The creator of the unit carrying the data.
+    pub creator: NodeIndex,
+    /// The round of the unit carrying the data.
+    pub round: Round,
+    /// The hash of the unit carrying the data.
+    pub hash: H::Hash,
+    /// The position of the unit among all the units finalized in the session so far, counting
+    /// from 0 and including units without data.
+    pub position: usize,
+}
+
 /// The source of finalization of the units that consensus produces.
 ///
 /// The [`FinalizationHandler::data_finalized`] method is called whenever a piece of data input to the algorithm
@@ -20,4 +41,51 @@ pub trait FinalizationHandler<Data>: Sync + Send + 'static {
     /// Data, provided by [DataProvider::get_data], has been finalized.
     /// The calls to this function follow the order of finalization.
     fn data_finalized(&mut self, data: Data);
+
+    /// All the data of an ordered batch has been finalized, in order of finalization, e.g. for
+    /// committing every batch atomically. By default passes the data one by one to
+    /// [`FinalizationHandler::data_finalized`].
+    fn data_finalized_batch(&mut self, batch: Vec<Data>) {
+        for data in batch {
+            self.data_finalized(data)
+        }
+    }
+
+    /// Data has been finalized as part of the unit with the given creator, round and hash, e.g. for
+    /// attributing it to the node that proposed it. Called in order of finalization for every
+    /// item of a batch, before its data is finalized. Does nothing by default.
+    fn unit_finalized<H: Hasher>(
+        &mut self,
+        _creator: NodeIndex,
+        _round: Round,
+        _hash: H::Hash,
+        _data: &Data,
+    ) where
+        Self: Sized,
+    {
+    }
+
+    /// Data has been finalized, with the full provenance of the unit carrying it, e.g. for audits
+    /// or indexing. Called in order of finalization for every item of a batch, before its data is
+    /// finalized. By default passes the item on to [`FinalizationHandler::unit_finalized`].
+    fn item_finalized<H: Hasher>(&mut self, item: &FinalizedItem<H, Data>)
+    where
+        Self: Sized,
+    {
+        self.unit_finalized::<H>(item.creator, item.round, item.hash, &item.data)
+    }
+
+    /// All the items of an ordered batch have been finalized, in order of finalization. This is
+    /// what gets called for every ordered batch, by default it passes the items one by one to
+    /// [`FinalizationHandler::item_finalized`], then all their data at once to
+    /// [`FinalizationHandler::data_finalized_batch`].
+    fn items_finalized<H: Hasher>(&mut self, items: Vec<FinalizedItem<H, Data>>)
+    where
+        Self: Sized,
+    {
+        for item in &items {
+            self.item_finalized::<H>(item)
+        }
+        self.data_finalized_batch(items.into_iter().map(|item| item.data).collect())
+    }
 }
@@ -1,5 +1,6 @@
 //! Traits that need to be implemented by the user.
 
+mod clock;
 mod dataio;
 mod network;
 mod tasks;
@@ -9,9 +10,10 @@ pub use aleph_bft_crypto::{
     NodeIndex, NodeMap, NodeSubset, PartialMultisignature, PartiallyMultisigned, Signable,
     Signature, SignatureError, SignatureSet, Signed, UncheckedSigned,
 };
-pub use dataio::{DataProvider, FinalizationHandler};
+pub use clock::{Clock, SystemClock};
+pub use dataio::{DataProvider, FinalizationHandler, FinalizedItem};
 pub use network::{Network, Recipient};
-pub use tasks::{SpawnHandle, TaskHandle};
+pub use tasks::{SchedulingHint, SpawnHandle, TaskHandle};
 
 use codec::Codec;
 use std::{fmt::Debug, hash::Hash as StdHash};
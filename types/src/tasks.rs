@@ -4,6 +4,17 @@ use std::pin::Pin;
 /// A handle for waiting the task's completion.
 pub type TaskHandle = Pin<Box<dyn Future<Output = Result<(), ()>> + Send>>;
 
+/// A hint on how to schedule a task, which a [`SpawnHandle`] is free to ignore.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum SchedulingHint {
+    /// Nothing special, the task can be run like any other.
+    #[default]
+    Normal,
+    /// The progress of consensus depends on the task, so it should be scheduled promptly, e.g. on
+    /// a dedicated executor or with a higher priority.
+    Critical,
+}
+
 /// An abstraction for an execution engine for Rust's asynchronous tasks.
 pub trait SpawnHandle: Clone + Send + 'static {
     /// Run a new task.
@@ -15,4 +26,14 @@ pub trait SpawnHandle: Clone + Send + 'static {
         name: &'static str,
         task: impl Future<Output = ()> + Send + 'static,
     ) -> TaskHandle;
+    /// Run a new task like [`SpawnHandle::spawn_essential`], with a hint on how to schedule it.
+    /// By default the hint is ignored.
+    fn spawn_essential_with_hint(
+        &self,
+        name: &'static str,
+        task: impl Future<Output = ()> + Send + 'static,
+        _hint: SchedulingHint,
+    ) -> TaskHandle {
+        self.spawn_essential(name, task)
+    }
 }